@@ -186,3 +186,45 @@ pub fn light_yellow() -> ColorArray {
         .unwrap()
         .to_arr()
 }
+
+pub fn selection_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#44C9F0"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn selection_foreground() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#0F0D0E"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn search_match_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#FCBA28"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn hyperlink_hover_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#2C2728"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn vi_cursor_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#FC7428"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn bar_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#1C191A"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn bar_foreground() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#F9F4DA"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
@@ -6,7 +6,19 @@ use regex::Regex;
 use serde::{de, Deserialize};
 use std::num::ParseIntError;
 
+#[cfg(feature = "wgpu")]
 pub type ColorWGPU = wgpu::Color;
+
+/// Same fields as `wgpu::Color`, used when built without the renderer.
+#[cfg(not(feature = "wgpu"))]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ColorWGPU {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64,
+}
+
 pub type ColorArray = [f32; 4];
 pub type ColorComposition = (ColorArray, ColorWGPU);
 
@@ -61,6 +73,14 @@ pub struct Colors {
     pub tabs_active: ColorArray,
     #[serde(default = "defaults::cursor", deserialize_with = "deserialize_to_arr")]
     pub cursor: ColorArray,
+    /// Color of the text under a block cursor, the color of the cell
+    /// background when unset.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_to_option_arr",
+        rename = "cursor-text"
+    )]
+    pub cursor_text: Option<ColorArray>,
 
     #[serde(default = "defaults::black", deserialize_with = "deserialize_to_arr")]
     pub black: ColorArray,
@@ -180,6 +200,48 @@ pub struct Colors {
         rename = "light-yellow"
     )]
     pub light_yellow: ColorArray,
+    #[serde(
+        default = "defaults::selection_background",
+        deserialize_with = "deserialize_to_arr",
+        rename = "selection-background"
+    )]
+    pub selection_background: ColorArray,
+    #[serde(
+        default = "defaults::selection_foreground",
+        deserialize_with = "deserialize_to_arr",
+        rename = "selection-foreground"
+    )]
+    pub selection_foreground: ColorArray,
+    #[serde(
+        default = "defaults::search_match_background",
+        deserialize_with = "deserialize_to_arr",
+        rename = "search-match-background"
+    )]
+    pub search_match_background: ColorArray,
+    #[serde(
+        default = "defaults::hyperlink_hover_background",
+        deserialize_with = "deserialize_to_arr",
+        rename = "hyperlink-hover-background"
+    )]
+    pub hyperlink_hover_background: ColorArray,
+    #[serde(
+        default = "defaults::vi_cursor_background",
+        deserialize_with = "deserialize_to_arr",
+        rename = "vi-cursor-background"
+    )]
+    pub vi_cursor_background: ColorArray,
+    #[serde(
+        default = "defaults::bar_background",
+        deserialize_with = "deserialize_to_arr",
+        rename = "bar-background"
+    )]
+    pub bar_background: ColorArray,
+    #[serde(
+        default = "defaults::bar_foreground",
+        deserialize_with = "deserialize_to_arr",
+        rename = "bar-foreground"
+    )]
+    pub bar_foreground: ColorArray,
 }
 
 impl Default for Colors {
@@ -193,6 +255,7 @@ impl Default for Colors {
             yellow: defaults::yellow(),
             tabs_active: defaults::tabs_active(),
             cursor: defaults::cursor(),
+            cursor_text: None,
             black: defaults::black(),
             cyan: defaults::cyan(),
             magenta: defaults::magenta(),
@@ -216,16 +279,135 @@ impl Default for Colors {
             light_red: defaults::light_red(),
             light_white: defaults::light_white(),
             light_yellow: defaults::light_yellow(),
+            selection_background: defaults::selection_background(),
+            selection_foreground: defaults::selection_foreground(),
+            search_match_background: defaults::search_match_background(),
+            hyperlink_hover_background: defaults::hyperlink_hover_background(),
+            vi_cursor_background: defaults::vi_cursor_background(),
+            bar_background: defaults::bar_background(),
+            bar_foreground: defaults::bar_foreground(),
         }
     }
 }
 
+impl Colors {
+    /// White on black with saturated colors, used by the high-contrast mode.
+    /// Dim colors are the same as normal ones.
+    pub fn high_contrast() -> Colors {
+        let red = hex_to_color_arr("#FF5555");
+        let green = hex_to_color_arr("#55FF55");
+        let yellow = hex_to_color_arr("#FFFF55");
+        let blue = hex_to_color_arr("#6FA8FF");
+        let magenta = hex_to_color_arr("#FF55FF");
+        let cyan = hex_to_color_arr("#55FFFF");
+        let white = hex_to_color_arr("#FFFFFF");
+        let black = hex_to_color_arr("#000000");
+
+        Colors {
+            background: (black, hex_to_color_wgpu("#000000")),
+            foreground: white,
+            cursor: yellow,
+            cursor_text: Some(black),
+            tabs: white,
+            tabs_active: yellow,
+            black,
+            red,
+            green,
+            yellow,
+            blue,
+            magenta,
+            cyan,
+            white,
+            dim_black: black,
+            dim_red: red,
+            dim_green: green,
+            dim_yellow: yellow,
+            dim_blue: blue,
+            dim_magenta: magenta,
+            dim_cyan: cyan,
+            dim_white: white,
+            dim_foreground: white,
+            light_black: hex_to_color_arr("#BFBFBF"),
+            light_red: red,
+            light_green: green,
+            light_yellow: yellow,
+            light_blue: blue,
+            light_magenta: magenta,
+            light_cyan: cyan,
+            light_white: white,
+            light_foreground: white,
+            selection_background: yellow,
+            selection_foreground: black,
+            search_match_background: cyan,
+            hyperlink_hover_background: blue,
+            vi_cursor_background: yellow,
+            bar_background: black,
+            bar_foreground: white,
+        }
+    }
+}
+
+/// WCAG contrast ratio between two colors, from 1 to 21.
+pub fn contrast_ratio(a: ColorArray, b: ColorArray) -> f32 {
+    let luminance = |color: ColorArray| {
+        let channel = |value: f32| {
+            if value <= 0.03928 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * channel(color[0])
+            + 0.7152 * channel(color[1])
+            + 0.0722 * channel(color[2])
+    };
+
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// `foreground`, or black or white when its contrast with `background` is
+/// under `min_ratio`.
+pub fn readable_foreground(
+    foreground: ColorArray,
+    background: ColorArray,
+    min_ratio: f32,
+) -> ColorArray {
+    if contrast_ratio(foreground, background) >= min_ratio {
+        return foreground;
+    }
+
+    let black = [0.0, 0.0, 0.0, foreground[3]];
+    let white = [1.0, 1.0, 1.0, foreground[3]];
+    if contrast_ratio(black, background) > contrast_ratio(white, background) {
+        black
+    } else {
+        white
+    }
+}
+
+/// `color` darkened by `factor`, from 0.0 (black) to 1.0 (unchanged).
+pub fn dim(color: ColorArray, factor: f32) -> ColorArray {
+    [
+        color[0] * factor,
+        color[1] * factor,
+        color[2] * factor,
+        color[3],
+    ]
+}
+
 pub fn hex_to_color_arr(s: &str) -> ColorArray {
     ColorBuilder::from_hex(s.to_string(), Format::SRGB0_1)
         .unwrap_or_default()
         .to_arr()
 }
 
+/// `#RRGGBB` notation of `color`, as read by `hex_to_color_arr`.
+pub fn color_arr_to_hex(color: ColorArray) -> String {
+    let [red, green, blue, _] = color.map(|channel| (channel * 255.0).round() as u8);
+    format!("#{red:02X}{green:02X}{blue:02X}")
+}
+
 pub fn hex_to_color_wgpu(s: &str) -> ColorWGPU {
     ColorBuilder::from_hex(s.to_string(), Format::SRGB0_1)
         .unwrap_or_default()
@@ -446,8 +628,8 @@ impl ColorBuilder {
         }
     }
 
-    pub fn to_wgpu(&self) -> wgpu::Color {
-        wgpu::Color {
+    pub fn to_wgpu(&self) -> ColorWGPU {
+        ColorWGPU {
             r: self.red,
             g: self.green,
             b: self.blue,
@@ -540,10 +722,52 @@ where
     }
 }
 
+pub fn deserialize_to_option_arr<'de, D>(
+    deserializer: D,
+) -> Result<Option<ColorArray>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserialize_to_arr(deserializer).map(Some)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_contrast_ratio() {
+        let black = hex_to_color_arr("#000000");
+        let white = hex_to_color_arr("#FFFFFF");
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(white, white) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_readable_foreground() {
+        let black = hex_to_color_arr("#000000");
+        let white = hex_to_color_arr("#FFFFFF");
+        let gray = hex_to_color_arr("#333333");
+        let yellow = hex_to_color_arr("#FFFF55");
+
+        assert_eq!(readable_foreground(yellow, black, 7.0), yellow);
+        assert_eq!(readable_foreground(gray, black, 7.0), white);
+        assert_eq!(readable_foreground(yellow, white, 7.0), black);
+    }
+
+    #[test]
+    fn test_dim() {
+        let color = [1.0, 0.5, 0.0, 0.8];
+        assert_eq!(dim(color, 0.5), [0.5, 0.25, 0.0, 0.8]);
+        assert_eq!(dim(color, 1.0), color);
+    }
+
+    #[test]
+    fn test_color_arr_to_hex() {
+        assert_eq!(color_arr_to_hex(hex_to_color_arr("#F38BA3")), "#F38BA3");
+        assert_eq!(color_arr_to_hex([0.0, 0.0, 0.0, 1.0]), "#000000");
+    }
+
     #[test]
     fn test_conversion_from_hex_invalid_character() {
         let invalid_character_color = match ColorBuilder::from_hex(
@@ -585,7 +809,7 @@ mod tests {
 
     #[test]
     fn test_conversion_from_hex_sgb_255() {
-        let color: wgpu::Color =
+        let color: ColorWGPU =
             ColorBuilder::from_hex(String::from("#151515"), Format::SRGB0_1)
                 .unwrap()
                 .to_wgpu();
@@ -614,7 +838,7 @@ mod tests {
 
     #[test]
     fn test_conversion_from_hex_sgb_1() {
-        let color: wgpu::Color =
+        let color: ColorWGPU =
             ColorBuilder::from_hex(String::from("#151515"), Format::SRGB0_255)
                 .unwrap()
                 .to_wgpu();
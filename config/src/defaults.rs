@@ -12,6 +12,10 @@ pub fn default_env_vars() -> Vec<String> {
     vec![]
 }
 
+pub fn default_term() -> String {
+    String::from("rio")
+}
+
 pub fn default_option_as_alt() -> String {
     String::from("None")
 }
@@ -36,6 +40,26 @@ pub fn default_font_size() -> f32 {
     16.0
 }
 
+pub fn default_text_gamma() -> f32 {
+    1.0
+}
+
+pub fn default_text_contrast() -> f32 {
+    0.0
+}
+
+pub fn default_unfocused_dim() -> f32 {
+    0.0
+}
+
+pub fn default_background_image_opacity() -> f32 {
+    1.0
+}
+
+pub fn default_sample_count() -> u32 {
+    1
+}
+
 pub fn default_tab_character_active() -> char {
     '●'
 }
@@ -43,3 +67,98 @@ pub fn default_tab_character_active() -> char {
 pub fn default_tab_character_inactive() -> char {
     '■'
 }
+
+pub fn default_scroll_multiplier() -> f64 {
+    3.0
+}
+
+pub fn default_scroll_indicator() -> bool {
+    true
+}
+
+pub fn default_scroll_history() -> usize {
+    10_000
+}
+
+pub fn default_bar_title() -> bool {
+    true
+}
+
+pub fn default_bar_scrollback() -> bool {
+    true
+}
+
+pub fn default_bar_height() -> f32 {
+    32.0
+}
+
+pub fn default_primary_selection() -> bool {
+    true
+}
+
+pub fn default_word_separators() -> String {
+    String::from(",│`|:\"' ()[]{}<>\t")
+}
+
+pub fn default_link_schemes() -> Vec<String> {
+    ["http", "https", "file", "mailto"]
+        .iter()
+        .map(|scheme| scheme.to_string())
+        .collect()
+}
+
+pub fn default_dropdown_hotkey() -> String {
+    String::from("F12")
+}
+
+pub fn default_window_decorations() -> bool {
+    true
+}
+
+pub fn default_window_class() -> String {
+    String::from("Rio")
+}
+
+pub fn default_window_name() -> String {
+    String::from("rio")
+}
+
+pub fn default_feature() -> bool {
+    true
+}
+
+pub fn default_confirm_quit() -> bool {
+    true
+}
+
+pub fn default_paste_confirm() -> bool {
+    true
+}
+
+pub fn default_paste_strip_escapes() -> bool {
+    true
+}
+
+pub fn default_prompt_pattern() -> String {
+    String::from(r"^\S*[$#%❯](\s|$)")
+}
+
+pub fn default_notify_after() -> u64 {
+    10
+}
+
+pub fn default_command_marks() -> bool {
+    true
+}
+
+pub fn default_bell_volume() -> f32 {
+    1.0
+}
+
+pub fn default_bell_cooldown() -> u64 {
+    500
+}
+
+pub fn default_dropdown_height() -> f32 {
+    0.4
+}
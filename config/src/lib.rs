@@ -1,25 +1,132 @@
 mod defaults;
+pub mod schema;
+mod themes;
 use crate::defaults::*;
 use colors::Colors;
 use log::warn;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::default::Default;
 
-#[derive(Default, Debug, Deserialize, PartialEq, Clone, Copy)]
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
 pub enum Performance {
     #[default]
     High,
     Low,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum FontRasterizer {
+    #[default]
+    AbGlyph,
+    Swash,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum TextAntialiasing {
+    #[default]
+    Grayscale,
+    Rgb,
+    Bgr,
+}
+
+/// How text with the blink attribute (SGR 5) is drawn.
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum BlinkingText {
+    #[default]
+    Blink,
+    Bold,
+    Normal,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum BackgroundImageMode {
+    Fit,
+    #[default]
+    Fill,
+    Tile,
+}
+
+/// Color blindness simulated, or corrected for by the `Daltonize` filters.
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    DaltonizeProtanopia,
+    DaltonizeDeuteranopia,
+    DaltonizeTritanopia,
+}
+
+/// Change made to pasted text before it is sent.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum PasteTransform {
+    TrimTrailingWhitespace,
+    TrimTrailingNewline,
+    JoinLines,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum BarPosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum BarVisibility {
+    #[default]
+    Always,
+    /// Shown over the terminal while the mouse is over it.
+    AutoHide,
+    MultipleTabs,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct Style {
     #[serde(rename = "font-size", default = "default_font_size")]
     pub font_size: f32,
     #[serde(default = "default_theme")]
     pub theme: String,
+    /// Used instead of `theme` while the system appearance is light.
+    #[serde(rename = "light-theme", default = "default_theme")]
+    pub light_theme: String,
+    /// Used instead of `theme` while the system appearance is dark.
+    #[serde(rename = "dark-theme", default = "default_theme")]
+    pub dark_theme: String,
     #[serde(default = "default_font")]
     pub font: String,
+    #[serde(rename = "font-rasterizer", default = "FontRasterizer::default")]
+    pub font_rasterizer: FontRasterizer,
+    #[serde(rename = "text-antialiasing", default = "TextAntialiasing::default")]
+    pub text_antialiasing: TextAntialiasing,
+    #[serde(rename = "text-gamma", default = "default_text_gamma")]
+    pub text_gamma: f32,
+    #[serde(rename = "text-contrast", default = "default_text_contrast")]
+    pub text_contrast: f32,
+    /// How much colors of unfocused windows are dimmed, from 0.0 to 1.0.
+    #[serde(rename = "unfocused-dim", default = "default_unfocused_dim")]
+    pub unfocused_dim: f32,
+    #[serde(rename = "blinking-text", default = "BlinkingText::default")]
+    pub blinking_text: BlinkingText,
+    /// Whether bold text in one of the eight normal colors is drawn in its
+    /// bright variant.
+    #[serde(rename = "bold-brightens-colors", default = "bool::default")]
+    pub bold_brightens_colors: bool,
+    /// Overrides `text_antialiasing` by display name.
+    #[serde(rename = "text-antialiasing-displays", default = "HashMap::default")]
+    pub text_antialiasing_displays: HashMap<String, TextAntialiasing>,
+}
+
+impl Style {
+    pub fn text_antialiasing_for(&self, display: Option<&str>) -> TextAntialiasing {
+        display
+            .and_then(|display| self.text_antialiasing_displays.get(display))
+            .copied()
+            .unwrap_or(self.text_antialiasing)
+    }
 }
 
 impl Default for Style {
@@ -27,12 +134,22 @@ impl Default for Style {
         Style {
             font_size: default_font_size(),
             theme: default_theme(),
+            light_theme: default_theme(),
+            dark_theme: default_theme(),
             font: default_font(),
+            font_rasterizer: FontRasterizer::default(),
+            text_antialiasing: TextAntialiasing::default(),
+            text_gamma: default_text_gamma(),
+            text_contrast: default_text_contrast(),
+            unfocused_dim: default_unfocused_dim(),
+            blinking_text: BlinkingText::default(),
+            bold_brightens_colors: false,
+            text_antialiasing_displays: HashMap::default(),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct Advanced {
     #[serde(
         default = "default_tab_character_active",
@@ -46,6 +163,16 @@ pub struct Advanced {
     pub tab_character_inactive: char,
     #[serde(default = "bool::default", rename = "disable-render-when-unfocused")]
     pub disable_render_when_unfocused: bool,
+    #[serde(default = "default_sample_count", rename = "sample-count")]
+    pub sample_count: u32,
+    /// Use the first GPU whose name contains this, e.g. "NVIDIA" or "Intel".
+    #[serde(default = "Option::default", rename = "adapter-name")]
+    pub adapter_name: Option<String>,
+    #[serde(default = "bool::default", rename = "force-fallback-adapter")]
+    pub force_fallback_adapter: bool,
+    /// Draw the echo of a keystroke as soon as it's parsed.
+    #[serde(default = "bool::default", rename = "latency-mode")]
+    pub latency_mode: bool,
 }
 
 impl Default for Advanced {
@@ -54,28 +181,492 @@ impl Default for Advanced {
             tab_character_active: default_tab_character_active(),
             tab_character_inactive: default_tab_character_inactive(),
             disable_render_when_unfocused: false,
+            sample_count: default_sample_count(),
+            adapter_name: None,
+            force_fallback_adapter: false,
+            latency_mode: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Scroll {
+    #[serde(default = "default_scroll_multiplier")]
+    pub multiplier: f64,
+    #[serde(default = "bool::default")]
+    pub smooth: bool,
+    #[serde(default = "default_scroll_indicator")]
+    pub indicator: bool,
+    #[serde(default = "bool::default", rename = "click-to-jump")]
+    pub click_to_jump: bool,
+    #[serde(default = "default_scroll_history")]
+    pub history: usize,
+    /// Megabytes the scrollback may take, unlimited when zero.
+    #[serde(default = "usize::default", rename = "history-memory-limit")]
+    pub history_memory_limit: usize,
+}
+
+impl Default for Scroll {
+    fn default() -> Scroll {
+        Scroll {
+            multiplier: default_scroll_multiplier(),
+            smooth: false,
+            indicator: default_scroll_indicator(),
+            click_to_jump: false,
+            history: default_scroll_history(),
+            history_memory_limit: 0,
+        }
+    }
+}
+
+/// Bar drawn above the terminal, with the title on the left and status
+/// segments on the right.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Bar {
+    #[serde(default = "bool::default")]
+    pub enabled: bool,
+    #[serde(default = "default_bar_title")]
+    pub title: bool,
+    /// Working directory reported by the shell with OSC 7.
+    #[serde(default = "bool::default")]
+    pub cwd: bool,
+    #[serde(default = "bool::default")]
+    pub clock: bool,
+    /// Exit status and duration of the last command marked by the shell.
+    #[serde(default = "bool::default")]
+    pub command: bool,
+    /// Lines scrolled into the scrollback.
+    #[serde(default = "default_bar_scrollback")]
+    pub scrollback: bool,
+    #[serde(default = "BarPosition::default")]
+    pub position: BarPosition,
+    /// In logical pixels.
+    #[serde(default = "default_bar_height")]
+    pub height: f32,
+    #[serde(default = "BarVisibility::default")]
+    pub visibility: BarVisibility,
+}
+
+impl Default for Bar {
+    fn default() -> Bar {
+        Bar {
+            enabled: false,
+            title: default_bar_title(),
+            cwd: false,
+            clock: false,
+            command: false,
+            scrollback: default_bar_scrollback(),
+            position: BarPosition::default(),
+            height: default_bar_height(),
+            visibility: BarVisibility::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Selection {
+    #[serde(default = "default_primary_selection")]
+    pub primary: bool,
+    /// Characters ending the word selected by double-clicking.
+    #[serde(default = "default_word_separators", rename = "word-separators")]
+    pub word_separators: String,
+    /// Whether text hidden with SGR 8 is copied, instead of spaces.
+    #[serde(default = "bool::default", rename = "copy-hidden-text")]
+    pub copy_hidden_text: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Selection {
+        Selection {
+            primary: default_primary_selection(),
+            word_separators: default_word_separators(),
+            copy_hidden_text: false,
+        }
+    }
+}
+
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    Maximized,
+    Fullscreen,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Window {
+    /// Without decorations the bar moves the window and the edges resize it.
+    #[serde(default = "default_window_decorations")]
+    pub decorations: bool,
+    #[serde(default = "WindowMode::default")]
+    pub mode: WindowMode,
+    /// Startup width in columns, instead of `width`.
+    #[serde(default = "Option::default")]
+    pub columns: Option<u16>,
+    /// Startup height in lines, instead of `height`.
+    #[serde(default = "Option::default")]
+    pub lines: Option<u16>,
+    /// Logical pixels from the top left corner of the monitor, centered
+    /// when not set.
+    #[serde(default = "Option::default")]
+    pub position: Option<[i32; 2]>,
+    /// Part of the name of the monitor to open on, the primary one when
+    /// not set.
+    #[serde(default = "Option::default")]
+    pub monitor: Option<String>,
+    /// Save the position and size on exit and open with them next time.
+    #[serde(default = "bool::default")]
+    pub remember: bool,
+    /// General part of `WM_CLASS` on X11 and the app ID on Wayland.
+    #[serde(default = "default_window_class")]
+    pub class: String,
+    /// Instance part of `WM_CLASS` on X11.
+    #[serde(default = "default_window_name")]
+    pub name: String,
+    /// Resize the window by whole cells, where the platform supports it.
+    #[serde(rename = "resize-increments", default = "bool::default")]
+    pub resize_increments: bool,
+    /// Keep native macOS window tabbing, with its tab bar and menu items.
+    #[serde(rename = "native-tabs", default = "bool::default")]
+    pub native_tabs: bool,
+}
+
+impl Default for Window {
+    fn default() -> Window {
+        Window {
+            decorations: default_window_decorations(),
+            mode: WindowMode::default(),
+            columns: None,
+            lines: None,
+            position: None,
+            monitor: None,
+            remember: false,
+            class: default_window_class(),
+            name: default_window_name(),
+            resize_increments: false,
+            native_tabs: false,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Deserialize)]
+/// Ask before closing the window while a program is running in the
+/// foreground of the shell.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct ConfirmQuit {
+    #[serde(default = "default_confirm_quit")]
+    pub enabled: bool,
+    /// Programs which are fine to close without asking, e.g. `tmux`.
+    #[serde(default = "Vec::default")]
+    pub ignore: Vec<String>,
+}
+
+impl Default for ConfirmQuit {
+    fn default() -> ConfirmQuit {
+        ConfirmQuit {
+            enabled: default_confirm_quit(),
+            ignore: vec![],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Paste {
+    /// Ask before pasting text with newlines or control characters when
+    /// the application didn't enable bracketed paste.
+    #[serde(default = "default_paste_confirm")]
+    pub confirm: bool,
+    /// Remove ESC and C1 control characters, which could start escape
+    /// sequences.
+    #[serde(default = "default_paste_strip_escapes", rename = "strip-escapes")]
+    pub strip_escapes: bool,
+    /// Paste Windows line endings (`\r\n`) as `\n`.
+    #[serde(default = "bool::default", rename = "normalize-newlines")]
+    pub normalize_newlines: bool,
+    /// Applied in order after the above.
+    #[serde(default = "Vec::default")]
+    pub transforms: Vec<PasteTransform>,
+}
+
+impl Default for Paste {
+    fn default() -> Paste {
+        Paste {
+            confirm: default_paste_confirm(),
+            strip_escapes: default_paste_strip_escapes(),
+            normalize_newlines: false,
+            transforms: vec![],
+        }
+    }
+}
+
+/// Action run by clicking a mouse button, replacing the default one for the
+/// same button, modifiers and mode.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct MouseBinding {
+    /// `Left`, `Middle` or `Right`.
+    pub button: String,
+    /// Modifiers held, e.g. `Control|Shift`.
+    #[serde(default = "String::default")]
+    pub with: String,
+    pub action: String,
+    /// `MouseReport` to only apply while the application reports the
+    /// mouse, `~MouseReport` while it doesn't.
+    #[serde(default = "String::default")]
+    pub mode: String,
+}
+
+/// Key pressed to run an action or write text to the terminal, replacing the
+/// default binding for the same key, modifiers and mode.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct KeyBinding {
+    /// Named like in `rio msg send-key`, e.g. `T` or `PageUp`.
+    pub key: String,
+    /// Modifiers held, e.g. `Control|Shift`.
+    #[serde(default = "String::default")]
+    pub with: String,
+    /// Action run, e.g. `TabCreateNew`, unless `text` is set.
+    #[serde(default = "String::default")]
+    pub action: String,
+    /// Written to the terminal instead of running an action.
+    #[serde(default = "Option::default")]
+    pub text: Option<String>,
+    /// Modes the binding applies in, e.g. `Vi` or `~AltScreen|~Vi`.
+    #[serde(default = "String::default")]
+    pub mode: String,
+    /// Whether holding the key repeats the binding, by default only text
+    /// and scrolling do.
+    #[serde(default = "Option::default")]
+    pub repeat: Option<bool>,
+    /// Keys pressed one after the other before `key`, named like `key` with
+    /// their modifiers, e.g. `Control+A`.
+    #[serde(default = "String::default")]
+    pub chord: String,
+}
+
+/// Prompts and commands marked by the shell with OSC 133.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct ShellIntegration {
+    /// Regular expression matching the lines where a prompt starts, used
+    /// when the shell doesn't mark them. Empty to disable.
+    #[serde(default = "default_prompt_pattern", rename = "prompt-pattern")]
+    pub prompt_pattern: String,
+    /// Seconds a command has to run for a notification to be sent when it
+    /// finishes while the window is unfocused. Zero to disable.
+    #[serde(default = "default_notify_after", rename = "notify-after")]
+    pub notify_after: u64,
+    /// Mark the rows where commands were entered in the left margin, green
+    /// when they succeeded and red when they failed.
+    #[serde(default = "default_command_marks", rename = "command-marks")]
+    pub command_marks: bool,
+}
+
+impl Default for ShellIntegration {
+    fn default() -> ShellIntegration {
+        ShellIntegration {
+            prompt_pattern: default_prompt_pattern(),
+            notify_after: default_notify_after(),
+            command_marks: default_command_marks(),
+        }
+    }
+}
+
+/// Links opened with Ctrl+click.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Links {
+    /// Command opening `path:line` links, with `{file}`, `{line}` and
+    /// `{column}` replaced. Empty to use `$EDITOR`.
+    #[serde(default = "String::default")]
+    pub editor: String,
+    /// Schemes of the URLs which are opened, others are ignored.
+    #[serde(default = "default_link_schemes")]
+    pub schemes: Vec<String>,
+}
+
+impl Default for Links {
+    fn default() -> Links {
+        Links {
+            editor: String::default(),
+            schemes: default_link_schemes(),
+        }
+    }
+}
+
+/// Sound played when an application rings the bell.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Bell {
+    #[serde(default = "bool::default")]
+    pub audible: bool,
+    /// Path of the sound file, may start with `~/`. Empty for the default
+    /// chime of the system.
+    #[serde(default = "String::default")]
+    pub sound: String,
+    /// From 0.0 to 1.0.
+    #[serde(default = "default_bell_volume")]
+    pub volume: f32,
+    /// Milliseconds during which further bells are not heard.
+    #[serde(default = "default_bell_cooldown")]
+    pub cooldown: u64,
+}
+
+impl Default for Bell {
+    fn default() -> Bell {
+        Bell {
+            audible: false,
+            sound: String::default(),
+            volume: default_bell_volume(),
+            cooldown: default_bell_cooldown(),
+        }
+    }
+}
+
+/// Named preset applied with `rio --profile <name>`, on top of the rest of
+/// the configuration.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, Default)]
+pub struct Profile {
+    /// Program run instead of `$SHELL`.
+    #[serde(default = "Option::default")]
+    pub shell: Option<String>,
+    /// Working directory of the shell, may start with `~/`.
+    #[serde(default = "Option::default")]
+    pub cwd: Option<String>,
+    /// Added to the `env-vars` of the configuration.
+    #[serde(default = "Vec::default", rename = "env-vars")]
+    pub env_vars: Vec<String>,
+    #[serde(default = "Option::default")]
+    pub theme: Option<String>,
+    #[serde(default = "Option::default")]
+    pub font: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Dropdown {
+    #[serde(default = "bool::default")]
+    pub enabled: bool,
+    #[serde(default = "default_dropdown_hotkey")]
+    pub hotkey: String,
+    /// Height of the window as a fraction of the screen height.
+    #[serde(default = "default_dropdown_height")]
+    pub height: f32,
+}
+
+impl Default for Dropdown {
+    fn default() -> Dropdown {
+        Dropdown {
+            enabled: false,
+            hotkey: default_dropdown_hotkey(),
+            height: default_dropdown_height(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, Default)]
+pub struct Session {
+    #[serde(default = "bool::default")]
+    pub restore: bool,
+    #[serde(default = "bool::default", rename = "single-instance")]
+    pub single_instance: bool,
+}
+
+/// Environment variable listing features to disable, separated by commas.
+pub const DISABLE_FEATURES_ENV: &str = "RIO_DISABLE_FEATURES";
+
+/// Features which can be turned off in security-sensitive environments,
+/// clipboard writes being opt-in.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Features {
+    /// Set `COLORTERM=truecolor` and report `Tc` to XTGETTCAP.
+    #[serde(default = "default_feature")]
+    pub truecolor: bool,
+    /// Highlight and open hyperlinks set by applications (OSC 8).
+    #[serde(default = "default_feature")]
+    pub hyperlinks: bool,
+    /// Let applications write to the clipboard (OSC 52).
+    #[serde(default = "bool::default", rename = "clipboard-write")]
+    pub clipboard_write: bool,
+}
+
+impl Default for Features {
+    fn default() -> Features {
+        Features {
+            truecolor: default_feature(),
+            hyperlinks: default_feature(),
+            clipboard_write: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, Default)]
+pub struct RemoteControl {
+    /// Allow `rio msg send-text` and `send-key` to write to the terminal.
+    #[serde(default = "bool::default", rename = "allow-input")]
+    pub allow_input: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct BackgroundImage {
+    /// PNG or JPEG image drawn behind the text.
+    #[serde(default = "Option::default")]
+    pub path: Option<String>,
+    #[serde(default = "default_background_image_opacity")]
+    pub opacity: f32,
+    #[serde(default = "BackgroundImageMode::default")]
+    pub mode: BackgroundImageMode,
+}
+
+impl Default for BackgroundImage {
+    fn default() -> BackgroundImage {
+        BackgroundImage {
+            path: None,
+            opacity: default_background_image_opacity(),
+            mode: BackgroundImageMode::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, Default)]
+pub struct PostProcessing {
+    /// Paths of WGSL shaders, applied in order.
+    #[serde(default = "Vec::default")]
+    pub shaders: Vec<String>,
+    /// Applied after the shaders.
+    #[serde(default = "ColorFilter::default", rename = "color-filter")]
+    pub color_filter: ColorFilter,
+    /// Keep redrawing so shaders can animate with `uniforms.time`.
+    #[serde(default = "bool::default")]
+    pub animate: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, Default)]
+pub struct Accessibility {
+    /// Read lines of output aloud as they are printed.
+    #[serde(default = "bool::default", rename = "reader-mode")]
+    pub reader_mode: bool,
+    /// Replace the colors with a high-contrast palette, see
+    /// `Config::apply_high_contrast`.
+    #[serde(default = "bool::default", rename = "high-contrast")]
+    pub high_contrast: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct Developer {
     #[serde(default = "bool::default", rename = "enable-fps-counter")]
     pub enable_fps_counter: bool,
     #[serde(default = "default_log_level", rename = "log-level")]
     pub log_level: String,
+    #[serde(default = "Option::default", rename = "log-file")]
+    pub log_file: Option<String>,
 }
 
 impl Default for Developer {
     fn default() -> Developer {
         Developer {
             log_level: default_log_level(),
+            log_file: None,
             enable_fps_counter: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Config {
     #[serde(default = "Performance::default")]
     pub performance: Performance,
@@ -87,14 +678,57 @@ pub struct Config {
     pub cursor: char,
     #[serde(default = "default_env_vars", rename = "env-vars")]
     pub env_vars: Vec<String>,
+    /// `TERM` of the shell, `rio` falls back to `xterm-256color` when the
+    /// `rio` terminfo entry is not installed.
+    #[serde(default = "default_term")]
+    pub term: String,
     #[serde(default = "default_option_as_alt", rename = "option-as-alt")]
     pub option_as_alt: String,
     #[serde(default = "Style::default")]
     pub style: Style,
-    #[serde(default = "Colors::default")]
+    // `Colors` only implements `Deserialize`.
+    #[serde(default = "Colors::default", skip_serializing)]
     pub colors: Colors,
+    #[serde(default = "BackgroundImage::default", rename = "background-image")]
+    pub background_image: BackgroundImage,
+    #[serde(default = "Scroll::default")]
+    pub scroll: Scroll,
+    #[serde(default = "Bar::default")]
+    pub bar: Bar,
+    #[serde(default = "Selection::default")]
+    pub selection: Selection,
+    #[serde(default = "Session::default")]
+    pub session: Session,
+    #[serde(default = "RemoteControl::default", rename = "remote-control")]
+    pub remote_control: RemoteControl,
+    #[serde(default = "Features::default")]
+    pub features: Features,
+    #[serde(default = "Window::default")]
+    pub window: Window,
+    #[serde(default = "ConfirmQuit::default", rename = "confirm-quit")]
+    pub confirm_quit: ConfirmQuit,
+    #[serde(default = "Paste::default")]
+    pub paste: Paste,
+    #[serde(default = "Vec::default", rename = "key-bindings")]
+    pub key_bindings: Vec<KeyBinding>,
+    #[serde(default = "Vec::default", rename = "mouse-bindings")]
+    pub mouse_bindings: Vec<MouseBinding>,
+    #[serde(default = "ShellIntegration::default", rename = "shell-integration")]
+    pub shell_integration: ShellIntegration,
+    #[serde(default = "Links::default")]
+    pub links: Links,
+    #[serde(default = "Bell::default")]
+    pub bell: Bell,
+    #[serde(default = "HashMap::default")]
+    pub profiles: HashMap<String, Profile>,
+    #[serde(default = "Dropdown::default")]
+    pub dropdown: Dropdown,
     #[serde(default = "Advanced::default")]
     pub advanced: Advanced,
+    #[serde(default = "PostProcessing::default", rename = "post-processing")]
+    pub post_processing: PostProcessing,
+    #[serde(default = "Accessibility::default")]
+    pub accessibility: Accessibility,
     #[serde(default = "Developer::default")]
     pub developer: Developer,
 }
@@ -134,11 +768,9 @@ impl Config {
                         return Ok(decoded);
                     }
 
-                    let path = format!("/tmp/{theme}.toml");
-                    if let Ok(loaded_theme) = Config::load_theme(&path) {
-                        decoded.colors = loaded_theme.colors;
-                    } else {
-                        warn!("failed to load theme: {}", theme);
+                    match Config::load_theme("/tmp", theme) {
+                        Ok(colors) => decoded.colors = colors,
+                        Err(err) => warn!("failed to load theme {}: {}", theme, err),
                     }
 
                     Ok(decoded)
@@ -150,47 +782,181 @@ impl Config {
         }
     }
 
-    fn load_theme(path: &str) -> Result<Theme, String> {
-        if std::path::Path::new(&path).exists() {
-            let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Theme>(&content) {
-                Ok(decoded) => Ok(decoded),
-                Err(err_message) => Err(format!("error parsing: {:?}", err_message)),
+    /// Colors of `theme`, a Rio theme in `themes_dir` named without its
+    /// extension, or a scheme of another terminal named with it, in
+    /// `themes_dir` or at an absolute or `~/` path.
+    fn load_theme(themes_dir: &str, theme: &str) -> Result<Colors, String> {
+        let extension = std::path::Path::new(theme)
+            .extension()
+            .and_then(|extension| extension.to_str());
+        let path = match extension {
+            Some(extension) if themes::EXTENSIONS.contains(&extension) => {
+                match theme.strip_prefix("~/") {
+                    Some(relative) => format!("{}/{relative}", home_dir_path()),
+                    None if theme.starts_with('/') => theme.to_string(),
+                    None => format!("{themes_dir}/{theme}"),
+                }
             }
+            _ => format!("{themes_dir}/{theme}.toml"),
+        };
+
+        let path = std::path::Path::new(&path);
+        if path.exists() {
+            themes::load(path).map_err(|err| format!("error parsing: {err}"))
         } else {
             Err(String::from("filepath does not exists"))
         }
     }
 
-    pub fn load() -> Self {
-        let base_dir = home_dir_path();
-        let path = format!("{base_dir}/.rio/config.toml");
-        if std::path::Path::new(&path).exists() {
-            let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Config>(&content) {
-                Ok(mut decoded) => {
-                    let theme = &decoded.style.theme;
-                    if theme.is_empty() {
-                        return decoded;
-                    }
-
-                    let path = format!("{base_dir}/.rio/themes/{theme}.toml");
-                    if let Ok(loaded_theme) = Config::load_theme(&path) {
-                        decoded.colors = loaded_theme.colors;
-                    } else {
-                        warn!("failed to load theme: {}", theme);
-                    }
+    /// Apply the profile `name`, returning it for the settings used when
+    /// the shell starts.
+    pub fn apply_profile(&mut self, name: &str) -> Result<Profile, String> {
+        let themes_dir = format!("{}/.rio/themes", home_dir_path());
+        self.apply_profile_with_themes(name, &themes_dir)
+    }
 
-                    decoded
-                }
-                Err(err_message) => {
-                    warn!("failure to parse config file, failling back to default...\n{err_message:?}");
-                    Config::default()
+    fn apply_profile_with_themes(
+        &mut self,
+        name: &str,
+        themes_dir: &str,
+    ) -> Result<Profile, String> {
+        let profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("unknown profile {name:?}"))?;
+
+        self.env_vars.extend(profile.env_vars.iter().cloned());
+        if let Some(font) = &profile.font {
+            self.style.font = font.clone();
+        }
+        if let Some(theme) = &profile.theme {
+            match Config::load_theme(themes_dir, theme) {
+                Ok(colors) => {
+                    self.style.theme = theme.clone();
+                    self.colors = colors;
                 }
+                Err(err) => warn!("failed to load theme {}: {}", theme, err),
             }
+        }
+
+        Ok(profile)
+    }
+
+    /// Force the high-contrast palette and a block cursor, without dimming
+    /// or a background image, when `accessibility.high-contrast` is set.
+    pub fn apply_high_contrast(&mut self) {
+        if !self.accessibility.high_contrast {
+            return;
+        }
+
+        self.colors = Colors::high_contrast();
+        self.cursor = '█';
+        self.style.unfocused_dim = 0.0;
+        self.background_image.path = None;
+    }
+
+    /// Turn off the features listed in `RIO_DISABLE_FEATURES`, and the
+    /// truecolor advertisement when `NO_COLOR` is set.
+    pub fn apply_features_env(&mut self) {
+        let disabled = std::env::var(DISABLE_FEATURES_ENV).unwrap_or_default();
+        let no_color =
+            std::env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty());
+        self.apply_features_env_with(&disabled, no_color);
+    }
+
+    fn apply_features_env_with(&mut self, disabled: &str, no_color: bool) {
+        if no_color {
+            self.features.truecolor = false;
+        }
+
+        for feature in disabled.split(',').map(str::trim) {
+            match feature {
+                "" => (),
+                "truecolor" => self.features.truecolor = false,
+                "hyperlinks" => self.features.hyperlinks = false,
+                "clipboard-write" => self.features.clipboard_write = false,
+                feature => warn!("unknown feature {feature} in {DISABLE_FEATURES_ENV}"),
+            }
+        }
+    }
+
+    /// Colors of `light-theme` or `dark-theme`, when the one for the
+    /// system appearance is set.
+    pub fn appearance_colors(&self, is_dark: bool) -> Option<Colors> {
+        let themes_dir = format!("{}/.rio/themes", home_dir_path());
+        self.appearance_colors_with_themes(is_dark, &themes_dir)
+    }
+
+    fn appearance_colors_with_themes(
+        &self,
+        is_dark: bool,
+        themes_dir: &str,
+    ) -> Option<Colors> {
+        if self.accessibility.high_contrast {
+            return None;
+        }
+
+        let theme = if is_dark {
+            &self.style.dark_theme
         } else {
+            &self.style.light_theme
+        };
+        if theme.is_empty() {
+            return None;
+        }
+
+        match Config::load_theme(themes_dir, theme) {
+            Ok(colors) => Some(colors),
+            Err(err) => {
+                warn!("failed to load theme {}: {}", theme, err);
+                None
+            }
+        }
+    }
+
+    /// Colors of `theme`, named like `style.theme`.
+    pub fn theme_colors(theme: &str) -> Result<Colors, String> {
+        let themes_dir = format!("{}/.rio/themes", home_dir_path());
+        Config::load_theme(&themes_dir, theme)
+    }
+
+    pub fn load() -> Self {
+        Config::try_load().unwrap_or_else(|err_message| {
+            warn!("failure to parse config file, failling back to default...\n{err_message}");
             Config::default()
+        })
+    }
+
+    /// Path of the configuration file.
+    pub fn path() -> String {
+        format!("{}/.rio/config.toml", home_dir_path())
+    }
+
+    /// Read the configuration file, failing on parse errors instead of
+    /// falling back to the defaults.
+    pub fn try_load() -> Result<Self, String> {
+        let base_dir = home_dir_path();
+        let path = Config::path();
+        if !std::path::Path::new(&path).exists() {
+            return Ok(Config::default());
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let mut decoded =
+            toml::from_str::<Config>(&content).map_err(|err| format!("{err:?}"))?;
+        let theme = &decoded.style.theme;
+        if theme.is_empty() {
+            return Ok(decoded);
         }
+
+        let themes_dir = format!("{base_dir}/.rio/themes");
+        match Config::load_theme(&themes_dir, theme) {
+            Ok(colors) => decoded.colors = colors,
+            Err(err) => warn!("failed to load theme {}: {}", theme, err),
+        }
+
+        Ok(decoded)
     }
 }
 
@@ -198,18 +964,34 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             env_vars: default_env_vars(),
+            term: default_term(),
             performance: Performance::default(),
             width: default_width(),
             height: default_height(),
             cursor: default_cursor(),
             option_as_alt: default_option_as_alt(),
             colors: Colors::default(),
-            style: Style {
-                font_size: default_font_size(),
-                theme: default_theme(),
-                font: default_font(),
-            },
+            background_image: BackgroundImage::default(),
+            style: Style::default(),
+            scroll: Scroll::default(),
+            bar: Bar::default(),
+            selection: Selection::default(),
+            session: Session::default(),
+            remote_control: RemoteControl::default(),
+            features: Features::default(),
+            window: Window::default(),
+            confirm_quit: ConfirmQuit::default(),
+            paste: Paste::default(),
+            key_bindings: vec![],
+            mouse_bindings: vec![],
+            shell_integration: ShellIntegration::default(),
+            links: Links::default(),
+            bell: Bell::default(),
+            profiles: HashMap::default(),
+            dropdown: Dropdown::default(),
             advanced: Advanced::default(),
+            post_processing: PostProcessing::default(),
+            accessibility: Accessibility::default(),
             developer: Developer::default(),
         }
     }
@@ -266,10 +1048,27 @@ mod tests {
         assert_eq!(result.height, default_height());
         // Style
         assert_eq!(result.style.font, default_font());
+        assert_eq!(result.style.font_rasterizer, FontRasterizer::AbGlyph);
+        assert_eq!(result.style.text_antialiasing, TextAntialiasing::Grayscale);
+        assert!(result.style.text_antialiasing_displays.is_empty());
+        assert_eq!(result.style.text_gamma, default_text_gamma());
+        assert_eq!(result.style.text_contrast, default_text_contrast());
+        assert_eq!(result.style.unfocused_dim, default_unfocused_dim());
+        assert_eq!(result.style.blinking_text, BlinkingText::Blink);
+        assert!(!result.style.bold_brightens_colors);
         assert_eq!(result.style.font_size, default_font_size());
         assert_eq!(result.style.theme, default_theme());
+        assert_eq!(result.style.light_theme, default_theme());
+        assert_eq!(result.style.dark_theme, default_theme());
         // Colors
         assert_eq!(result.colors, Colors::default());
+        // Background image
+        assert_eq!(result.background_image.path, None);
+        assert_eq!(
+            result.background_image.opacity,
+            default_background_image_opacity()
+        );
+        assert_eq!(result.background_image.mode, BackgroundImageMode::Fill);
 
         // Advanced
         assert_eq!(
@@ -281,9 +1080,65 @@ mod tests {
             default_tab_character_inactive()
         );
         assert!(!result.advanced.disable_render_when_unfocused);
+        assert_eq!(result.advanced.sample_count, default_sample_count());
+        assert_eq!(result.advanced.adapter_name, None);
+        assert!(!result.advanced.force_fallback_adapter);
+        assert!(!result.advanced.latency_mode);
+        // Scroll
+        assert_eq!(result.scroll, Scroll::default());
+        // Bar
+        assert_eq!(result.bar, Bar::default());
+        // Selection
+        assert!(result.selection.primary);
+        assert_eq!(result.selection.word_separators, default_word_separators());
+        assert!(!result.selection.copy_hidden_text);
+        // Session
+        assert!(!result.session.restore);
+        assert!(!result.session.single_instance);
+        // Remote control
+        assert!(!result.remote_control.allow_input);
+        // Features
+        assert_eq!(result.features, Features::default());
+        assert!(!result.features.clipboard_write);
+        // Window
+        assert_eq!(result.window, Window::default());
+        // Confirm quit
+        assert!(result.confirm_quit.enabled);
+        assert!(result.confirm_quit.ignore.is_empty());
+        assert!(result.paste.confirm);
+        assert!(result.paste.strip_escapes);
+        assert!(!result.paste.normalize_newlines);
+        assert!(result.paste.transforms.is_empty());
+        assert!(result.key_bindings.is_empty());
+        assert!(result.mouse_bindings.is_empty());
+        // Shell integration
+        assert_eq!(
+            result.shell_integration.prompt_pattern,
+            default_prompt_pattern()
+        );
+        assert_eq!(
+            result.shell_integration.notify_after,
+            default_notify_after()
+        );
+        assert!(result.shell_integration.command_marks);
+        // Links
+        assert_eq!(result.links.editor, "");
+        assert_eq!(result.links.schemes, ["http", "https", "file", "mailto"]);
+        assert_eq!(result.bell, Bell::default());
+        // Profiles
+        assert!(result.profiles.is_empty());
+        // Dropdown
+        assert_eq!(result.dropdown, Dropdown::default());
+        // Post-processing
+        assert!(result.post_processing.shaders.is_empty());
+        assert!(!result.post_processing.animate);
+        assert_eq!(result.post_processing.color_filter, ColorFilter::None);
+        assert!(!result.accessibility.reader_mode);
+        assert!(!result.accessibility.high_contrast);
         // Developer
         assert_eq!(result.developer.log_level, default_log_level());
         assert!(!result.developer.enable_fps_counter);
+        assert_eq!(result.developer.log_file, None);
     }
 
     #[test]
@@ -326,6 +1181,7 @@ mod tests {
 
         assert_eq!(result.performance, Performance::default());
         assert_eq!(result.env_vars, default_env_vars());
+        assert_eq!(result.term, default_term());
         assert_eq!(result.width, default_width());
         assert_eq!(result.height, default_height());
         assert_eq!(result.cursor, default_cursor());
@@ -365,6 +1221,22 @@ mod tests {
         assert_eq!(result.colors.foreground, colors::defaults::foreground());
         assert_eq!(result.colors.tabs_active, colors::defaults::tabs_active());
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
+        assert_eq!(result.colors.cursor_text, None);
+    }
+
+    #[test]
+    fn test_change_cursor_colors() {
+        let result = create_temporary_config(
+            "change-cursor-colors",
+            r#"
+            [colors]
+            cursor      = '#FFFFFF'
+            cursor-text = '#000000'
+        "#,
+        );
+
+        assert_eq!(result.colors.cursor, hex_to_color_arr("#FFFFFF"));
+        assert_eq!(result.colors.cursor_text, Some(hex_to_color_arr("#000000")));
     }
 
     #[test]
@@ -396,11 +1268,13 @@ mod tests {
             "change-env-vars",
             r#"
             env-vars = ['A=5', 'B=8']
+            term = "xterm-256color"
         "#,
         );
 
         assert_eq!(result.performance, Performance::High);
         assert_eq!(result.env_vars, [String::from("A=5"), String::from("B=8")]);
+        assert_eq!(result.term, "xterm-256color");
         assert_eq!(result.width, default_width());
         assert_eq!(result.height, default_height());
         assert_eq!(result.cursor, default_cursor());
@@ -497,6 +1371,7 @@ mod tests {
             [style]
             font = "Novamono"
             font-size = 14.0
+            font-rasterizer = "Swash"
         "#,
         );
 
@@ -506,6 +1381,7 @@ mod tests {
         // Style
         assert_eq!(result.style.font, "Novamono");
         assert_eq!(result.style.font_size, 14.0);
+        assert_eq!(result.style.font_rasterizer, FontRasterizer::Swash);
         // Colors
         assert_eq!(result.colors.background, colors::defaults::background());
         assert_eq!(result.colors.foreground, colors::defaults::foreground());
@@ -513,6 +1389,97 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_change_text_antialiasing() {
+        let result = create_temporary_config(
+            "change-text-antialiasing",
+            r#"
+            [style]
+            text-antialiasing = "Rgb"
+
+            [style.text-antialiasing-displays]
+            "DELL U2720Q" = "Grayscale"
+            "LG HDR WFHD" = "Bgr"
+        "#,
+        );
+
+        assert_eq!(result.style.text_antialiasing, TextAntialiasing::Rgb);
+        assert_eq!(
+            result.style.text_antialiasing_for(Some("DELL U2720Q")),
+            TextAntialiasing::Grayscale
+        );
+        assert_eq!(
+            result.style.text_antialiasing_for(Some("LG HDR WFHD")),
+            TextAntialiasing::Bgr
+        );
+        assert_eq!(
+            result
+                .style
+                .text_antialiasing_for(Some("Built-in Retina Display")),
+            TextAntialiasing::Rgb
+        );
+        assert_eq!(
+            result.style.text_antialiasing_for(None),
+            TextAntialiasing::Rgb
+        );
+    }
+
+    #[test]
+    fn test_change_text_blending() {
+        let result = create_temporary_config(
+            "change-text-blending",
+            r#"
+            [style]
+            text-gamma = 1.8
+            text-contrast = 0.5
+        "#,
+        );
+
+        assert_eq!(result.style.text_gamma, 1.8);
+        assert_eq!(result.style.text_contrast, 0.5);
+        assert_eq!(result.style.font_size, default_font_size());
+    }
+
+    #[test]
+    fn test_change_unfocused_dim() {
+        let result = create_temporary_config(
+            "change-unfocused-dim",
+            r#"
+            [style]
+            unfocused-dim = 0.4
+        "#,
+        );
+
+        assert_eq!(result.style.unfocused_dim, 0.4);
+        assert_eq!(result.style.text_gamma, default_text_gamma());
+    }
+
+    #[test]
+    fn test_change_blinking_text() {
+        let result = create_temporary_config(
+            "change-blinking-text",
+            r#"
+            [style]
+            blinking-text = "Bold"
+        "#,
+        );
+
+        assert_eq!(result.style.blinking_text, BlinkingText::Bold);
+    }
+
+    #[test]
+    fn test_change_bold_brightens_colors() {
+        let result = create_temporary_config(
+            "change-bold-brightens-colors",
+            r#"
+            [style]
+            bold-brightens-colors = true
+        "#,
+        );
+
+        assert!(result.style.bold_brightens_colors);
+    }
+
     #[test]
     fn test_change_theme() {
         let result = create_temporary_config(
@@ -567,6 +1534,52 @@ mod tests {
         assert_eq!(result.colors.background.0, hex_to_color_arr("#2B3E50"));
     }
 
+    #[test]
+    fn test_appearance_colors() {
+        create_temporary_theme(
+            "test-rio-light",
+            r#"
+            [colors]
+            background = '#FAFAFA'
+        "#,
+        );
+
+        let result = create_temporary_config(
+            "appearance-colors",
+            r#"
+            [style]
+            light-theme = "test-rio-light"
+        "#,
+        );
+
+        assert_eq!(result.style.light_theme, "test-rio-light");
+        let colors = result.appearance_colors_with_themes(false, "/tmp").unwrap();
+        assert_eq!(colors.background.0, hex_to_color_arr("#FAFAFA"));
+        assert_eq!(result.appearance_colors_with_themes(true, "/tmp"), None);
+    }
+
+    #[test]
+    fn test_change_theme_from_other_terminal() {
+        let mut file = std::fs::File::create("/tmp/test-rio-alacritty.yml").unwrap();
+        writeln!(
+            file,
+            "colors:\n  primary:\n    background: '0x1d1f21'\n  normal:\n    red: '#cc6666'"
+        )
+        .unwrap();
+
+        let result = create_temporary_config(
+            "change-theme-from-other-terminal",
+            r#"
+            [style]
+            theme = "test-rio-alacritty.yml"
+        "#,
+        );
+
+        assert_eq!(result.colors.background.0, hex_to_color_arr("#1d1f21"));
+        assert_eq!(result.colors.red, hex_to_color_arr("#cc6666"));
+        assert_eq!(result.colors.cursor, colors::defaults::cursor());
+    }
+
     #[test]
     fn test_change_one_color() {
         let result = create_temporary_config(
@@ -620,6 +1633,9 @@ mod tests {
             light-red        = '#030303'
             light-white      = '#030303'
             light-yellow     = '#030303'
+            selection-background = '#030303'
+            vi-cursor-background = '#FFFFFF'
+            bar-background = '#030303'
         "#,
         );
 
@@ -662,6 +1678,23 @@ mod tests {
         assert_eq!(result.colors.light_red, hex_to_color_arr("#030303"));
         assert_eq!(result.colors.light_white, hex_to_color_arr("#030303"));
         assert_eq!(result.colors.light_yellow, hex_to_color_arr("#030303"));
+        assert_eq!(
+            result.colors.selection_background,
+            hex_to_color_arr("#030303")
+        );
+        assert_eq!(
+            result.colors.vi_cursor_background,
+            hex_to_color_arr("#FFFFFF")
+        );
+        assert_eq!(
+            result.colors.search_match_background,
+            colors::defaults::search_match_background()
+        );
+        assert_eq!(result.colors.bar_background, hex_to_color_arr("#030303"));
+        assert_eq!(
+            result.colors.bar_foreground,
+            colors::defaults::bar_foreground()
+        );
     }
 
     #[test]
@@ -673,6 +1706,10 @@ mod tests {
 
             [advanced]
             disable-render-when-unfocused = true
+            sample-count = 4
+            adapter-name = "NVIDIA"
+            force-fallback-adapter = true
+            latency-mode = true
             tab-character-active = '▲'
             tab-character-inactive = '●'
         "#,
@@ -683,6 +1720,10 @@ mod tests {
         assert_eq!(result.height, default_height());
         // Advanced
         assert!(result.advanced.disable_render_when_unfocused);
+        assert_eq!(result.advanced.sample_count, 4);
+        assert_eq!(result.advanced.adapter_name, Some(String::from("NVIDIA")));
+        assert!(result.advanced.force_fallback_adapter);
+        assert!(result.advanced.latency_mode);
         assert_eq!(result.advanced.tab_character_active, '▲');
         assert_eq!(result.advanced.tab_character_inactive, '●');
 
@@ -693,6 +1734,493 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_change_bar() {
+        let result = create_temporary_config(
+            "change-bar",
+            r#"
+            [bar]
+            enabled = true
+            cwd = true
+            clock = true
+            command = true
+            scrollback = false
+            position = "Bottom"
+            height = 24.0
+            visibility = "MultipleTabs"
+        "#,
+        );
+
+        assert!(result.bar.enabled);
+        assert!(result.bar.title);
+        assert!(result.bar.cwd);
+        assert!(result.bar.clock);
+        assert!(result.bar.command);
+        assert!(!result.bar.scrollback);
+        assert_eq!(result.bar.position, BarPosition::Bottom);
+        assert_eq!(result.bar.height, 24.0);
+        assert_eq!(result.bar.visibility, BarVisibility::MultipleTabs);
+    }
+
+    #[test]
+    fn test_change_scroll() {
+        let result = create_temporary_config(
+            "change-scroll",
+            r#"
+            [scroll]
+            multiplier = 1.5
+            smooth = true
+            indicator = false
+            click-to-jump = true
+            history = 500
+            history-memory-limit = 64
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::High);
+        assert_eq!(result.width, default_width());
+        assert_eq!(result.height, default_height());
+        // Scroll
+        assert_eq!(result.scroll.multiplier, 1.5);
+        assert!(result.scroll.smooth);
+        assert!(!result.scroll.indicator);
+        assert!(result.scroll.click_to_jump);
+        assert_eq!(result.scroll.history, 500);
+        assert_eq!(result.scroll.history_memory_limit, 64);
+    }
+
+    #[test]
+    fn test_change_selection() {
+        let result = create_temporary_config(
+            "change-selection",
+            r#"
+            [selection]
+            primary = false
+            word-separators = " ,;"
+            copy-hidden-text = true
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::High);
+        // Selection
+        assert!(!result.selection.primary);
+        assert_eq!(result.selection.word_separators, " ,;");
+        assert!(result.selection.copy_hidden_text);
+    }
+
+    #[test]
+    fn test_change_session() {
+        let result = create_temporary_config(
+            "change-session",
+            r#"
+            [session]
+            restore = true
+            single-instance = true
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::High);
+        // Session
+        assert!(result.session.restore);
+        assert!(result.session.single_instance);
+    }
+
+    #[test]
+    fn test_change_remote_control() {
+        let result = create_temporary_config(
+            "change-remote-control",
+            r#"
+            [remote-control]
+            allow-input = true
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::High);
+        assert!(result.remote_control.allow_input);
+    }
+
+    #[test]
+    fn test_change_features() {
+        let mut result = create_temporary_config(
+            "change-features",
+            r#"
+            [features]
+            clipboard-write = true
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::High);
+        assert!(result.features.truecolor);
+        assert!(result.features.hyperlinks);
+        assert!(result.features.clipboard_write);
+
+        result.apply_features_env_with(" hyperlinks, unknown,clipboard-write", false);
+        assert!(result.features.truecolor);
+        assert!(!result.features.hyperlinks);
+        assert!(!result.features.clipboard_write);
+
+        result.apply_features_env_with("", true);
+        assert!(!result.features.truecolor);
+    }
+
+    #[test]
+    fn test_change_window() {
+        let result = create_temporary_config(
+            "change-window",
+            r#"
+            [window]
+            decorations = false
+            mode = "Maximized"
+            columns = 120
+            position = [100, -20]
+            monitor = "DELL"
+            remember = true
+            class = "Scratchpad"
+            name = "scratch"
+            resize-increments = true
+            native-tabs = true
+        "#,
+        );
+
+        assert!(!result.window.decorations);
+        assert_eq!(result.window.mode, WindowMode::Maximized);
+        assert_eq!(result.window.columns, Some(120));
+        assert_eq!(result.window.lines, None);
+        assert_eq!(result.window.position, Some([100, -20]));
+        assert_eq!(result.window.monitor, Some(String::from("DELL")));
+        assert!(result.window.remember);
+        assert_eq!(result.window.class, "Scratchpad");
+        assert_eq!(result.window.name, "scratch");
+        assert!(result.window.resize_increments);
+        assert!(result.window.native_tabs);
+        assert_eq!(result.dropdown, Dropdown::default());
+    }
+
+    #[test]
+    fn test_change_confirm_quit() {
+        let result = create_temporary_config(
+            "change-confirm-quit",
+            r#"
+            [confirm-quit]
+            ignore = ["tmux", "htop"]
+        "#,
+        );
+
+        assert!(result.confirm_quit.enabled);
+        assert_eq!(result.confirm_quit.ignore, vec!["tmux", "htop"]);
+    }
+
+    #[test]
+    fn test_change_paste() {
+        let result = create_temporary_config(
+            "change-paste",
+            r#"
+            [paste]
+            confirm = false
+            normalize-newlines = true
+            transforms = ["TrimTrailingWhitespace", "JoinLines"]
+        "#,
+        );
+
+        assert!(!result.paste.confirm);
+        assert!(result.paste.strip_escapes);
+        assert!(result.paste.normalize_newlines);
+        assert_eq!(
+            result.paste.transforms,
+            vec![
+                PasteTransform::TrimTrailingWhitespace,
+                PasteTransform::JoinLines
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_mouse_bindings() {
+        let result = create_temporary_config(
+            "change-mouse-bindings",
+            r#"
+            [[mouse-bindings]]
+            button = "Right"
+            action = "Paste"
+            mode = "~MouseReport"
+
+            [[mouse-bindings]]
+            button = "Middle"
+            with = "Shift"
+            action = "None"
+        "#,
+        );
+
+        assert_eq!(
+            result.mouse_bindings,
+            vec![
+                MouseBinding {
+                    button: String::from("Right"),
+                    with: String::new(),
+                    action: String::from("Paste"),
+                    mode: String::from("~MouseReport"),
+                },
+                MouseBinding {
+                    button: String::from("Middle"),
+                    with: String::from("Shift"),
+                    action: String::from("None"),
+                    mode: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_key_bindings() {
+        let result = create_temporary_config(
+            "change-key-bindings",
+            r#"
+            [[key-bindings]]
+            key = "T"
+            with = "Control|Shift"
+            action = "TabCreateNew"
+
+            [[key-bindings]]
+            key = "Up"
+            with = "Alt"
+            text = "\u001b[1;3A"
+            mode = "~Vi"
+            repeat = false
+
+            [[key-bindings]]
+            chord = "Control+A"
+            key = "V"
+            action = "TabCreateNew"
+        "#,
+        );
+
+        assert_eq!(
+            result.key_bindings,
+            vec![
+                KeyBinding {
+                    key: String::from("T"),
+                    with: String::from("Control|Shift"),
+                    action: String::from("TabCreateNew"),
+                    text: None,
+                    mode: String::new(),
+                    repeat: None,
+                    chord: String::new(),
+                },
+                KeyBinding {
+                    key: String::from("Up"),
+                    with: String::from("Alt"),
+                    action: String::new(),
+                    text: Some(String::from("\x1b[1;3A")),
+                    mode: String::from("~Vi"),
+                    repeat: Some(false),
+                    chord: String::new(),
+                },
+                KeyBinding {
+                    key: String::from("V"),
+                    with: String::new(),
+                    action: String::from("TabCreateNew"),
+                    text: None,
+                    mode: String::new(),
+                    repeat: None,
+                    chord: String::from("Control+A"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_shell_integration() {
+        let result = create_temporary_config(
+            "change-shell-integration",
+            r#"
+            [shell-integration]
+            prompt-pattern = "^λ "
+            notify-after = 0
+            command-marks = false
+        "#,
+        );
+
+        assert_eq!(result.shell_integration.prompt_pattern, "^λ ");
+        assert_eq!(result.shell_integration.notify_after, 0);
+        assert!(!result.shell_integration.command_marks);
+    }
+
+    #[test]
+    fn test_change_links() {
+        let result = create_temporary_config(
+            "change-links",
+            r#"
+            [links]
+            editor = "code --goto {file}:{line}:{column}"
+            schemes = ["https"]
+        "#,
+        );
+
+        assert_eq!(result.links.editor, "code --goto {file}:{line}:{column}");
+        assert_eq!(result.links.schemes, ["https"]);
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        create_temporary_theme(
+            "profile-theme",
+            r#"
+            [colors]
+            background = '#333333'
+        "#,
+        );
+        let mut result = create_temporary_config(
+            "apply-profile",
+            r#"
+            env-vars = ["A=1"]
+
+            [profiles.staging]
+            shell = "/bin/zsh"
+            cwd = "~/staging"
+            env-vars = ["STAGE=1"]
+            theme = "profile-theme"
+            font = "Menlo"
+        "#,
+        );
+
+        assert!(result
+            .apply_profile_with_themes("production", "/tmp")
+            .is_err());
+
+        let profile = result.apply_profile_with_themes("staging", "/tmp").unwrap();
+        assert_eq!(profile.shell.as_deref(), Some("/bin/zsh"));
+        assert_eq!(profile.cwd.as_deref(), Some("~/staging"));
+        assert_eq!(
+            result.env_vars,
+            [String::from("A=1"), String::from("STAGE=1")]
+        );
+        assert_eq!(result.style.font, "Menlo");
+        assert_eq!(result.style.theme, "profile-theme");
+        assert_eq!(result.colors.background.0, hex_to_color_arr("#333333"));
+    }
+
+    #[test]
+    fn test_change_dropdown() {
+        let result = create_temporary_config(
+            "change-dropdown",
+            r#"
+            [dropdown]
+            enabled = true
+            hotkey = "ctrl+grave"
+            height = 0.5
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::High);
+        // Dropdown
+        assert!(result.dropdown.enabled);
+        assert_eq!(result.dropdown.hotkey, "ctrl+grave");
+        assert_eq!(result.dropdown.height, 0.5);
+    }
+
+    #[test]
+    fn test_change_background_image() {
+        let result = create_temporary_config(
+            "change-background-image",
+            r#"
+            [background-image]
+            path = "~/Pictures/wallpaper.png"
+            opacity = 0.3
+            mode = "Tile"
+        "#,
+        );
+
+        assert_eq!(
+            result.background_image.path,
+            Some(String::from("~/Pictures/wallpaper.png"))
+        );
+        assert_eq!(result.background_image.opacity, 0.3);
+        assert_eq!(result.background_image.mode, BackgroundImageMode::Tile);
+        assert_eq!(result.colors, Colors::default());
+    }
+
+    #[test]
+    fn test_change_bell() {
+        let result = create_temporary_config(
+            "change-bell",
+            r#"
+            [bell]
+            audible = true
+            sound = "~/sounds/bell.wav"
+            volume = 0.5
+        "#,
+        );
+
+        assert!(result.bell.audible);
+        assert_eq!(result.bell.sound, "~/sounds/bell.wav");
+        assert_eq!(result.bell.volume, 0.5);
+        assert_eq!(result.bell.cooldown, default_bell_cooldown());
+    }
+
+    #[test]
+    fn test_change_accessibility() {
+        let result = create_temporary_config(
+            "change-accessibility",
+            r#"
+            [accessibility]
+            reader-mode = true
+        "#,
+        );
+
+        assert!(result.accessibility.reader_mode);
+        assert!(!result.accessibility.high_contrast);
+    }
+
+    #[test]
+    fn test_apply_high_contrast() {
+        let mut result = create_temporary_config(
+            "apply-high-contrast",
+            r#"
+            [style]
+            unfocused-dim = 0.4
+
+            [background-image]
+            path = "~/Pictures/wallpaper.png"
+
+            [accessibility]
+            high-contrast = true
+        "#,
+        );
+
+        result.apply_high_contrast();
+        assert_eq!(result.colors, Colors::high_contrast());
+        assert_eq!(result.cursor, '█');
+        assert_eq!(result.style.unfocused_dim, 0.0);
+        assert_eq!(result.background_image.path, None);
+    }
+
+    #[test]
+    fn test_change_post_processing() {
+        let result = create_temporary_config(
+            "change-post-processing",
+            r#"
+            [post-processing]
+            shaders = ["~/.config/rio/shaders/crt.wgsl", "/tmp/bloom.wgsl"]
+            animate = true
+            color-filter = "DaltonizeDeuteranopia"
+        "#,
+        );
+
+        assert_eq!(
+            result.post_processing.shaders,
+            vec![
+                String::from("~/.config/rio/shaders/crt.wgsl"),
+                String::from("/tmp/bloom.wgsl")
+            ]
+        );
+        assert!(result.post_processing.animate);
+        assert_eq!(
+            result.post_processing.color_filter,
+            ColorFilter::DaltonizeDeuteranopia
+        );
+        assert_eq!(result.advanced, Advanced::default());
+    }
+
     #[test]
     fn test_change_developer() {
         let result = create_temporary_config(
@@ -703,6 +2231,7 @@ mod tests {
             [developer]
             enable-fps-counter = true
             log-level = "INFO"
+            log-file = "/tmp/rio.log"
         "#,
         );
 
@@ -711,6 +2240,10 @@ mod tests {
         assert_eq!(result.height, default_height());
         // Developer
         assert_eq!(result.developer.log_level, String::from("INFO"));
+        assert_eq!(
+            result.developer.log_file,
+            Some(String::from("/tmp/rio.log"))
+        );
         assert!(result.developer.enable_fps_counter);
 
         // Colors
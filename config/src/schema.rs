@@ -0,0 +1,1363 @@
+// Keys of the configuration file, with their documentation and how to read
+// them back from a `Config`. It drives `rio config default`, which prints
+// every key commented out, `rio config dump`, which prints the values in
+// effect, and `rio config validate`, which also reports unknown keys since
+// serde silently ignores them.
+
+use crate::{Config, KeyBinding, MouseBinding, Profile};
+use colors::{color_arr_to_hex, ColorArray};
+use std::fmt;
+use toml::{Table, Value};
+
+/// Type of the value of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Bool,
+    Integer,
+    Float,
+    String,
+    Char,
+    /// `#RRGGBB`.
+    Color,
+    /// One of the given names.
+    Enum(&'static [&'static str]),
+    List,
+    /// Table of names chosen by the user, e.g. profiles.
+    Map,
+}
+
+/// Key of the configuration file.
+pub struct Key {
+    /// Dotted path, where `*` stands for any name of a map or any item of a
+    /// list, e.g. `profiles.*.shell`.
+    pub path: &'static str,
+    pub kind: Kind,
+    pub doc: &'static str,
+    /// Value in `config`, `None` while unset and for keys under `*`.
+    value: fn(&Config) -> Option<Value>,
+}
+
+impl Key {
+    pub fn value(&self, config: &Config) -> Option<Value> {
+        (self.value)(config)
+    }
+
+    /// Table the key is written under, empty for top-level keys.
+    fn section(&self) -> &'static str {
+        self.path
+            .rsplit_once('.')
+            .map_or("", |(section, _)| section)
+    }
+
+    fn name(&self) -> &'static str {
+        self.path
+            .rsplit_once('.')
+            .map_or(self.path, |(_, name)| name)
+    }
+}
+
+const fn key(
+    path: &'static str,
+    kind: Kind,
+    doc: &'static str,
+    value: fn(&Config) -> Option<Value>,
+) -> Key {
+    Key {
+        path,
+        kind,
+        doc,
+        value,
+    }
+}
+
+fn unset(_: &Config) -> Option<Value> {
+    None
+}
+
+fn boolean(value: bool) -> Option<Value> {
+    Some(Value::Boolean(value))
+}
+
+fn integer<T: TryInto<i64>>(value: T) -> Option<Value> {
+    value.try_into().ok().map(Value::Integer)
+}
+
+/// Written with the digits of the `f32`, e.g. `0.4` rather than the
+/// `0.4000000059604645` of its `f64` conversion.
+fn float(value: f32) -> Option<Value> {
+    value.to_string().parse().ok().map(Value::Float)
+}
+
+fn string<T: ToString>(value: T) -> Option<Value> {
+    Some(Value::String(value.to_string()))
+}
+
+fn optional_string(value: &Option<String>) -> Option<Value> {
+    value.as_ref().and_then(string)
+}
+
+/// Name of an enum variant, as read by serde.
+fn variant<T: fmt::Debug>(value: T) -> Option<Value> {
+    string(format!("{value:?}"))
+}
+
+fn list<T: fmt::Display>(values: &[T]) -> Option<Value> {
+    Some(Value::Array(values.iter().filter_map(string).collect()))
+}
+
+fn color(value: ColorArray) -> Option<Value> {
+    string(color_arr_to_hex(value))
+}
+
+fn profiles(config: &Config) -> Option<Value> {
+    let profile_table = |profile: &Profile| {
+        let mut table = Table::new();
+        let values = [
+            ("shell", optional_string(&profile.shell)),
+            ("cwd", optional_string(&profile.cwd)),
+            ("env-vars", list(&profile.env_vars)),
+            ("theme", optional_string(&profile.theme)),
+            ("font", optional_string(&profile.font)),
+        ];
+        for (name, value) in values {
+            if let Some(value) = value {
+                table.insert(name.to_string(), value);
+            }
+        }
+        Value::Table(table)
+    };
+
+    let mut names = config.profiles.keys().collect::<Vec<_>>();
+    names.sort();
+    Some(Value::Table(
+        names
+            .into_iter()
+            .map(|name| (name.clone(), profile_table(&config.profiles[name])))
+            .collect(),
+    ))
+}
+
+fn key_bindings(config: &Config) -> Option<Value> {
+    let binding_table = |binding: &KeyBinding| {
+        let mut table = Table::new();
+        table.insert(String::from("key"), Value::from(binding.key.as_str()));
+        table.insert(String::from("with"), Value::from(binding.with.as_str()));
+        table.insert(String::from("action"), Value::from(binding.action.as_str()));
+        if let Some(text) = &binding.text {
+            table.insert(String::from("text"), Value::from(text.as_str()));
+        }
+        table.insert(String::from("mode"), Value::from(binding.mode.as_str()));
+        if let Some(repeat) = binding.repeat {
+            table.insert(String::from("repeat"), Value::Boolean(repeat));
+        }
+        if !binding.chord.is_empty() {
+            table.insert(String::from("chord"), Value::from(binding.chord.as_str()));
+        }
+        Value::Table(table)
+    };
+
+    Some(Value::Array(
+        config.key_bindings.iter().map(binding_table).collect(),
+    ))
+}
+
+fn mouse_bindings(config: &Config) -> Option<Value> {
+    let binding_table = |binding: &MouseBinding| {
+        let mut table = Table::new();
+        table.insert(String::from("button"), Value::from(binding.button.as_str()));
+        table.insert(String::from("with"), Value::from(binding.with.as_str()));
+        table.insert(String::from("action"), Value::from(binding.action.as_str()));
+        table.insert(String::from("mode"), Value::from(binding.mode.as_str()));
+        Value::Table(table)
+    };
+
+    Some(Value::Array(
+        config.mouse_bindings.iter().map(binding_table).collect(),
+    ))
+}
+
+fn text_antialiasing_displays(config: &Config) -> Option<Value> {
+    let mut displays = config
+        .style
+        .text_antialiasing_displays
+        .iter()
+        .collect::<Vec<_>>();
+    displays.sort_by_key(|(display, _)| *display);
+    Some(Value::Table(
+        displays
+            .into_iter()
+            .filter_map(|(display, mode)| Some((display.clone(), variant(mode)?)))
+            .collect(),
+    ))
+}
+
+const TEXT_ANTIALIASING: Kind = Kind::Enum(&["Grayscale", "Rgb", "Bgr"]);
+
+/// Every key, top-level ones first and the others grouped by table.
+pub static KEYS: &[Key] = &[
+    key(
+        "performance",
+        Kind::Enum(&["High", "Low"]),
+        "GPU used to render, the most powerful one or the one using the least power.",
+        |c| variant(c.performance),
+    ),
+    key(
+        "width",
+        Kind::Integer,
+        "Startup width in logical pixels.",
+        |c| integer(c.width),
+    ),
+    key(
+        "height",
+        Kind::Integer,
+        "Startup height in logical pixels.",
+        |c| integer(c.height),
+    ),
+    key(
+        "cursor",
+        Kind::Char,
+        "Shape of the cursor: '█' (block), '_' (underline) or '|' (beam).",
+        |c| string(c.cursor),
+    ),
+    key(
+        "env-vars",
+        Kind::List,
+        "Set in the environment of the shell, e.g. [\"FOO=bar\"].",
+        |c| list(&c.env_vars),
+    ),
+    key(
+        "term",
+        Kind::String,
+        "TERM of the shell, rio falls back to xterm-256color when the rio terminfo entry is not installed.",
+        |c| string(&c.term),
+    ),
+    key(
+        "option-as-alt",
+        Kind::Enum(&["None", "Left", "Right", "Both"]),
+        "Option keys sending Alt on macOS.",
+        |c| string(&c.option_as_alt),
+    ),
+    key(
+        "key-bindings",
+        Kind::List,
+        "Keys running actions or writing text, e.g. [{ key = \"T\", with = \"Control|Shift\", action = \"TabCreateNew\" }].",
+        key_bindings,
+    ),
+    key(
+        "key-bindings.*.key",
+        Kind::String,
+        "Named like in rio msg send-key, e.g. T or PageUp.",
+        unset,
+    ),
+    key(
+        "key-bindings.*.with",
+        Kind::String,
+        "Modifiers held, e.g. Control|Shift.",
+        unset,
+    ),
+    key(
+        "key-bindings.*.action",
+        Kind::String,
+        "Action run, unless text is set.",
+        unset,
+    ),
+    key(
+        "key-bindings.*.text",
+        Kind::String,
+        "Written to the terminal instead of running an action.",
+        unset,
+    ),
+    key(
+        "key-bindings.*.mode",
+        Kind::String,
+        "Modes the binding applies in, e.g. Vi or ~AltScreen|~Vi.",
+        unset,
+    ),
+    key(
+        "key-bindings.*.repeat",
+        Kind::Bool,
+        "Whether holding the key repeats the binding, by default only text and scrolling do.",
+        unset,
+    ),
+    key(
+        "key-bindings.*.chord",
+        Kind::String,
+        "Keys pressed one after the other before key, e.g. Control+A.",
+        unset,
+    ),
+    key(
+        "mouse-bindings",
+        Kind::List,
+        "Actions run by clicking a mouse button, e.g. [{ button = \"Middle\", action = \"Paste\" }].",
+        mouse_bindings,
+    ),
+    key(
+        "mouse-bindings.*.button",
+        Kind::String,
+        "Left, Middle or Right.",
+        unset,
+    ),
+    key(
+        "mouse-bindings.*.with",
+        Kind::String,
+        "Modifiers held, e.g. Control|Shift.",
+        unset,
+    ),
+    key("mouse-bindings.*.action", Kind::String, "Action run.", unset),
+    key(
+        "mouse-bindings.*.mode",
+        Kind::String,
+        "MouseReport to only apply while the application reports the mouse, ~MouseReport while it doesn't.",
+        unset,
+    ),
+    key(
+        "profiles",
+        Kind::Map,
+        "Named presets applied with `rio --profile <name>`, e.g. { work = { cwd = \"~/work\" } }.",
+        profiles,
+    ),
+    key(
+        "profiles.*.shell",
+        Kind::String,
+        "Program run instead of $SHELL.",
+        unset,
+    ),
+    key(
+        "profiles.*.cwd",
+        Kind::String,
+        "Working directory of the shell, may start with ~/.",
+        unset,
+    ),
+    key(
+        "profiles.*.env-vars",
+        Kind::List,
+        "Added to env-vars.",
+        unset,
+    ),
+    key("profiles.*.theme", Kind::String, "Replaces style.theme.", unset),
+    key("profiles.*.font", Kind::String, "Replaces style.font.", unset),
+    key(
+        "style.font-size",
+        Kind::Float,
+        "In logical pixels.",
+        |c| float(c.style.font_size),
+    ),
+    key(
+        "style.theme",
+        Kind::String,
+        "Name of a theme in ~/.rio/themes, without its extension.",
+        |c| string(&c.style.theme),
+    ),
+    key(
+        "style.light-theme",
+        Kind::String,
+        "Used instead of theme while the system appearance is light.",
+        |c| string(&c.style.light_theme),
+    ),
+    key(
+        "style.dark-theme",
+        Kind::String,
+        "Used instead of theme while the system appearance is dark.",
+        |c| string(&c.style.dark_theme),
+    ),
+    key("style.font", Kind::String, "Font family.", |c| {
+        string(&c.style.font)
+    }),
+    key(
+        "style.font-rasterizer",
+        Kind::Enum(&["AbGlyph", "Swash"]),
+        "Library rasterizing the glyphs.",
+        |c| variant(c.style.font_rasterizer),
+    ),
+    key(
+        "style.text-antialiasing",
+        TEXT_ANTIALIASING,
+        "Grayscale, or subpixel antialiasing for RGB or BGR displays.",
+        |c| variant(c.style.text_antialiasing),
+    ),
+    key(
+        "style.text-gamma",
+        Kind::Float,
+        "Gamma of the glyph coverage.",
+        |c| float(c.style.text_gamma),
+    ),
+    key(
+        "style.text-contrast",
+        Kind::Float,
+        "Contrast added to thin glyph strokes.",
+        |c| float(c.style.text_contrast),
+    ),
+    key(
+        "style.unfocused-dim",
+        Kind::Float,
+        "How much colors of unfocused windows are dimmed, from 0.0 to 1.0.",
+        |c| float(c.style.unfocused_dim),
+    ),
+    key(
+        "style.blinking-text",
+        Kind::Enum(&["Blink", "Bold", "Normal"]),
+        "How text with the blink attribute is drawn.",
+        |c| variant(c.style.blinking_text),
+    ),
+    key(
+        "style.bold-brightens-colors",
+        Kind::Bool,
+        "Draw bold text in one of the eight normal colors in its bright variant.",
+        |c| boolean(c.style.bold_brightens_colors),
+    ),
+    key(
+        "style.text-antialiasing-displays",
+        Kind::Map,
+        "Overrides text-antialiasing by display name, e.g. { \"DELL U2720Q\" = \"Rgb\" }.",
+        text_antialiasing_displays,
+    ),
+    key(
+        "style.text-antialiasing-displays.*",
+        TEXT_ANTIALIASING,
+        "Antialiasing of the display.",
+        unset,
+    ),
+    key(
+        "colors.background",
+        Kind::Color,
+        "Colors are written #RRGGBB.",
+        |c| color(c.colors.background.0),
+    ),
+    key("colors.foreground", Kind::Color, "", |c| {
+        color(c.colors.foreground)
+    }),
+    key("colors.black", Kind::Color, "", |c| color(c.colors.black)),
+    key("colors.blue", Kind::Color, "", |c| color(c.colors.blue)),
+    key("colors.cyan", Kind::Color, "", |c| color(c.colors.cyan)),
+    key("colors.green", Kind::Color, "", |c| color(c.colors.green)),
+    key("colors.magenta", Kind::Color, "", |c| color(c.colors.magenta)),
+    key("colors.red", Kind::Color, "", |c| color(c.colors.red)),
+    key("colors.white", Kind::Color, "", |c| color(c.colors.white)),
+    key("colors.yellow", Kind::Color, "", |c| color(c.colors.yellow)),
+    key("colors.cursor", Kind::Color, "", |c| color(c.colors.cursor)),
+    key(
+        "colors.cursor-text",
+        Kind::Color,
+        "Text under a block cursor, the background of its cell when unset.",
+        |c| c.colors.cursor_text.and_then(color),
+    ),
+    key("colors.tabs", Kind::Color, "", |c| color(c.colors.tabs)),
+    key("colors.tabs-active", Kind::Color, "", |c| {
+        color(c.colors.tabs_active)
+    }),
+    key("colors.dim-black", Kind::Color, "", |c| {
+        color(c.colors.dim_black)
+    }),
+    key("colors.dim-blue", Kind::Color, "", |c| color(c.colors.dim_blue)),
+    key("colors.dim-cyan", Kind::Color, "", |c| color(c.colors.dim_cyan)),
+    key("colors.dim-foreground", Kind::Color, "", |c| {
+        color(c.colors.dim_foreground)
+    }),
+    key("colors.dim-green", Kind::Color, "", |c| {
+        color(c.colors.dim_green)
+    }),
+    key("colors.dim-magenta", Kind::Color, "", |c| {
+        color(c.colors.dim_magenta)
+    }),
+    key("colors.dim-red", Kind::Color, "", |c| color(c.colors.dim_red)),
+    key("colors.dim-white", Kind::Color, "", |c| {
+        color(c.colors.dim_white)
+    }),
+    key("colors.dim-yellow", Kind::Color, "", |c| {
+        color(c.colors.dim_yellow)
+    }),
+    key("colors.light-black", Kind::Color, "", |c| {
+        color(c.colors.light_black)
+    }),
+    key("colors.light-blue", Kind::Color, "", |c| {
+        color(c.colors.light_blue)
+    }),
+    key("colors.light-cyan", Kind::Color, "", |c| {
+        color(c.colors.light_cyan)
+    }),
+    key("colors.light-foreground", Kind::Color, "", |c| {
+        color(c.colors.light_foreground)
+    }),
+    key("colors.light-green", Kind::Color, "", |c| {
+        color(c.colors.light_green)
+    }),
+    key("colors.light-magenta", Kind::Color, "", |c| {
+        color(c.colors.light_magenta)
+    }),
+    key("colors.light-red", Kind::Color, "", |c| {
+        color(c.colors.light_red)
+    }),
+    key("colors.light-white", Kind::Color, "", |c| {
+        color(c.colors.light_white)
+    }),
+    key("colors.light-yellow", Kind::Color, "", |c| {
+        color(c.colors.light_yellow)
+    }),
+    key("colors.selection-background", Kind::Color, "", |c| {
+        color(c.colors.selection_background)
+    }),
+    key("colors.selection-foreground", Kind::Color, "", |c| {
+        color(c.colors.selection_foreground)
+    }),
+    key("colors.search-match-background", Kind::Color, "", |c| {
+        color(c.colors.search_match_background)
+    }),
+    key("colors.hyperlink-hover-background", Kind::Color, "", |c| {
+        color(c.colors.hyperlink_hover_background)
+    }),
+    key("colors.vi-cursor-background", Kind::Color, "", |c| {
+        color(c.colors.vi_cursor_background)
+    }),
+    key("colors.bar-background", Kind::Color, "", |c| {
+        color(c.colors.bar_background)
+    }),
+    key("colors.bar-foreground", Kind::Color, "", |c| {
+        color(c.colors.bar_foreground)
+    }),
+    key(
+        "background-image.path",
+        Kind::String,
+        "PNG or JPEG image drawn behind the text.",
+        |c| optional_string(&c.background_image.path),
+    ),
+    key(
+        "background-image.opacity",
+        Kind::Float,
+        "From 0.0 to 1.0.",
+        |c| float(c.background_image.opacity),
+    ),
+    key(
+        "background-image.mode",
+        Kind::Enum(&["Fit", "Fill", "Tile"]),
+        "How the image covers the window.",
+        |c| variant(c.background_image.mode),
+    ),
+    key(
+        "scroll.multiplier",
+        Kind::Float,
+        "Lines scrolled by a step of the mouse wheel.",
+        |c| Some(Value::Float(c.scroll.multiplier)),
+    ),
+    key(
+        "scroll.smooth",
+        Kind::Bool,
+        "Scroll by pixels instead of whole lines.",
+        |c| boolean(c.scroll.smooth),
+    ),
+    key(
+        "scroll.indicator",
+        Kind::Bool,
+        "Show the position in the scrollback while scrolled.",
+        |c| boolean(c.scroll.indicator),
+    ),
+    key(
+        "scroll.click-to-jump",
+        Kind::Bool,
+        "Jump to the position clicked on the indicator.",
+        |c| boolean(c.scroll.click_to_jump),
+    ),
+    key(
+        "scroll.history",
+        Kind::Integer,
+        "Lines kept in the scrollback.",
+        |c| integer(c.scroll.history),
+    ),
+    key(
+        "scroll.history-memory-limit",
+        Kind::Integer,
+        "Megabytes the scrollback may take, unlimited when zero.",
+        |c| integer(c.scroll.history_memory_limit),
+    ),
+    key("bar.enabled", Kind::Bool, "Draw the bar.", |c| {
+        boolean(c.bar.enabled)
+    }),
+    key("bar.title", Kind::Bool, "Show the title on the left.", |c| {
+        boolean(c.bar.title)
+    }),
+    key(
+        "bar.cwd",
+        Kind::Bool,
+        "Show the working directory reported by the shell with OSC 7.",
+        |c| boolean(c.bar.cwd),
+    ),
+    key("bar.clock", Kind::Bool, "Show the time.", |c| {
+        boolean(c.bar.clock)
+    }),
+    key(
+        "bar.command",
+        Kind::Bool,
+        "Show the exit status and duration of the last command.",
+        |c| boolean(c.bar.command),
+    ),
+    key(
+        "bar.scrollback",
+        Kind::Bool,
+        "Show the lines scrolled into the scrollback.",
+        |c| boolean(c.bar.scrollback),
+    ),
+    key(
+        "bar.position",
+        Kind::Enum(&["Top", "Bottom"]),
+        "Edge of the window the bar is drawn at.",
+        |c| variant(c.bar.position),
+    ),
+    key("bar.height", Kind::Float, "In logical pixels.", |c| {
+        float(c.bar.height)
+    }),
+    key(
+        "bar.visibility",
+        Kind::Enum(&["Always", "AutoHide", "MultipleTabs"]),
+        "When the bar is shown.",
+        |c| variant(c.bar.visibility),
+    ),
+    key(
+        "selection.primary",
+        Kind::Bool,
+        "Copy selected text to the primary selection.",
+        |c| boolean(c.selection.primary),
+    ),
+    key(
+        "selection.word-separators",
+        Kind::String,
+        "Characters ending the word selected by double-clicking.",
+        |c| string(&c.selection.word_separators),
+    ),
+    key(
+        "selection.copy-hidden-text",
+        Kind::Bool,
+        "Copy text hidden with SGR 8, instead of spaces.",
+        |c| boolean(c.selection.copy_hidden_text),
+    ),
+    key(
+        "session.restore",
+        Kind::Bool,
+        "Reopen the windows and tabs of the last session.",
+        |c| boolean(c.session.restore),
+    ),
+    key(
+        "session.single-instance",
+        Kind::Bool,
+        "Launch new windows from the running instance.",
+        |c| boolean(c.session.single_instance),
+    ),
+    key(
+        "remote-control.allow-input",
+        Kind::Bool,
+        "Allow `rio msg send-text` and `send-key` to write to the terminal.",
+        |c| boolean(c.remote_control.allow_input),
+    ),
+    key(
+        "features.truecolor",
+        Kind::Bool,
+        "Set COLORTERM=truecolor and report Tc to XTGETTCAP.",
+        |c| boolean(c.features.truecolor),
+    ),
+    key(
+        "features.hyperlinks",
+        Kind::Bool,
+        "Highlight and open hyperlinks set by applications (OSC 8).",
+        |c| boolean(c.features.hyperlinks),
+    ),
+    key(
+        "features.clipboard-write",
+        Kind::Bool,
+        "Let applications write to the clipboard (OSC 52), off by default.",
+        |c| boolean(c.features.clipboard_write),
+    ),
+    key(
+        "window.decorations",
+        Kind::Bool,
+        "Without decorations the bar moves the window and the edges resize it.",
+        |c| boolean(c.window.decorations),
+    ),
+    key(
+        "window.mode",
+        Kind::Enum(&["Windowed", "Maximized", "Fullscreen"]),
+        "State of the window at startup.",
+        |c| variant(c.window.mode),
+    ),
+    key(
+        "window.columns",
+        Kind::Integer,
+        "Startup width in columns, instead of width.",
+        |c| c.window.columns.and_then(integer),
+    ),
+    key(
+        "window.lines",
+        Kind::Integer,
+        "Startup height in lines, instead of height.",
+        |c| c.window.lines.and_then(integer),
+    ),
+    key(
+        "window.position",
+        Kind::List,
+        "Logical pixels from the top left corner of the monitor, e.g. [0, 0], centered when unset.",
+        |c| {
+            let position = c.window.position?;
+            Some(Value::Array(position.iter().filter_map(|&p| integer(p)).collect()))
+        },
+    ),
+    key(
+        "window.monitor",
+        Kind::String,
+        "Part of the name of the monitor to open on, the primary one when unset.",
+        |c| optional_string(&c.window.monitor),
+    ),
+    key(
+        "window.remember",
+        Kind::Bool,
+        "Save the position and size on exit and open with them next time.",
+        |c| boolean(c.window.remember),
+    ),
+    key(
+        "window.class",
+        Kind::String,
+        "General part of WM_CLASS on X11 and the app ID on Wayland.",
+        |c| string(&c.window.class),
+    ),
+    key(
+        "window.name",
+        Kind::String,
+        "Instance part of WM_CLASS on X11.",
+        |c| string(&c.window.name),
+    ),
+    key(
+        "window.resize-increments",
+        Kind::Bool,
+        "Resize the window by whole cells, where the platform supports it.",
+        |c| boolean(c.window.resize_increments),
+    ),
+    key(
+        "window.native-tabs",
+        Kind::Bool,
+        "Keep native macOS window tabbing, with its tab bar and menu items.",
+        |c| boolean(c.window.native_tabs),
+    ),
+    key(
+        "confirm-quit.enabled",
+        Kind::Bool,
+        "Ask before closing while a program runs in the foreground of the shell.",
+        |c| boolean(c.confirm_quit.enabled),
+    ),
+    key(
+        "confirm-quit.ignore",
+        Kind::List,
+        "Programs which are fine to close without asking, e.g. [\"tmux\"].",
+        |c| list(&c.confirm_quit.ignore),
+    ),
+    key(
+        "paste.confirm",
+        Kind::Bool,
+        "Ask before pasting text with newlines or control characters without bracketed paste.",
+        |c| boolean(c.paste.confirm),
+    ),
+    key(
+        "paste.strip-escapes",
+        Kind::Bool,
+        "Remove ESC and C1 control characters, which could start escape sequences.",
+        |c| boolean(c.paste.strip_escapes),
+    ),
+    key(
+        "paste.normalize-newlines",
+        Kind::Bool,
+        "Paste Windows line endings as \\n.",
+        |c| boolean(c.paste.normalize_newlines),
+    ),
+    key(
+        "paste.transforms",
+        Kind::List,
+        "Applied in order: TrimTrailingWhitespace, TrimTrailingNewline or JoinLines.",
+        |c| {
+            let transforms = c.paste.transforms.iter().filter_map(variant);
+            Some(Value::Array(transforms.collect()))
+        },
+    ),
+    key(
+        "shell-integration.prompt-pattern",
+        Kind::String,
+        "Regular expression matching the lines where a prompt starts, used when the shell doesn't mark them. Empty to disable.",
+        |c| string(&c.shell_integration.prompt_pattern),
+    ),
+    key(
+        "shell-integration.notify-after",
+        Kind::Integer,
+        "Seconds a command has to run to notify when it finishes unfocused. Zero to disable.",
+        |c| integer(c.shell_integration.notify_after),
+    ),
+    key(
+        "shell-integration.command-marks",
+        Kind::Bool,
+        "Mark the rows where commands were entered in the left margin.",
+        |c| boolean(c.shell_integration.command_marks),
+    ),
+    key(
+        "links.editor",
+        Kind::String,
+        "Command opening path:line links, with {file}, {line} and {column} replaced. Empty to use $EDITOR.",
+        |c| string(&c.links.editor),
+    ),
+    key(
+        "links.schemes",
+        Kind::List,
+        "Schemes of the URLs which are opened, others are ignored.",
+        |c| list(&c.links.schemes),
+    ),
+    key("bell.audible", Kind::Bool, "Play a sound.", |c| {
+        boolean(c.bell.audible)
+    }),
+    key(
+        "bell.sound",
+        Kind::String,
+        "Path of the sound file, may start with ~/. Empty for the chime of the system.",
+        |c| string(&c.bell.sound),
+    ),
+    key("bell.volume", Kind::Float, "From 0.0 to 1.0.", |c| {
+        float(c.bell.volume)
+    }),
+    key(
+        "bell.cooldown",
+        Kind::Integer,
+        "Milliseconds during which further bells are not heard.",
+        |c| integer(c.bell.cooldown),
+    ),
+    key(
+        "dropdown.enabled",
+        Kind::Bool,
+        "Slide the window from the top of the screen with the hotkey.",
+        |c| boolean(c.dropdown.enabled),
+    ),
+    key("dropdown.hotkey", Kind::String, "Global hotkey.", |c| {
+        string(&c.dropdown.hotkey)
+    }),
+    key(
+        "dropdown.height",
+        Kind::Float,
+        "Height of the window as a fraction of the screen height.",
+        |c| float(c.dropdown.height),
+    ),
+    key(
+        "advanced.tab-character-active",
+        Kind::Char,
+        "Drawn for the active tab.",
+        |c| string(c.advanced.tab_character_active),
+    ),
+    key(
+        "advanced.tab-character-inactive",
+        Kind::Char,
+        "Drawn for the other tabs.",
+        |c| string(c.advanced.tab_character_inactive),
+    ),
+    key(
+        "advanced.disable-render-when-unfocused",
+        Kind::Bool,
+        "Stop drawing while the window is unfocused.",
+        |c| boolean(c.advanced.disable_render_when_unfocused),
+    ),
+    key(
+        "advanced.sample-count",
+        Kind::Integer,
+        "Samples of the multisample antialiasing.",
+        |c| integer(c.advanced.sample_count),
+    ),
+    key(
+        "advanced.adapter-name",
+        Kind::String,
+        "Use the first GPU whose name contains this, e.g. \"NVIDIA\" or \"Intel\".",
+        |c| optional_string(&c.advanced.adapter_name),
+    ),
+    key(
+        "advanced.force-fallback-adapter",
+        Kind::Bool,
+        "Render on the CPU.",
+        |c| boolean(c.advanced.force_fallback_adapter),
+    ),
+    key(
+        "advanced.latency-mode",
+        Kind::Bool,
+        "Draw the echo of a keystroke as soon as it's parsed.",
+        |c| boolean(c.advanced.latency_mode),
+    ),
+    key(
+        "post-processing.shaders",
+        Kind::List,
+        "Paths of WGSL shaders, applied in order.",
+        |c| list(&c.post_processing.shaders),
+    ),
+    key(
+        "post-processing.color-filter",
+        Kind::Enum(&[
+            "None",
+            "Protanopia",
+            "Deuteranopia",
+            "Tritanopia",
+            "DaltonizeProtanopia",
+            "DaltonizeDeuteranopia",
+            "DaltonizeTritanopia",
+        ]),
+        "Applied after the shaders.",
+        |c| variant(c.post_processing.color_filter),
+    ),
+    key(
+        "post-processing.animate",
+        Kind::Bool,
+        "Keep redrawing so shaders can animate with uniforms.time.",
+        |c| boolean(c.post_processing.animate),
+    ),
+    key(
+        "accessibility.reader-mode",
+        Kind::Bool,
+        "Read lines of output aloud as they are printed.",
+        |c| boolean(c.accessibility.reader_mode),
+    ),
+    key(
+        "accessibility.high-contrast",
+        Kind::Bool,
+        "Replace the colors with a high-contrast palette.",
+        |c| boolean(c.accessibility.high_contrast),
+    ),
+    key(
+        "developer.enable-fps-counter",
+        Kind::Bool,
+        "Show the frames per second.",
+        |c| boolean(c.developer.enable_fps_counter),
+    ),
+    key(
+        "developer.log-level",
+        Kind::Enum(&["OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"]),
+        "Messages logged.",
+        |c| string(&c.developer.log_level),
+    ),
+    key(
+        "developer.log-file",
+        Kind::String,
+        "Write the log to this file instead of the standard error.",
+        |c| optional_string(&c.developer.log_file),
+    ),
+];
+
+/// Key at `path`, where names of maps and items of lists are written `*`.
+pub fn find(path: &str) -> Option<&'static Key> {
+    KEYS.iter().find(|key| key.path == path)
+}
+
+/// Whether `path` is a table holding keys, e.g. `style`.
+fn is_table(path: &str) -> bool {
+    KEYS.iter().any(|key| {
+        key.path
+            .strip_prefix(path)
+            .map_or(false, |rest| rest.starts_with('.'))
+    })
+}
+
+/// Tables in the order of `KEYS`, the top-level one first.
+fn sections() -> Vec<&'static str> {
+    let mut sections = vec![""];
+    for key in KEYS {
+        if !key.path.contains('*') && !sections.contains(&key.section()) {
+            sections.push(key.section());
+        }
+    }
+    sections
+}
+
+/// Write `text` as comments of at most 78 columns.
+fn write_comment(out: &mut String, text: &str) {
+    let mut line = String::from("#");
+    for word in text.split_whitespace() {
+        if line.len() > 1 && line.chars().count() + 1 + word.chars().count() > 78 {
+            out.push_str(&line);
+            out.push('\n');
+            line = String::from("#");
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
+
+/// Write the keys of `config`, commented out with their documentation
+/// when `commented`.
+fn write(config: &Config, commented: bool) -> String {
+    let mut out = String::new();
+    for section in sections() {
+        let keys = KEYS
+            .iter()
+            .filter(|key| key.section() == section && !key.path.contains('*'));
+        let mut header = (!section.is_empty()).then(|| format!("[{section}]\n"));
+        for key in keys {
+            let value = key.value(config);
+            if value.is_none() && !commented {
+                continue;
+            }
+
+            if let Some(header) = header.take() {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(if commented { "# " } else { "" });
+                out.push_str(&header);
+            }
+            if commented {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                write_comment(&mut out, key.doc);
+                if let Kind::Enum(names) = key.kind {
+                    write_comment(&mut out, &format!("Options: {}.", names.join(", ")));
+                }
+            }
+
+            match value {
+                Some(value) if commented => {
+                    out.push_str(&format!("# {} = {value}\n", key.name()))
+                }
+                Some(value) => out.push_str(&format!("{} = {value}\n", key.name())),
+                None => out.push_str(&format!("# {} is unset\n", key.name())),
+            }
+        }
+    }
+    out
+}
+
+/// Every key with its default value and documentation, commented out, as
+/// a starting point for `~/.rio/config.toml`.
+pub fn default_config() -> String {
+    let mut out = String::new();
+    write_comment(
+        &mut out,
+        "Configuration of Rio, read from ~/.rio/config.toml. Uncomment the \
+         keys to change, the values shown are the defaults.",
+    );
+    out.push('\n');
+    out.push_str(&write(&Config::default(), true));
+    out
+}
+
+/// Values of `config`, e.g. after profiles and the environment changed it,
+/// without the keys which are unset.
+pub fn dump(config: &Config) -> String {
+    write(config, false)
+}
+
+/// Error found in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Line and column, starting from 1, when known.
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl Problem {
+    fn at_offset(content: &str, offset: usize, message: String) -> Problem {
+        let before = &content[..offset.min(content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        Problem {
+            location: Some((line, column)),
+            message,
+        }
+    }
+
+    fn from_toml(content: &str, err: &toml::de::Error) -> Problem {
+        let message = err.message().trim().to_string();
+        match err.span() {
+            Some(span) => Problem::at_offset(content, span.start, message),
+            None => Problem {
+                location: None,
+                message,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "{line}:{column}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Dotted key of a `key = value` line or a `[table]` header.
+fn parse_key(text: &str) -> String {
+    text.split('.')
+        .map(|part| part.trim().trim_matches(|c| c == '"' || c == '\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Offset of each key written in `content`, by dotted path. Items of lists
+/// of tables share the path of the list.
+fn key_offsets(content: &str) -> Vec<(String, usize)> {
+    let mut offsets = Vec::new();
+    let mut table = String::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_start();
+        let start = offset + line.len() - text.len();
+        offset += line.len();
+
+        if text.starts_with('[') {
+            let header = text.trim_start_matches('[');
+            let header = header.split(']').next().unwrap_or_default();
+            table = parse_key(header);
+            offsets.push((table.clone(), start));
+        } else if let Some((key, _)) = text.split_once('=') {
+            if !text.starts_with('#') {
+                let key = parse_key(key);
+                let path = if table.is_empty() {
+                    key
+                } else {
+                    format!("{table}.{key}")
+                };
+                offsets.push((path, start));
+            }
+        }
+    }
+    offsets
+}
+
+/// Report the keys of `table` which are not in `KEYS`. `path` is the
+/// dotted path of `table`, and `schema_path` the same with `*` for the
+/// names of maps and items of lists.
+fn unknown_keys(
+    table: &Table,
+    path: &str,
+    schema_path: &str,
+    found: &mut Vec<(String, String)>,
+) {
+    let join = |prefix: &str, name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{prefix}.{name}")
+        }
+    };
+
+    for (name, value) in table {
+        let path = join(path, name);
+        let schema_path = join(schema_path, name);
+        let children = join(&schema_path, "*");
+        let has_children = is_table(&children) || find(&children).is_some();
+        match (find(&schema_path), value) {
+            (Some(_), Value::Table(table)) if has_children => {
+                for (item, value) in table {
+                    if let Value::Table(table) = value {
+                        unknown_keys(table, &join(&path, item), &children, found);
+                    }
+                }
+            }
+            (Some(_), Value::Array(items)) if has_children => {
+                for value in items {
+                    if let Value::Table(table) = value {
+                        unknown_keys(table, &path, &children, found);
+                    }
+                }
+            }
+            (Some(_), _) => (),
+            (None, Value::Table(table)) if is_table(&schema_path) => {
+                unknown_keys(table, &path, &schema_path, found)
+            }
+            (None, _) => found.push((path, schema_path)),
+        }
+    }
+}
+
+/// Check the configuration file `content`, returning the configuration it
+/// describes or every problem found: syntax errors, values of the wrong
+/// type and unknown keys.
+pub fn validate(content: &str) -> Result<Config, Vec<Problem>> {
+    let table = toml::from_str::<Table>(content)
+        .map_err(|err| vec![Problem::from_toml(content, &err)])?;
+
+    let mut found = Vec::new();
+    unknown_keys(&table, "", "", &mut found);
+    let offsets = key_offsets(content);
+    let mut problems = found
+        .into_iter()
+        .map(|(path, _)| {
+            let message = format!("unknown key `{path}`");
+            match offsets.iter().find(|(key, _)| *key == path) {
+                Some((_, offset)) => Problem::at_offset(content, *offset, message),
+                None => Problem {
+                    location: None,
+                    message,
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match toml::from_str::<Config>(content) {
+        Ok(config) if problems.is_empty() => return Ok(config),
+        Ok(_) => (),
+        Err(err) => problems.push(Problem::from_toml(content, &err)),
+    }
+
+    problems.sort_by_key(|problem| problem.location.unwrap_or((usize::MAX, 0)));
+    Err(problems)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_dump_default() {
+        let config = Config::default();
+        let dumped = dump(&config);
+        assert!(dumped.starts_with("performance = \"High\"\n"));
+        assert!(dumped.contains("\n[style]\nfont-size = 16.0\n"));
+        assert!(!dumped.contains("cursor-text"));
+        assert_eq!(validate(&dumped), Ok(config));
+    }
+
+    #[test]
+    fn test_default_config() {
+        let default = default_config();
+        assert!(default
+            .lines()
+            .all(|line| line.is_empty() || line.starts_with('#')));
+        assert!(default.contains("\n# [colors]\n"));
+        assert!(default.contains("\n# Options: High, Low.\n# performance = \"High\"\n"));
+        assert!(default.contains("\n# cursor-text is unset\n"));
+
+        // Every key is documented.
+        for key in KEYS.iter().filter(|key| !key.path.starts_with("colors.")) {
+            assert!(!key.doc.is_empty(), "{} has no documentation", key.path);
+        }
+    }
+
+    /// Dotted paths of the keys in `table`, with `*` for the names of maps
+    /// and items of lists like in `KEYS`.
+    fn serialized_keys(table: &Table, path: &str, keys: &mut Vec<String>) {
+        for (name, value) in table {
+            let path = if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{path}.{name}")
+            };
+            let children = format!("{path}.*");
+            let is_map = is_table(&children) || find(&children).is_some();
+            match value {
+                Value::Table(table) if is_map => {
+                    for (_, value) in table {
+                        if let Value::Table(item) = value {
+                            serialized_keys(item, &children, keys);
+                        }
+                    }
+                }
+                Value::Table(table) => serialized_keys(table, &path, keys),
+                Value::Array(items) => {
+                    for value in items {
+                        if let Value::Table(item) = value {
+                            serialized_keys(item, &children, keys);
+                        }
+                    }
+                }
+                _ => (),
+            }
+            keys.push(path);
+        }
+    }
+
+    #[test]
+    fn test_keys_cover_config() {
+        let mut config = Config::default();
+        config.key_bindings.push(KeyBinding {
+            key: String::from("T"),
+            with: String::from("Control"),
+            action: String::from("TabCreateNew"),
+            text: Some(String::from("t")),
+            mode: String::new(),
+            repeat: Some(true),
+            chord: String::new(),
+        });
+        config.mouse_bindings.push(MouseBinding {
+            button: String::from("Middle"),
+            with: String::new(),
+            action: String::from("Paste"),
+            mode: String::new(),
+        });
+        config.profiles.insert(
+            String::from("work"),
+            Profile {
+                shell: Some(String::from("zsh")),
+                cwd: Some(String::from("~/work")),
+                theme: Some(String::from("dracula")),
+                font: Some(String::from("Menlo")),
+                ..Profile::default()
+            },
+        );
+
+        let table = match Value::try_from(&config).unwrap() {
+            Value::Table(table) => table,
+            value => panic!("unexpected value {value:?}"),
+        };
+        let mut keys = Vec::new();
+        serialized_keys(&table, "", &mut keys);
+        for key in keys {
+            assert!(
+                find(&key).is_some() || is_table(&key),
+                "{key} is missing from KEYS"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dump_profiles() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            String::from("work"),
+            Profile {
+                cwd: Some(String::from("~/work")),
+                ..Profile::default()
+            },
+        );
+        config.window.position = Some([10, 20]);
+
+        let dumped = dump(&config);
+        assert!(dumped.contains("position = [10, 20]\n"));
+        assert_eq!(validate(&dumped), Ok(config));
+    }
+
+    #[test]
+    fn test_validate() {
+        let content = "width = 600\n\
+                       [style]\n\
+                       font-size = 18\n\
+                       fnot = \"Fira Code\"\n\
+                       \n\
+                       [profiles.work]\n\
+                       shel = \"zsh\"\n\
+                       [stlye]\n\
+                       font = \"Menlo\"\n";
+        let problems = validate(content).unwrap_err();
+        let problems = problems.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                "4:1: unknown key `style.fnot`",
+                "7:1: unknown key `profiles.work.shel`",
+                "8:1: unknown key `stlye`",
+            ]
+        );
+
+        let problems = validate("[window]\ncolumns = \"80\"\n").unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].location, Some((2, 11)));
+
+        let problems = validate("[style\nfont = 1\n").unwrap_err();
+        assert_eq!(problems[0].location.map(|(line, _)| line), Some(1));
+
+        let config = validate("[bar]\nenabled = true\n").unwrap();
+        assert!(config.bar.enabled);
+    }
+}
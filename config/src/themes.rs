@@ -0,0 +1,429 @@
+// Color schemes of other terminals, converted to Rio colors when loaded.
+//
+// Alacritty (YAML and TOML), iTerm2 (.itermcolors) and base16 (YAML) schemes
+// are read into a list of Rio color names with hex values, which goes through
+// the same deserializer as Rio themes, so colors a scheme doesn't define keep
+// their defaults.
+
+use crate::Theme;
+use colors::Colors;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Extensions of the scheme files that can be loaded, Rio themes included.
+pub const EXTENSIONS: &[&str] = &["toml", "yml", "yaml", "itermcolors"];
+
+/// Names of the 16 ANSI colors, in order.
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "light-black",
+    "light-red",
+    "light-green",
+    "light-yellow",
+    "light-blue",
+    "light-magenta",
+    "light-cyan",
+    "light-white",
+];
+
+/// base16 colors used for each Rio color, like base16-shell.
+const BASE16: &[(&str, &str)] = &[
+    ("background", "base00"),
+    ("foreground", "base05"),
+    ("cursor", "base05"),
+    ("selection-background", "base02"),
+    ("selection-foreground", "base05"),
+    ("black", "base00"),
+    ("red", "base08"),
+    ("green", "base0B"),
+    ("yellow", "base0A"),
+    ("blue", "base0D"),
+    ("magenta", "base0E"),
+    ("cyan", "base0C"),
+    ("white", "base05"),
+    ("light-black", "base03"),
+    ("light-red", "base08"),
+    ("light-green", "base0B"),
+    ("light-yellow", "base0A"),
+    ("light-blue", "base0D"),
+    ("light-magenta", "base0E"),
+    ("light-cyan", "base0C"),
+    ("light-white", "base07"),
+];
+
+/// Colors of the scheme at `path`, in the format given by its extension.
+pub fn load(path: &Path) -> Result<Colors, String> {
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    convert(extension, &content)
+}
+
+fn convert(extension: &str, content: &str) -> Result<Colors, String> {
+    let colors = match extension {
+        "itermcolors" => iterm(content),
+        "yml" | "yaml" => {
+            let values = flatten_yaml(content);
+            if values.keys().any(|key| key.ends_with("base00")) {
+                base16(&values)
+            } else {
+                alacritty(&values)
+            }
+        }
+        _ => {
+            let value: toml::Value =
+                toml::from_str(content).map_err(|err| err.to_string())?;
+            let mut values = HashMap::new();
+            flatten_toml("", &value, &mut values);
+            // Rio themes have colors directly under `[colors]`.
+            if !values.keys().any(|key| key.starts_with("colors.primary.")) {
+                let theme: Theme =
+                    toml::from_str(content).map_err(|err| err.to_string())?;
+                return Ok(theme.colors);
+            }
+            alacritty(&values)
+        }
+    };
+
+    if colors.is_empty() {
+        return Err(String::from("no colors found"));
+    }
+
+    let mut content = String::from("[colors]\n");
+    for (name, hex) in colors {
+        content.push_str(&format!("{name} = '{hex}'\n"));
+    }
+    let theme: Theme = toml::from_str(&content).map_err(|err| err.to_string())?;
+    Ok(theme.colors)
+}
+
+/// `#rrggbb` from `#rrggbb`, `0xrrggbb` or `rrggbb`, quoted or not.
+fn normalize_hex(value: &str) -> Option<String> {
+    let value = value.trim().trim_matches(|c| c == '\'' || c == '"');
+    let digits = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+    if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(format!("#{digits}"))
+    } else {
+        None
+    }
+}
+
+fn alacritty(values: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut keys = vec![
+        ("colors.primary.background", String::from("background")),
+        ("colors.primary.foreground", String::from("foreground")),
+        (
+            "colors.primary.dim_foreground",
+            String::from("dim-foreground"),
+        ),
+        (
+            "colors.primary.bright_foreground",
+            String::from("light-foreground"),
+        ),
+        ("colors.cursor.cursor", String::from("cursor")),
+        (
+            "colors.selection.background",
+            String::from("selection-background"),
+        ),
+        (
+            "colors.selection.text",
+            String::from("selection-foreground"),
+        ),
+    ];
+    let mut ansi = vec![];
+    for name in &ANSI_NAMES[..8] {
+        ansi.push((format!("colors.normal.{name}"), name.to_string()));
+        ansi.push((format!("colors.bright.{name}"), format!("light-{name}")));
+        ansi.push((format!("colors.dim.{name}"), format!("dim-{name}")));
+    }
+    keys.extend(ansi.iter().map(|(key, name)| (key.as_str(), name.clone())));
+
+    keys.into_iter()
+        .filter_map(|(key, name)| Some((name, normalize_hex(values.get(key)?)?)))
+        .collect()
+}
+
+fn base16(values: &HashMap<String, String>) -> Vec<(String, String)> {
+    // Newer schemes nest the colors under `palette`.
+    let get = |base: &str| {
+        values
+            .get(base)
+            .or_else(|| values.get(&format!("palette.{base}")))
+            .and_then(|value| normalize_hex(value))
+    };
+
+    BASE16
+        .iter()
+        .filter_map(|(name, base)| Some((name.to_string(), get(base)?)))
+        .collect()
+}
+
+fn iterm(content: &str) -> Vec<(String, String)> {
+    let mut colors = vec![];
+    let mut rest = content;
+
+    // Each color is a `<key>` followed by a `<dict>` of its components.
+    while let Some(start) = rest.find("<key>") {
+        let after = &rest[start + "<key>".len()..];
+        let end = match after.find("</key>") {
+            Some(end) => end,
+            None => break,
+        };
+        let key = &after[..end];
+        rest = &after[end + "</key>".len()..];
+
+        if !rest.trim_start().starts_with("<dict>") {
+            continue;
+        }
+        let dict_end = match rest.find("</dict>") {
+            Some(dict_end) => dict_end,
+            None => break,
+        };
+        let dict = &rest[..dict_end];
+        rest = &rest[dict_end + "</dict>".len()..];
+
+        let name = match key {
+            "Background Color" => "background",
+            "Foreground Color" => "foreground",
+            "Cursor Color" => "cursor",
+            "Selection Color" => "selection-background",
+            "Selected Text Color" => "selection-foreground",
+            _ => match key
+                .strip_prefix("Ansi ")
+                .and_then(|key| key.strip_suffix(" Color"))
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| ANSI_NAMES.get(index))
+            {
+                Some(name) => name,
+                None => continue,
+            },
+        };
+
+        let component = |component: &str| -> Option<u8> {
+            let key = format!("<key>{component} Component</key>");
+            let after = &dict[dict.find(&key)? + key.len()..];
+            let start = after.find("<real>")? + "<real>".len();
+            let end = after.find("</real>")?;
+            let value: f64 = after.get(start..end)?.trim().parse().ok()?;
+            Some((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+        };
+        if let (Some(red), Some(green), Some(blue)) =
+            (component("Red"), component("Green"), component("Blue"))
+        {
+            colors.push((name.to_string(), format!("#{red:02x}{green:02x}{blue:02x}")));
+        }
+    }
+
+    colors
+}
+
+fn flatten_toml(prefix: &str, value: &toml::Value, values: &mut HashMap<String, String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_toml(&key, value, values);
+            }
+        }
+        toml::Value::String(string) => {
+            values.insert(prefix.to_string(), string.clone());
+        }
+        _ => {}
+    }
+}
+
+/// Scalar values of the mappings in a YAML document, by their dotted path.
+/// Lists, anchors and multi-line values are not needed for color schemes.
+fn flatten_yaml(content: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut parents: Vec<(usize, String)> = vec![];
+
+    for line in content.lines() {
+        let line = strip_yaml_comment(line);
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('-') {
+            continue;
+        }
+
+        let (key, value) = match trimmed.split_once(':') {
+            Some((key, value)) => (
+                key.trim().trim_matches(|c| c == '\'' || c == '"'),
+                value.trim(),
+            ),
+            None => continue,
+        };
+        let indent = line.len() - trimmed.len();
+        while parents
+            .last()
+            .map_or(false, |(parent, _)| *parent >= indent)
+        {
+            parents.pop();
+        }
+
+        if value.is_empty() {
+            parents.push((indent, key.to_string()));
+        } else {
+            let mut path: Vec<&str> =
+                parents.iter().map(|(_, key)| key.as_str()).collect();
+            path.push(key);
+            values.insert(path.join("."), value.to_string());
+        }
+    }
+
+    values
+}
+
+/// `line` without a comment, which starts with `#` outside of quotes.
+fn strip_yaml_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in line.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('#', None) if index == 0 || line[..index].ends_with(char::is_whitespace) => {
+                return &line[..index];
+            }
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use colors::hex_to_color_arr;
+
+    #[test]
+    fn test_alacritty_yaml() {
+        let colors = convert(
+            "yml",
+            r##"
+# Tomorrow Night
+colors:
+  primary:
+    background: '#1d1f21' # comment
+    foreground: '0xc5c8c6'
+  normal:
+    red:   '#cc6666'
+  bright:
+    red:   "#d54e53"
+font:
+  size: 12
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(colors.background.0, hex_to_color_arr("#1d1f21"));
+        assert_eq!(colors.foreground, hex_to_color_arr("#c5c8c6"));
+        assert_eq!(colors.red, hex_to_color_arr("#cc6666"));
+        assert_eq!(colors.light_red, hex_to_color_arr("#d54e53"));
+        assert_eq!(colors.blue, Colors::default().blue);
+    }
+
+    #[test]
+    fn test_alacritty_toml() {
+        let colors = convert(
+            "toml",
+            r##"
+[colors.primary]
+background = "#282a36"
+[colors.cursor]
+cursor = "#f8f8f2"
+[colors.dim]
+green = "#1ef956"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(colors.background.0, hex_to_color_arr("#282a36"));
+        assert_eq!(colors.cursor, hex_to_color_arr("#f8f8f2"));
+        assert_eq!(colors.dim_green, hex_to_color_arr("#1ef956"));
+    }
+
+    #[test]
+    fn test_rio_theme() {
+        let colors = convert("toml", "[colors]\nbackground = '#333333'").unwrap();
+        assert_eq!(colors.background.0, hex_to_color_arr("#333333"));
+    }
+
+    #[test]
+    fn test_base16() {
+        let colors = convert(
+            "yaml",
+            r#"
+scheme: "Default Dark"
+base00: "181818"
+base05: "d8d8d8"
+base08: "ab4642"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(colors.background.0, hex_to_color_arr("#181818"));
+        assert_eq!(colors.foreground, hex_to_color_arr("#d8d8d8"));
+        assert_eq!(colors.red, hex_to_color_arr("#ab4642"));
+        assert_eq!(colors.light_red, hex_to_color_arr("#ab4642"));
+
+        let colors = convert("yaml", "palette:\n  base00: \"#101010\"\n").unwrap();
+        assert_eq!(colors.black, hex_to_color_arr("#101010"));
+    }
+
+    #[test]
+    fn test_iterm() {
+        let colors = convert(
+            "itermcolors",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>Ansi 1 Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>0.0</real>
+		<key>Green Component</key>
+		<real>0.0</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Background Color</key>
+	<dict>
+		<key>Color Space</key>
+		<string>sRGB</string>
+		<key>Blue Component</key>
+		<real>0.2</real>
+		<key>Green Component</key>
+		<real>0.2</real>
+		<key>Red Component</key>
+		<real>0.2</real>
+	</dict>
+</dict>
+</plist>"#,
+        )
+        .unwrap();
+
+        assert_eq!(colors.red, hex_to_color_arr("#ff0000"));
+        assert_eq!(colors.background.0, hex_to_color_arr("#333333"));
+    }
+
+    #[test]
+    fn test_no_colors() {
+        assert!(convert("yml", "font:\n  size: 12\n").is_err());
+        assert!(convert("itermcolors", "<plist></plist>").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rio_core::crosswords::Crosswords;
+use rio_core::event::VoidListener;
+use rio_core::performer::handler::ParserProcessor;
+
+const COLUMNS: usize = 80;
+const LINES: usize = 1_000;
+
+fn ascii() -> Vec<u8> {
+    (0..LINES)
+        .flat_map(|line| {
+            (0..COLUMNS)
+                .map(move |column| b' ' + ((line + column) % 95) as u8)
+                .chain(*b"\r\n")
+        })
+        .collect()
+}
+
+fn colors() -> Vec<u8> {
+    let mut output = String::new();
+    for line in 0..LINES {
+        for column in 0..COLUMNS {
+            let color = (line * COLUMNS + column) % 256;
+            output.push_str(&format!("\x1b[38;5;{color};48;5;{}mx", 255 - color));
+        }
+        output.push_str("\x1b[0m\r\n");
+    }
+    output.into_bytes()
+}
+
+/// Moves of the cursor around the screen, erasing the rest of each line,
+/// as full-screen applications redraw.
+fn cursor_motion() -> Vec<u8> {
+    let mut output = String::new();
+    for line in 0..LINES {
+        for column in (0..COLUMNS).step_by(8) {
+            let row = line % 24 + 1;
+            output.push_str(&format!("\x1b[{row};{}Hx\x1b[K", column + 1));
+        }
+    }
+    output.into_bytes()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, content) in [
+        ("ascii", ascii()),
+        ("colors", colors()),
+        ("cursor_motion", cursor_motion()),
+    ] {
+        group.throughput(Throughput::Bytes(content.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    (
+                        Crosswords::new(COLUMNS, 24, VoidListener {}),
+                        ParserProcessor::new(),
+                    )
+                },
+                |(terminal, parser)| parser.advance_bytes(terminal, &content),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rio_core::crosswords::Crosswords;
+use rio_core::event::VoidListener;
+use rio_core::performer::handler::ParserProcessor;
+
+const COLUMNS: usize = 80;
+const ROWS: usize = 24;
+const LINES: usize = 10_000;
+
+/// Short lines, so the time goes into scrolling rather than writing.
+fn lines() -> Vec<u8> {
+    (0..LINES)
+        .flat_map(|line| format!("{line}\r\n").into_bytes())
+        .collect()
+}
+
+/// The same lines scrolled within a region above a fixed status line.
+fn region() -> Vec<u8> {
+    let mut output = format!("\x1b[1;{}r", ROWS - 1).into_bytes();
+    output.extend(lines());
+    output
+}
+
+fn scroll(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scroll");
+    for (name, content) in [("lines", lines()), ("region", region())] {
+        group.throughput(Throughput::Elements(LINES as u64));
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || {
+                    (
+                        Crosswords::new(COLUMNS, ROWS, VoidListener {}),
+                        ParserProcessor::new(),
+                    )
+                },
+                |(terminal, parser)| parser.advance_bytes(terminal, &content),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scroll);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rio_core::crosswords::Crosswords;
+use rio_core::event::VoidListener;
+use rio_core::performer::handler::ParserProcessor;
+
+fuzz_target!(|data: &[u8]| {
+    let mut terminal = Crosswords::new(80, 24, VoidListener {});
+    let mut parser = ParserProcessor::new();
+    parser.advance_bytes(&mut terminal, data);
+
+    let _ = terminal.visible_rows();
+    let _ = terminal.scrollback_to_string();
+});
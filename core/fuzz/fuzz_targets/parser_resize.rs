@@ -0,0 +1,32 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rio_core::crosswords::Crosswords;
+use rio_core::event::VoidListener;
+use rio_core::performer::handler::ParserProcessor;
+
+// The first two bytes are the initial size, every `\x00` in the input
+// resizes the terminal using the byte that follows it.
+fuzz_target!(|data: &[u8]| {
+    let [columns, rows, data @ ..] = data else {
+        return;
+    };
+
+    let size = |value: u8| usize::from(value % 200) + 1;
+    let mut terminal = Crosswords::new(size(*columns), size(*rows), VoidListener {});
+    let mut parser = ParserProcessor::new();
+
+    let mut chunks = data.split(|byte| *byte == 0);
+    if let Some(chunk) = chunks.next() {
+        parser.advance_bytes(&mut terminal, chunk);
+    }
+    for chunk in chunks {
+        if let [value, chunk @ ..] = chunk {
+            let (columns, rows) = (size(*value), size(value.rotate_left(4)));
+            terminal.resize::<(usize, usize)>(columns, rows);
+            parser.advance_bytes(&mut terminal, chunk);
+        }
+    }
+
+    let _ = terminal.visible_rows();
+});
@@ -0,0 +1,98 @@
+pub mod charset;
+pub mod control;
+pub mod mode;
+
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum CursorShape {
+    /// Cursor is a block like `▒`.
+    Block,
+    /// Cursor is an underscore like `_`.
+    #[allow(unused)]
+    Underline,
+    /// Cursor is a vertical bar `⎸`.
+    #[allow(unused)]
+    Beam,
+    #[default]
+    Hidden,
+}
+
+#[derive(Debug)]
+pub enum ClearMode {
+    /// Clear below cursor.
+    Below,
+    /// Clear above cursor.
+    Above,
+    /// Clear entire terminal.
+    All,
+    /// Clear 'saved' lines (scrollback).
+    Saved,
+}
+
+#[derive(Debug)]
+pub enum TabulationClearMode {
+    /// Clear stop under cursor.
+    Current,
+    /// Clear all stops.
+    All,
+}
+
+#[derive(Debug)]
+pub enum LineClearMode {
+    /// Clear right of cursor.
+    Right,
+    /// Clear left of cursor.
+    Left,
+    /// Clear entire line.
+    All,
+}
+
+/// Shell integration mark (OSC 133), sent by the shell around prompts and
+/// commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticPrompt {
+    /// `A`, a prompt is about to be drawn.
+    PromptStart,
+    /// `B`, the user starts typing a command.
+    CommandStart,
+    /// `C`, the command was entered and its output follows.
+    OutputStart,
+    /// `D`, the command finished, with its exit status when known.
+    CommandEnd(Option<i32>),
+}
+
+/// Text with the escapes `\e`, `\r`, `\n`, `\t`, `\b` and `\xHH` (ASCII only)
+/// replaced, used to write control characters in plain text such as
+/// `rio msg send-text` arguments. Other escaped characters are kept
+/// without the backslash and invalid `\x` escapes are kept as they are.
+pub fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('e') => unescaped.push('\x1b'),
+            Some('b') => unescaped.push('\x08'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(byte) if byte.is_ascii() => unescaped.push(byte as char),
+                    _ => {
+                        unescaped.push_str("\\x");
+                        unescaped.push_str(&hex);
+                    }
+                }
+            }
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
@@ -56,6 +56,8 @@ pub enum Mode {
     SwapScreenAndSetRestoreCursor = 1049,
     /// ?2004
     BracketedPaste = 2004,
+    /// ?2031
+    ReportColorScheme = 2031,
 }
 
 impl Mode {
@@ -85,6 +87,7 @@ impl Mode {
                 1042 => Mode::UrgencyHints,
                 1049 => Mode::SwapScreenAndSetRestoreCursor,
                 2004 => Mode::BracketedPaste,
+                2031 => Mode::ReportColorScheme,
                 _ => {
                     warn!("[unimplemented] primitive mode: {}", num);
                     return None;
@@ -18,11 +18,8 @@ use storage::Storage;
 #[derive(Debug, Copy, Clone)]
 pub enum Scroll {
     Delta(i32),
-    #[allow(unused)]
     PageUp,
-    #[allow(unused)]
     PageDown,
-    #[allow(unused)]
     Top,
     Bottom,
 }
@@ -77,7 +74,6 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
     }
 
     /// Update the size of the scrollback history.
-    #[allow(dead_code)]
     pub fn update_history(&mut self, history_size: usize) {
         let current_history_size = self.history_size();
         if current_history_size > history_size {
@@ -87,6 +83,12 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
         self.max_scroll_limit = history_size;
     }
 
+    /// Most lines the scrollback may hold.
+    #[inline]
+    pub fn max_scroll_limit(&self) -> usize {
+        self.max_scroll_limit
+    }
+
     pub fn scroll_display(&mut self, scroll: Scroll) {
         self.display_offset = match scroll {
             Scroll::Delta(count) => min(
@@ -181,7 +183,9 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
 
     /// Move lines at the bottom toward the top.
     ///
-    /// This is the performance-sensitive part of scrolling.
+    /// This is the performance-sensitive part of scrolling. Rows are rotated
+    /// through the ring buffer of the storage rather than copied, only the
+    /// rows fixed outside of the region are swapped back into place.
     pub fn scroll_up<D>(&mut self, region: &Range<Line>, positions: usize)
     where
         T: ResetDiscriminant<D>,
@@ -259,7 +263,6 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
     }
 
     /// Completely reset the grid state.
-    #[allow(dead_code)]
     pub fn reset<D>(&mut self)
     where
         T: ResetDiscriminant<D>,
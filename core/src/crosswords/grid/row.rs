@@ -1,11 +1,32 @@
 use crate::crosswords::grid::GridSquare;
 use crate::crosswords::square::ResetDiscriminant;
 use crate::crosswords::Column;
+use bitflags::bitflags;
 use core::cmp::min;
 use std::cmp::max;
 use std::ops::{Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive};
 use std::{ptr, slice};
 
+bitflags! {
+    /// Shell integration marks (OSC 133) on a row, kept with it as it moves
+    /// into the scrollback.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Marks: u8 {
+        /// A prompt starts on this row.
+        const PROMPT = 0b0000_0001;
+        /// The user starts typing a command on this row.
+        const COMMAND = 0b0000_1000;
+        /// The output of a command starts on this row.
+        const OUTPUT = 0b0000_0010;
+        /// The output of a command ends on this row.
+        const OUTPUT_END = 0b0000_0100;
+        /// The command entered on this row exited with status zero.
+        const SUCCEEDED = 0b0001_0000;
+        /// The command entered on this row exited with another status.
+        const FAILED = 0b0010_0000;
+    }
+}
+
 /// A row in the grid.
 #[derive(Default, Clone, Debug)]
 pub struct Row<T> {
@@ -16,6 +37,9 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// Shell integration marks, cleared when the row is reset.
+    pub marks: Marks,
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
@@ -46,7 +70,11 @@ impl<T: Clone + Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row {
+            inner,
+            occ: 0,
+            marks: Marks::empty(),
+        }
     }
 
     /// Increase the number of columns in the row.
@@ -105,6 +133,7 @@ impl<T: Clone + Default> Row<T> {
         }
 
         self.occ = 0;
+        self.marks = Marks::empty();
     }
 }
 
@@ -112,7 +141,11 @@ impl<T: Clone + Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row {
+            inner: vec,
+            occ,
+            marks: Marks::empty(),
+        }
     }
 
     #[inline]
@@ -52,6 +52,20 @@ fn scroll_up() {
     assert_eq!(grid[Line(9)].occ, 0);
 }
 
+// Scroll up rotates rows into history without copying their cells.
+#[test]
+fn scroll_up_rotates_rows() {
+    let mut grid = Grid::<usize>::new(3, 4, 10);
+    let rows: Vec<*const usize> = (0..3).map(|i| grid[Line(i)].inner.as_ptr()).collect();
+
+    grid.scroll_up::<usize>(&(Line(0)..Line(3)), 1);
+
+    assert_eq!(grid.history_size(), 1);
+    assert_eq!(grid[Line(-1)].inner.as_ptr(), rows[0]);
+    assert_eq!(grid[Line(0)].inner.as_ptr(), rows[1]);
+    assert_eq!(grid[Line(1)].inner.as_ptr(), rows[2]);
+}
+
 // Scroll down moves lines downward.
 #[test]
 fn scroll_down() {
@@ -16,34 +16,46 @@ pub mod attr;
 pub mod grid;
 pub mod pos;
 pub mod square;
+mod termcap;
 
 use crate::ansi::{
-    mode::Mode as AnsiMode, ClearMode, CursorShape, LineClearMode, TabulationClearMode,
+    mode::Mode as AnsiMode, ClearMode, CursorShape, LineClearMode, SemanticPrompt,
+    TabulationClearMode,
 };
 use crate::clipboard::ClipboardType;
 use crate::crosswords::grid::{BidirectionalIterator, Dimensions, Grid, Scroll};
-use crate::event::{EventListener, RioEvent};
+use crate::event::{EventListener, Observed, Observer, RioEvent};
 use crate::performer::handler::Handler;
 use crate::selection::{Selection, SelectionRange, SelectionType};
 use attr::*;
 use base64::{engine::general_purpose, Engine as _};
 use bitflags::bitflags;
 use colors::{AnsiColor, ColorRgb, Colors};
-use grid::row::Row;
+use grid::row::{Marks, Row};
 use log::{debug, info, warn};
 use pos::{CharsetIndex, Column, Cursor, CursorState, Line, Pos};
-use square::{LineLength, Square};
+use square::{CellExtra, LineLength, Square};
 use std::mem;
 use std::ops::{Index, IndexMut, Range};
 use std::option::Option;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::sync::Arc;
+use std::time::Duration;
 use unicode_width::UnicodeWidthChar;
 
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
 pub type NamedColor = colors::NamedColor;
 
 pub const MIN_COLUMNS: usize = 2;
 pub const MIN_VISIBLE_ROWS: usize = 1;
+/// Most lines of output kept until they are announced.
+const MAX_ANNOUNCEMENTS: usize = 100;
+const DEFAULT_HISTORY_LINES: usize = 10_000;
 const BRACKET_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
 
 bitflags! {
@@ -69,6 +81,7 @@ bitflags! {
         const ALTERNATE_SCROLL    = 0b0000_1000_0000_0000_0000;
         const VI                  = 0b0001_0000_0000_0000_0000;
         const URGENCY_HINTS       = 0b0010_0000_0000_0000_0000;
+        const COLOR_SCHEME_REPORT = 0b0100_0000_0000_0000_0000;
         const ANY                 = u32::MAX;
     }
 }
@@ -102,7 +115,6 @@ impl LineDamageBounds {
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn reset(&mut self, num_cols: usize) {
         *self = Self::undamaged(num_cols, self.line);
     }
@@ -114,7 +126,6 @@ impl LineDamageBounds {
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn is_damaged(&self) -> bool {
         self.left <= self.right
     }
@@ -179,7 +190,6 @@ impl TermDamageState {
         self.lines[line].expand(left, right);
     }
 
-    #[allow(dead_code)]
     fn damage_selection(
         &mut self,
         selection: SelectionRange,
@@ -210,6 +220,65 @@ impl TermDamageState {
     }
 }
 
+/// Lines of the viewport changed since the damage was last reset.
+#[derive(Debug)]
+pub enum TermDamage<'a> {
+    /// Every line changed.
+    Full,
+    /// Only the lines yielded by the iterator changed.
+    Partial(TermDamageIterator<'a>),
+}
+
+/// Damaged lines of the viewport, skipping undamaged ones.
+#[derive(Clone, Debug)]
+pub struct TermDamageIterator<'a> {
+    line_damage: std::slice::Iter<'a, LineDamageBounds>,
+    display_offset: usize,
+}
+
+impl<'a> TermDamageIterator<'a> {
+    pub fn new(line_damage: &'a [LineDamageBounds], display_offset: usize) -> Self {
+        // Lines scrolled below the viewport can't be damaged.
+        let num_lines = line_damage.len().saturating_sub(display_offset);
+
+        Self {
+            display_offset,
+            line_damage: line_damage[..num_lines].iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for TermDamageIterator<'a> {
+    type Item = LineDamageBounds;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line_damage.find_map(|line| {
+            line.is_damaged().then_some(LineDamageBounds {
+                line: line.line + self.display_offset,
+                ..*line
+            })
+        })
+    }
+}
+
+/// What the renderer needs from the terminal to draw a frame, taken while
+/// it is locked so drawing doesn't hold the lock. Input and resizing still
+/// change the terminal under the lock.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub rows: Vec<Row<Square>>,
+    /// Lines changed since the last snapshot, `None` when all of them did.
+    pub damaged_lines: Option<Vec<usize>>,
+    pub cursor: CursorState,
+    pub is_vi_mode: bool,
+    pub cursor_color: Option<ColorRgb>,
+    pub display_offset: usize,
+    pub history_size: usize,
+    pub title: Option<String>,
+    pub current_directory: Option<PathBuf>,
+    pub last_command: Option<(Option<i32>, Duration)>,
+}
+
 #[derive(Debug, Clone)]
 struct TabStops {
     tabs: Vec<bool>,
@@ -260,6 +329,23 @@ impl IndexMut<Column> for TabStops {
     }
 }
 
+/// Bytes of extra storage held by the squares of `row`. Neighbours sharing
+/// it, like the squares of a hyperlink, are counted once.
+fn row_extra_memory(row: &Row<Square>) -> usize {
+    let mut memory = 0;
+    let mut previous: Option<&Arc<CellExtra>> = None;
+    for square in row {
+        if let Some(extra) = &square.extra {
+            if !previous.map_or(false, |previous| Arc::ptr_eq(previous, extra)) {
+                memory += extra.memory_size();
+            }
+        }
+        previous = square.extra.as_ref();
+    }
+
+    memory
+}
+
 #[derive(Debug, Clone)]
 pub struct Crosswords<U>
 where
@@ -269,6 +355,21 @@ where
     mode: Mode,
     #[allow(unused)]
     semantic_escape_chars: String,
+    /// Whether text hidden with SGR 8 is copied, instead of spaces.
+    copy_hidden_text: bool,
+    /// Terminal name reported to XTGETTCAP, the `TERM` of the shell.
+    term_name: String,
+    /// Whether `Tc` is reported to XTGETTCAP.
+    truecolor: bool,
+    /// Whether applications may write to the clipboard with OSC 52.
+    clipboard_write: bool,
+    /// Most lines of scrollback kept.
+    history_lines: usize,
+    /// Bytes the scrollback may take, unlimited when zero.
+    history_memory_limit: usize,
+    /// Bytes of extra square storage in the scrollback, only counted with
+    /// `history_memory_limit`.
+    history_extra_memory: usize,
     pub grid: Grid<Square>,
     inactive_grid: Grid<Square>,
     scroll_region: Range<Line>,
@@ -278,13 +379,30 @@ where
     #[allow(dead_code)]
     colors: Colors,
     title: Option<String>,
+    current_directory: Option<PathBuf>,
+    /// Whether the shell marked a prompt with OSC 133.
+    has_prompt_marks: bool,
+    /// Column where the user started typing the last command.
+    command_column: Column,
+    /// Command entered by the user and when, until the shell reports it
+    /// finished.
+    running_command: Option<(String, Instant)>,
+    /// Exit status and duration of the last command.
+    last_command: Option<(Option<i32>, Duration)>,
+    /// Whether the colors in use are dark, reported to applications asking.
+    is_dark: bool,
+    /// Lines of output finished since they were last taken, `None` unless
+    /// they are announced by a screen reader.
+    announcements: Option<Vec<String>>,
+    /// Cursor color set by the application with OSC 12.
+    cursor_color: Option<ColorRgb>,
     damage: TermDamageState,
     pub vi_mode_cursor: Pos,
 }
 
 impl<U: EventListener> Crosswords<U> {
     pub fn new(cols: usize, rows: usize, event_proxy: U) -> Crosswords<U> {
-        let grid = Grid::new(rows, cols, 10_000);
+        let grid = Grid::new(rows, cols, DEFAULT_HISTORY_LINES);
         let alt = Grid::new(rows, cols, 0);
 
         let scroll_region = Line(0)..Line(rows as i32);
@@ -293,6 +411,13 @@ impl<U: EventListener> Crosswords<U> {
         Crosswords {
             vi_mode_cursor: Pos::default(),
             semantic_escape_chars,
+            copy_hidden_text: false,
+            term_name: String::from("rio"),
+            truecolor: true,
+            clipboard_write: false,
+            history_lines: DEFAULT_HISTORY_LINES,
+            history_memory_limit: 0,
+            history_extra_memory: 0,
             selection: None,
             grid,
             inactive_grid: alt,
@@ -301,6 +426,14 @@ impl<U: EventListener> Crosswords<U> {
             event_proxy,
             colors: Colors::default(),
             title: None,
+            current_directory: None,
+            has_prompt_marks: false,
+            command_column: Column(0),
+            running_command: None,
+            last_command: None,
+            is_dark: true,
+            announcements: None,
+            cursor_color: None,
             tabs: TabStops::new(cols),
             mode: Mode::SHOW_CURSOR
                 | Mode::LINE_WRAP
@@ -314,11 +447,92 @@ impl<U: EventListener> Crosswords<U> {
         self.damage.is_fully_damaged = true;
     }
 
-    #[allow(dead_code)]
+    /// Lines of the viewport changed since the last call to `reset_damage`,
+    /// including the lines the cursor and the selection moved from.
+    pub fn damage(&mut self) -> TermDamage<'_> {
+        // Lines moved by inserting characters are not tracked.
+        if self.mode.contains(Mode::INSERT) {
+            self.mark_fully_damaged();
+        }
+
+        let previous_cursor =
+            mem::replace(&mut self.damage.last_cursor, self.grid.cursor.pos);
+
+        if self.damage.is_fully_damaged {
+            return TermDamage::Full;
+        }
+
+        if previous_cursor != self.grid.cursor.pos {
+            self.damage.damage_point(previous_cursor);
+        }
+        self.damage_cursor();
+
+        let display_offset = self.grid.display_offset();
+        let num_cols = self.grid.columns();
+
+        let vi_cursor_point = self.mode.contains(Mode::VI).then_some(self.vi_mode_cursor);
+        let previous_vi_cursor_point =
+            mem::replace(&mut self.damage.last_vi_cursor_point, vi_cursor_point);
+        let selection = self.selection.as_ref().and_then(|s| s.to_range(self));
+        let previous_selection = mem::replace(&mut self.damage.last_selection, selection);
+
+        let is_vi_cursor_moved = previous_vi_cursor_point != vi_cursor_point;
+        let is_selection_changed = previous_selection != selection;
+
+        // Damage is tracked for the lines of the screen, not the scrollback.
+        if display_offset != 0 && (is_vi_cursor_moved || is_selection_changed) {
+            self.mark_fully_damaged();
+            return TermDamage::Full;
+        }
+
+        if is_vi_cursor_moved {
+            let screen_lines = self.grid.screen_lines() as i32;
+            for point in [previous_vi_cursor_point, vi_cursor_point]
+                .into_iter()
+                .flatten()
+                .filter(|point| point.row.0 >= 0 && point.row.0 < screen_lines)
+            {
+                self.damage.damage_point(point);
+            }
+        }
+
+        if is_selection_changed {
+            for selection in [previous_selection, selection].into_iter().flatten() {
+                self.damage.damage_selection(selection, 0, num_cols);
+            }
+        }
+
+        TermDamage::Partial(TermDamageIterator::new(&self.damage.lines, display_offset))
+    }
+
+    /// Forget the damage, once the renderer has drawn it.
     pub fn reset_damage(&mut self) {
         self.damage.reset(self.grid.columns());
     }
 
+    /// Copy what is drawn in the next frame and reset the damage.
+    pub fn snapshot(&mut self) -> Snapshot {
+        let rows = self.visible_rows();
+        let damaged_lines = match self.damage() {
+            TermDamage::Full => None,
+            TermDamage::Partial(lines) => Some(lines.map(|damage| damage.line).collect()),
+        };
+        self.reset_damage();
+
+        Snapshot {
+            rows,
+            damaged_lines,
+            cursor: self.cursor(),
+            is_vi_mode: self.mode.contains(Mode::VI),
+            cursor_color: self.cursor_color,
+            display_offset: self.grid.display_offset(),
+            history_size: self.history_size(),
+            title: self.title.clone(),
+            current_directory: self.current_directory.clone(),
+            last_command: self.last_command,
+        }
+    }
+
     pub fn display_offset(&mut self) -> usize {
         self.grid.display_offset()
     }
@@ -347,6 +561,130 @@ impl<U: EventListener> Crosswords<U> {
         self.grid.bottommost_line()
     }
 
+    /// Cursor color set by the application, overriding the configured one.
+    #[inline]
+    pub fn cursor_color(&self) -> Option<ColorRgb> {
+        self.cursor_color
+    }
+
+    /// Characters ending the words selected semantically.
+    pub fn set_semantic_escape_chars(&mut self, chars: &str) {
+        self.semantic_escape_chars = chars.to_string();
+    }
+
+    /// Copy text hidden with SGR 8 as it is, rather than as spaces.
+    pub fn set_copy_hidden_text(&mut self, enabled: bool) {
+        self.copy_hidden_text = enabled;
+    }
+
+    /// Report `name` as the terminal name to XTGETTCAP.
+    pub fn set_term_name(&mut self, name: &str) {
+        self.term_name = name.to_string();
+    }
+
+    /// Report truecolor support to XTGETTCAP.
+    pub fn set_truecolor(&mut self, enabled: bool) {
+        self.truecolor = enabled;
+    }
+
+    /// Let applications write to the clipboard with OSC 52.
+    pub fn set_clipboard_write(&mut self, enabled: bool) {
+        self.clipboard_write = enabled;
+    }
+
+    /// Keep at most `lines` lines of scrollback, and no more than fit in
+    /// `memory_limit` bytes when it is not zero. The oldest lines are
+    /// dropped first.
+    pub fn set_history_limit(&mut self, lines: usize, memory_limit: usize) {
+        self.history_lines = lines;
+        self.history_memory_limit = memory_limit;
+        self.count_history_memory();
+        self.update_history();
+    }
+
+    /// Count the extra storage of the whole scrollback, after it changed by
+    /// more than scrolling.
+    fn count_history_memory(&mut self) {
+        self.history_extra_memory = 0;
+        if self.history_memory_limit == 0 {
+            return;
+        }
+
+        let grid = if self.mode.contains(Mode::ALT_SCREEN) {
+            &self.inactive_grid
+        } else {
+            &self.grid
+        };
+        self.history_extra_memory = (1..=grid.history_size())
+            .map(|line| row_extra_memory(&grid[Line(-(line as i32))]))
+            .sum();
+    }
+
+    fn update_history(&mut self) {
+        // The alternate screen has no scrollback.
+        let grid = if self.mode.contains(Mode::ALT_SCREEN) {
+            &mut self.inactive_grid
+        } else {
+            &mut self.grid
+        };
+
+        let mut lines = self.history_lines;
+        let mut kept = grid.history_size();
+        if self.history_memory_limit != 0 {
+            let row_size =
+                mem::size_of::<Row<Square>>() + grid.columns() * mem::size_of::<Square>();
+            let (max_lines, limit) = (self.history_lines, self.history_memory_limit);
+            let fitting = |extra: usize| {
+                std::cmp::min(max_lines, limit.saturating_sub(extra) / row_size)
+            };
+
+            // Lines with zerowidth characters or hyperlinks take more memory,
+            // the oldest lines are dropped until the others fit.
+            while kept > fitting(self.history_extra_memory) {
+                self.history_extra_memory -=
+                    row_extra_memory(&grid[Line(-(kept as i32))]);
+                kept -= 1;
+            }
+            lines = fitting(self.history_extra_memory);
+        }
+
+        kept = std::cmp::min(kept, lines);
+        if kept < grid.history_size() {
+            self.damage.is_fully_damaged = true;
+            grid.update_history(kept);
+        }
+        grid.update_history(lines);
+    }
+
+    /// Drop the scrollback, asked by the user rather than by the
+    /// application with ED 3.
+    pub fn clear_history(&mut self) {
+        self.grid.clear_history();
+        self.count_history_memory();
+        self.selection = self
+            .selection
+            .take()
+            .filter(|s| !s.intersects_range(..Line(0)));
+        self.mark_fully_damaged();
+    }
+
+    /// Drop the scrollback and the rows above the cursor, moving the row of
+    /// the cursor (usually the prompt) to the top of the screen.
+    pub fn clear_screen_and_history(&mut self) {
+        if self.mode.contains(Mode::ALT_SCREEN) {
+            self.clear_history();
+            return;
+        }
+
+        let rows = self.grid.cursor.pos.row.0 as usize;
+        if rows > 0 {
+            self.scroll_up_relative(Line(0), rows);
+            self.grid.cursor.pos.row = Line(0);
+        }
+        self.selection = None;
+        self.clear_history();
+    }
+
     pub fn resize<S: Dimensions>(&mut self, num_cols: usize, num_lines: usize) {
         let old_cols = self.grid.columns();
         let old_lines = self.grid.screen_lines();
@@ -368,6 +706,11 @@ impl<U: EventListener> Crosswords<U> {
         self.grid.resize(!is_alt, num_lines, num_cols);
         self.inactive_grid.resize(is_alt, num_lines, num_cols);
 
+        // Reflowed lines may enter the scrollback, and wider rows take more
+        // memory, so fewer of them fit in the budget.
+        self.count_history_memory();
+        self.update_history();
+
         // Invalidate selection and tabs only when necessary.
         if old_cols != num_cols {
             self.selection = None;
@@ -459,6 +802,186 @@ impl<U: EventListener> Crosswords<U> {
         self.damage_cursor();
     }
 
+    /// Title set with OSC 0 or 2.
+    #[inline]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Working directory reported with OSC 7.
+    #[inline]
+    pub fn current_directory(&self) -> Option<&Path> {
+        self.current_directory.as_deref()
+    }
+
+    /// Whether the shell marked a prompt starting on `line` (OSC 133).
+    #[inline]
+    pub fn is_prompt(&self, line: Line) -> bool {
+        self.grid[line].marks.contains(Marks::PROMPT)
+    }
+
+    /// Whether the shell marks prompts at all, otherwise they can only be
+    /// guessed from the text.
+    #[inline]
+    pub fn has_prompt_marks(&self) -> bool {
+        self.has_prompt_marks
+    }
+
+    /// First and last line of the output of the last command, as marked by
+    /// the shell. The output of a running command goes up to the cursor.
+    pub fn last_output_lines(&self) -> Option<(Line, Line)> {
+        let start = (self.grid.topmost_line().0..=self.grid.bottommost_line().0)
+            .rev()
+            .map(Line)
+            .find(|line| self.grid[*line].marks.contains(Marks::OUTPUT))?;
+        self.output_lines(start)
+    }
+
+    /// First and last line of the output marked by the shell which `line`
+    /// is part of.
+    pub fn output_lines_at(&self, line: Line) -> Option<(Line, Line)> {
+        let start = (self.grid.topmost_line().0..=line.0)
+            .rev()
+            .map(Line)
+            .find(|line| self.grid[*line].marks.contains(Marks::OUTPUT))?;
+        self.output_lines(start).filter(|(_, end)| line <= *end)
+    }
+
+    /// First and last line of the output starting on `start`.
+    fn output_lines(&self, start: Line) -> Option<(Line, Line)> {
+        // Commands without output end on the row before their output starts.
+        if start > self.grid.topmost_line()
+            && self.grid[start - 1i32].marks.contains(Marks::OUTPUT_END)
+        {
+            return None;
+        }
+
+        let mut end = self.grid.cursor.pos.row;
+        for line in (start.0..=self.grid.bottommost_line().0).map(Line) {
+            let marks = self.grid[line].marks;
+            if marks.contains(Marks::OUTPUT_END) {
+                end = line;
+                break;
+            }
+            if line > start && marks.contains(Marks::PROMPT) {
+                end = line - 1i32;
+                break;
+            }
+        }
+
+        Some((start, end))
+    }
+
+    /// Exit status and duration of the last command marked by the shell.
+    #[inline]
+    pub fn last_command(&self) -> Option<(Option<i32>, Duration)> {
+        self.last_command
+    }
+
+    /// Collect lines of output to be announced.
+    pub fn set_reader_mode(&mut self, enabled: bool) {
+        self.announcements = if enabled { Some(vec![]) } else { None };
+    }
+
+    /// Lines of output finished since the last call, oldest first.
+    pub fn take_announcements(&mut self) -> Vec<String> {
+        self.announcements
+            .as_mut()
+            .map(mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Keep the line the cursor leaves to be announced, unless it's blank
+    /// or drawn by a full screen application.
+    fn collect_announcement(&mut self) {
+        if self.announcements.is_none() || self.mode.contains(Mode::ALT_SCREEN) {
+            return;
+        }
+
+        let line = self.grid.cursor.pos.row;
+        let text = self.line_to_string(line, Column(0)..self.grid.last_column(), true);
+        let text = text.trim();
+        if let Some(announcements) = &mut self.announcements {
+            if text.is_empty() {
+                return;
+            }
+            // Output nobody takes doesn't pile up, the latest lines are kept.
+            if announcements.len() == MAX_ANNOUNCEMENTS {
+                announcements.remove(0);
+            }
+            announcements.push(text.to_string());
+        }
+    }
+
+    /// Switch between dark and light colors, telling applications that
+    /// asked to be notified with mode 2031.
+    pub fn set_dark(&mut self, is_dark: bool) {
+        if self.is_dark == is_dark {
+            return;
+        }
+
+        self.is_dark = is_dark;
+        if self.mode.contains(Mode::COLOR_SCHEME_REPORT) {
+            self.report_color_scheme();
+        }
+    }
+
+    /// Mark the row where the command whose output ends on `line` was
+    /// entered with its exit status, when the shell reported one.
+    fn mark_command_status(&mut self, line: Line, status: Option<i32>) {
+        let mark = match status {
+            Some(0) => Marks::SUCCEEDED,
+            Some(_) => Marks::FAILED,
+            None => return,
+        };
+
+        let topmost_line = self.grid.topmost_line();
+        if line < topmost_line {
+            return;
+        }
+        let command = (topmost_line.0..=line.0)
+            .rev()
+            .map(Line)
+            .find(|line| self.grid[*line].marks.contains(Marks::COMMAND));
+        if let Some(command) = command {
+            self.grid[command].marks.insert(mark);
+        }
+    }
+
+    /// Text typed by the user from the last command mark up to the line
+    /// before the cursor, once the command was entered.
+    fn entered_command(&self) -> String {
+        let cursor = self.grid.cursor.pos;
+        let start = (self.grid.topmost_line().0..=cursor.row.0)
+            .rev()
+            .map(Line)
+            .find(|line| self.grid[*line].marks.contains(Marks::COMMAND));
+        let end = if cursor.col == Column(0) {
+            cursor.row - 1i32
+        } else {
+            cursor.row
+        };
+
+        match start {
+            Some(start) if start <= end => {
+                let start = Pos::new(start, self.command_column);
+                let end = Pos::new(end, self.grid.last_column());
+                self.bounds_to_string(start, end).trim().to_string()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Text of `line`, without the trailing newline.
+    pub fn line_text(&self, line: Line) -> String {
+        let mut text =
+            self.line_to_string(line, Column(0)..self.grid.last_column(), true);
+        if text.ends_with('\n') {
+            text.pop();
+        }
+        text
+    }
+
     pub fn history_size(&self) -> usize {
         self.grid
             .total_lines()
@@ -521,8 +1044,35 @@ impl<U: EventListener> Crosswords<U> {
             .take()
             .and_then(|s| s.rotate(&self.grid, &region, lines as i32));
 
+        // The extra storage of the lines is counted as they enter the
+        // scrollback. The lines it can't hold are dropped ahead, so theirs
+        // is still there to be subtracted.
+        let count_memory = self.history_memory_limit != 0
+            && !self.mode.contains(Mode::ALT_SCREEN)
+            && (region.start == 0 || region.end - region.start > lines);
+        if count_memory {
+            let history = self.grid.history_size();
+            let max_history = self.grid.max_scroll_limit();
+            let overflow =
+                std::cmp::min(history, (history + lines).saturating_sub(max_history));
+            for line in (history - overflow + 1)..=history {
+                self.history_extra_memory -=
+                    row_extra_memory(&self.grid[Line(-(line as i32))]);
+            }
+            self.grid.update_history(history - overflow);
+            self.grid.update_history(max_history);
+        }
+
         self.grid.scroll_up(&region, lines);
 
+        if count_memory {
+            for line in 1..=std::cmp::min(lines, self.grid.history_size()) {
+                self.history_extra_memory +=
+                    row_extra_memory(&self.grid[Line(-(line as i32))]);
+            }
+            self.update_history();
+        }
+
         // Scroll vi mode cursor.
         let viewport_top = Line(-(self.grid.display_offset() as i32));
         let top = if region.start == 0 {
@@ -634,6 +1184,8 @@ impl<U: EventListener> Crosswords<U> {
     }
 
     pub fn write_at_cursor(&mut self, c: char) {
+        self.damage_cursor();
+
         let c = self.grid.cursor.charsets[self.active_charset].map(c);
         let fg = self.grid.cursor.template.fg;
         let bg = self.grid.cursor.template.bg;
@@ -813,6 +1365,136 @@ impl<U: EventListener> Crosswords<U> {
         Some(res)
     }
 
+    /// Grid of the primary screen, even while the alternate screen is active.
+    #[inline]
+    pub fn primary_grid(&self) -> &Grid<Square> {
+        if self.mode.contains(Mode::ALT_SCREEN) {
+            &self.inactive_grid
+        } else {
+            &self.grid
+        }
+    }
+
+    /// Bounds of the lines currently visible on the screen.
+    pub fn screen_bounds(&self) -> (Pos, Pos) {
+        let display_offset = self.grid.display_offset() as i32;
+        let start = Pos::new(Line(-display_offset), Column(0));
+        let end = Pos::new(
+            Line(self.grid.screen_lines() as i32 - 1 - display_offset),
+            self.grid.last_column(),
+        );
+
+        (start, end)
+    }
+
+    /// Bounds of the whole scrollback, including the visible screen.
+    pub fn scrollback_bounds(&self) -> (Pos, Pos) {
+        let start = Pos::new(self.grid.topmost_line(), Column(0));
+        let end = Pos::new(self.grid.bottommost_line(), self.grid.last_column());
+
+        (start, end)
+    }
+
+    /// Bounds of the lines `first..=last` clamped to the grid, where line 0
+    /// is the top of the screen and negative lines are in the scrollback.
+    pub fn lines_bounds(&self, first: i32, last: i32) -> Option<(Pos, Pos)> {
+        let first = Line(first).max(self.grid.topmost_line());
+        let last = Line(last).min(self.grid.bottommost_line());
+        if first > last {
+            return None;
+        }
+
+        Some((
+            Pos::new(first, Column(0)),
+            Pos::new(last, self.grid.last_column()),
+        ))
+    }
+
+    /// Text of the lines currently visible on the screen.
+    pub fn screen_to_string(&self) -> String {
+        let (start, end) = self.screen_bounds();
+        self.bounds_to_string(start, end)
+    }
+
+    /// Text of the whole scrollback, including the visible screen.
+    pub fn scrollback_to_string(&self) -> String {
+        let (start, end) = self.scrollback_bounds();
+        self.bounds_to_string(start, end)
+    }
+
+    /// Collect the selected squares, one entry per line of output.
+    ///
+    /// Wrapped lines are joined and trailing blanks are dropped, following
+    /// the text produced by `selection_to_string`.
+    pub fn selection_to_squares(&self) -> Option<Vec<Vec<Square>>> {
+        let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
+        let SelectionRange {
+            start,
+            end,
+            is_block,
+        } = selection_range;
+
+        Some(self.squares_in(start, end, is_block))
+    }
+
+    /// Collect the squares between `start` and `end`, one entry per line of
+    /// output, like `selection_to_squares` does for the selection.
+    pub fn bounds_to_squares(&self, start: Pos, end: Pos) -> Vec<Vec<Square>> {
+        self.squares_in(start, end, false)
+    }
+
+    fn squares_in(&self, start: Pos, end: Pos, is_block: bool) -> Vec<Vec<Square>> {
+        let last_column = self.grid.last_column();
+
+        let mut lines = Vec::new();
+        let mut current: Vec<Square> = Vec::new();
+        for line in (start.row.0..=end.row.0).map(Line::from) {
+            let start_col = if is_block || line == start.row {
+                start.col
+            } else {
+                Column(0)
+            };
+            let end_col = if is_block || line == end.row {
+                std::cmp::min(end.col, last_column)
+            } else {
+                last_column
+            };
+
+            let grid_line = &self.grid[line];
+            for column in (start_col.0..=end_col.0).map(Column::from) {
+                let square = &grid_line[column];
+                if !square.flags.intersects(
+                    square::Flags::WIDE_CHAR_SPACER
+                        | square::Flags::LEADING_WIDE_CHAR_SPACER,
+                ) {
+                    if self.is_copy_hidden(square) {
+                        current.push(Square {
+                            c: ' ',
+                            extra: None,
+                            ..square.clone()
+                        });
+                    } else {
+                        current.push(square.clone());
+                    }
+                }
+            }
+
+            let is_wrapped = !is_block
+                && end_col == last_column
+                && grid_line[last_column]
+                    .flags
+                    .contains(square::Flags::WRAPLINE);
+            if !is_wrapped || line == end.row {
+                while current.last().map_or(false, |square| square.c == ' ') {
+                    current.pop();
+                }
+                lines.push(mem::take(&mut current));
+            }
+        }
+
+        lines
+    }
+
     pub fn bounds_to_string(&self, start: Pos, end: Pos) -> String {
         let mut res = String::new();
 
@@ -834,6 +1516,14 @@ impl<U: EventListener> Crosswords<U> {
         res.strip_suffix('\n').map(str::to_owned).unwrap_or(res)
     }
 
+    /// Whether `square` is hidden with SGR 8 and copied as a space.
+    #[inline]
+    fn is_copy_hidden(&self, square: &Square) -> bool {
+        !self.copy_hidden_text
+            && square.flags.contains(square::Flags::HIDDEN)
+            && square.c != '\t'
+    }
+
     /// Convert a single line in the grid to a String.
     fn line_to_string(
         &self,
@@ -874,6 +1564,11 @@ impl<U: EventListener> Crosswords<U> {
             if !cell.flags.intersects(
                 square::Flags::WIDE_CHAR_SPACER | square::Flags::LEADING_WIDE_CHAR_SPACER,
             ) {
+                if self.is_copy_hidden(cell) {
+                    text.push(' ');
+                    continue;
+                }
+
                 // Push cells primary character.
                 text.push(cell.c);
 
@@ -938,6 +1633,14 @@ impl<U: EventListener> Crosswords<U> {
     }
 }
 
+impl<O: Observer> Crosswords<Observed<O>> {
+    /// Observer of the events of the terminal, e.g. to read what it kept.
+    #[inline]
+    pub fn observer(&self) -> &O {
+        &self.event_proxy.0
+    }
+}
+
 impl<U: EventListener> Handler for Crosswords<U> {
     #[inline]
     fn set_mode(&mut self, mode: AnsiMode) {
@@ -968,6 +1671,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             AnsiMode::ReportFocusInOut => self.mode.insert(Mode::FOCUS_IN_OUT),
             AnsiMode::BracketedPaste => self.mode.insert(Mode::BRACKETED_PASTE),
+            AnsiMode::ReportColorScheme => self.mode.insert(Mode::COLOR_SCHEME_REPORT),
             // Mouse encodings are mutually exclusive.
             AnsiMode::SgrMouse => {
                 self.mode.remove(Mode::UTF8_MOUSE);
@@ -1016,6 +1720,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             AnsiMode::ReportFocusInOut => self.mode.remove(Mode::FOCUS_IN_OUT),
             AnsiMode::BracketedPaste => self.mode.remove(Mode::BRACKETED_PASTE),
+            AnsiMode::ReportColorScheme => self.mode.remove(Mode::COLOR_SCHEME_REPORT),
             AnsiMode::SgrMouse => self.mode.remove(Mode::SGR_MOUSE),
             AnsiMode::Utf8Mouse => self.mode.remove(Mode::UTF8_MOUSE),
             AnsiMode::AlternateScroll => self.mode.remove(Mode::ALTERNATE_SCROLL),
@@ -1325,20 +2030,99 @@ impl<U: EventListener> Handler for Crosswords<U> {
             Attr::CancelHidden => cursor.template.flags.remove(square::Flags::HIDDEN),
             Attr::Strike => cursor.template.flags.insert(square::Flags::STRIKEOUT),
             Attr::CancelStrike => cursor.template.flags.remove(square::Flags::STRIKEOUT),
-            _ => {
-                warn!("Term got unhandled attr: {:?}", attr);
+            // Both blink at the same rate.
+            Attr::BlinkSlow | Attr::BlinkFast => {
+                cursor.template.flags.insert(square::Flags::BLINK)
+            }
+            Attr::CancelBlink => cursor.template.flags.remove(square::Flags::BLINK),
+        }
+    }
+
+    fn set_current_directory(&mut self, path: PathBuf) {
+        self.current_directory = Some(path);
+    }
+
+    fn semantic_prompt(&mut self, mark: SemanticPrompt) {
+        let cursor = self.grid.cursor.pos;
+        match mark {
+            SemanticPrompt::PromptStart => {
+                self.grid[cursor.row].marks.insert(Marks::PROMPT);
+                self.has_prompt_marks = true;
+            }
+            SemanticPrompt::CommandStart => {
+                self.grid[cursor.row].marks.insert(Marks::COMMAND);
+                self.command_column = cursor.col;
+            }
+            SemanticPrompt::OutputStart => {
+                self.grid[cursor.row].marks.insert(Marks::OUTPUT);
+                let command = self.entered_command();
+                self.running_command = Some((command, Instant::now()));
+            }
+            SemanticPrompt::CommandEnd(status) => {
+                // Output usually ends with a newline, leaving the cursor at
+                // the start of the next row.
+                let line = if cursor.col == Column(0) {
+                    cursor.row - 1i32
+                } else {
+                    cursor.row
+                };
+                if line >= self.grid.topmost_line() {
+                    self.grid[line].marks.insert(Marks::OUTPUT_END);
+                }
+
+                if let Some((command, started_at)) = self.running_command.take() {
+                    let duration = started_at.elapsed();
+                    self.mark_command_status(line, status);
+                    self.last_command = Some((status, duration));
+                    self.event_proxy.send_event(RioEvent::CommandFinished {
+                        command,
+                        status,
+                        duration,
+                    });
+                }
             }
         }
     }
 
+    /// Reset the terminal like RIS, keeping its size and the settings it
+    /// was configured with (e.g. history limits and enabled features).
+    fn reset_state(&mut self) {
+        if self.mode.contains(Mode::ALT_SCREEN) {
+            mem::swap(&mut self.grid, &mut self.inactive_grid);
+        }
+        self.grid.reset::<AnsiColor>();
+        self.inactive_grid.reset::<AnsiColor>();
+        self.history_extra_memory = 0;
+
+        let is_vi = self.mode.contains(Mode::VI);
+        self.mode = Mode::default();
+        self.mode.set(Mode::VI, is_vi);
+        self.active_charset = CharsetIndex::default();
+        self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.tabs = TabStops::new(self.grid.columns());
+        self.selection = None;
+        self.vi_mode_cursor = Pos::default();
+        self.current_directory = None;
+        self.has_prompt_marks = false;
+        self.command_column = Column(0);
+        self.running_command = None;
+        self.last_command = None;
+        self.cursor_color = None;
+        if let Some(announcements) = &mut self.announcements {
+            announcements.clear();
+        }
+        self.set_title(None);
+        self.mark_fully_damaged();
+    }
+
     fn set_title(&mut self, title: Option<String>) {
         self.title = title;
 
-        let _title: String = match &self.title {
-            Some(title) => title.to_string(),
-            None => String::from(""),
+        let event = match &self.title {
+            Some(title) => RioEvent::Title(title.to_string()),
+            None => RioEvent::ResetTitle,
         };
-        // title
+        self.event_proxy.send_event(event);
     }
 
     fn input(&mut self, c: char) {
@@ -1365,6 +2149,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
 
             self.grid[row][column].push_zerowidth(c);
+            self.damage.damage_point(Pos::new(row, column));
             return;
         }
 
@@ -1443,6 +2228,32 @@ impl<U: EventListener> Handler for Crosswords<U> {
         }
     }
 
+    #[inline]
+    fn input_ascii(&mut self, text: &[u8]) {
+        // Inserting shifts the rest of the line for each character.
+        if self.mode.contains(Mode::INSERT) {
+            for byte in text {
+                self.input(*byte as char);
+            }
+            return;
+        }
+
+        let columns = self.grid.columns();
+        for byte in text {
+            if self.grid.cursor.should_wrap {
+                self.wrapline();
+            }
+
+            self.write_at_cursor(*byte as char);
+
+            if self.grid.cursor.pos.col + 1 < columns {
+                self.grid.cursor.pos.col += 1;
+            } else {
+                self.grid.cursor.should_wrap = true;
+            }
+        }
+    }
+
     #[inline]
     fn newline(&mut self) {
         self.linefeed();
@@ -1510,6 +2321,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
                     // let old_offset = self.grid.display_offset();
 
                     self.grid.clear_viewport();
+                    self.count_history_memory();
+                    self.update_history();
 
                     // Compute number of lines scrolled by clearing the viewport.
                     // let lines = self.grid.display_offset().saturating_sub(old_offset);
@@ -1522,6 +2335,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             ClearMode::Saved if self.history_size() > 0 => {
                 self.grid.clear_history();
+                self.count_history_memory();
 
                 // self.vi_mode_cursor.pos.row =
                 // self.vi_mode_cursor.pos.row.grid_clamp(self, Boundary::Cursor);
@@ -1551,6 +2365,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
     }
 
     fn linefeed(&mut self) {
+        self.collect_announcement();
         let next = self.grid.cursor.pos.row + 1;
         if next == self.scroll_region.end {
             self.scroll_up_relative(self.scroll_region.start, 1);
@@ -1561,30 +2376,26 @@ impl<U: EventListener> Handler for Crosswords<U> {
         }
     }
 
-    /// Set the indexed color value.
+    /// Set the indexed color value, only the cursor color is supported.
     #[inline]
-    fn set_color(&mut self, _index: usize, _color: ColorRgb) {
-        // Damage terminal if the color changed and it's not the cursor.
-        // if index != NamedColor::Cursor as usize && self.colors[index] != Some(color) {
-        // self.mark_fully_damaged();
-        // }
-
-        // self.colors[index] = Some(color);
+    fn set_color(&mut self, index: usize, color: ColorRgb) {
+        if index == NamedColor::Cursor as usize {
+            self.cursor_color = Some(color);
+            self.damage_cursor();
+        }
     }
 
-    // #[inline]
-    // fn reset_color(&mut self, index: usize) {
-    //     // Damage terminal if the color changed and it's not the cursor.
-    //     if index != NamedColor::Cursor as usize && self.colors[index].is_some() {
-    //         // self.mark_fully_damaged();
-    //     }
-
-    //     self.colors[index] = None;
-    // }
+    #[inline]
+    fn reset_color(&mut self, index: usize) {
+        if index == NamedColor::Cursor as usize {
+            self.cursor_color = None;
+            self.damage_cursor();
+        }
+    }
 
     #[inline]
     fn bell(&mut self) {
-        warn!("[unimplemented] Bell");
+        self.event_proxy.send_event(RioEvent::Bell);
     }
 
     #[inline]
@@ -1592,6 +2403,29 @@ impl<U: EventListener> Handler for Crosswords<U> {
         warn!("[unimplemented] Substitute");
     }
 
+    #[inline]
+    fn clipboard_store(&mut self, clipboard: u8, base64: &[u8]) {
+        if !self.clipboard_write {
+            debug!("clipboard write disabled, ignoring OSC 52");
+            return;
+        }
+
+        let clipboard_type = match clipboard {
+            b'c' => ClipboardType::Clipboard,
+            b'p' | b's' => ClipboardType::Selection,
+            _ => return,
+        };
+
+        let text = general_purpose::STANDARD
+            .decode(base64)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+        if let Some(text) = text {
+            self.event_proxy
+                .send_event(RioEvent::ClipboardStore(clipboard_type, text));
+        }
+    }
+
     #[inline]
     fn clipboard_load(&mut self, clipboard: u8, terminator: &str) {
         let clipboard_type = match clipboard {
@@ -1711,6 +2545,19 @@ impl<U: EventListener> Handler for Crosswords<U> {
         // })));
     }
 
+    #[inline]
+    fn report_termcap(&mut self, name: &[u8]) {
+        let answer = termcap::answer(name, &self.term_name, self.truecolor);
+        self.event_proxy.send_event(RioEvent::PtyWrite(answer));
+    }
+
+    #[inline]
+    fn report_color_scheme(&mut self) {
+        let scheme = if self.is_dark { 1 } else { 2 };
+        self.event_proxy
+            .send_event(RioEvent::PtyWrite(format!("\x1b[?997;{scheme}n")));
+    }
+
     #[inline]
     fn text_area_size_chars(&mut self) {
         let text = format!(
@@ -1727,6 +2574,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
 #[cfg(test)]
 pub mod test {
     use super::*;
+    use crate::event::VoidListener;
+    use crate::performer::handler::ParserProcessor;
 
     pub struct CrosswordsSize {
         pub columns: usize,
@@ -1755,15 +2604,191 @@ pub mod test {
             self.columns
         }
     }
+
+    /// Listener keeping every event sent by the terminal, passed by
+    /// reference so tests can read them afterwards.
+    #[derive(Default)]
+    pub struct RecordingListener(std::cell::RefCell<Vec<RioEvent>>);
+
+    impl RecordingListener {
+        pub fn events(&self) -> std::cell::Ref<'_, Vec<RioEvent>> {
+            self.0.borrow()
+        }
+
+        /// Text written back to the PTY, e.g. answers to queries.
+        pub fn pty_writes(&self) -> Vec<String> {
+            self.events()
+                .iter()
+                .filter_map(|event| match event {
+                    RioEvent::PtyWrite(text) => Some(text.clone()),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// Text stored into any clipboard.
+        pub fn clipboard_stores(&self) -> Vec<String> {
+            self.events()
+                .iter()
+                .filter_map(|event| match event {
+                    RioEvent::ClipboardStore(_, text) => Some(text.clone()),
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
+    impl EventListener for &RecordingListener {
+        fn send_event(&self, event: RioEvent) {
+            self.0.borrow_mut().push(event);
+        }
+    }
+
+    /// Terminal of a given size, with text and escape sequences fed to the
+    /// parser as a starting point.
+    pub struct CrosswordsBuilder {
+        columns: usize,
+        screen_lines: usize,
+        input: Vec<u8>,
+    }
+
+    impl CrosswordsBuilder {
+        pub fn new(columns: usize, screen_lines: usize) -> Self {
+            Self {
+                columns,
+                screen_lines,
+                input: vec![],
+            }
+        }
+
+        /// Write `rows` from the top left corner, one per line.
+        pub fn rows(mut self, rows: &[&str]) -> Self {
+            self.input.extend_from_slice(rows.join("\r\n").as_bytes());
+            self
+        }
+
+        /// Move the cursor to `row` and `col`, counted from 0.
+        pub fn cursor(mut self, row: usize, col: usize) -> Self {
+            let goto = format!("\x1b[{};{}H", row + 1, col + 1);
+            self.input.extend_from_slice(goto.as_bytes());
+            self
+        }
+
+        /// Feed `bytes` to the parser, e.g. escape sequences.
+        pub fn input(mut self, bytes: impl AsRef<[u8]>) -> Self {
+            self.input.extend_from_slice(bytes.as_ref());
+            self
+        }
+
+        pub fn build(self) -> Crosswords<VoidListener> {
+            self.build_with(VoidListener {})
+        }
+
+        /// Same as `build`, sending the events to `listener`.
+        pub fn build_with<U: EventListener>(self, listener: U) -> Crosswords<U> {
+            let mut terminal = Crosswords::new(self.columns, self.screen_lines, listener);
+            ParserProcessor::new().advance_bytes(&mut terminal, &self.input);
+            terminal
+        }
+    }
+
+    /// Text of each row of the screen, without trailing spaces.
+    pub fn screen_rows<U: EventListener>(terminal: &Crosswords<U>) -> Vec<String> {
+        (0..terminal.grid.screen_lines())
+            .map(|line| {
+                let text: String = terminal.grid[Line(line as i32)][..]
+                    .iter()
+                    .filter(|cell| !cell.flags.contains(square::Flags::WIDE_CHAR_SPACER))
+                    .map(|cell| cell.c)
+                    .collect();
+                text.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Every row, with the ones which differ as `-` for `expected` and `+`
+    /// for `actual`, or `None` when they are the same. Missing rows are
+    /// empty.
+    pub fn grid_diff(actual: &[String], expected: &[&str]) -> Option<String> {
+        let mut diff = String::new();
+        let mut differs = false;
+        for line in 0..actual.len().max(expected.len()) {
+            let actual = actual.get(line).map_or("", String::as_str);
+            let expected = expected.get(line).copied().unwrap_or_default();
+            if actual == expected {
+                diff.push_str(&format!("  {line:>3} |{actual}|\n"));
+            } else {
+                differs = true;
+                diff.push_str(&format!("- {line:>3} |{expected}|\n"));
+                diff.push_str(&format!("+ {line:>3} |{actual}|\n"));
+            }
+        }
+
+        differs.then_some(diff)
+    }
+
+    /// Assert the screen of a terminal shows the `expected` rows, trailing
+    /// spaces aside, printing the rows which differ otherwise.
+    #[macro_export]
+    macro_rules! assert_grid_eq {
+        ($terminal:expr, $expected:expr $(,)?) => {
+            $crate::assert_grid_eq!($terminal, $expected, "grids differ")
+        };
+        ($terminal:expr, $expected:expr, $($message:tt)+) => {{
+            let rows = $crate::crosswords::test::screen_rows(&$terminal);
+            if let Some(diff) = $crate::crosswords::test::grid_diff(&rows, &$expected) {
+                panic!("{}:\n{}", format_args!($($message)+), diff);
+            }
+        }};
+    }
+
+    /// Terminal drawn by a corpus of escape sequences, and the rows it is
+    /// expected to show. Corpora have `#` comments, a `size COLUMNSxLINES`,
+    /// `input` lines with escaped bytes and the rows after `---`.
+    pub fn run_corpus(corpus: &str) -> (Crosswords<VoidListener>, Vec<&str>) {
+        let (header, expected) =
+            corpus.split_once("\n---\n").expect("corpus without `---`");
+
+        let mut builder = None;
+        let mut input = vec![];
+        for line in header.lines() {
+            if let Some(size) = line.strip_prefix("size ") {
+                let (columns, lines) = size.split_once('x').expect("invalid size");
+                builder = Some(CrosswordsBuilder::new(
+                    columns.parse().expect("invalid columns"),
+                    lines.parse().expect("invalid lines"),
+                ));
+            } else if let Some(text) = line.strip_prefix("input ") {
+                input.extend(crate::ansi::unescape(text).into_bytes());
+            } else if !line.is_empty() && !line.starts_with('#') {
+                panic!("unexpected corpus line {line:?}");
+            }
+        }
+
+        let terminal = builder.expect("corpus without size").input(input).build();
+        (terminal, expected.lines().collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert_grid_eq;
     use crate::crosswords::pos::{Column, Line, Pos, Side};
-    use crate::crosswords::test::CrosswordsSize;
+    use crate::crosswords::test::{
+        grid_diff, run_corpus, CrosswordsBuilder, CrosswordsSize, RecordingListener,
+    };
     use crate::event::VoidListener;
 
+    /// Escape sequences and the screen they draw, run by `test_corpora`.
+    const CORPORA: [(&str, &str); 5] = [
+        ("cursor_motion", include_str!("corpus/cursor_motion.txt")),
+        ("erase", include_str!("corpus/erase.txt")),
+        ("scroll_region", include_str!("corpus/scroll_region.txt")),
+        ("insert_delete", include_str!("corpus/insert_delete.txt")),
+        ("wrap", include_str!("corpus/wrap.txt")),
+    ];
+
     #[test]
     fn scroll_up() {
         let mut cw = Crosswords::new(1, 10, VoidListener {});
@@ -2014,4 +3039,457 @@ mod tests {
             Some(String::from("\na\"\na\"\na"))
         );
     }
+
+    #[test]
+    fn test_screen_and_scrollback_to_string() {
+        let mut cw = Crosswords::new(3, 2, VoidListener {});
+        cw.grid[Line(0)][Column(0)].c = 'a';
+        cw.grid[Line(1)][Column(0)].c = 'b';
+        cw.grid.scroll_up(&(Line(0)..Line(2)), 1);
+        cw.grid[Line(1)][Column(0)].c = 'c';
+
+        assert_eq!(cw.screen_to_string(), String::from("b\nc"));
+        assert_eq!(cw.scrollback_to_string(), String::from("a\nb\nc"));
+    }
+
+    #[test]
+    fn test_lines_bounds() {
+        let mut cw = Crosswords::new(3, 2, VoidListener {});
+        cw.grid[Line(0)][Column(0)].c = 'a';
+        cw.grid[Line(1)][Column(0)].c = 'b';
+        cw.grid.scroll_up(&(Line(0)..Line(2)), 1);
+        cw.grid[Line(1)][Column(0)].c = 'c';
+
+        let (start, end) = cw.lines_bounds(-100, 0).unwrap();
+        assert_eq!(cw.bounds_to_string(start, end), String::from("a\nb"));
+        let squares = cw.bounds_to_squares(start, end);
+        assert_eq!(squares.len(), 2);
+        assert_eq!(squares[1][0].c, 'b');
+
+        assert_eq!(
+            cw.lines_bounds(1, 1).map(|(start, _)| start.row),
+            Some(Line(1))
+        );
+        assert_eq!(cw.lines_bounds(2, 10), None);
+        assert_eq!(cw.lines_bounds(1, 0), None);
+    }
+
+    #[test]
+    fn test_title_event() {
+        let events = RecordingListener::default();
+        let mut cw = CrosswordsBuilder::new(20, 4)
+            .input("\x1b]2;vim\x07")
+            .build_with(&events);
+        assert_eq!(cw.title.as_deref(), Some("vim"));
+        match events.events().last() {
+            Some(RioEvent::Title(title)) => assert_eq!(title, "vim"),
+            event => panic!("unexpected event {event:?}"),
+        }
+
+        cw.set_title(None);
+        assert!(matches!(events.events().last(), Some(RioEvent::ResetTitle)));
+    }
+
+    #[test]
+    fn test_command_finished() {
+        let events = RecordingListener::default();
+        let mut cw = Crosswords::new(20, 4, &events);
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"\x1b]133;A\x07$ \x1b]133;B\x07make test\r\n");
+        parser.advance_bytes(&mut cw, b"\x1b]133;C\x07ok\r\n\x1b]133;D;2\x07");
+
+        match events.events().last() {
+            Some(RioEvent::CommandFinished {
+                command, status, ..
+            }) => {
+                assert_eq!(command, "make test");
+                assert_eq!(*status, Some(2));
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+
+        assert!(cw.grid[Line(0)].marks.contains(Marks::FAILED));
+        assert!(!cw.grid[Line(1)].marks.contains(Marks::FAILED));
+        assert_eq!(cw.last_command().map(|(status, _)| status), Some(Some(2)));
+
+        parser.advance_bytes(&mut cw, b"\x1b]133;A\x07$ \x1b]133;B\x07true\r\n");
+        parser.advance_bytes(&mut cw, b"\x1b]133;C\x07\x1b]133;D;0\x07");
+        assert!(cw.grid[Line(2)].marks.contains(Marks::SUCCEEDED));
+    }
+
+    #[test]
+    fn test_announcements() {
+        let mut cw = Crosswords::new(20, 4, VoidListener {});
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"ignored\r\n");
+        assert!(cw.take_announcements().is_empty());
+
+        cw.set_reader_mode(true);
+        parser.advance_bytes(&mut cw, b"first\r\n\r\n  second  \r\nthird");
+        assert_eq!(cw.take_announcements(), vec!["first", "second"]);
+        assert!(cw.take_announcements().is_empty());
+
+        parser.advance_bytes(&mut cw, b"\x1b[?1049hmenu\r\n");
+        assert!(cw.take_announcements().is_empty());
+
+        parser.advance_bytes(&mut cw, b"\x1b[?1049l");
+        for line in 0..MAX_ANNOUNCEMENTS + 5 {
+            parser.advance_bytes(&mut cw, format!("{line}\r\n").as_bytes());
+        }
+        let announcements = cw.take_announcements();
+        assert_eq!(announcements.len(), MAX_ANNOUNCEMENTS);
+        assert_eq!(announcements[0], "5");
+        assert_eq!(announcements[MAX_ANNOUNCEMENTS - 1], "104");
+    }
+
+    #[test]
+    fn test_semantic_escape_chars() {
+        let mut cw = CrosswordsBuilder::new(20, 3).input("a:b:c d").build();
+        let point = Pos::new(Line(0), Column(2));
+        assert_eq!(cw.semantic_search_left(point).col, Column(2));
+        assert_eq!(cw.semantic_search_right(point).col, Column(2));
+
+        cw.set_semantic_escape_chars(" ");
+        assert_eq!(cw.semantic_search_left(point).col, Column(0));
+        assert_eq!(cw.semantic_search_right(point).col, Column(4));
+    }
+
+    #[test]
+    fn test_blink() {
+        let cw = CrosswordsBuilder::new(20, 3)
+            .input("\x1b[5ma\x1b[25mb\x1b[6mc\x1b[0md")
+            .build();
+        let row = &cw.grid[Line(0)];
+        assert!(row[Column(0)].flags.contains(square::Flags::BLINK));
+        assert!(!row[Column(1)].flags.contains(square::Flags::BLINK));
+        assert!(row[Column(2)].flags.contains(square::Flags::BLINK));
+        assert!(!row[Column(3)].flags.contains(square::Flags::BLINK));
+    }
+
+    #[test]
+    fn test_damage() {
+        let mut cw = Crosswords::new(20, 5, VoidListener {});
+        assert!(matches!(cw.damage(), TermDamage::Full));
+        cw.reset_damage();
+
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"\x1b[3;1Hab");
+        let damage: Vec<LineDamageBounds> = match cw.damage() {
+            TermDamage::Partial(lines) => lines.collect(),
+            TermDamage::Full => panic!("expected partial damage"),
+        };
+        assert_eq!(
+            damage,
+            vec![
+                LineDamageBounds {
+                    line: 0,
+                    left: 0,
+                    right: 0
+                },
+                LineDamageBounds {
+                    line: 2,
+                    left: 0,
+                    right: 2
+                },
+            ]
+        );
+
+        // The cursor line is always damaged.
+        cw.reset_damage();
+        let lines: Vec<usize> = match cw.damage() {
+            TermDamage::Partial(lines) => lines.map(|damage| damage.line).collect(),
+            TermDamage::Full => panic!("expected partial damage"),
+        };
+        assert_eq!(lines, vec![2]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut cw = CrosswordsBuilder::new(20, 3)
+            .input("ab\x1b]2;title\x07")
+            .build();
+
+        let snapshot = cw.snapshot();
+        assert_eq!(snapshot.rows.len(), 3);
+        assert_eq!(snapshot.rows[0][Column(1)].c, 'b');
+        assert_eq!(snapshot.damaged_lines, None);
+        assert_eq!(snapshot.cursor.pos, Pos::new(Line(0), Column(2)));
+        assert_eq!(snapshot.title.as_deref(), Some("title"));
+
+        // Only the cursor line is damaged since the last snapshot.
+        assert_eq!(cw.snapshot().damaged_lines, Some(vec![0]));
+    }
+
+    #[test]
+    fn test_copy_hidden_text() {
+        let mut cw = CrosswordsBuilder::new(20, 3)
+            .input("a\x1b[8;9mbc\x1b[28;29md")
+            .build();
+        let row = &cw.grid[Line(0)];
+        assert!(row[Column(1)]
+            .flags
+            .contains(square::Flags::HIDDEN | square::Flags::STRIKEOUT));
+        assert!(!row[Column(3)]
+            .flags
+            .intersects(square::Flags::HIDDEN | square::Flags::STRIKEOUT));
+
+        let start = Pos::new(Line(0), Column(0));
+        let end = Pos::new(Line(0), Column(3));
+        assert_eq!(cw.bounds_to_string(start, end), "a  d");
+
+        cw.set_copy_hidden_text(true);
+        assert_eq!(cw.bounds_to_string(start, end), "abcd");
+    }
+
+    #[test]
+    fn test_cursor_color() {
+        let mut cw = CrosswordsBuilder::new(20, 3)
+            .input("\x1b]12;#ff8000\x07")
+            .build();
+        assert_eq!(
+            cw.cursor_color(),
+            Some(ColorRgb {
+                r: 0xff,
+                g: 0x80,
+                b: 0x00
+            })
+        );
+
+        crate::performer::handler::ParserProcessor::new()
+            .advance_bytes(&mut cw, b"\x1b]112\x07");
+        assert_eq!(cw.cursor_color(), None);
+    }
+
+    #[test]
+    fn test_clear_screen_and_history() {
+        let mut cw = CrosswordsBuilder::new(20, 3)
+            .rows(&["1", "2", "3", "4", "$ ls"])
+            .build();
+        assert_eq!(cw.history_size(), 2);
+
+        cw.clear_history();
+        assert_eq!(cw.history_size(), 0);
+        assert_grid_eq!(cw, ["3", "4", "$ ls"]);
+
+        cw.clear_screen_and_history();
+        assert_eq!(cw.history_size(), 0);
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(0), Column(4)));
+        assert_grid_eq!(cw, ["$ ls"]);
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut cw = CrosswordsBuilder::new(20, 3)
+            .rows(&["1", "2", "3", "4", "5", "6"])
+            .build();
+        assert_eq!(cw.history_size(), 3);
+
+        // Oldest lines are dropped first.
+        cw.set_history_limit(2, 0);
+        assert_eq!(cw.history_size(), 2);
+        cw.scroll_display(Scroll::Top);
+        assert_eq!(cw.screen_to_string().lines().next(), Some("2"));
+        cw.scroll_display(Scroll::Bottom);
+
+        let row_size = mem::size_of::<Row<Square>>() + 20 * mem::size_of::<Square>();
+        cw.set_history_limit(10, row_size);
+        assert_eq!(cw.history_size(), 1);
+
+        // Fewer wider rows fit in the same budget.
+        cw.set_history_limit(10, row_size * 2);
+        crate::performer::handler::ParserProcessor::new()
+            .advance_bytes(&mut cw, b"\r\n7\r\n8");
+        assert_eq!(cw.history_size(), 2);
+        cw.resize::<CrosswordsSize>(40, 3);
+        assert_eq!(cw.history_size(), 1);
+    }
+
+    #[test]
+    fn test_history_memory_limit_counts_extras() {
+        use crate::crosswords::square::{CellExtra, Hyperlink};
+
+        let mut cw = CrosswordsBuilder::new(20, 3).build();
+        cw.set_history_limit(10, 1024 * 1024);
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, "e\u{301}\r\n".as_bytes());
+        let hyperlink = Hyperlink::new(Some("x"), String::from("http://x"));
+        cw.grid.cursor.template.set_hyperlink(Some(hyperlink));
+        parser.advance_bytes(&mut cw, b"ab");
+        cw.grid.cursor.template.set_hyperlink(None);
+        parser.advance_bytes(&mut cw, b"\r\n1\r\n2\r\n3\r\n4");
+
+        // The scrollback is "e\u{301}", "ab" and "1". The accent is a char of
+        // 4 bytes, "a" and "b" share one hyperlink with 1 + 8 bytes of text.
+        let row = mem::size_of::<Row<Square>>() + 20 * mem::size_of::<Square>();
+        let extra = mem::size_of::<CellExtra>();
+        let accent = extra + 4;
+        let hyperlink = extra + 9;
+        assert_eq!(cw.history_size(), 3);
+        assert_eq!(cw.history_extra_memory, accent + hyperlink);
+
+        cw.set_history_limit(10, 3 * row + accent + hyperlink);
+        assert_eq!(cw.history_size(), 3);
+
+        // One byte short, the line with the accent is dropped.
+        cw.set_history_limit(10, 3 * row + accent + hyperlink - 1);
+        assert_eq!(cw.history_size(), 2);
+        assert_eq!(cw.history_extra_memory, hyperlink);
+        cw.scroll_display(Scroll::Top);
+        assert_eq!(cw.screen_to_string().lines().next(), Some("ab"));
+        cw.scroll_display(Scroll::Bottom);
+
+        // Three lines fit with the hyperlink, a fourth one pushes out its
+        // line.
+        parser.advance_bytes(&mut cw, b"\r\n5");
+        assert_eq!(cw.history_size(), 3);
+        parser.advance_bytes(&mut cw, b"\r\n6");
+        assert_eq!(cw.history_size(), 3);
+        assert_eq!(cw.history_extra_memory, 0);
+        cw.scroll_display(Scroll::Top);
+        assert_eq!(cw.screen_to_string().lines().next(), Some("1"));
+    }
+
+    #[test]
+    fn test_report_termcap() {
+        let events = RecordingListener::default();
+        CrosswordsBuilder::new(20, 4)
+            .input("\x1bP+q436f;78\x1b\\")
+            .build_with(&events);
+
+        assert_eq!(
+            events.pty_writes(),
+            vec![
+                String::from("\x1bP1+r436f=323536\x1b\\"),
+                String::from("\x1bP0+r78\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clipboard_store() {
+        let events = RecordingListener::default();
+        let mut cw = Crosswords::new(20, 4, &events);
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"\x1b]52;c;aWdub3JlZA==\x07");
+        cw.set_clipboard_write(true);
+        parser.advance_bytes(&mut cw, b"\x1b]52;c;cmlv\x07");
+        cw.set_clipboard_write(false);
+        parser.advance_bytes(&mut cw, b"\x1b]52;c;cmlv\x07");
+
+        assert_eq!(events.clipboard_stores(), vec![String::from("rio")]);
+    }
+
+    #[test]
+    fn test_reset_state() {
+        let events = RecordingListener::default();
+        let mut cw = Crosswords::new(20, 2, &events);
+        cw.set_clipboard_write(true);
+        cw.set_history_limit(1, 0);
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"\x1b]0;vi\x071\r\n2\r\n3");
+        assert_eq!(cw.history_size(), 1);
+        parser.advance_bytes(&mut cw, b"\x1b[?1049h\x1b[?1hab");
+
+        parser.advance_bytes(&mut cw, b"\x1bc");
+        assert_grid_eq!(cw, [""]);
+        assert_eq!(cw.history_size(), 0);
+        assert_eq!(cw.mode().bits(), Mode::default().bits());
+        assert_eq!(cw.title(), None);
+        assert_eq!(cw.grid.cursor.pos, Pos::default());
+
+        // The settings of the terminal are kept.
+        parser.advance_bytes(&mut cw, b"1\r\n2\r\n3\r\n4\x1b]52;c;cmlv\x07");
+        assert_eq!(cw.history_size(), 1);
+        assert_eq!(events.clipboard_stores(), vec![String::from("rio")]);
+    }
+
+    #[test]
+    fn test_report_color_scheme() {
+        let events = RecordingListener::default();
+        let mut cw = Crosswords::new(20, 4, &events);
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"\x1b[?996n");
+        // Changes are only reported once enabled.
+        cw.set_dark(false);
+        parser.advance_bytes(&mut cw, b"\x1b[?996n\x1b[?2031h");
+        cw.set_dark(true);
+        cw.set_dark(true);
+
+        assert_eq!(
+            events.pty_writes(),
+            vec!["\x1b[?997;1n", "\x1b[?997;2n", "\x1b[?997;1n"]
+        );
+    }
+
+    #[test]
+    fn test_grid_diff() {
+        let rows = vec![String::from("abc"), String::from("de")];
+        assert_eq!(grid_diff(&rows, &["abc", "de"]), None);
+        assert_eq!(grid_diff(&rows, &["abc", "de", ""]), None);
+        assert_eq!(
+            grid_diff(&rows, &["abc", "xy"]),
+            Some(String::from("    0 |abc|\n-   1 |xy|\n+   1 |de|\n"))
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let terminal = CrosswordsBuilder::new(6, 3)
+            .rows(&["abc", "def"])
+            .cursor(0, 1)
+            .input("\x1b[2P")
+            .build();
+
+        assert_grid_eq!(terminal, ["a", "def"]);
+        assert_eq!(terminal.grid.cursor.pos, Pos::new(Line(0), Column(1)));
+    }
+
+    #[test]
+    fn test_corpora() {
+        for (name, corpus) in CORPORA {
+            let (terminal, expected) = run_corpus(corpus);
+            assert_grid_eq!(terminal, expected, "corpus {name}");
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        #[derive(Default)]
+        struct Host(std::cell::RefCell<Vec<String>>);
+        impl Observer for Host {
+            fn title_changed(&self, title: Option<&str>) {
+                self.0.borrow_mut().push(format!("title {title:?}"));
+            }
+
+            fn bell(&self) {
+                self.0.borrow_mut().push(String::from("bell"));
+            }
+
+            fn clipboard_write(&self, _clipboard: ClipboardType, text: &str) {
+                self.0.borrow_mut().push(format!("clipboard {text}"));
+            }
+
+            fn child_exited(&self, code: Option<i32>) {
+                self.0.borrow_mut().push(format!("exit {code:?}"));
+            }
+        }
+
+        let mut cw = Crosswords::new(20, 4, Observed(Host::default()));
+        cw.set_clipboard_write(true);
+        let mut parser = crate::performer::handler::ParserProcessor::new();
+        parser.advance_bytes(&mut cw, b"\x1b]2;vim\x07\x07\x1b]52;c;cmlv\x07");
+        cw.set_title(None);
+        cw.event_proxy.send_event(RioEvent::ChildExit(Some(1)));
+
+        assert_eq!(
+            *cw.observer().0.borrow(),
+            vec![
+                "title Some(\"vim\")",
+                "bell",
+                "clipboard rio",
+                "title None",
+                "exit Some(1)"
+            ]
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::crosswords::Column;
 use crate::crosswords::Row;
 use bitflags::bitflags;
 use colors::{AnsiColor, NamedColor};
+use std::mem;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
@@ -26,6 +27,7 @@ bitflags! {
         const UNDERCURL                 = 0b0001_0000_0000_0000;
         const DOTTED_UNDERLINE          = 0b0010_0000_0000_0000;
         const DASHED_UNDERLINE          = 0b0100_0000_0000_0000;
+        const BLINK                     = 0b1000_0000_0000_0000;
         const ALL_UNDERLINES            = Self::UNDERLINE.bits() | Self::DOUBLE_UNDERLINE.bits()
                                         | Self::UNDERCURL.bits() | Self::DOTTED_UNDERLINE.bits()
                                         | Self::DASHED_UNDERLINE.bits();
@@ -98,6 +100,19 @@ pub struct CellExtra {
     hyperlink: Option<Hyperlink>,
 }
 
+impl CellExtra {
+    /// Estimate of the bytes taken by the storage, with its zerowidth
+    /// characters and the text of its hyperlink.
+    pub fn memory_size(&self) -> usize {
+        let hyperlink = self.hyperlink.as_ref().map_or(0, |hyperlink| {
+            hyperlink.inner.id.len() + hyperlink.inner.uri.len()
+        });
+        mem::size_of::<CellExtra>()
+            + self.zerowidth.len() * mem::size_of::<char>()
+            + hyperlink
+    }
+}
+
 /// Content and attributes of a single cell in the terminal grid.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Square {
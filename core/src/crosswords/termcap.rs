@@ -0,0 +1,72 @@
+// Answers to XTGETTCAP (`DCS + q Pt ST`), which asks for terminfo
+// capabilities by their hex encoded names. Programs reaching Rio over SSH,
+// where the `rio` terminfo entry is missing, use it to find the extensions
+// of `misc/rio.terminfo`.
+
+/// Value of the capability `name` of the terminal `term`, empty for
+/// boolean capabilities.
+fn lookup(name: &str, term: &str, truecolor: bool) -> Option<String> {
+    let value = match name {
+        "TN" | "name" => term,
+        "Co" | "colors" => "256",
+        "Tc" if truecolor => "",
+        "Smulx" => "\x1b[4:%p1%dm",
+        "Setulc" => "\x1b[58:2:%p1%{65536}%/%d:%p1%{256}%/%{255}%&%d:%p1%{255}%&%d%;m",
+        "Sync" => "\x1bP=%p1%ds\x1b\\",
+        _ => return None,
+    };
+
+    Some(value.to_string())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+fn hex_decode(hex: &[u8]) -> Option<String> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    let bytes = hex
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Reply to the request of the capability with the hex encoded `name` by
+/// the terminal `term`, where `Tc` is only reported with `truecolor`.
+pub fn answer(name: &[u8], term: &str, truecolor: bool) -> String {
+    let hex_name = String::from_utf8_lossy(name);
+    match hex_decode(name).and_then(|name| lookup(&name, term, truecolor)) {
+        Some(value) if value.is_empty() => format!("\x1bP1+r{hex_name}\x1b\\"),
+        Some(value) => {
+            format!("\x1bP1+r{hex_name}={}\x1b\\", hex_encode(value.as_bytes()))
+        }
+        None => format!("\x1bP0+r{hex_name}\x1b\\"),
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(answer(b"436f", "rio", true), "\x1bP1+r436f=323536\x1b\\");
+        assert_eq!(answer(b"5463", "rio", true), "\x1bP1+r5463\x1b\\");
+        assert_eq!(answer(b"5463", "rio", false), "\x1bP0+r5463\x1b\\");
+        assert_eq!(answer(b"544e", "rio", true), "\x1bP1+r544e=72696F\x1b\\");
+        assert_eq!(
+            answer(b"544e", "xterm-256color", true),
+            "\x1bP1+r544e=787465726D2D323536636F6C6F72\x1b\\"
+        );
+        assert_eq!(answer(b"78", "rio", true), "\x1bP0+r78\x1b\\");
+        assert_eq!(answer(b"4", "rio", true), "\x1bP0+r4\x1b\\");
+        assert_eq!(answer(b"zz", "rio", true), "\x1bP0+rzz\x1b\\");
+    }
+}
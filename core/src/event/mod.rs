@@ -0,0 +1,187 @@
+pub mod sync;
+
+use crate::clipboard::ClipboardType;
+use crate::recorder::Recorder;
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use teletypewriter::WinsizeBuilder;
+
+/// Terminal size in cells and pixels, same as `teletypewriter::WinsizeBuilder`
+/// which is not available without a local PTY.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone)]
+pub struct WinsizeBuilder {
+    pub rows: u16,
+    pub cols: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    /// Data that should be written to the PTY.
+    Input(Cow<'static, [u8]>),
+
+    #[allow(dead_code)]
+    Shutdown,
+
+    Resize(WinsizeBuilder),
+
+    /// Start recording the PTY output, or stop it with `None`.
+    Record(Option<Recorder>),
+}
+
+#[derive(Clone)]
+pub enum RioEvent {
+    PrepareRender(u64),
+    Render,
+
+    /// Grid has changed possibly requiring a mouse cursor shape change.
+    MouseCursorDirty,
+
+    /// Window title change.
+    Title(String),
+
+    /// Reset to the default window title.
+    ResetTitle,
+
+    /// Request to store a text string in the clipboard.
+    ClipboardStore(ClipboardType, String),
+
+    /// Request to write the contents of the clipboard to the PTY.
+    ///
+    /// The attached function is a formatter which will corectly transform the clipboard content
+    /// into the expected escape sequence format.
+    ClipboardLoad(
+        ClipboardType,
+        Arc<dyn Fn(&str) -> String + Sync + Send + 'static>,
+    ),
+
+    /// Request to write the RGB value of a color to the PTY.
+    ///
+    /// The attached function is a formatter which will corectly transform the RGB color into the
+    /// expected escape sequence format.
+    // ColorRequest(usize, Arc<dyn Fn(Rgb) -> String + Sync + Send + 'static>),
+
+    /// Write some text to the PTY.
+    PtyWrite(String),
+
+    /// Request to write the text area size.
+    TextAreaSizeRequest(Arc<dyn Fn(WinsizeBuilder) -> String + Sync + Send + 'static>),
+
+    /// Cursor blinking state has changed.
+    CursorBlinkingChange,
+
+    /// New terminal content available.
+    Wakeup,
+
+    /// Terminal bell ring.
+    Bell,
+
+    /// A command marked by the shell (OSC 133) finished, with its exit
+    /// status when known and how long it ran.
+    CommandFinished {
+        command: String,
+        status: Option<i32>,
+        duration: Duration,
+    },
+
+    /// The program running in the terminal exited, with its exit code when
+    /// it exited normally.
+    ChildExit(Option<i32>),
+
+    /// Shutdown request.
+    Exit,
+}
+
+impl Debug for RioEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RioEvent::ClipboardStore(ty, text) => {
+                write!(f, "ClipboardStore({ty:?}, {text})")
+            }
+            RioEvent::ClipboardLoad(ty, _) => write!(f, "ClipboardLoad({ty:?})"),
+            RioEvent::TextAreaSizeRequest(_) => write!(f, "TextAreaSizeRequest"),
+            // RioEvent::ColorRequest(index, _) => write!(f, "ColorRequest({index})"),
+            RioEvent::PtyWrite(text) => write!(f, "PtyWrite({text})"),
+            RioEvent::Title(title) => write!(f, "Title({title})"),
+            RioEvent::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
+            RioEvent::MouseCursorDirty => write!(f, "MouseCursorDirty"),
+            RioEvent::ResetTitle => write!(f, "ResetTitle"),
+            RioEvent::Wakeup => write!(f, "Wakeup"),
+            RioEvent::PrepareRender(millis) => write!(f, "PrepareRender({millis})"),
+            RioEvent::Render => write!(f, "Render"),
+            RioEvent::Bell => write!(f, "Bell"),
+            RioEvent::CommandFinished {
+                command,
+                status,
+                duration,
+            } => write!(f, "CommandFinished({command}, {status:?}, {duration:?})"),
+            RioEvent::ChildExit(code) => write!(f, "ChildExit({code:?})"),
+            RioEvent::Exit => write!(f, "Exit"),
+        }
+    }
+}
+
+pub trait OnResize {
+    fn on_resize(&mut self, window_size: WinsizeBuilder);
+}
+
+/// Event Loop for notifying the renderer about terminal events.
+pub trait EventListener {
+    fn send_event(&self, _event: RioEvent) {}
+}
+
+pub struct VoidListener;
+
+impl EventListener for VoidListener {}
+
+/// Terminal events for hosts embedding the terminal, which only implement
+/// the ones they react to. Wrapped in `Observed`, it is the `EventListener`
+/// of a terminal.
+pub trait Observer {
+    /// The application set the title, or reset it with `None`.
+    fn title_changed(&self, _title: Option<&str>) {}
+
+    /// The application rang the bell.
+    fn bell(&self) {}
+
+    /// The application asked to copy `text` to the clipboard (OSC 52).
+    fn clipboard_write(&self, _clipboard: ClipboardType, _text: &str) {}
+
+    /// Output from the PTY was parsed, `Crosswords::damage` has the lines to
+    /// draw again.
+    fn damaged(&self) {}
+
+    /// The program running in the terminal exited, with its exit code when
+    /// it exited normally.
+    fn child_exited(&self, _code: Option<i32>) {}
+
+    /// Other events, e.g. answers to write to the PTY.
+    fn event(&self, _event: RioEvent) {}
+}
+
+/// `EventListener` calling the methods of an `Observer`.
+#[derive(Debug, Clone)]
+pub struct Observed<O: Observer>(pub O);
+
+impl<O: Observer> EventListener for Observed<O> {
+    fn send_event(&self, event: RioEvent) {
+        match event {
+            RioEvent::Title(title) => self.0.title_changed(Some(&title)),
+            RioEvent::ResetTitle => self.0.title_changed(None),
+            RioEvent::Bell => self.0.bell(),
+            RioEvent::ClipboardStore(clipboard, text) => {
+                self.0.clipboard_write(clipboard, &text)
+            }
+            RioEvent::Wakeup => self.0.damaged(),
+            RioEvent::ChildExit(code) => self.0.child_exited(code),
+            event => self.0.event(event),
+        }
+    }
+}
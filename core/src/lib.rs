@@ -0,0 +1,15 @@
+// Terminal model of Rio: the grid, the ANSI parser and the PTY event loop.
+//
+// Nothing here depends on winit or wgpu, terminal events are sent to an
+// `EventListener` so the model can be driven headlessly by tests, fuzzers
+// or other frontends.
+
+pub mod ansi;
+pub mod clipboard;
+pub mod crosswords;
+pub mod event;
+pub mod perf;
+pub mod performer;
+pub mod recorder;
+pub mod selection;
+pub mod transport;
@@ -0,0 +1,38 @@
+use log::Level;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Target of the timings logged by `Span`.
+pub const PERF_TARGET: &str = "rio::perf";
+
+/// Logs the time spent until it is dropped, at trace level under the
+/// `rio::perf` target (e.g. `RIO_LOG=rio::perf=trace`).
+pub struct Span {
+    name: &'static str,
+    started_at: Option<Instant>,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(name: &'static str) -> Span {
+        let started_at =
+            log::log_enabled!(target: PERF_TARGET, Level::Trace).then(Instant::now);
+        Span { name, started_at }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(started_at) = self.started_at {
+            log::trace!(
+                target: PERF_TARGET,
+                "{} took {:?}",
+                self.name,
+                started_at.elapsed()
+            );
+        }
+    }
+}
@@ -2,14 +2,20 @@ use crate::ansi::mode::Mode;
 use crate::crosswords::pos::{CharsetIndex, Column, Line, StandardCharset};
 use colors::ColorRgb;
 use log::{info, warn};
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 use crate::crosswords::attr::Attr;
 
 use crate::ansi::control::C0;
-use crate::ansi::{ClearMode, LineClearMode, TabulationClearMode};
+use crate::ansi::{ClearMode, LineClearMode, SemanticPrompt, TabulationClearMode};
 use colors::{AnsiColor, NamedColor};
 use std::fmt::Write;
+use std::path::PathBuf;
 
 // https://vt100.net/emu/dec_ansi_parser
 use vte::{Params, ParamsIter};
@@ -28,6 +34,9 @@ const SYNC_START_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] =
 const SYNC_END_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] =
     [b'\x1b', b'P', b'=', b'2', b's'];
 
+/// Maximum number of bytes of capability names read in one XTGETTCAP request.
+const MAX_TERMCAP_REQUEST_LEN: usize = 1024;
+
 fn xparse_color(color: &[u8]) -> Option<ColorRgb> {
     if !color.is_empty() && color[0] == b'#' {
         parse_legacy_color(&color[1..])
@@ -115,6 +124,26 @@ fn parse_sgr_color(params: &mut dyn Iterator<Item = u16>) -> Option<AnsiColor> {
     }
 }
 
+/// Path of a `file://host/path` URI, percent-decoded.
+fn parse_file_uri(uri: &[u8]) -> Option<PathBuf> {
+    let uri = uri.strip_prefix(b"file://")?;
+    let path = &uri[uri.iter().position(|byte| *byte == b'/')?..];
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.iter();
+    while let Some(byte) = bytes.next() {
+        if *byte == b'%' {
+            let hex = [*bytes.next()?, *bytes.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            decoded.push(*byte);
+        }
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
 #[inline]
 fn handle_colon_rgb(params: &[u16]) -> Option<AnsiColor> {
     let rgb_start = if params.len() > 4 { 2 } else { 1 };
@@ -128,6 +157,12 @@ pub trait Handler {
     /// OSC to set window title.
     fn set_title(&mut self, _: Option<String>) {}
 
+    /// OSC 7 to report the current working directory.
+    fn set_current_directory(&mut self, _: PathBuf) {}
+
+    /// OSC 133 to mark prompts and commands.
+    fn semantic_prompt(&mut self, _: SemanticPrompt) {}
+
     /// Set the cursor style.
     // fn set_cursor_style(&mut self, _: Option<CursorStyle>) {}
 
@@ -137,6 +172,13 @@ pub trait Handler {
     /// A character to be displayed.
     fn input(&mut self, _c: char) {}
 
+    /// A run of printable ASCII characters to be displayed.
+    fn input_ascii(&mut self, text: &[u8]) {
+        for byte in text {
+            self.input(*byte as char);
+        }
+    }
+
     /// Set cursor to position.
     fn goto(&mut self, _: Line, _: Column) {}
 
@@ -161,6 +203,9 @@ pub trait Handler {
     /// Report device status.
     fn device_status(&mut self, _: usize) {}
 
+    /// Report whether the colors are dark or light (CSI ? 996 n).
+    fn report_color_scheme(&mut self) {}
+
     /// Move cursor forward `cols`.
     fn move_forward(&mut self, _: Column) {}
 
@@ -313,6 +358,9 @@ pub trait Handler {
 
     /// Report text area size in characters.
     fn text_area_size_chars(&mut self) {}
+
+    /// Report the terminfo capability with the hex encoded `name` (XTGETTCAP).
+    fn report_termcap(&mut self, _name: &[u8]) {}
 }
 
 #[derive(Debug, Default)]
@@ -325,6 +373,35 @@ struct ProcessorState {
 
     /// State for synchronized terminal updates.
     sync_state: SyncState,
+
+    /// Whether the parser is known to be in the ground state, where
+    /// printable ASCII is printed as it is.
+    is_ground: bool,
+}
+
+/// Length of the run of printable ASCII (0x20 to 0x7e) at the start of
+/// `bytes`.
+#[inline]
+fn printable_ascii_len(bytes: &[u8]) -> usize {
+    const LOW: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+
+    let mut len = 0;
+    // Eight bytes at a time, as long as none is below 0x20 or above 0x7e.
+    for chunk in bytes.chunks_exact(8) {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        let below = word.wrapping_sub(LOW * 0x20) & !word;
+        let above = word.wrapping_add(LOW) | word;
+        if (below | above) & HIGH != 0 {
+            break;
+        }
+        len += 8;
+    }
+
+    len + bytes[len..]
+        .iter()
+        .take_while(|byte| (0x20..=0x7e).contains(*byte))
+        .count()
 }
 
 /// Maximum number of bytes read in one synchronized update (2MiB).
@@ -360,6 +437,9 @@ enum Dcs {
 
     /// End of the synchronized update.
     SyncEnd,
+
+    /// Request of terminfo capabilities, with the names read so far.
+    TermcapRequest(Vec<u8>),
 }
 
 #[derive(Default)]
@@ -370,7 +450,6 @@ pub struct ParserProcessor {
 
 impl ParserProcessor {
     #[inline]
-    #[allow(dead_code)]
     pub fn new() -> Self {
         Self::default()
     }
@@ -382,6 +461,7 @@ impl ParserProcessor {
         H: Handler,
     {
         if self.state.sync_state.timeout.is_none() {
+            self.state.is_ground = false;
             let mut performer = Performer::new(&mut self.state, handler);
             self.parser.advance(&mut performer, byte);
         } else {
@@ -389,6 +469,33 @@ impl ParserProcessor {
         }
     }
 
+    /// Process a slice of bytes, e.g. a whole PTY read.
+    ///
+    /// This only needs a `Handler` (usually `Crosswords`), so it can be driven
+    /// without a window or GPU, as the fuzz targets do.
+    #[inline]
+    pub fn advance_bytes<H>(&mut self, handler: &mut H, bytes: &[u8])
+    where
+        H: Handler,
+    {
+        let mut bytes = bytes;
+        while let Some((&byte, rest)) = bytes.split_first() {
+            // Runs of printable ASCII skip the state machine.
+            if self.state.is_ground && self.state.sync_state.timeout.is_none() {
+                let len = printable_ascii_len(bytes);
+                if len > 0 {
+                    handler.input_ascii(&bytes[..len]);
+                    self.state.preceding_char = Some(bytes[len - 1] as char);
+                    bytes = &bytes[len..];
+                    continue;
+                }
+            }
+
+            self.advance(handler, byte);
+            bytes = rest;
+        }
+    }
+
     /// End a synchronized update.
     pub fn stop_sync<H>(&mut self, handler: &mut H)
     where
@@ -397,6 +504,7 @@ impl ParserProcessor {
         // Process all synchronized bytes.
         for i in 0..self.state.sync_state.buffer.len() {
             let byte = self.state.sync_state.buffer[i];
+            self.state.is_ground = false;
             let mut performer = Performer::new(&mut self.state, handler);
             self.parser.advance(&mut performer, byte);
         }
@@ -465,7 +573,8 @@ impl ParserProcessor {
                         Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
                 }
                 Some(Dcs::SyncEnd) => self.stop_sync(handler),
-                None => (),
+                // Only synchronized update sequences are buffered here.
+                Some(Dcs::TermcapRequest(_)) | None => (),
             },
         }
     }
@@ -492,6 +601,7 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
         // println!("[print] {c:?}");
         self.handler.input(c);
         self.state.preceding_char = Some(c);
+        self.state.is_ground = true;
     }
 
     fn execute(&mut self, byte: u8) {
@@ -529,6 +639,9 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
                     self.state.dcs = Some(Dcs::SyncStart);
                 }
             }
+            ('q', [b'+']) => {
+                self.state.dcs = Some(Dcs::TermcapRequest(Vec::new()));
+            }
             _ => warn!(
                 "[unhandled hook] params={:?}, ints: {:?}, ignore: {:?}, action: {:?}",
                 params, intermediates, ignore, action
@@ -536,18 +649,32 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
         }
     }
 
-    fn put(&mut self, _byte: u8) {
-        info!("[put] {_byte:02x}");
+    fn put(&mut self, byte: u8) {
+        match &mut self.state.dcs {
+            Some(Dcs::TermcapRequest(names)) => {
+                if names.len() < MAX_TERMCAP_REQUEST_LEN {
+                    names.push(byte);
+                }
+            }
+            _ => info!("[put] {byte:02x}"),
+        }
     }
 
     #[inline]
     fn unhook(&mut self) {
-        match self.state.dcs {
+        match self.state.dcs.take() {
             Some(Dcs::SyncStart) => {
                 self.state.sync_state.timeout =
                     Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
             }
             Some(Dcs::SyncEnd) => (),
+            Some(Dcs::TermcapRequest(names)) => {
+                for name in names.split(|byte| *byte == b';') {
+                    if !name.is_empty() {
+                        self.handler.report_termcap(name);
+                    }
+                }
+            }
             _ => warn!("[unhandled unhook]"),
         }
     }
@@ -590,6 +717,33 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
                 unhandled(params);
             }
 
+            // Set current working directory.
+            b"7" => {
+                if let Some(path) = params.get(1).and_then(|uri| parse_file_uri(uri)) {
+                    self.handler.set_current_directory(path);
+                    return;
+                }
+                unhandled(params);
+            }
+
+            // Shell integration.
+            b"133" => {
+                let mark = match params.get(1).copied() {
+                    Some(b"A") => SemanticPrompt::PromptStart,
+                    Some(b"B") => SemanticPrompt::CommandStart,
+                    Some(b"C") => SemanticPrompt::OutputStart,
+                    Some(b"D") => {
+                        let status = params
+                            .get(2)
+                            .and_then(|status| std::str::from_utf8(status).ok())
+                            .and_then(|status| status.parse().ok());
+                        SemanticPrompt::CommandEnd(status)
+                    }
+                    _ => return unhandled(params),
+                };
+                self.handler.semantic_prompt(mark);
+            }
+
             // Set color index.
             b"4" => {
                 if params.len() <= 1 || params.len() % 2 == 0 {
@@ -710,6 +864,7 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
         action: char,
     ) {
         info!("[csi_dispatch] {params:?} {action:?}");
+        self.state.is_ground = true;
         macro_rules! csi_unhandled {
             () => {{
                 warn!(
@@ -832,6 +987,10 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
                 }
             }
             ('n', []) => handler.device_status(next_param_or(0) as usize),
+            ('n', [b'?']) => match next_param_or(0) {
+                996 => handler.report_color_scheme(),
+                _ => csi_unhandled!(),
+            },
             ('P', []) => handler.delete_chars(next_param_or(1) as usize),
             // ('q', [b' ']) => {
             //     // DECSCUSR (CSI Ps SP q) -- Set Cursor Style.
@@ -878,6 +1037,7 @@ impl<U: Handler> vte::Perform for Performer<'_, U> {
     }
 
     fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        self.state.is_ground = true;
         macro_rules! unhandled {
             () => {{
                 warn!(
@@ -1022,3 +1182,117 @@ fn attrs_from_sgr_parameters(params: &mut ParamsIter<'_>) -> Vec<Option<Attr>> {
 
     attrs
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::crosswords::Crosswords;
+    use crate::assert_grid_eq;
+    use crate::event::VoidListener;
+
+    #[test]
+    fn test_printable_ascii_len() {
+        assert_eq!(printable_ascii_len(b""), 0);
+        assert_eq!(printable_ascii_len(b"hello world, hello world!"), 25);
+        assert_eq!(printable_ascii_len(b"0123456789abcdef\x1b[m"), 16);
+        assert_eq!(printable_ascii_len(b"01234567\x7f"), 8);
+        assert_eq!(printable_ascii_len(b"012\n4567"), 3);
+        assert_eq!(printable_ascii_len("0123456é".as_bytes()), 7);
+        assert_eq!(printable_ascii_len(b"~~~~~~~~ "), 9);
+    }
+
+    #[test]
+    fn test_advance_bytes_printable_runs() {
+        let mut terminal = Crosswords::new(10, 4, VoidListener {});
+        let mut parser = ParserProcessor::new();
+
+        // Runs after escapes, within an escape and repeated with REP.
+        parser.advance_bytes(&mut terminal, b"ab\x1b[1mcdefghijklm\x1b[");
+        parser.advance_bytes(&mut terminal, b"2;1Hx\x1b[2b");
+
+        assert_grid_eq!(terminal, ["abcdefghij", "xxx"]);
+        assert!(terminal.grid[Line(0)][Column(2)]
+            .flags
+            .contains(crate::crosswords::square::Flags::BOLD));
+        assert_eq!(terminal.grid.cursor.pos.col, Column(3));
+    }
+
+    #[test]
+    fn test_advance_bytes_with_malformed_sequences() {
+        let mut terminal = Crosswords::new(10, 4, VoidListener {});
+        let mut parser = ParserProcessor::new();
+
+        for sequence in [
+            &b"\x1b[99999999999999999999;99999999999999999999H"[..],
+            b"\x1b[38;2;999;999;999;48;5;999m",
+            b"\x1b[?2026h\x1b[?2026l\x1b[?2026l",
+            b"\x1b]0;unterminated title",
+            b"\x1b]52;c;not base64!\x07",
+            b"\x1bP$qm\x1b\\",
+            b"\xff\xfe\xc3\x28\xe2\x82",
+            b"\x1b[3;1r\x1b[99L\x1b[99M\x1b[99@\x1b[99P\x1b[99X",
+            b"\x1b#8\x1b[5n\x1b[6n\x1b[c\x1b[>c",
+            b"\t\t\t\t\t\t\t\t\t\t\x08\x08\x1b[99Z\x1b[99I",
+        ] {
+            parser.advance_bytes(&mut terminal, sequence);
+        }
+
+        parser.advance_bytes(&mut terminal, b"\x1b[0m\x1b[Hok");
+        assert_eq!(terminal.grid.cursor.pos.col, Column(2));
+    }
+
+    #[test]
+    fn test_parse_file_uri() {
+        assert_eq!(
+            parse_file_uri(b"file://host/home/rio/my%20dir"),
+            Some(PathBuf::from("/home/rio/my dir"))
+        );
+        assert_eq!(parse_file_uri(b"file:///tmp"), Some(PathBuf::from("/tmp")));
+        assert_eq!(parse_file_uri(b"file://host"), None);
+        assert_eq!(parse_file_uri(b"file:///tmp%2"), None);
+        assert_eq!(parse_file_uri(b"https://host/tmp"), None);
+    }
+
+    #[test]
+    fn test_current_directory() {
+        let mut terminal = Crosswords::new(10, 4, VoidListener {});
+        let mut parser = ParserProcessor::new();
+        assert_eq!(terminal.current_directory(), None);
+
+        parser.advance_bytes(&mut terminal, b"\x1b]7;file://host/home/rio\x07");
+        assert_eq!(
+            terminal.current_directory(),
+            Some(std::path::Path::new("/home/rio"))
+        );
+    }
+
+    #[test]
+    fn test_semantic_prompt() {
+        let mut terminal = Crosswords::new(10, 4, VoidListener {});
+        let mut parser = ParserProcessor::new();
+
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;C\x07out\r\n\x1b]133;D;0\x07");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07$ ");
+        assert!(terminal.is_prompt(Line(0)));
+        assert!(!terminal.is_prompt(Line(1)));
+        assert!(terminal.is_prompt(Line(2)));
+        assert!(terminal.has_prompt_marks());
+        assert_eq!(terminal.last_output_lines(), Some((Line(1), Line(1))));
+        assert_eq!(terminal.output_lines_at(Line(1)), Some((Line(1), Line(1))));
+        assert_eq!(terminal.output_lines_at(Line(0)), None);
+        assert_eq!(terminal.output_lines_at(Line(2)), None);
+
+        // Without output.
+        parser.advance_bytes(&mut terminal, b"\x1b]133;B\x07true\r\n\x1b]133;C\x07");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;D;0\x07");
+        assert_eq!(terminal.last_output_lines(), None);
+
+        // Still running, without a newline at the end.
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07$ \x1b]133;B\x07cat\r\n");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;C\x07a\r\nb");
+        assert_eq!(terminal.screen_to_string().lines().last(), Some("b"));
+        let (start, end) = terminal.last_output_lines().unwrap();
+        assert_eq!(end.0 - start.0, 1);
+    }
+}
@@ -1,26 +1,37 @@
-pub mod handler;
-
+use super::{handler, FlowControl};
 use crate::crosswords::Crosswords;
 use crate::event::sync::FairMutex;
 use crate::event::EventListener;
-use log::error;
+use log::{error, warn};
 use mio::{self, Events, PollOpt, Ready};
 use mio_extras::channel;
 
 use crate::event::{Msg, RioEvent};
+use crate::perf::Span;
+use crate::recorder::Recorder;
 
 use std::borrow::Cow;
 use std::collections::VecDeque;
 
 use std::io::{self, Read};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use std::io::{ErrorKind, Write};
 
 const READ_BUFFER_SIZE: usize = 0x10_0000;
 /// Max bytes to read from the PTY while the terminal is locked.
 const MAX_LOCKED_READ: usize = u16::MAX as usize;
+/// Bytes parsed between checks of the parsing time budget.
+const PARSE_CHUNK_SIZE: usize = 0x4000;
+/// Max time spent parsing while the terminal is locked, the bytes left are
+/// parsed after the renderer had a chance to draw a frame.
+const PARSE_TIME_BUDGET: Duration = Duration::from_millis(8);
+/// Interval used to check if the renderer caught up while reading is paused.
+const FLOW_CONTROL_INTERVAL: Duration = Duration::from_millis(2);
+/// Max time reading can be paused, in case the renderer never presents a frame
+/// (e.g. window is minimized or rendering is disabled when unfocused).
+const FLOW_CONTROL_MAX_PAUSE: Duration = Duration::from_millis(100);
 
 pub struct Machine<T: teletypewriter::EventedPty, U: EventListener> {
     sender: channel::Sender<Msg>,
@@ -29,6 +40,7 @@ pub struct Machine<T: teletypewriter::EventedPty, U: EventListener> {
     poll: mio::Poll,
     terminal: Arc<FairMutex<Crosswords<U>>>,
     event_proxy: U,
+    flow: Arc<FlowControl>,
 }
 
 #[derive(Default)]
@@ -36,6 +48,10 @@ pub struct State {
     write_list: VecDeque<Cow<'static, [u8]>>,
     writing: Option<Writing>,
     parser: handler::ParserProcessor,
+    paused_at: Option<Instant>,
+    recorder: Option<Recorder>,
+    /// Bytes read from the PTY which did not fit in the parsing time budget.
+    pending: Vec<u8>,
 }
 
 impl State {
@@ -118,13 +134,18 @@ where
             pty,
             terminal,
             event_proxy,
+            flow: Arc::new(FlowControl::default()),
         })
     }
 
     #[inline]
     fn pty_read(&mut self, state: &mut State, buf: &mut [u8]) -> io::Result<()> {
-        let mut unprocessed = 0;
+        // Bytes left from the last time are parsed first.
+        let mut unprocessed = state.pending.len();
+        buf[..unprocessed].copy_from_slice(&state.pending);
+        state.pending.clear();
         let mut processed = 0;
+        let started = Instant::now();
 
         // Reserve the next terminal lock for PTY reading.
         let _terminal_lease = Some(self.terminal.lease());
@@ -135,7 +156,16 @@ where
             match self.pty.reader().read(&mut buf[unprocessed..]) {
                 // This is received on Windows/macOS when no more data is readable from the PTY.
                 Ok(0) if unprocessed == 0 => break,
-                Ok(got) => unprocessed += got,
+                Ok(got) => {
+                    if let Some(recorder) = &mut state.recorder {
+                        let read = &buf[unprocessed..unprocessed + got];
+                        if let Err(err) = recorder.output(read) {
+                            warn!("Unable to write recording, stopping it: {}", err);
+                            state.recorder = None;
+                        }
+                    }
+                    unprocessed += got;
+                }
                 Err(err) => match err.kind() {
                     ErrorKind::Interrupted | ErrorKind::WouldBlock => {
                         // Go back to mio if we're caught up on parsing and the PTY would block.
@@ -160,16 +190,28 @@ where
                 }),
             };
 
-            // Parse the incoming bytes.
-            for byte in &buf[..unprocessed] {
-                state.parser.advance(&mut **terminal, *byte);
+            // Parse the incoming bytes, in chunks until the time budget is
+            // spent.
+            let _span = Span::new("parse");
+            let mut parsed = 0;
+            for chunk in buf[..unprocessed].chunks(PARSE_CHUNK_SIZE) {
+                state.parser.advance_bytes(&mut **terminal, chunk);
+                parsed += chunk.len();
+                if started.elapsed() >= PARSE_TIME_BUDGET {
+                    break;
+                }
             }
+            state.pending.extend_from_slice(&buf[parsed..unprocessed]);
 
-            processed += unprocessed;
+            self.flow.consume(parsed);
+            processed += parsed;
             unprocessed = 0;
 
             // Assure we're not blocking the terminal too long unnecessarily.
-            if processed >= MAX_LOCKED_READ {
+            if processed >= MAX_LOCKED_READ
+                || self.flow.is_saturated()
+                || !state.pending.is_empty()
+            {
                 break;
             }
         }
@@ -189,8 +231,14 @@ where
                     state.write_list.push_back(input);
                 }
                 Msg::Resize(window_size) => {
+                    if let Some(recorder) = &mut state.recorder {
+                        let _ = recorder.resize(window_size.cols, window_size.rows);
+                    }
                     let _ = self.pty.set_winsize(window_size);
                 }
+                Msg::Record(recorder) => {
+                    state.recorder = recorder;
+                }
                 Msg::Shutdown => return false,
             }
         }
@@ -257,6 +305,28 @@ where
         self.sender.clone()
     }
 
+    pub fn flow(&self) -> Arc<FlowControl> {
+        self.flow.clone()
+    }
+
+    /// Returns `true` if PTY reading should stay paused until the renderer catches up.
+    #[inline]
+    fn should_pause_reading(&self, state: &mut State) -> bool {
+        if !self.flow.is_saturated() {
+            state.paused_at = None;
+            return false;
+        }
+
+        let paused_at = *state.paused_at.get_or_insert_with(Instant::now);
+        if paused_at.elapsed() >= FLOW_CONTROL_MAX_PAUSE {
+            self.flow.frame_rendered();
+            state.paused_at = None;
+            return false;
+        }
+
+        true
+    }
+
     pub fn spawn(mut self) {
         tokio::spawn(async move {
             let mut state = State::default();
@@ -281,9 +351,18 @@ where
             'event_loop: loop {
                 // Wakeup the event loop when a synchronized update timeout was reached.
                 let sync_timeout = state.parser.sync_timeout();
-                let timeout =
+                let mut timeout =
                     sync_timeout.map(|st| st.saturating_duration_since(Instant::now()));
 
+                // Wakeup periodically to resume reading while it is paused by flow
+                // control, or to parse the bytes left by the last read.
+                if state.paused_at.is_some() || !state.pending.is_empty() {
+                    timeout =
+                        Some(timeout.map_or(FLOW_CONTROL_INTERVAL, |t| {
+                            t.min(FLOW_CONTROL_INTERVAL)
+                        }));
+                }
+
                 if let Err(err) = self.poll.poll(&mut events, timeout) {
                     match err.kind() {
                         ErrorKind::Interrupted => continue,
@@ -292,10 +371,14 @@ where
                 }
 
                 // Handle synchronized update timeout.
-                if events.is_empty() {
+                if events.is_empty()
+                    && state
+                        .parser
+                        .sync_timeout()
+                        .map_or(false, |st| *st <= Instant::now())
+                {
                     state.parser.stop_sync(&mut *self.terminal.lock());
                     self.event_proxy.send_event(RioEvent::Wakeup);
-                    continue;
                 }
 
                 for event in events.iter() {
@@ -307,13 +390,13 @@ where
                             }
                         }
                         token if token == self.pty.child_event_token() => {
-                            // if let Some(teletypewriter::ChildEvent::Exited) =
-                            //     self.pty.next_child_event()
-                            // {
+                            if let Some(teletypewriter::ChildEvent::Exited(code)) =
+                                self.pty.next_child_event()
+                            {
+                                self.event_proxy.send_event(RioEvent::ChildExit(code));
+                            }
                             let _ = self.pty_read(&mut state, &mut buf);
                             self.event_proxy.send_event(RioEvent::Wakeup);
-                            // break 'event_loop;
-                            // }
                         }
 
                         token
@@ -356,8 +439,20 @@ where
                     }
                 }
 
-                // Register write interest if necessary.
-                let mut interest = Ready::readable();
+                // Register read interest unless flow control paused reading,
+                // and write interest if necessary.
+                let mut interest = Ready::empty();
+                if !self.should_pause_reading(&mut state) {
+                    // Bytes left by the last read are parsed once the renderer
+                    // had a chance to draw, when polling timed out.
+                    if events.is_empty() && !state.pending.is_empty() {
+                        if let Err(err) = self.pty_read(&mut state, &mut buf) {
+                            error!("Error reading from PTY in event loop: {}", err);
+                            break 'event_loop;
+                        }
+                    }
+                    interest.insert(Ready::readable());
+                }
                 if state.needs_write() {
                     interest.insert(Ready::writable());
                 }
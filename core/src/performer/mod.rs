@@ -0,0 +1,39 @@
+pub mod handler;
+#[cfg(not(target_arch = "wasm32"))]
+mod machine;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use machine::{Machine, State};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Max bytes to parse before yielding to the renderer.
+///
+/// Once reached the PTY stops being polled for reading until a frame is presented,
+/// which applies backpressure to the child process instead of starving the UI.
+const MAX_BYTES_PER_FRAME: usize = 0x4_0000;
+
+/// Output flow control shared between the PTY reader and the renderer.
+#[derive(Debug, Default)]
+pub struct FlowControl {
+    /// Bytes parsed since the last presented frame.
+    unrendered: AtomicUsize,
+}
+
+impl FlowControl {
+    #[inline]
+    fn consume(&self, bytes: usize) {
+        self.unrendered.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn is_saturated(&self) -> bool {
+        self.unrendered.load(Ordering::Relaxed) >= MAX_BYTES_PER_FRAME
+    }
+
+    /// Notify the reader that a frame was presented, so it can resume parsing.
+    #[inline]
+    pub fn frame_rendered(&self) {
+        self.unrendered.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,160 @@
+// Session recording in asciicast v2 format
+// https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Writes PTY output into an asciicast file as it is read.
+#[derive(Debug)]
+pub struct Recorder {
+    file: BufWriter<File>,
+    started_at: Instant,
+    decoder: Utf8Decoder,
+}
+
+impl Recorder {
+    pub fn new(path: &Path, columns: usize, rows: usize) -> io::Result<Recorder> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let shell = std::env::var("SHELL").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        writeln!(
+            file,
+            "{{\"version\": 2, \"width\": {columns}, \"height\": {rows}, \
+            \"timestamp\": {timestamp}, \"env\": {{\"SHELL\": \"{}\", \"TERM\": \"{}\"}}}}",
+            escape_json(&shell),
+            escape_json(&term)
+        )?;
+        file.flush()?;
+
+        Ok(Recorder {
+            file,
+            started_at: Instant::now(),
+            decoder: Utf8Decoder::default(),
+        })
+    }
+
+    /// Record bytes read from the PTY.
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let data = self.decoder.decode(bytes);
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        self.event('o', &data)
+    }
+
+    /// Record a terminal resize.
+    pub fn resize(&mut self, columns: u16, rows: u16) -> io::Result<()> {
+        self.event('r', &format!("{columns}x{rows}"))
+    }
+
+    fn event(&mut self, code: char, data: &str) -> io::Result<()> {
+        let time = self.started_at.elapsed().as_secs_f64();
+        writeln!(
+            self.file,
+            "[{time:.6}, \"{code}\", \"{}\"]",
+            escape_json(data)
+        )?;
+        self.file.flush()
+    }
+}
+
+/// Turns a byte stream into strings, keeping multi-byte characters split
+/// across reads until they are complete.
+#[derive(Debug, Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+
+        let mut output = String::new();
+        let mut position = 0;
+        while position < self.pending.len() {
+            match std::str::from_utf8(&self.pending[position..]) {
+                Ok(valid) => {
+                    output.push_str(valid);
+                    position = self.pending.len();
+                }
+                Err(err) => {
+                    let valid_up_to = position + err.valid_up_to();
+                    if let Ok(valid) =
+                        std::str::from_utf8(&self.pending[position..valid_up_to])
+                    {
+                        output.push_str(valid);
+                    }
+
+                    match err.error_len() {
+                        Some(len) => {
+                            output.push(char::REPLACEMENT_CHARACTER);
+                            position = valid_up_to + len;
+                        }
+                        // Incomplete sequence at the end, wait for the next read.
+                        None => {
+                            position = valid_up_to;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.pending.drain(..position);
+        output
+    }
+}
+
+fn escape_json(content: &str) -> String {
+    let mut escaped = String::with_capacity(content.len());
+    for c in content.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_json() {
+        assert_eq!(escape_json("ls -la\r\n"), "ls -la\\r\\n");
+        assert_eq!(escape_json("\x1b[31m\"a\"\\"), "\\u001b[31m\\\"a\\\"\\\\");
+        assert_eq!(escape_json("ação"), "ação");
+    }
+
+    #[test]
+    fn test_utf8_decoder_keeps_split_characters() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "aç".as_bytes();
+
+        assert_eq!(decoder.decode(&bytes[..2]), "a");
+        assert_eq!(decoder.decode(&bytes[2..]), "ç");
+        assert!(decoder.pending.is_empty());
+    }
+
+    #[test]
+    fn test_utf8_decoder_replaces_invalid_bytes() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"a\xffb"), "a\u{FFFD}b");
+        assert!(decoder.pending.is_empty());
+    }
+}
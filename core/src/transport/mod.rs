@@ -0,0 +1,21 @@
+// Connection to the program running in the terminal.
+//
+// On desktop this is the local PTY, driven by `performer::Machine`. Frontends
+// without one (e.g. the web build) implement `Transport` for whatever carries
+// the bytes and feed the received output to a `ParserProcessor`.
+
+#[cfg(target_arch = "wasm32")]
+mod websocket;
+
+#[cfg(target_arch = "wasm32")]
+pub use websocket::WebSocketTransport;
+
+use crate::event::WinsizeBuilder;
+
+pub trait Transport {
+    /// Send input (keys, pastes or replies to queries) to the program.
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Let the program know that the terminal was resized.
+    fn resize(&mut self, size: WinsizeBuilder);
+}
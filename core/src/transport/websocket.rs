@@ -0,0 +1,113 @@
+use super::Transport;
+use crate::event::WinsizeBuilder;
+use js_sys::{ArrayBuffer, Uint8Array};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+/// PTY running behind a WebSocket server.
+///
+/// Binary messages carry the PTY data in both directions. The client also
+/// sends text messages with commands, currently only
+/// `resize <columns> <rows> <width> <height>`.
+pub struct WebSocketTransport {
+    socket: WebSocket,
+    /// Last size, sent again once the connection is open.
+    size: Rc<RefCell<Option<WinsizeBuilder>>>,
+    _on_open: Closure<dyn FnMut(Event)>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+impl WebSocketTransport {
+    /// Connect to `url`, `on_output` receives the program output and
+    /// `on_close` is called when the connection is closed.
+    pub fn connect(
+        url: &str,
+        mut on_output: impl FnMut(&[u8]) + 'static,
+        mut on_close: impl FnMut() + 'static,
+    ) -> Result<WebSocketTransport, JsValue> {
+        let socket = WebSocket::new(url)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let size: Rc<RefCell<Option<WinsizeBuilder>>> = Rc::new(RefCell::new(None));
+
+        let on_open = {
+            let socket = socket.clone();
+            let size = size.clone();
+            Closure::<dyn FnMut(Event)>::new(move |_| {
+                if let Some(size) = size.borrow().as_ref() {
+                    send_resize(&socket, size);
+                }
+            })
+        };
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+        let on_message =
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                let data = event.data();
+                if let Some(buffer) = data.dyn_ref::<ArrayBuffer>() {
+                    on_output(&Uint8Array::new(buffer).to_vec());
+                } else if let Some(text) = data.as_string() {
+                    on_output(text.as_bytes());
+                }
+            });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        let on_close = Closure::<dyn FnMut(CloseEvent)>::new(move |_| on_close());
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(WebSocketTransport {
+            socket,
+            size,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    #[inline]
+    fn is_open(&self) -> bool {
+        self.socket.ready_state() == WebSocket::OPEN
+    }
+}
+
+fn send_resize(socket: &WebSocket, size: &WinsizeBuilder) {
+    let command = format!(
+        "resize {} {} {} {}",
+        size.cols, size.rows, size.width, size.height
+    );
+    if let Err(err) = socket.send_with_str(&command) {
+        log::warn!("websocket: unable to send resize: {err:?}");
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn write(&mut self, bytes: &[u8]) {
+        if !self.is_open() {
+            return;
+        }
+
+        if let Err(err) = self.socket.send_with_u8_array(bytes) {
+            log::warn!("websocket: unable to send input: {err:?}");
+        }
+    }
+
+    fn resize(&mut self, size: WinsizeBuilder) {
+        if self.is_open() {
+            send_resize(&self.socket, &size);
+        }
+        *self.size.borrow_mut() = Some(size);
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+    }
+}
@@ -0,0 +1,55 @@
+// System appearance, picking between `style.light-theme` and
+// `style.dark-theme`.
+//
+// winit reports the appearance and its changes on macOS and Windows. Other
+// platforms are asked once when the window opens, through the GNOME
+// `color-scheme` setting read with `gsettings`.
+
+use winit::window::Theme;
+
+/// Whether the system appearance is dark, `None` when unknown.
+pub fn is_dark(theme: Option<Theme>) -> Option<bool> {
+    if let Some(theme) = theme {
+        return Some(theme == Theme::Dark);
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        let output = std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+            .output()
+            .ok()?;
+        parse_color_scheme(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    None
+}
+
+/// Whether a `color-scheme` value printed by `gsettings` is dark.
+#[cfg_attr(any(target_os = "macos", windows), allow(dead_code))]
+fn parse_color_scheme(output: &str) -> Option<bool> {
+    match output.trim().trim_matches('\'') {
+        "prefer-dark" => Some(true),
+        "prefer-light" | "default" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_dark() {
+        assert_eq!(is_dark(Some(Theme::Dark)), Some(true));
+        assert_eq!(is_dark(Some(Theme::Light)), Some(false));
+    }
+
+    #[test]
+    fn test_parse_color_scheme() {
+        assert_eq!(parse_color_scheme("'prefer-dark'\n"), Some(true));
+        assert_eq!(parse_color_scheme("'default'\n"), Some(false));
+        assert_eq!(parse_color_scheme(""), None);
+    }
+}
@@ -0,0 +1,191 @@
+// Throughput benchmark, started with `rio --bench`.
+//
+// Replays vtebench-style workloads through the parser and renderer in the
+// real window, then prints the parsing throughput in cells per second and
+// the time spent rendering each frame. No shell is started, so the numbers
+// only depend on Rio itself.
+
+use crate::screen::Screen;
+use std::time::Duration;
+
+/// Bytes parsed between frames, similar to a busy PTY read.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+/// Lines written by each workload.
+const LINES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    ScrollingAscii,
+    Unicode,
+    DenseColors,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 3] = [
+        Workload::ScrollingAscii,
+        Workload::Unicode,
+        Workload::DenseColors,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::ScrollingAscii => "scrolling ascii",
+            Workload::Unicode => "unicode",
+            Workload::DenseColors => "dense colors",
+        }
+    }
+
+    /// Output filling `lines` lines of `columns` cells.
+    pub fn generate(&self, columns: usize, lines: usize) -> Vec<u8> {
+        let mut output = String::new();
+        // Small LCG, so every run writes the same content.
+        let mut seed: u32 = 0x2545_f491;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            seed >> 16
+        };
+
+        for line in 0..lines {
+            match self {
+                Workload::ScrollingAscii => {
+                    for column in 0..columns {
+                        output.push((b' ' + ((line + column) % 95) as u8) as char);
+                    }
+                }
+                Workload::Unicode => {
+                    // Wide characters take two cells.
+                    const SAMPLES: [(char, usize); 6] = [
+                        ('é', 1),
+                        ('λ', 1),
+                        ('ж', 1),
+                        ('中', 2),
+                        ('ア', 2),
+                        ('😀', 2),
+                    ];
+                    let mut width = 0;
+                    while width < columns {
+                        let (c, c_width) = SAMPLES[next() as usize % SAMPLES.len()];
+                        if width + c_width > columns {
+                            output.push(' ');
+                            width += 1;
+                            continue;
+                        }
+                        output.push(c);
+                        width += c_width;
+                    }
+                }
+                Workload::DenseColors => {
+                    for column in 0..columns {
+                        let color = next();
+                        output.push_str(&format!(
+                            "\x1b[38;2;{};{};{};48;5;{}m{}",
+                            color & 0xff,
+                            (color >> 8) & 0xff,
+                            (color >> 4) & 0xff,
+                            color % 256,
+                            (b'a' + (column % 26) as u8) as char
+                        ));
+                    }
+                    output.push_str("\x1b[0m");
+                }
+            }
+            output.push_str("\r\n");
+        }
+
+        output.into_bytes()
+    }
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub workload: Workload,
+    pub cells: usize,
+    pub parse_time: Duration,
+    pub frame_times: Vec<Duration>,
+}
+
+impl Report {
+    pub fn cells_per_second(&self) -> f64 {
+        self.cells as f64 / self.parse_time.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Frame time below which `percentile` percent of the frames are.
+    pub fn frame_time(&self, percentile: usize) -> Duration {
+        let mut frame_times = self.frame_times.clone();
+        frame_times.sort();
+        let index = (frame_times.len() * percentile / 100)
+            .min(frame_times.len().saturating_sub(1));
+        frame_times.get(index).copied().unwrap_or_default()
+    }
+}
+
+pub fn run(screen: &mut Screen) -> Vec<Report> {
+    let columns = screen.layout().columns;
+
+    Workload::ALL
+        .iter()
+        .map(|workload| {
+            let content = workload.generate(columns, LINES);
+            let (parse_time, frame_times) = screen.replay(&content, CHUNK_SIZE);
+            Report {
+                workload: *workload,
+                cells: columns * LINES,
+                parse_time,
+                frame_times,
+            }
+        })
+        .collect()
+}
+
+pub fn print(reports: &[Report]) {
+    println!(
+        "{:<16} {:>14} {:>8} {:>12} {:>12}",
+        "workload", "cells/s", "frames", "frame p50", "frame p99"
+    );
+    for report in reports {
+        println!(
+            "{:<16} {:>14.0} {:>8} {:>12.2?} {:>12.2?}",
+            report.workload.name(),
+            report.cells_per_second(),
+            report.frame_times.len(),
+            report.frame_time(50),
+            report.frame_time(99)
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use unicode_width::UnicodeWidthStr;
+
+    #[test]
+    fn test_workloads_fill_lines() {
+        for workload in Workload::ALL {
+            let content = String::from_utf8(workload.generate(80, 3)).unwrap();
+            let lines: Vec<&str> = content.split_terminator("\r\n").collect();
+            assert_eq!(lines.len(), 3, "{}", workload.name());
+
+            if workload != Workload::DenseColors {
+                for line in lines {
+                    assert_eq!(line.width(), 80, "{}", workload.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_report_frame_time() {
+        let report = Report {
+            workload: Workload::ScrollingAscii,
+            cells: 1000,
+            parse_time: Duration::from_millis(500),
+            frame_times: (1..=100).rev().map(Duration::from_millis).collect(),
+        };
+
+        assert_eq!(report.cells_per_second(), 2000.0);
+        assert_eq!(report.frame_time(50), Duration::from_millis(51));
+        assert_eq!(report.frame_time(99), Duration::from_millis(100));
+        assert_eq!(report.frame_time(100), Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,250 @@
+use std::path::PathBuf;
+
+/// Subcommand of `rio config`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print every key with its default value, commented out.
+    Default,
+    /// Check a configuration file, `~/.rio/config.toml` when not given.
+    Validate(Option<PathBuf>),
+    /// Print the configuration in effect, after the profile and the
+    /// environment changed it.
+    Dump,
+    /// Missing or unknown subcommand.
+    Usage,
+}
+
+/// Options passed on the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cli {
+    /// Record the session into an asciicast file.
+    pub record: Option<PathBuf>,
+    /// Play an asciicast file or raw typescript instead of running a shell.
+    pub play: Option<PathBuf>,
+    /// Run the throughput benchmark and exit.
+    pub bench: bool,
+    /// Profile from the configuration applied at startup.
+    pub profile: Option<String>,
+    /// Session file describing the windows to open.
+    pub session: Option<PathBuf>,
+    /// Typed into the shell once it starts, from the session file.
+    pub commands: Vec<String>,
+    /// Arguments of `rio msg`, sent to the running instance.
+    pub msg: Option<Vec<String>>,
+    /// `rio config` subcommand, run instead of opening a window.
+    pub config: Option<ConfigCommand>,
+    /// General part of `WM_CLASS` and app ID, instead of `window.class`.
+    pub class: Option<String>,
+    /// Instance part of `WM_CLASS`, instead of `window.name`.
+    pub name: Option<String>,
+    /// Program and its arguments run instead of the shell, after `-e`.
+    pub command: Option<Vec<String>>,
+}
+
+impl Cli {
+    pub fn parse() -> Cli {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(args: impl Iterator<Item = String>) -> Cli {
+        let mut cli = Cli::default();
+        let mut args = args.peekable();
+
+        if args.next_if(|arg| arg == "msg").is_some() {
+            cli.msg = Some(args.collect());
+            return cli;
+        }
+
+        if args.next_if(|arg| arg == "config").is_some() {
+            let command = match args.next().as_deref() {
+                Some("default") => ConfigCommand::Default,
+                Some("validate") => ConfigCommand::Validate(
+                    args.next_if(|next| !next.starts_with("--"))
+                        .map(PathBuf::from),
+                ),
+                // Options such as `--profile` still apply to the dump.
+                Some("dump") => ConfigCommand::Dump,
+                _ => ConfigCommand::Usage,
+            };
+            cli.config = Some(command);
+        }
+
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            let target = match name.as_str() {
+                "--bench" => {
+                    cli.bench = true;
+                    continue;
+                }
+                // Everything after it belongs to the program.
+                "-e" | "--command" => {
+                    let command: Vec<String> =
+                        value.into_iter().chain(args.by_ref()).collect();
+                    if command.is_empty() {
+                        log::warn!("{name} expects a program");
+                    } else {
+                        cli.command = Some(command);
+                    }
+                    break;
+                }
+                "--profile" => {
+                    cli.profile =
+                        value.or_else(|| args.next_if(|next| !next.starts_with("--")));
+                    if cli.profile.is_none() {
+                        log::warn!("{name} expects a profile name");
+                    }
+                    continue;
+                }
+                "--class" | "--name" => {
+                    let value =
+                        value.or_else(|| args.next_if(|next| !next.starts_with("--")));
+                    if value.is_none() {
+                        log::warn!("{name} expects a name");
+                    }
+                    if name == "--class" {
+                        cli.class = value;
+                    } else {
+                        cli.name = value;
+                    }
+                    continue;
+                }
+                "--record" => &mut cli.record,
+                "--play" => &mut cli.play,
+                "--session" => &mut cli.session,
+                other => {
+                    log::warn!("unknown argument {other:?}");
+                    continue;
+                }
+            };
+
+            match value.or_else(|| args.next_if(|next| !next.starts_with("--"))) {
+                Some(path) => *target = Some(PathBuf::from(path)),
+                None => log::warn!("{name} expects a file path"),
+            }
+        }
+
+        cli
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(parse(&[]), Cli::default());
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let expected = Some(PathBuf::from("session.cast"));
+        assert_eq!(parse(&["--record", "session.cast"]).record, expected);
+        assert_eq!(parse(&["--record=session.cast"]).record, expected);
+        assert_eq!(parse(&["--record"]).record, None);
+    }
+
+    #[test]
+    fn test_parse_msg() {
+        let cli = parse(&["msg", "send-text", "--record"]);
+        assert_eq!(
+            cli.msg,
+            Some(vec![String::from("send-text"), String::from("--record")])
+        );
+        assert_eq!(cli.record, None);
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(
+            parse(&["config", "default"]).config,
+            Some(ConfigCommand::Default)
+        );
+        assert_eq!(
+            parse(&["config", "validate", "rio.toml"]).config,
+            Some(ConfigCommand::Validate(Some(PathBuf::from("rio.toml"))))
+        );
+        assert_eq!(
+            parse(&["config", "validate"]).config,
+            Some(ConfigCommand::Validate(None))
+        );
+        assert_eq!(
+            parse(&["config", "lint"]).config,
+            Some(ConfigCommand::Usage)
+        );
+
+        let cli = parse(&["config", "dump", "--profile", "work"]);
+        assert_eq!(cli.config, Some(ConfigCommand::Dump));
+        assert_eq!(cli.profile, Some(String::from("work")));
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let cli = parse(&["--profile", "work", "-e", "less", "--", "--record"]);
+        assert_eq!(
+            cli.command,
+            Some(vec![
+                String::from("less"),
+                String::from("--"),
+                String::from("--record")
+            ])
+        );
+        assert_eq!(cli.profile, Some(String::from("work")));
+        assert_eq!(cli.record, None);
+
+        assert_eq!(
+            parse(&["--command=htop"]).command,
+            Some(vec![String::from("htop")])
+        );
+        assert_eq!(parse(&["-e"]).command, None);
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert!(parse(&["--bench"]).bench);
+        assert!(!parse(&["--record", "out.cast"]).bench);
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(
+            parse(&["--profile", "staging"]).profile,
+            Some(String::from("staging"))
+        );
+        assert_eq!(
+            parse(&["--profile=staging", "--bench"]).profile,
+            Some(String::from("staging"))
+        );
+        assert_eq!(parse(&["--profile", "--bench"]).profile, None);
+    }
+
+    #[test]
+    fn test_parse_class() {
+        let cli = parse(&["--class", "Scratchpad", "--name=scratch"]);
+        assert_eq!(cli.class, Some(String::from("Scratchpad")));
+        assert_eq!(cli.name, Some(String::from("scratch")));
+        assert_eq!(parse(&["--class", "--bench"]).class, None);
+    }
+
+    #[test]
+    fn test_parse_session() {
+        let cli = parse(&["--session", "dev.toml"]);
+        assert_eq!(cli.session, Some(PathBuf::from("dev.toml")));
+        assert!(cli.commands.is_empty());
+    }
+
+    #[test]
+    fn test_parse_play() {
+        let cli = parse(&["--play", "demo.cast", "--record", "out.cast"]);
+        assert_eq!(cli.play, Some(PathBuf::from("demo.cast")));
+        assert_eq!(cli.record, Some(PathBuf::from("out.cast")));
+    }
+}
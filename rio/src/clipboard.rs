@@ -1,14 +1,9 @@
 use log::warn;
+use rio_core::clipboard::ClipboardType;
 
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use std::ffi::c_void;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ClipboardType {
-    Clipboard,
-    Selection,
-}
-
 #[cfg(any(test, not(any(feature = "x11", target_os = "macos", windows))))]
 use copypasta::nop_clipboard::NopClipboardContext;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
@@ -91,6 +86,29 @@ impl Clipboard {
         });
     }
 
+    /// Store text along with its HTML and RTF representations.
+    ///
+    /// Only the macOS pasteboard is able to hold multiple flavors, other
+    /// platforms only get the plain text.
+    #[cfg(target_os = "macos")]
+    pub fn set_rich_text(&mut self, text: String, html: String, rtf: String) {
+        if !crate::platform::macos::set_pasteboard_rich_text(&text, &html, &rtf) {
+            warn!("Unable to store rich text in clipboard");
+            self.set(ClipboardType::Clipboard, text);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn set_rich_text(&mut self, text: String, _html: String, _rtf: String) {
+        self.set(ClipboardType::Clipboard, text);
+    }
+
+    /// Whether the platform provides a primary selection clipboard (X11/Wayland).
+    #[inline]
+    pub fn has_selection(&self) -> bool {
+        self.selection.is_some()
+    }
+
     pub fn get(&mut self, ty: ClipboardType) -> String {
         let clipboard = match (ty, &mut self.selection) {
             (ClipboardType::Selection, Some(provider)) => provider,
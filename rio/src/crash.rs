@@ -0,0 +1,219 @@
+// Panic hook writing a crash report with the renderer and config state.
+//
+// The report is saved as `~/.rio/crash-<timestamp>.log` (or in the temporary
+// directory) and its path is shown in a dialog, since Rio is usually started
+// from a launcher where nobody would see a panic message.
+
+use std::fmt::Write;
+use std::panic::PanicInfo;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Default)]
+struct Diagnostics {
+    adapter: Option<String>,
+    surface_format: Option<String>,
+    window_size: Option<(u32, u32)>,
+    config: String,
+}
+
+static DIAGNOSTICS: Mutex<Option<Diagnostics>> = Mutex::new(None);
+
+fn update(apply: impl FnOnce(&mut Diagnostics)) {
+    if let Ok(mut diagnostics) = DIAGNOSTICS.lock() {
+        apply(diagnostics.get_or_insert_with(Diagnostics::default));
+    }
+}
+
+/// Install the panic hook. The config is kept with the values of
+/// environment variables removed, since they may hold secrets.
+pub fn install(config: &config::Config) {
+    let mut config = config.clone();
+    config.env_vars = config
+        .env_vars
+        .iter()
+        .map(|env_var| match env_var.split_once('=') {
+            Some((name, _)) => format!("{name}=<redacted>"),
+            None => env_var.clone(),
+        })
+        .collect();
+    update(|diagnostics| diagnostics.config = format!("{config:#?}"));
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let content = report(info);
+        match save(&content) {
+            Ok(path) => show_dialog(&path),
+            Err(err) => eprintln!("unable to write crash report: {err}"),
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn set_renderer(adapter: &wgpu::AdapterInfo, surface_format: wgpu::TextureFormat) {
+    let adapter = format!(
+        "{} ({:?}, {:?}, driver {} {})",
+        adapter.name,
+        adapter.backend,
+        adapter.device_type,
+        adapter.driver,
+        adapter.driver_info
+    );
+    update(|diagnostics| {
+        diagnostics.adapter = Some(adapter);
+        diagnostics.surface_format = Some(format!("{surface_format:?}"));
+    });
+}
+
+pub fn set_window_size(width: u32, height: u32) {
+    update(|diagnostics| diagnostics.window_size = Some((width, height)));
+}
+
+fn report(info: &PanicInfo) -> String {
+    // Avoid a deadlock if the panic happened while holding the lock.
+    let diagnostics = DIAGNOSTICS.try_lock().ok();
+    let diagnostics = diagnostics
+        .as_ref()
+        .and_then(|diagnostics| diagnostics.as_ref());
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    format_report(&info.to_string(), diagnostics, &backtrace.to_string())
+}
+
+fn format_report(
+    message: &str,
+    diagnostics: Option<&Diagnostics>,
+    backtrace: &str,
+) -> String {
+    let unknown = || String::from("unknown");
+    let mut report = String::new();
+    let _ = writeln!(report, "Rio {} crashed", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "OS: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "{message}\n");
+
+    let _ = writeln!(
+        report,
+        "Adapter: {}",
+        diagnostics
+            .and_then(|diagnostics| diagnostics.adapter.clone())
+            .unwrap_or_else(unknown)
+    );
+    let _ = writeln!(
+        report,
+        "Surface format: {}",
+        diagnostics
+            .and_then(|diagnostics| diagnostics.surface_format.clone())
+            .unwrap_or_else(unknown)
+    );
+    let _ = writeln!(
+        report,
+        "Window size: {}",
+        diagnostics
+            .and_then(|diagnostics| diagnostics.window_size)
+            .map_or_else(unknown, |(width, height)| format!("{width}x{height}"))
+    );
+
+    let config = diagnostics.map_or("", |diagnostics| diagnostics.config.as_str());
+    let _ = writeln!(report, "\nConfig:\n{config}\n\nBacktrace:\n{backtrace}");
+    report
+}
+
+fn save(content: &str) -> std::io::Result<PathBuf> {
+    let directory = dirs::home_dir()
+        .map(|home| home.join(".rio"))
+        .filter(|directory| directory.is_dir())
+        .unwrap_or_else(std::env::temp_dir);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let path = directory.join(format!("crash-{timestamp}.log"));
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+fn show_dialog(path: &std::path::Path) {
+    let message = format!(
+        "Rio crashed unexpectedly. A report was saved to {}",
+        path.display()
+    );
+    eprintln!("{message}");
+
+    // The dialogs are spawned without waiting, the message is passed as an
+    // argument or variable so it is never parsed as script.
+    #[cfg(target_os = "macos")]
+    let command = std::process::Command::new("osascript")
+        .args(["-e", "on run argv"])
+        .args([
+            "-e",
+            "display alert \"Rio crashed\" message (item 1 of argv) as critical",
+        ])
+        .args(["-e", "end run"])
+        .arg(&message)
+        .spawn();
+
+    // A message box only shows in the session of the user, unlike `msg *`.
+    #[cfg(windows)]
+    let command = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-WindowStyle", "Hidden", "-Command"])
+        .arg(
+            "Add-Type -AssemblyName PresentationFramework; \
+             [System.Windows.MessageBox]::Show($env:RIO_CRASH_MESSAGE, 'Rio crashed')",
+        )
+        .env("RIO_CRASH_MESSAGE", &message)
+        .spawn();
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let command = std::process::Command::new("zenity")
+        .arg("--error")
+        .arg("--no-markup")
+        .arg("--title=Rio crashed")
+        .arg(format!("--text={message}"))
+        .spawn()
+        .or_else(|_| {
+            std::process::Command::new("kdialog")
+                .arg("--error")
+                .arg(&message)
+                .spawn()
+        });
+
+    if let Err(err) = command {
+        log::warn!("unable to show crash dialog: {err}");
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let diagnostics = Diagnostics {
+            adapter: Some(String::from("Apple M1 (Metal, IntegratedGpu, driver  )")),
+            surface_format: Some(String::from("Bgra8UnormSrgb")),
+            window_size: Some((1324, 876)),
+            config: String::from("Config { .. }"),
+        };
+
+        let report = format_report(
+            "panicked at 'Request adapter'",
+            Some(&diagnostics),
+            "<backtrace>",
+        );
+        assert!(report.contains("panicked at 'Request adapter'\n"));
+        assert!(report.contains("Adapter: Apple M1 (Metal, IntegratedGpu, driver  )\n"));
+        assert!(report.contains("Surface format: Bgra8UnormSrgb\n"));
+        assert!(report.contains("Window size: 1324x876\n"));
+        assert!(report.ends_with("Config:\nConfig { .. }\n\nBacktrace:\n<backtrace>\n"));
+
+        let report = format_report("panicked", None, "");
+        assert!(report.contains("Adapter: unknown\n"));
+        assert!(report.contains("Window size: unknown\n"));
+    }
+}
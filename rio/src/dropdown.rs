@@ -0,0 +1,260 @@
+// Quake-style dropdown mode: a global hotkey slides the window down from the
+// top of the screen and hides it again.
+//
+// Global shortcuts are registered with Carbon on macOS and with XGrabKey on
+// X11. Wayland compositors don't allow clients to grab keys, so there (and on
+// any other platform) a shortcut has to be bound in the system settings to
+// run `rio msg toggle-dropdown`.
+
+use crate::event::{EventProxy, RioEventType};
+use std::time::{Duration, Instant};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::ModifiersState;
+use winit::window::Window;
+
+const ANIMATION_DURATION: Duration = Duration::from_millis(150);
+pub const ANIMATION_FRAME_MS: u64 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyKey {
+    /// Lowercase ASCII letter, digit or the grave accent.
+    Char(char),
+    /// F1 to F12.
+    Function(u8),
+    Space,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: ModifiersState,
+    pub key: HotkeyKey,
+}
+
+impl Hotkey {
+    /// Parse a shortcut like `F12`, `ctrl+grave` or `cmd+shift+space`.
+    pub fn parse(spec: &str) -> Result<Hotkey, String> {
+        let spec = spec.to_lowercase();
+        let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+
+        let mut modifiers = ModifiersState::empty();
+        for modifier in parts {
+            modifiers |= match modifier {
+                "ctrl" | "control" => ModifiersState::CTRL,
+                "alt" | "option" => ModifiersState::ALT,
+                "shift" => ModifiersState::SHIFT,
+                "super" | "cmd" | "command" => ModifiersState::LOGO,
+                other => return Err(format!("unknown modifier {other:?}")),
+            };
+        }
+
+        let key = match key {
+            "space" => HotkeyKey::Space,
+            "grave" | "`" => HotkeyKey::Char('`'),
+            function if function.len() > 1 && function.starts_with('f') => {
+                match function[1..].parse() {
+                    Ok(number @ 1..=12) => HotkeyKey::Function(number),
+                    _ => return Err(format!("unknown key {function:?}")),
+                }
+            }
+            key => match key.chars().next() {
+                Some(c) if key.len() == 1 && c.is_ascii_alphanumeric() => {
+                    HotkeyKey::Char(c)
+                }
+                _ => return Err(format!("unknown key {key:?}")),
+            },
+        };
+
+        Ok(Hotkey { modifiers, key })
+    }
+}
+
+/// Position and size of the window when fully shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Frame {
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+}
+
+impl Frame {
+    /// Full width of the monitor, `height` is a fraction of its height.
+    fn new(
+        monitor_position: PhysicalPosition<i32>,
+        monitor_size: PhysicalSize<u32>,
+        height: f32,
+    ) -> Frame {
+        let height = (monitor_size.height as f32 * height.clamp(0.1, 1.0)).round();
+        Frame {
+            position: monitor_position,
+            size: PhysicalSize::new(monitor_size.width, height as u32),
+        }
+    }
+
+    /// Window position while sliding, from 0.0 (hidden above the monitor)
+    /// to 1.0 (shown).
+    fn position_at(&self, progress: f64) -> PhysicalPosition<i32> {
+        let offset = (self.size.height as f64 * (1.0 - progress)).round() as i32;
+        PhysicalPosition::new(self.position.x, self.position.y - offset)
+    }
+}
+
+pub struct Dropdown {
+    height: f32,
+    frame: Option<Frame>,
+    visible: bool,
+    /// Start of the current slide animation.
+    animation: Option<Instant>,
+}
+
+impl Dropdown {
+    pub fn new(height: f32) -> Dropdown {
+        Dropdown {
+            height,
+            frame: None,
+            visible: true,
+            animation: None,
+        }
+    }
+
+    /// Fit the window to the top of the monitor it is in.
+    pub fn place(&mut self, window: &Window) {
+        let Some(monitor) = window.current_monitor().or_else(|| window.primary_monitor())
+        else {
+            return;
+        };
+
+        let frame = Frame::new(monitor.position(), monitor.size(), self.height);
+        window.set_inner_size(frame.size);
+        window.set_outer_position(frame.position);
+        self.frame = Some(frame);
+    }
+
+    /// Start sliding the window in or out.
+    pub fn toggle(&mut self, window: &Window) {
+        self.visible = !self.visible;
+
+        if self.visible {
+            self.place(window);
+            if let Some(frame) = self.frame {
+                window.set_outer_position(frame.position_at(0.0));
+            }
+            window.set_visible(true);
+            window.focus_window();
+        }
+
+        self.animation = Some(Instant::now());
+    }
+
+    /// Move the window to the current animation step, returns whether the
+    /// animation is still running.
+    pub fn animate(&mut self, window: &Window) -> bool {
+        let (Some(started_at), Some(frame)) = (self.animation, self.frame) else {
+            self.finish(window);
+            return false;
+        };
+
+        let elapsed = started_at.elapsed().as_secs_f64();
+        let progress = (elapsed / ANIMATION_DURATION.as_secs_f64()).min(1.0);
+        let progress = if self.visible {
+            progress
+        } else {
+            1.0 - progress
+        };
+        window.set_outer_position(frame.position_at(ease_out(progress)));
+
+        if elapsed >= ANIMATION_DURATION.as_secs_f64() {
+            self.finish(window);
+            return false;
+        }
+
+        true
+    }
+
+    fn finish(&mut self, window: &Window) {
+        self.animation = None;
+        if !self.visible {
+            window.set_visible(false);
+        }
+    }
+}
+
+#[inline]
+fn ease_out(progress: f64) -> f64 {
+    1.0 - (1.0 - progress).powi(3)
+}
+
+#[cfg(target_os = "macos")]
+use crate::platform::macos::register_hotkey as register;
+
+#[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+use crate::platform::x11::register_hotkey as register;
+
+#[cfg(not(any(
+    target_os = "macos",
+    all(feature = "x11", not(any(target_os = "macos", windows)))
+)))]
+fn register(_hotkey: Hotkey, _callback: Box<dyn Fn() + Send>) -> Result<(), String> {
+    Err(String::from(
+        "global shortcuts are not supported on this platform",
+    ))
+}
+
+/// Register the global shortcut that toggles the dropdown window.
+pub fn register_hotkey(hotkey: Hotkey, event_proxy: EventProxy) -> Result<(), String> {
+    register(
+        hotkey,
+        Box::new(move || {
+            event_proxy.send_event(RioEventType::ToggleDropdown);
+        }),
+    )
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(
+            Hotkey::parse("F12"),
+            Ok(Hotkey {
+                modifiers: ModifiersState::empty(),
+                key: HotkeyKey::Function(12),
+            })
+        );
+        assert_eq!(
+            Hotkey::parse("Ctrl+Shift+grave"),
+            Ok(Hotkey {
+                modifiers: ModifiersState::CTRL | ModifiersState::SHIFT,
+                key: HotkeyKey::Char('`'),
+            })
+        );
+        assert_eq!(
+            Hotkey::parse("cmd+space"),
+            Ok(Hotkey {
+                modifiers: ModifiersState::LOGO,
+                key: HotkeyKey::Space,
+            })
+        );
+        assert_eq!(
+            Hotkey::parse("alt+T").map(|hotkey| hotkey.key),
+            Ok(HotkeyKey::Char('t'))
+        );
+        assert!(Hotkey::parse("f13").is_err());
+        assert!(Hotkey::parse("hyper+a").is_err());
+        assert!(Hotkey::parse("ctrl+").is_err());
+    }
+
+    #[test]
+    fn test_frame_slides_from_above_the_monitor() {
+        let frame = Frame::new(
+            PhysicalPosition::new(1920, 0),
+            PhysicalSize::new(2560, 1440),
+            0.5,
+        );
+        assert_eq!(frame.size, PhysicalSize::new(2560, 720));
+        assert_eq!(frame.position_at(1.0), PhysicalPosition::new(1920, 0));
+        assert_eq!(frame.position_at(0.5), PhysicalPosition::new(1920, -360));
+        assert_eq!(frame.position_at(0.0), PhysicalPosition::new(1920, -720));
+    }
+}
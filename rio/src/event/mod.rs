@@ -1,24 +1,8 @@
-pub mod sync;
-
-use crate::clipboard::ClipboardType;
-use std::borrow::Cow;
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::sync::Arc;
-use teletypewriter::WinsizeBuilder;
+use crate::ipc;
+use rio_core::event::{EventListener, RioEvent};
+use std::sync::mpsc;
 use winit::event_loop::EventLoopProxy;
 
-#[derive(Debug)]
-pub enum Msg {
-    /// Data that should be written to the PTY.
-    Input(Cow<'static, [u8]>),
-
-    #[allow(dead_code)]
-    Shutdown,
-
-    Resize(WinsizeBuilder),
-}
-
 #[derive(Debug, Eq, PartialEq)]
 pub enum ClickState {
     None,
@@ -27,80 +11,6 @@ pub enum ClickState {
     TripleClick,
 }
 
-#[derive(Clone)]
-pub enum RioEvent {
-    PrepareRender(u64),
-    Render,
-
-    /// Grid has changed possibly requiring a mouse cursor shape change.
-    MouseCursorDirty,
-
-    /// Window title change.
-    Title(String),
-
-    /// Reset to the default window title.
-    ResetTitle,
-
-    /// Request to store a text string in the clipboard.
-    ClipboardStore(ClipboardType, String),
-
-    /// Request to write the contents of the clipboard to the PTY.
-    ///
-    /// The attached function is a formatter which will corectly transform the clipboard content
-    /// into the expected escape sequence format.
-    ClipboardLoad(
-        ClipboardType,
-        Arc<dyn Fn(&str) -> String + Sync + Send + 'static>,
-    ),
-
-    /// Request to write the RGB value of a color to the PTY.
-    ///
-    /// The attached function is a formatter which will corectly transform the RGB color into the
-    /// expected escape sequence format.
-    // ColorRequest(usize, Arc<dyn Fn(Rgb) -> String + Sync + Send + 'static>),
-
-    /// Write some text to the PTY.
-    PtyWrite(String),
-
-    /// Request to write the text area size.
-    TextAreaSizeRequest(Arc<dyn Fn(WinsizeBuilder) -> String + Sync + Send + 'static>),
-
-    /// Cursor blinking state has changed.
-    CursorBlinkingChange,
-
-    /// New terminal content available.
-    Wakeup,
-
-    /// Terminal bell ring.
-    Bell,
-
-    /// Shutdown request.
-    Exit,
-}
-
-impl Debug for RioEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            RioEvent::ClipboardStore(ty, text) => {
-                write!(f, "ClipboardStore({ty:?}, {text})")
-            }
-            RioEvent::ClipboardLoad(ty, _) => write!(f, "ClipboardLoad({ty:?})"),
-            RioEvent::TextAreaSizeRequest(_) => write!(f, "TextAreaSizeRequest"),
-            // RioEvent::ColorRequest(index, _) => write!(f, "ColorRequest({index})"),
-            RioEvent::PtyWrite(text) => write!(f, "PtyWrite({text})"),
-            RioEvent::Title(title) => write!(f, "Title({title})"),
-            RioEvent::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
-            RioEvent::MouseCursorDirty => write!(f, "MouseCursorDirty"),
-            RioEvent::ResetTitle => write!(f, "ResetTitle"),
-            RioEvent::Wakeup => write!(f, "Wakeup"),
-            RioEvent::PrepareRender(millis) => write!(f, "PrepareRender({millis})"),
-            RioEvent::Render => write!(f, "Render"),
-            RioEvent::Bell => write!(f, "Bell"),
-            RioEvent::Exit => write!(f, "Exit"),
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum RioEventType {
     ScaleFactorChanged(f64, (u32, u32)),
@@ -112,6 +22,26 @@ pub enum RioEventType {
     BlinkCursorTimeout,
     SearchNext,
     Render,
+
+    /// Remote control request, answered through the attached sender.
+    Ipc(ipc::Request, mpsc::Sender<ipc::Response>),
+
+    /// Show or hide the dropdown window.
+    ToggleDropdown,
+
+    /// Next step of the dropdown slide animation.
+    DropdownFrame,
+
+    /// Set the window title held back by `TitleThrottle`.
+    UpdateTitle,
+
+    /// Switch between borderless fullscreen and the previous window size.
+    ToggleFullscreen,
+
+    ToggleMaximized,
+
+    /// Close the window, once confirmed if a program was running.
+    Quit,
 }
 
 impl From<RioEvent> for RioEventType {
@@ -139,19 +69,6 @@ impl From<EventP> for winit::event::Event<'_, EventP> {
     }
 }
 
-pub trait OnResize {
-    fn on_resize(&mut self, window_size: WinsizeBuilder);
-}
-
-/// Event Loop for notifying the renderer about terminal events.
-pub trait EventListener {
-    fn send_event(&self, _event: RioEvent) {}
-}
-
-pub struct VoidListener;
-
-impl EventListener for VoidListener {}
-
 #[derive(Debug, Clone)]
 pub struct EventProxy {
     proxy: EventLoopProxy<EventP>,
@@ -162,7 +79,6 @@ impl EventProxy {
         Self { proxy }
     }
 
-    #[allow(dead_code)]
     pub fn send_event(&self, event: RioEventType) {
         let _ = self.proxy.send_event(EventP::new(event));
     }
@@ -0,0 +1,529 @@
+// Remote control over a Unix domain socket.
+//
+// Every connection carries a single request line, which is answered with
+// `ok` or `error <reason>` on the first line, followed by the response body.
+// The socket path is exported to child processes as `RIO_SOCKET`, so scripts
+// running inside Rio can use `rio msg` without any configuration.
+
+use rio_core::ansi;
+use std::path::PathBuf;
+
+pub const SOCKET_ENV: &str = "RIO_SOCKET";
+/// Set on processes spawned by `create-window`, so they don't hand the
+/// window back to the instance that spawned them in single-instance mode.
+pub const NEW_INSTANCE_ENV: &str = "RIO_NEW_INSTANCE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextExtent {
+    Screen,
+    Scrollback,
+    Selection,
+    /// Lines `first..=last`, where line 0 is the top of the screen and
+    /// negative lines are in the scrollback.
+    Lines(i32, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    Plain,
+    /// Lines as arrays of runs with their colors and styles.
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Open a window, in the working directory and with the environment
+    /// of the `rio` invocation handing over in single-instance mode, or
+    /// those of the running instance when not set.
+    CreateWindow(Option<Launch>),
+    CreateTab,
+    /// Pairs of color name (as used in the config file) and hex value.
+    SetColors(Vec<(String, String)>),
+    /// Name of a theme, as used for `style.theme`.
+    SetTheme(String),
+    /// Read the configuration file again.
+    ReloadConfig,
+    GetText(TextExtent, TextFormat),
+    SendText(String),
+    /// Key names, e.g. `Up` or `Control+C`, sent as if they were typed.
+    SendKeys(Vec<String>),
+    ToggleDropdown,
+    /// Save the next frame as a PNG, into the home directory by default.
+    Screenshot(Option<PathBuf>),
+    /// Turn Secure Keyboard Entry on, off, or toggle it with `None`.
+    SecureInput(Option<bool>),
+}
+
+/// Working directory and environment of a `rio` invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Launch {
+    pub cwd: PathBuf,
+    pub env: Vec<(String, String)>,
+}
+
+/// Separates the fields of a launch in a `create-window` request.
+const FIELD_SEPARATOR: char = '\x1f';
+
+impl Launch {
+    /// Launch of the current process.
+    pub fn current() -> Launch {
+        Launch {
+            cwd: std::env::current_dir().unwrap_or_default(),
+            env: std::env::vars_os()
+                .filter_map(|(name, value)| {
+                    Some((name.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+        }
+    }
+
+    /// Fields of the launch as the arguments of `create-window`. The
+    /// backslashes are doubled, so they are kept by the unescaping.
+    fn encode(&self) -> String {
+        let mut fields = vec![self.cwd.to_string_lossy().to_string()];
+        fields.extend(
+            self.env
+                .iter()
+                .map(|(name, value)| format!("{name}={value}")),
+        );
+        fields
+            .iter()
+            .map(|field| field.replace('\\', "\\\\"))
+            .collect::<Vec<_>>()
+            .join(&FIELD_SEPARATOR.to_string())
+    }
+
+    fn decode(arguments: &str) -> Result<Launch, String> {
+        let arguments = ansi::unescape(arguments);
+        let mut fields = arguments.split(FIELD_SEPARATOR);
+        let cwd = PathBuf::from(fields.next().unwrap_or_default());
+        let env = fields
+            .map(|field| {
+                field
+                    .split_once('=')
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .ok_or_else(|| format!("expected NAME=VALUE, got {field:?}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Launch { cwd, env })
+    }
+}
+
+pub type Response = Result<String, String>;
+
+impl Request {
+    pub fn parse(line: &str) -> Result<Request, String> {
+        let (command, arguments) = line.split_once(' ').unwrap_or((line, ""));
+
+        match command {
+            "create-window" => match arguments {
+                "" => Ok(Request::CreateWindow(None)),
+                arguments => Ok(Request::CreateWindow(Some(Launch::decode(arguments)?))),
+            },
+            "create-tab" => Ok(Request::CreateTab),
+            "set-colors" => {
+                let mut colors = Vec::new();
+                for color in arguments.split_whitespace() {
+                    let (name, value) = color
+                        .split_once('=')
+                        .ok_or_else(|| format!("expected name=#rrggbb, got {color:?}"))?;
+                    if !is_hex_color(value) {
+                        return Err(format!("invalid color {value:?} for {name}"));
+                    }
+                    colors.push((name.to_string(), value.to_string()));
+                }
+
+                if colors.is_empty() {
+                    return Err(String::from("set-colors expects name=#rrggbb pairs"));
+                }
+                Ok(Request::SetColors(colors))
+            }
+            "set-theme" => match arguments.trim() {
+                "" => Err(String::from("set-theme expects a theme name")),
+                theme => Ok(Request::SetTheme(theme.to_string())),
+            },
+            "reload-config" => Ok(Request::ReloadConfig),
+            "get-text" => {
+                let mut format = TextFormat::Plain;
+                let mut words = Vec::new();
+                for word in arguments.split_whitespace() {
+                    match word {
+                        "--json" => format = TextFormat::Json,
+                        word => words.push(word),
+                    }
+                }
+
+                let extent = match words.as_slice() {
+                    [] | ["screen"] => TextExtent::Screen,
+                    ["scrollback"] => TextExtent::Scrollback,
+                    ["selection"] => TextExtent::Selection,
+                    ["lines", first, last] => match (first.parse(), last.parse()) {
+                        (Ok(first), Ok(last)) => TextExtent::Lines(first, last),
+                        _ => {
+                            return Err(format!("invalid line range {first:?} {last:?}"))
+                        }
+                    },
+                    ["lines", ..] => {
+                        return Err(String::from("lines expects the first and last line"))
+                    }
+                    other => {
+                        return Err(format!("unknown text extent {:?}", other.join(" ")))
+                    }
+                };
+                Ok(Request::GetText(extent, format))
+            }
+            "send-text" => Ok(Request::SendText(ansi::unescape(arguments))),
+            "send-key" => {
+                let keys: Vec<String> =
+                    arguments.split_whitespace().map(str::to_string).collect();
+                if keys.is_empty() {
+                    return Err(String::from("send-key expects key names"));
+                }
+                Ok(Request::SendKeys(keys))
+            }
+            "toggle-dropdown" => Ok(Request::ToggleDropdown),
+            "screenshot" => match arguments.trim() {
+                "" => Ok(Request::Screenshot(None)),
+                path => Ok(Request::Screenshot(Some(PathBuf::from(path)))),
+            },
+            "secure-input" => match arguments.trim() {
+                "" | "toggle" => Ok(Request::SecureInput(None)),
+                "on" => Ok(Request::SecureInput(Some(true))),
+                "off" => Ok(Request::SecureInput(Some(false))),
+                other => Err(format!("expected on, off or toggle, got {other:?}")),
+            },
+            other => Err(format!("unknown command {other:?}")),
+        }
+    }
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').map_or(false, |hex| {
+        hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Escape control characters so a request always fits in one line.
+///
+/// Backslashes are kept as they are, so escapes typed by the user
+/// (e.g. `rio msg send-text 'ls\n'`) are interpreted by the server.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\x1b' => escaped.push_str("\\e"),
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\x{:02x}", c as u8)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Socket path of the running instance. In single-instance mode the path
+/// is well-known, so later invocations are able to find it.
+pub fn socket_path(single_instance: bool) -> PathBuf {
+    let directory = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(user_temp_dir);
+    if single_instance {
+        directory.join("rio.sock")
+    } else {
+        directory.join(format!("rio-{}.sock", std::process::id()))
+    }
+}
+
+/// Directory of the user in the temporary one, where other users can't
+/// reach or replace the sockets.
+#[cfg(unix)]
+fn user_temp_dir() -> PathBuf {
+    let uid = unsafe { libc::getuid() };
+    std::env::temp_dir().join(format!("rio-{uid}"))
+}
+
+#[cfg(not(unix))]
+fn user_temp_dir() -> PathBuf {
+    std::env::temp_dir()
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{Request, Response};
+    use crate::event::{EventProxy, RioEventType};
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// Max time to wait for the event loop to answer a request.
+    const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Create the directory of the sockets for the user alone, or check that
+    /// an existing one is owned by the user and closed to everyone else.
+    pub fn create_private_directory(path: &Path) -> io::Result<()> {
+        match std::fs::DirBuilder::new().mode(0o700).create(path) {
+            Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return Err(err),
+            _ => (),
+        }
+
+        let metadata = std::fs::symlink_metadata(path)?;
+        let uid = unsafe { libc::getuid() };
+        if !metadata.is_dir()
+            || metadata.uid() != uid
+            || metadata.permissions().mode() & 0o077 != 0
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is not a private directory of the user", path.display()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn spawn_server(path: &Path, event_proxy: EventProxy) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            create_private_directory(directory)?;
+        }
+
+        // The socket of an instance which didn't exit cleanly is left behind,
+        // while the one of a running instance is still answering.
+        match UnixStream::connect(path) {
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("{} is used by a running instance", path.display()),
+                ))
+            }
+            Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                std::fs::remove_file(path)?;
+            }
+            Err(_) => (),
+        }
+        let listener = UnixListener::bind(path)?;
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(err) = handle_connection(stream, &event_proxy) {
+                            log::warn!("ipc: unable to handle request: {err}");
+                        }
+                    }
+                    Err(err) => log::warn!("ipc: unable to accept connection: {err}"),
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn handle_connection(stream: UnixStream, event_proxy: &EventProxy) -> io::Result<()> {
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        let response = match Request::parse(line.trim_end_matches(['\r', '\n'])) {
+            Ok(request) => {
+                let (sender, receiver) = mpsc::channel();
+                event_proxy.send_event(RioEventType::Ipc(request, sender));
+                receiver
+                    .recv_timeout(RESPONSE_TIMEOUT)
+                    .unwrap_or_else(|_| Err(String::from("no response from rio")))
+            }
+            Err(err) => Err(err),
+        };
+
+        let mut stream = stream;
+        match response {
+            Ok(body) => write!(stream, "ok\n{body}"),
+            Err(err) => writeln!(stream, "error {err}"),
+        }
+    }
+
+    pub fn send(path: &Path, args: &[String]) -> Response {
+        let mut stream = UnixStream::connect(path)
+            .map_err(|err| format!("unable to connect to {}: {err}", path.display()))?;
+
+        let request = super::escape(&args.join(" "));
+        let mut response = String::new();
+        writeln!(stream, "{request}")
+            .and_then(|_| stream.shutdown(std::net::Shutdown::Write))
+            .and_then(|_| stream.read_to_string(&mut response).map(|_| ()))
+            .map_err(|err| err.to_string())?;
+
+        match response.split_once('\n') {
+            Some(("ok", body)) => Ok(body.to_string()),
+            _ => Err(response
+                .trim_end()
+                .strip_prefix("error ")
+                .unwrap_or("invalid response")
+                .to_string()),
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use unix::spawn_server;
+
+/// Send a request from the command line (`rio msg ...`) to a running instance.
+#[cfg(unix)]
+pub fn send(args: &[String]) -> Response {
+    let path = std::env::var_os(SOCKET_ENV)
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("{SOCKET_ENV} is not set"))?;
+
+    unix::send(&path, args)
+}
+
+/// Ask the instance started in single-instance mode to launch a new
+/// window, in the working directory and with the environment of this
+/// process. Tabs don't run terminals of their own yet, so a new tab would
+/// be empty.
+#[cfg(unix)]
+pub fn open_window_in_running_instance() -> Response {
+    unix::send(
+        &socket_path(true),
+        &[String::from("create-window"), Launch::current().encode()],
+    )
+}
+
+#[cfg(not(unix))]
+pub fn send(_args: &[String]) -> Response {
+    Err(String::from(
+        "remote control is not supported on this platform",
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn open_window_in_running_instance() -> Response {
+    send(&[])
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_requests() {
+        assert_eq!(Request::parse("create-tab"), Ok(Request::CreateTab));
+        assert_eq!(
+            Request::parse("create-window"),
+            Ok(Request::CreateWindow(None))
+        );
+        assert_eq!(
+            Request::parse("get-text scrollback"),
+            Ok(Request::GetText(TextExtent::Scrollback, TextFormat::Plain))
+        );
+        assert_eq!(
+            Request::parse("get-text"),
+            Ok(Request::GetText(TextExtent::Screen, TextFormat::Plain))
+        );
+        assert_eq!(
+            Request::parse("get-text --json lines -100 -1"),
+            Ok(Request::GetText(
+                TextExtent::Lines(-100, -1),
+                TextFormat::Json
+            ))
+        );
+        assert_eq!(
+            Request::parse("get-text screen --json"),
+            Ok(Request::GetText(TextExtent::Screen, TextFormat::Json))
+        );
+        assert!(Request::parse("get-text lines 1").is_err());
+        assert!(Request::parse("get-text lines a 1").is_err());
+        assert!(Request::parse("get-text everything").is_err());
+        assert_eq!(
+            Request::parse("set-colors background=#112233 red=#FF0000"),
+            Ok(Request::SetColors(vec![
+                (String::from("background"), String::from("#112233")),
+                (String::from("red"), String::from("#FF0000")),
+            ]))
+        );
+        assert_eq!(Request::parse("screenshot"), Ok(Request::Screenshot(None)));
+        assert_eq!(
+            Request::parse("screenshot /tmp/rio.png"),
+            Ok(Request::Screenshot(Some(PathBuf::from("/tmp/rio.png"))))
+        );
+        assert_eq!(
+            Request::parse("secure-input"),
+            Ok(Request::SecureInput(None))
+        );
+        assert_eq!(
+            Request::parse("secure-input off"),
+            Ok(Request::SecureInput(Some(false)))
+        );
+        assert!(Request::parse("secure-input maybe").is_err());
+        assert_eq!(
+            Request::parse("send-key Control+C Up"),
+            Ok(Request::SendKeys(vec![
+                String::from("Control+C"),
+                String::from("Up"),
+            ]))
+        );
+        assert!(Request::parse("send-key").is_err());
+        assert_eq!(
+            Request::parse("set-theme lucario"),
+            Ok(Request::SetTheme(String::from("lucario")))
+        );
+        assert_eq!(Request::parse("reload-config"), Ok(Request::ReloadConfig));
+        assert!(Request::parse("set-theme").is_err());
+        assert!(Request::parse("set-colors red=blue").is_err());
+        assert!(Request::parse("set-colors").is_err());
+        assert!(Request::parse("unknown").is_err());
+    }
+
+    #[test]
+    fn test_send_text_escapes() {
+        let line = escape("send-text echo 'a b'\\n\n\x1b[A\x01");
+        assert_eq!(line, "send-text echo 'a b'\\n\\n\\e[A\\x01");
+        assert_eq!(
+            Request::parse(&line),
+            Ok(Request::SendText(String::from("echo 'a b'\n\n\x1b[A\x01")))
+        );
+        assert_eq!(ansi::unescape("a\\\\b\\x41\\"), "a\\bA\\");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("rio-test-{}", std::process::id()));
+        unix::create_private_directory(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(unix::create_private_directory(&path).is_ok());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(unix::create_private_directory(&path).is_err());
+
+        std::fs::remove_dir(path).unwrap();
+    }
+
+    #[test]
+    fn test_create_window_launch() {
+        let launch = Launch {
+            cwd: PathBuf::from("/home/rio/my dir"),
+            env: vec![
+                (String::from("PATH"), String::from("/bin:/usr/bin")),
+                (String::from("PS1"), String::from("\\w \\$ ")),
+                (String::from("EMPTY"), String::new()),
+                (String::from("MULTI"), String::from("a=b\nc")),
+            ],
+        };
+        let line = escape(&format!("create-window {}", launch.encode()));
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            Request::parse(&line),
+            Ok(Request::CreateWindow(Some(launch)))
+        );
+        assert!(Request::parse("create-window /tmp\\x1fNOVALUE").is_err());
+    }
+}
@@ -1,9 +1,9 @@
 pub mod mouse;
 
-use crate::crosswords::grid::Dimensions;
-use crate::crosswords::pos::{Column, Line, Pos};
-use crate::crosswords::{MIN_COLUMNS, MIN_VISIBLE_ROWS};
 use mouse::{AccumulatedScroll, Mouse};
+use rio_core::crosswords::grid::Dimensions;
+use rio_core::crosswords::pos::{Column, Line, Pos};
+use config::BarPosition;
 use sugarloaf::core::SugarloafStyle;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -15,6 +15,10 @@ pub struct Delta<T: Default> {
 const PADDING_X: f32 = 10.0;
 const PADDING_Y: f32 = 50.0;
 
+/// Smallest grid, kept even when the window is smaller (or minimized).
+pub const MIN_COLUMNS: usize = 10;
+pub const MIN_ROWS: usize = 2;
+
 pub struct Layout {
     scale_factor: f32,
     pub width: f32,
@@ -26,9 +30,19 @@ pub struct Layout {
     pub columns: usize,
     pub rows: usize,
     padding: Delta<f32>,
+    bar: Bar,
     pub styles: Styles,
 }
 
+/// Bar drawn at the top or bottom edge, in logical pixels.
+#[derive(Clone, Copy, Debug, Default)]
+struct Bar {
+    position: BarPosition,
+    height: f32,
+    /// Whether the terminal makes room for it, instead of being drawn over.
+    reserved: bool,
+}
+
 #[derive(Default)]
 pub struct Styles {
     pub term: SugarloafStyle,
@@ -53,25 +67,27 @@ impl Dimensions for Layout {
 }
 
 fn update_styles(layout: &mut Layout) {
+    let (bar_top, bar_height) = layout.bar_area();
+    let text_scale = layout.text_scale();
     let new_styles = Styles {
         term: SugarloafStyle {
-            screen_position: (
-                layout.padding.x * layout.scale_factor,
-                (layout.padding.y * layout.scale_factor),
-            ),
+            screen_position: layout.padding(),
             bounds: (
                 layout.width * layout.scale_factor,
                 layout.height * layout.scale_factor,
             ),
-            text_scale: layout.font_size * layout.scale_factor,
+            text_scale,
         },
         tabs: SugarloafStyle {
-            screen_position: (80.0 * layout.scale_factor, (8.0 * layout.scale_factor)),
+            screen_position: (
+                (80.0 * layout.scale_factor).round(),
+                (bar_top + ((bar_height - text_scale) / 2.0).max(0.0)).round(),
+            ),
             bounds: (
                 layout.width * layout.scale_factor,
                 layout.height * layout.scale_factor,
             ),
-            text_scale: layout.font_size * layout.scale_factor,
+            text_scale,
         },
     };
     layout.styles = new_styles;
@@ -96,19 +112,98 @@ impl Layout {
                 x: PADDING_X,
                 y: PADDING_Y,
             },
+            bar: Bar::default(),
         };
 
         update_styles(&mut layout);
         layout
     }
 
+    /// Height of a line in physical pixels. It is whole, so lines start on
+    /// a pixel at fractional scales (e.g. 125% or 150%) and text is crisp.
+    #[inline]
+    fn text_scale(&self) -> f32 {
+        (self.font_size * self.scale_factor).round()
+    }
+
     #[inline]
     fn padding(&self) -> (f32, f32) {
         let padding_x = ((self.padding.x) * self.scale_factor).floor();
-        let padding_y = ((self.padding.y) * self.scale_factor).floor();
+        let padding_y = ((self.padding_top()) * self.scale_factor).floor();
         (padding_x, padding_y)
     }
 
+    /// Space above the terminal, in logical pixels. A bar at the top fits
+    /// in the padding unless it is taller.
+    #[inline]
+    fn padding_top(&self) -> f32 {
+        match self.bar {
+            Bar {
+                position: BarPosition::Top,
+                height,
+                reserved: true,
+            } => self.padding.y.max(height),
+            _ => self.padding.y,
+        }
+    }
+
+    /// Space below the terminal, in logical pixels.
+    #[inline]
+    fn padding_bottom(&self) -> f32 {
+        match self.bar {
+            Bar {
+                position: BarPosition::Bottom,
+                height,
+                reserved: true,
+            } => height,
+            _ => 0.0,
+        }
+    }
+
+    pub fn set_bar(&mut self, position: BarPosition, height: f32) -> &mut Self {
+        self.bar.position = position;
+        self.bar.height = height.max(0.0);
+        self
+    }
+
+    /// Make room for the bar or let it be drawn over the terminal. Returns
+    /// whether it changed, in which case the grid has to be computed again.
+    pub fn set_bar_reserved(&mut self, reserved: bool) -> bool {
+        let changed = self.bar.reserved != reserved;
+        self.bar.reserved = reserved;
+        changed
+    }
+
+    /// Logical size of a window fitting `columns` and `lines`.
+    pub fn size_for(&self, columns: u16, lines: u16) -> (f32, f32) {
+        let width = columns as f32 * self.font_size / 2.0 + 2.0 * self.padding.x;
+        let height = lines as f32 * self.font_size
+            + self.padding_top()
+            + self.padding_bottom();
+        (width.ceil(), height.ceil())
+    }
+
+    /// Width and height of a cell, in physical pixels.
+    #[inline]
+    pub fn cell_size(&self) -> (f32, f32) {
+        (self.font_size / 2.0 * self.scale_factor, self.text_scale())
+    }
+
+    /// Logical size of the smallest window fitting the minimum grid.
+    pub fn minimum_size(&self) -> (f32, f32) {
+        self.size_for(MIN_COLUMNS as u16, MIN_ROWS as u16)
+    }
+
+    /// Top and height of the bar, in physical pixels.
+    #[inline]
+    pub fn bar_area(&self) -> (f32, f32) {
+        let height = self.bar.height * self.scale_factor;
+        match self.bar.position {
+            BarPosition::Top => (0.0, height),
+            BarPosition::Bottom => (self.height - height, height),
+        }
+    }
+
     pub fn set_scale(&mut self, scale_factor: f32) -> &mut Self {
         self.scale_factor = scale_factor;
         self
@@ -142,7 +237,7 @@ impl Layout {
             self.mouse.x.saturating_sub(PADDING_X as usize) / self.font_size as usize;
         let col = std::cmp::min(Column(col), Column(self.columns));
 
-        let line = self.mouse.y.saturating_sub(PADDING_Y as usize) / text_scale;
+        let line = self.mouse.y.saturating_sub(self.padding_top() as usize) / text_scale;
         let line = std::cmp::min(line, self.rows - 1);
 
         let point = Pos::new(line, col);
@@ -155,9 +250,9 @@ impl Layout {
     #[inline]
     pub fn compute(&mut self) -> (usize, usize) {
         let (padding_x, padding_y) = self.padding();
-        let mut rows = (self.height - padding_y) / self.scale_factor;
-        rows /= self.font_size;
-        let visible_rows = std::cmp::max(rows as usize, MIN_VISIBLE_ROWS);
+        let padding_bottom = (self.padding_bottom() * self.scale_factor).floor();
+        let rows = (self.height - padding_y - padding_bottom) / self.text_scale();
+        let visible_rows = std::cmp::max(rows as usize, MIN_ROWS);
 
         let mut visible_columns = (self.width - 2. * padding_x) / self.scale_factor;
         visible_columns /= self.font_size / 2.;
@@ -169,3 +264,31 @@ impl Layout {
         (visible_columns, visible_rows)
     }
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_compute_minimum_grid() {
+        let mut layout = Layout::new(40.0, 10.0, 1.0, 18.0);
+        assert_eq!(layout.compute(), (MIN_COLUMNS, MIN_ROWS));
+
+        // Minimized windows have no size at all.
+        layout.set_size(0, 0).update();
+        assert_eq!(layout.compute(), (MIN_COLUMNS, MIN_ROWS));
+    }
+
+    #[test]
+    fn test_compute_fractional_scale() {
+        let mut layout = Layout::new(1000.0, 800.0, 1.0, 18.0);
+        assert_eq!(layout.compute(), (108, 41));
+
+        // Lines are 23 pixels high rather than 22.5.
+        layout.set_scale(1.25).update();
+        assert_eq!(layout.styles.term.text_scale, 23.0);
+        assert_eq!(layout.cell_size(), (11.25, 23.0));
+        assert_eq!(layout.styles.term.screen_position, (12.0, 62.0));
+        assert_eq!(layout.compute(), (86, 32));
+    }
+}
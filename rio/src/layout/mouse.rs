@@ -1,5 +1,5 @@
-use crate::crosswords::pos::Side;
 use crate::event::ClickState;
+use rio_core::crosswords::pos::Side;
 use std::time::Instant;
 use winit::event::ElementState;
 use winit::event::MouseButton;
@@ -0,0 +1,25 @@
+// Window, renderer and event loop of the `rio` binary. The terminal model
+// (grid, parser and PTY handling) lives in the `rio-core` crate.
+
+mod appearance;
+mod bench;
+pub mod cli;
+mod clipboard;
+pub mod crash;
+mod dropdown;
+pub mod event;
+mod ime;
+pub mod ipc;
+mod layout;
+pub mod logger;
+mod notification;
+pub mod platform;
+mod player;
+mod scheduler;
+mod screen;
+pub mod sequencer;
+mod session;
+pub mod session_file;
+mod speech;
+pub mod startup;
+mod tabs;
@@ -1,29 +1,159 @@
-use log::{Metadata, Record};
+use log::{Level, LevelFilter, Metadata, Record, SetLoggerError};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Mutex;
 
-pub struct Logger;
+pub use rio_core::perf::{Span, PERF_TARGET};
 
-impl log::Log for Logger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        // If declarative wants to ignore trace
-        // metadata.level() <= log::Level::Debug
+/// Overrides `developer.log-level`, e.g. `RIO_LOG=warn,rio::screen=debug`.
+pub const LOG_ENV: &str = "RIO_LOG";
+
+/// Levels per module, written as a default level followed by
+/// `target=level` pairs separated by commas.
+#[derive(Debug, PartialEq)]
+pub struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Filter {
+        let mut filter = Filter {
+            default: LevelFilter::Off,
+            targets: Vec::new(),
+        };
+
+        for directive in spec.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                Some((target, level)) => match LevelFilter::from_str(level.trim()) {
+                    Ok(level) => filter.targets.push((target.trim().to_string(), level)),
+                    Err(_) => eprintln!("invalid log level {level:?} for {target}"),
+                },
+                None if directive.is_empty() => (),
+                None => match LevelFilter::from_str(directive) {
+                    Ok(level) => filter.default = level,
+                    Err(_) => eprintln!("invalid log level {directive:?}"),
+                },
+            }
+        }
+
+        // The most specific target wins.
+        filter
+            .targets
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        filter
+    }
+
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let filter = self
+            .targets
+            .iter()
+            .find(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level);
+
+        level <= filter
+    }
+}
+
+pub struct Logger {
+    filter: Filter,
+    file: Option<Mutex<File>>,
+}
 
-        true
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filter.enabled(metadata.target(), metadata.level())
     }
 
     fn log(&self, record: &Record) {
-        // For cases where wants to validate if log is enabled
-        // if self.enabled(record.metadata()) {
-        //     println!("{}", record.level());
-        // }
+        if !self.enabled(record.metadata()) {
+            return;
+        }
 
-        let line = format!(
-            "\x1b[35m[{}]\x1b[0m \x1b[34m{}\x1b[0m {}\0",
+        println!(
+            "\x1b[35m[{}]\x1b[0m \x1b[34m{}\x1b[0m {}",
             record.level(),
             record.target(),
             record.args()
         );
-        println!("{line}");
+
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(
+                    file,
+                    "[{}] {} {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                );
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
     }
+}
+
+/// Install the logger, filtered by `RIO_LOG` or else `developer.log-level`.
+pub fn init(config: &config::Developer) -> Result<(), SetLoggerError> {
+    let spec = std::env::var(LOG_ENV).unwrap_or_else(|_| config.log_level.clone());
+    let filter = Filter::parse(&spec);
 
-    fn flush(&self) {}
+    let file = config.log_file.as_ref().and_then(|path| {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(Mutex::new(file)),
+            Err(err) => {
+                eprintln!("unable to open log file {path}: {err}");
+                None
+            }
+        }
+    });
+
+    let max_level = filter.max_level();
+    let logger = Box::leak(Box::new(Logger { filter, file }));
+    log::set_logger(logger).map(|()| log::set_max_level(max_level))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter_levels_by_target() {
+        let filter = Filter::parse("warn, rio::screen=debug,rio::screen::state=off");
+
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+        assert!(filter.enabled("sugarloaf", Level::Warn));
+        assert!(!filter.enabled("sugarloaf", Level::Info));
+        assert!(filter.enabled("rio::screen", Level::Debug));
+        assert!(filter.enabled("rio::screen::bindings", Level::Debug));
+        assert!(!filter.enabled("rio::screen::state", Level::Error));
+        assert!(!filter.enabled("rio::screenshot", Level::Debug));
+    }
+
+    #[test]
+    fn test_filter_from_config_level() {
+        let filter = Filter::parse("INFO");
+        assert_eq!(filter.max_level(), LevelFilter::Info);
+        assert!(filter.enabled("rio", Level::Info));
+
+        assert_eq!(Filter::parse("").max_level(), LevelFilter::Off);
+    }
 }
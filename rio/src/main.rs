@@ -1,39 +1,27 @@
-mod ansi;
-mod clipboard;
-mod crosswords;
-mod event;
-mod ime;
-mod layout;
-mod logger;
-mod performer;
-mod platform;
-mod scheduler;
-mod screen;
-mod selection;
-mod sequencer;
-mod tabs;
-use crate::event::EventP;
-use crate::sequencer::Sequencer;
-use log::{info, LevelFilter, SetLoggerError};
-use logger::Logger;
-use std::str::FromStr;
+use log::info;
+use rio::cli::{Cli, ConfigCommand};
+use rio::event::EventP;
+use rio::sequencer::Sequencer;
+use rio::session_file::{self, SessionFile};
+use rio::startup::StartupToken;
+use rio::{crash, ipc, logger};
+use std::path::{Path, PathBuf};
 
 pub fn setup_environment_variables(config: &config::Config) {
-    let terminfo = if teletypewriter::terminfo_exists("rio") {
-        "rio"
-    } else {
-        "xterm-256color"
-    };
+    let terminfo = teletypewriter::terminfo(&config.term);
 
     info!("[setup_environment_variables] terminfo: {terminfo}");
 
     std::env::set_var("TERM", terminfo);
-    std::env::set_var("COLORTERM", "truecolor");
-    std::env::remove_var("DESKTOP_STARTUP_ID");
+    if config.features.truecolor {
+        std::env::set_var("COLORTERM", "truecolor");
+    } else {
+        std::env::remove_var("COLORTERM");
+    }
     // Temporary approach for macos
     // https://pubs.opengroup.org/onlinepubs/7908799/xbd/envvar.html
     #[cfg(target_os = "macos")]
-    platform::macos::set_locale_environment();
+    rio::platform::macos::set_locale_environment();
 
     std::env::set_var("LC_CTYPE", "UTF-8");
 
@@ -53,28 +41,179 @@ pub fn setup_environment_variables(config: &config::Config) {
     }
 }
 
-static LOGGER: Logger = Logger;
+/// Start the shell of `profile`, in its working directory.
+pub fn setup_profile(profile: &config::Profile) {
+    if let Some(shell) = &profile.shell {
+        std::env::set_var("SHELL", shell);
+    }
+
+    if let Some(cwd) = &profile.cwd {
+        set_working_directory(cwd);
+    }
+}
+
+/// Working directory of the shell, which may start with `~/`.
+fn set_working_directory(cwd: &str) {
+    let cwd = match (cwd.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(relative), Some(home)) => home.join(relative),
+        _ => std::path::PathBuf::from(cwd),
+    };
+    if let Err(err) = std::env::set_current_dir(&cwd) {
+        log::warn!("unable to change directory to {}: {err}", cwd.display());
+    }
+}
+
+/// Check the configuration file at `path`, or the one in use, and exit
+/// with the problems found.
+fn validate_config(path: Option<&Path>) -> ! {
+    let path =
+        path.map_or_else(|| PathBuf::from(config::Config::path()), Path::to_path_buf);
+    let content = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("rio config: {}: {err}", path.display());
+        std::process::exit(1);
+    });
 
-fn setup_logs_by_filter_level(log_level: LevelFilter) -> Result<(), SetLoggerError> {
-    log::set_logger(&LOGGER).map(|()| log::set_max_level(log_level))
+    match config::schema::validate(&content) {
+        Ok(_) => {
+            println!("{}: ok", path.display());
+            std::process::exit(0);
+        }
+        Err(problems) => {
+            for problem in problems {
+                match problem.location {
+                    Some((line, column)) => eprintln!(
+                        "{}:{line}:{column}: {}",
+                        path.display(),
+                        problem.message
+                    ),
+                    None => eprintln!("{}: {}", path.display(), problem.message),
+                }
+            }
+            std::process::exit(1);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = config::Config::load();
-    let filter_level =
-        LevelFilter::from_str(&config.developer.log_level).unwrap_or(LevelFilter::Off);
-
-    let setup_logs = setup_logs_by_filter_level(filter_level);
+    let mut config = config::Config::load();
+    let setup_logs = logger::init(&config.developer);
     if setup_logs.is_err() {
         println!("unable to configure log level");
     }
 
+    crash::install(&config);
+
+    let mut cli = Cli::parse();
+    match &cli.config {
+        Some(ConfigCommand::Default) => {
+            print!("{}", config::schema::default_config());
+            return Ok(());
+        }
+        Some(ConfigCommand::Validate(path)) => validate_config(path.as_deref()),
+        Some(ConfigCommand::Usage) => {
+            eprintln!("usage: rio config default | validate [path] | dump");
+            std::process::exit(1);
+        }
+        Some(ConfigCommand::Dump) | None => (),
+    }
+
+    if let Some(args) = &cli.msg {
+        match ipc::send(args) {
+            Ok(body) => print!("{body}"),
+            Err(err) => {
+                eprintln!("rio msg: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Taken before other processes are started, which would report it too.
+    let startup = StartupToken::take();
+
+    // The first window of a session file is opened by this process.
+    let mut session_cwd = None;
+    if let Some(path) = &cli.session {
+        let session = SessionFile::load(path).unwrap_or_else(|err| {
+            eprintln!("rio: {err}");
+            std::process::exit(1);
+        });
+        let index = std::env::var(session_file::WINDOW_ENV).ok();
+        std::env::remove_var(session_file::WINDOW_ENV);
+        // The windows of a session file start as it describes them.
+        config.session.restore = false;
+        if index.is_none() {
+            session.open_other_windows(path);
+        }
+
+        if let Some(window) = session.window(index.as_deref()) {
+            if cli.profile.is_none() {
+                cli.profile = window.profile.clone();
+            }
+            session_cwd = window.cwd.clone();
+            cli.commands = window.commands.clone();
+        }
+    }
+
+    let profile = match &cli.profile {
+        Some(name) => match config.apply_profile(name) {
+            Ok(profile) => Some(profile),
+            Err(err) => {
+                eprintln!("rio: {err}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    config.apply_high_contrast();
+    config.apply_features_env();
+    if let Some(class) = &cli.class {
+        config.window.class = class.clone();
+    }
+    if let Some(name) = &cli.name {
+        config.window.name = name.clone();
+    }
+    if cli.config == Some(ConfigCommand::Dump) {
+        print!("{}", config::schema::dump(&config));
+        return Ok(());
+    }
+
+    // Windows created from a running instance don't take over its socket,
+    // nor its saved session, which would be restored in every window and
+    // written by the last one to exit.
+    if std::env::var_os(ipc::NEW_INSTANCE_ENV).is_some() {
+        std::env::remove_var(ipc::NEW_INSTANCE_ENV);
+        config.session.single_instance = false;
+        config.session.restore = false;
+    }
+
+    if config.session.single_instance
+        && cli.play.is_none()
+        && cli.record.is_none()
+        && cli.profile.is_none()
+        && cli.session.is_none()
+        && cli.class.is_none()
+        && cli.name.is_none()
+        && cli.command.is_none()
+    {
+        match ipc::open_window_in_running_instance() {
+            Ok(_) => return Ok(()),
+            Err(err) => log::info!("no running instance to reuse: {err}"),
+        }
+    }
+
     setup_environment_variables(&config);
+    if let Some(profile) = &profile {
+        setup_profile(profile);
+    }
+    if let Some(cwd) = &session_cwd {
+        set_working_directory(cwd);
+    }
 
     let window_event_loop =
         winit::event_loop::EventLoopBuilder::<EventP>::with_user_event().build();
-    let mut sequencer = Sequencer::new(config);
+    let mut sequencer = Sequencer::new(config, cli, startup);
     let result = sequencer.run(window_event_loop);
 
     result.await
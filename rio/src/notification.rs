@@ -0,0 +1,103 @@
+// Desktop notifications for commands finishing while the window is
+// unfocused.
+//
+// Commands are reported by shells marking them with OSC 133. Notifications
+// are sent with `osascript` on macOS and `notify-send` elsewhere, from a
+// separate thread so a slow notification daemon doesn't block the event loop.
+
+use crate::platform;
+#[cfg(not(windows))]
+use std::process::Command;
+use std::time::Duration;
+
+/// Title and body of the notification for a finished command.
+pub fn command_finished(
+    command: &str,
+    status: Option<i32>,
+    duration: Duration,
+) -> (String, String) {
+    let title = match status {
+        Some(status) if status != 0 => format!("Command failed (exit {status})"),
+        _ => String::from("Command finished"),
+    };
+    let command = if command.is_empty() {
+        "Command"
+    } else {
+        command
+    };
+    let body = format!("{command} ran for {}", format_duration(duration));
+    (title, body)
+}
+
+/// Duration rounded to the second, as `1h 2m 3s`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Send a notification. The command in the body is set by applications,
+/// so it is passed as an argument and never parsed as script or options.
+pub fn send(title: String, body: String) {
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut command = Command::new("osascript");
+        command
+            .args(["-e", "on run argv"])
+            .args([
+                "-e",
+                "display notification (item 2 of argv) with title (item 1 of argv)",
+            ])
+            .args(["-e", "end run"])
+            .arg(&title)
+            .arg(&body);
+        Some(command)
+    };
+
+    #[cfg(windows)]
+    let command = None;
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let command = {
+        let mut command = Command::new("notify-send");
+        command
+            .arg("--app-name=Rio")
+            .arg("--")
+            .arg(&title)
+            .arg(&body);
+        Some(command)
+    };
+
+    platform::run_in_background(command, format!("send notification {title}"));
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(900)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(72)), "1m 12s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 2m 3s");
+    }
+
+    #[test]
+    fn test_command_finished() {
+        let (title, body) =
+            command_finished("cargo build", Some(0), Duration::from_secs(72));
+        assert_eq!(title, "Command finished");
+        assert_eq!(body, "cargo build ran for 1m 12s");
+
+        let (title, body) = command_finished("", Some(101), Duration::from_secs(12));
+        assert_eq!(title, "Command failed (exit 101)");
+        assert_eq!(body, "Command ran for 12s");
+    }
+}
@@ -1,13 +1,15 @@
 // Retired from https://github.com/alacritty/alacritty/blob/6e7f466c68b387f41726757eed4f3e70d05479d2/alacritty/src/macos/locale.rs
 
-use std::ffi::{CStr, CString};
+use crate::dropdown::{Hotkey, HotkeyKey};
+use std::ffi::{c_void, CStr, CString};
 use std::os::raw::c_char;
-use std::{env, slice, str};
+use std::{env, ptr, slice, str};
 
 use libc::{setlocale, LC_ALL, LC_CTYPE};
 use log::debug;
-use objc::runtime::{Class, Object};
+use objc::runtime::{Class, Object, NO};
 use objc::{msg_send, sel, sel_impl};
+use winit::event::ModifiersState;
 const FALLBACK_LOCALE: &str = "UTF-8";
 
 pub fn set_locale_environment() {
@@ -89,6 +91,52 @@ fn system_locale() -> String {
     }
 }
 
+/// Turn off native window tabbing, with its "Show Tab Bar" and "Merge All
+/// Windows" menu items. It only groups windows of one process, while each
+/// Rio window has a process of its own.
+pub fn disable_window_tabbing() {
+    unsafe {
+        let window_class = Class::get("NSWindow").unwrap();
+        let _: () = msg_send![window_class, setAllowsAutomaticWindowTabbing: NO];
+    }
+}
+
+/// Write plain text, HTML and RTF flavors to the general pasteboard.
+pub fn set_pasteboard_rich_text(text: &str, html: &str, rtf: &str) -> bool {
+    unsafe {
+        let pasteboard_class = Class::get("NSPasteboard").unwrap();
+        let pasteboard: *mut Object = msg_send![pasteboard_class, generalPasteboard];
+        let _: isize = msg_send![pasteboard, clearContents];
+
+        let mut stored = true;
+        for (content, ty) in [
+            (text, "public.utf8-plain-text"),
+            (html, "public.html"),
+            (rtf, "public.rtf"),
+        ] {
+            let content = nsstring_from_str(content);
+            let ty = nsstring_from_str(ty);
+            let result: bool = msg_send![pasteboard, setString: content forType: ty];
+            stored &= result;
+            let _: () = msg_send![content, release];
+            let _: () = msg_send![ty, release];
+        }
+
+        stored
+    }
+}
+
+unsafe fn nsstring_from_str(string: &str) -> *mut Object {
+    let nsstring_class = Class::get("NSString").unwrap();
+    let nsstring: *mut Object = msg_send![nsstring_class, alloc];
+    msg_send![
+        nsstring,
+        initWithBytes: string.as_ptr()
+        length: string.len()
+        encoding: UTF8_ENCODING
+    ]
+}
+
 const UTF8_ENCODING: usize = 4;
 
 unsafe fn nsstring_as_str<'a>(nsstring: *const Object) -> &'a str {
@@ -96,3 +144,188 @@ unsafe fn nsstring_as_str<'a>(nsstring: *const Object) -> &'a str {
     let len: usize = msg_send![nsstring, lengthOfBytesUsingEncoding: UTF8_ENCODING];
     str::from_utf8(slice::from_raw_parts(cstr as *const u8, len)).unwrap()
 }
+
+type OSStatus = i32;
+type EventTargetRef = *mut c_void;
+type EventHandlerRef = *mut c_void;
+type EventHandlerCallRef = *mut c_void;
+type EventRef = *mut c_void;
+type EventHotKeyRef = *mut c_void;
+type EventHandlerUPP =
+    extern "C" fn(EventHandlerCallRef, EventRef, *mut c_void) -> OSStatus;
+
+#[repr(C)]
+struct EventTypeSpec {
+    event_class: u32,
+    event_kind: u32,
+}
+
+#[repr(C)]
+struct EventHotKeyID {
+    signature: u32,
+    id: u32,
+}
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn GetApplicationEventTarget() -> EventTargetRef;
+    fn InstallEventHandler(
+        target: EventTargetRef,
+        handler: EventHandlerUPP,
+        num_types: u32,
+        list: *const EventTypeSpec,
+        user_data: *mut c_void,
+        out_ref: *mut EventHandlerRef,
+    ) -> OSStatus;
+    fn EnableSecureEventInput() -> OSStatus;
+    fn DisableSecureEventInput() -> OSStatus;
+    fn RegisterEventHotKey(
+        key_code: u32,
+        modifiers: u32,
+        id: EventHotKeyID,
+        target: EventTargetRef,
+        options: u32,
+        out_ref: *mut EventHotKeyRef,
+    ) -> OSStatus;
+}
+
+// 'keyb'
+const K_EVENT_CLASS_KEYBOARD: u32 = 0x6b65_7962;
+const K_EVENT_HOT_KEY_PRESSED: u32 = 5;
+// 'rio '
+const HOT_KEY_SIGNATURE: u32 = 0x7269_6f20;
+
+extern "C" fn hotkey_handler(
+    _next: EventHandlerCallRef,
+    _event: EventRef,
+    user_data: *mut c_void,
+) -> OSStatus {
+    let callback = unsafe { &*(user_data as *const Box<dyn Fn() + Send>) };
+    callback();
+    0
+}
+
+/// Register a system-wide shortcut with Carbon, which is delivered through
+/// the application event loop.
+pub fn register_hotkey(
+    hotkey: Hotkey,
+    callback: Box<dyn Fn() + Send>,
+) -> Result<(), String> {
+    let key_code = virtual_key_code(hotkey.key)
+        .ok_or_else(|| format!("{:?} has no macOS key code", hotkey.key))?;
+
+    let mut modifiers = 0;
+    for (modifier, mask) in [
+        (ModifiersState::LOGO, 0x100),
+        (ModifiersState::SHIFT, 0x200),
+        (ModifiersState::ALT, 0x800),
+        (ModifiersState::CTRL, 0x1000),
+    ] {
+        if hotkey.modifiers.contains(modifier) {
+            modifiers |= mask;
+        }
+    }
+
+    unsafe {
+        let target = GetApplicationEventTarget();
+        let event_type = EventTypeSpec {
+            event_class: K_EVENT_CLASS_KEYBOARD,
+            event_kind: K_EVENT_HOT_KEY_PRESSED,
+        };
+        // The handler lives as long as the application.
+        let user_data = Box::into_raw(Box::new(callback)) as *mut c_void;
+        let status = InstallEventHandler(
+            target,
+            hotkey_handler,
+            1,
+            &event_type,
+            user_data,
+            ptr::null_mut(),
+        );
+        if status != 0 {
+            return Err(format!("InstallEventHandler failed with {status}"));
+        }
+
+        let id = EventHotKeyID {
+            signature: HOT_KEY_SIGNATURE,
+            id: 1,
+        };
+        let mut hotkey_ref: EventHotKeyRef = ptr::null_mut();
+        let status =
+            RegisterEventHotKey(key_code, modifiers, id, target, 0, &mut hotkey_ref);
+        if status != 0 {
+            return Err(format!("RegisterEventHotKey failed with {status}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Virtual key codes (kVK_*) of the ANSI keyboard layout.
+fn virtual_key_code(key: HotkeyKey) -> Option<u32> {
+    let code = match key {
+        HotkeyKey::Space => 0x31,
+        HotkeyKey::Function(number) => [
+            0x7a, 0x78, 0x63, 0x76, 0x60, 0x61, 0x62, 0x64, 0x65, 0x6d, 0x67, 0x6f,
+        ][usize::from(number).checked_sub(1)?],
+        HotkeyKey::Char(c) => match c {
+            'a' => 0x00,
+            's' => 0x01,
+            'd' => 0x02,
+            'f' => 0x03,
+            'h' => 0x04,
+            'g' => 0x05,
+            'z' => 0x06,
+            'x' => 0x07,
+            'c' => 0x08,
+            'v' => 0x09,
+            'b' => 0x0b,
+            'q' => 0x0c,
+            'w' => 0x0d,
+            'e' => 0x0e,
+            'r' => 0x0f,
+            'y' => 0x10,
+            't' => 0x11,
+            '1' => 0x12,
+            '2' => 0x13,
+            '3' => 0x14,
+            '4' => 0x15,
+            '6' => 0x16,
+            '5' => 0x17,
+            '9' => 0x19,
+            '7' => 0x1a,
+            '8' => 0x1c,
+            '0' => 0x1d,
+            'o' => 0x1f,
+            'u' => 0x20,
+            'i' => 0x22,
+            'p' => 0x23,
+            'l' => 0x25,
+            'j' => 0x26,
+            'k' => 0x28,
+            'n' => 0x2d,
+            'm' => 0x2e,
+            '`' => 0x32,
+            _ => return None,
+        },
+    };
+
+    Some(code)
+}
+
+/// Turn Secure Keyboard Entry on or off, which keeps other applications
+/// from reading keystrokes (e.g. passwords typed in the terminal).
+pub fn set_secure_keyboard_entry(enabled: bool) -> Result<(), String> {
+    let status = unsafe {
+        if enabled {
+            EnableSecureEventInput()
+        } else {
+            DisableSecureEventInput()
+        }
+    };
+
+    if status != 0 {
+        return Err(format!("secure keyboard entry failed with {status}"));
+    }
+    Ok(())
+}
@@ -1,2 +1,25 @@
 #[cfg(target_os = "macos")]
 pub mod macos;
+#[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+pub mod x11;
+#[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+pub mod wayland;
+
+/// Run `command` on a thread of its own and wait for it there, so a slow
+/// program doesn't block the caller. `None` is a platform without a
+/// program for the task, failures are logged as being unable to `task`.
+pub fn run_in_background(command: Option<std::process::Command>, task: String) {
+    std::thread::spawn(move || {
+        let result = match command {
+            Some(mut command) => command.status().map(|_| ()),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "unsupported platform",
+            )),
+        };
+
+        if let Err(err) = result {
+            log::warn!("unable to {task}: {err}");
+        }
+    });
+}
@@ -0,0 +1,63 @@
+use std::os::raw::c_void;
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::sys::client::{wl_display, wl_proxy};
+use wayland_client::{Display, EventQueue, GlobalManager, Proxy};
+use wayland_protocols::staging::xdg_activation::v1::client::xdg_activation_v1::XdgActivationV1;
+
+/// Global of the xdg-decoration protocol, through which compositors draw
+/// the title bar and borders of their clients.
+const DECORATION_MANAGER: &str = "zxdg_decoration_manager_v1";
+
+/// Globals of the compositor behind `display`, on a queue of their own so
+/// the events of winit's queue are left alone.
+fn globals(display: *mut c_void) -> Result<(EventQueue, GlobalManager), String> {
+    let display = unsafe { Display::from_external_display(display as *mut wl_display) };
+    let mut queue = display.create_event_queue();
+    let attached = display.attach(queue.token());
+    let globals = GlobalManager::new(&attached);
+
+    queue
+        .sync_roundtrip(&mut (), |_, _, _| {})
+        .map_err(|err| format!("unable to list the Wayland globals: {err}"))?;
+    Ok((queue, globals))
+}
+
+/// Whether the compositor behind `display` draws window decorations. Without
+/// it (e.g. GNOME), windows have to draw their own.
+pub fn has_server_decorations(display: *mut c_void) -> bool {
+    match globals(display) {
+        Ok((_, globals)) => globals
+            .list()
+            .iter()
+            .any(|(_, interface, _)| interface == DECORATION_MANAGER),
+        Err(err) => {
+            log::warn!("{err}");
+            // Keep the decorations, winit draws its own when needed.
+            true
+        }
+    }
+}
+
+/// Give the focus to `surface` with the xdg-activation `token` of the
+/// launcher, which also ends its startup notification.
+pub fn activate(
+    display: *mut c_void,
+    surface: *mut c_void,
+    token: &str,
+) -> Result<(), String> {
+    let (mut queue, globals) = globals(display)?;
+    let activation = globals
+        .instantiate_exact::<XdgActivationV1>(1)
+        .map_err(|err| format!("no xdg-activation support: {err}"))?;
+
+    let surface = WlSurface::from(unsafe {
+        Proxy::<WlSurface>::from_c_ptr(surface as *mut wl_proxy)
+    });
+    activation.activate(token.to_string(), &surface);
+    activation.destroy();
+
+    queue
+        .sync_roundtrip(&mut (), |_, _, _| {})
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
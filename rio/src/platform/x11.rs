@@ -0,0 +1,196 @@
+use crate::dropdown::{Hotkey, HotkeyKey};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_uint};
+use std::sync::mpsc;
+use std::{mem, ptr};
+use winit::event::ModifiersState;
+use x11_dl::xlib;
+
+/// Grab a key combination on the root window, so it is received regardless
+/// of the focused window. Events are read from a dedicated connection, owned
+/// by a background thread.
+pub fn register_hotkey(
+    hotkey: Hotkey,
+    callback: Box<dyn Fn() + Send>,
+) -> Result<(), String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return Err(String::from(
+            "Wayland doesn't allow global shortcuts, bind one to `rio msg toggle-dropdown` instead",
+        ));
+    }
+
+    let keysym_name = match hotkey.key {
+        HotkeyKey::Space => String::from("space"),
+        HotkeyKey::Char('`') => String::from("grave"),
+        HotkeyKey::Char(c) => c.to_string(),
+        HotkeyKey::Function(number) => format!("F{number}"),
+    };
+    let keysym_name = CString::new(keysym_name).map_err(|err| err.to_string())?;
+
+    let mut modifiers: c_uint = 0;
+    for (modifier, mask) in [
+        (ModifiersState::CTRL, xlib::ControlMask),
+        (ModifiersState::ALT, xlib::Mod1Mask),
+        (ModifiersState::SHIFT, xlib::ShiftMask),
+        (ModifiersState::LOGO, xlib::Mod4Mask),
+    ] {
+        if hotkey.modifiers.contains(modifier) {
+            modifiers |= mask;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let (xlib, display) = match grab_key(&keysym_name, modifiers) {
+            Ok(connection) => {
+                let _ = sender.send(Ok(()));
+                connection
+            }
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                return;
+            }
+        };
+
+        let mut event: xlib::XEvent = unsafe { mem::zeroed() };
+        loop {
+            unsafe { (xlib.XNextEvent)(display, &mut event) };
+            if event.get_type() == xlib::KeyPress {
+                callback();
+            }
+        }
+    });
+
+    receiver
+        .recv()
+        .unwrap_or_else(|_| Err(String::from("unable to grab the key")))
+}
+
+fn grab_key(
+    keysym_name: &CStr,
+    modifiers: c_uint,
+) -> Result<(xlib::Xlib, *mut xlib::Display), String> {
+    let xlib = xlib::Xlib::open().map_err(|err| err.to_string())?;
+
+    unsafe {
+        let display = (xlib.XOpenDisplay)(ptr::null());
+        if display.is_null() {
+            return Err(String::from("unable to open the X display"));
+        }
+
+        let root = (xlib.XDefaultRootWindow)(display);
+        let keysym = (xlib.XStringToKeysym)(keysym_name.as_ptr());
+        let keycode = (xlib.XKeysymToKeycode)(display, keysym);
+        if keycode == 0 {
+            (xlib.XCloseDisplay)(display);
+            return Err(format!("no keycode for {keysym_name:?}"));
+        }
+
+        // Grab with Caps Lock and Num Lock in any state, otherwise the
+        // shortcut stops working as soon as one of them is on.
+        for locks in [
+            0,
+            xlib::LockMask,
+            xlib::Mod2Mask,
+            xlib::LockMask | xlib::Mod2Mask,
+        ] {
+            (xlib.XGrabKey)(
+                display,
+                c_int::from(keycode),
+                modifiers | locks,
+                root,
+                xlib::False,
+                xlib::GrabModeAsync,
+                xlib::GrabModeAsync,
+            );
+        }
+        (xlib.XSelectInput)(display, root, xlib::KeyPressMask);
+        (xlib.XSync)(display, xlib::False);
+
+        Ok((xlib, display))
+    }
+}
+
+/// Tell the launcher the startup sequence `id` is over, with a `remove`
+/// message of the startup notification protocol.
+pub fn complete_startup(id: &str) -> Result<(), String> {
+    let xlib = xlib::Xlib::open().map_err(|err| err.to_string())?;
+    let begin = CString::new("_NET_STARTUP_INFO_BEGIN").unwrap();
+    let more = CString::new("_NET_STARTUP_INFO").unwrap();
+
+    unsafe {
+        let display = (xlib.XOpenDisplay)(ptr::null());
+        if display.is_null() {
+            return Err(String::from("unable to open the X display"));
+        }
+
+        let root = (xlib.XDefaultRootWindow)(display);
+        // Messages are sent on behalf of a window of this connection.
+        let window = (xlib.XCreateSimpleWindow)(display, root, 0, 0, 1, 1, 0, 0, 0);
+        let begin = (xlib.XInternAtom)(display, begin.as_ptr(), xlib::False);
+        let more = (xlib.XInternAtom)(display, more.as_ptr(), xlib::False);
+
+        for (index, chunk) in startup_chunks(&remove_message(id)).iter().enumerate() {
+            let mut event: xlib::XEvent = mem::zeroed();
+            event.client_message.type_ = xlib::ClientMessage;
+            event.client_message.display = display;
+            event.client_message.window = window;
+            event.client_message.message_type = if index == 0 { begin } else { more };
+            event.client_message.format = 8;
+            for (byte, value) in chunk.iter().enumerate() {
+                event.client_message.data.set_byte(byte, *value as c_char);
+            }
+            (xlib.XSendEvent)(
+                display,
+                root,
+                xlib::False,
+                xlib::PropertyChangeMask,
+                &mut event,
+            );
+        }
+
+        (xlib.XDestroyWindow)(display, window);
+        (xlib.XSync)(display, xlib::False);
+        (xlib.XCloseDisplay)(display);
+    }
+
+    Ok(())
+}
+
+/// Message ending the startup sequence `id`, NUL terminated.
+fn remove_message(id: &str) -> Vec<u8> {
+    let id = id.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut message = format!("remove: ID=\"{id}\"").into_bytes();
+    message.push(0);
+    message
+}
+
+/// `message` split in the 20 bytes a client message carries, the last one
+/// padded with NUL.
+fn startup_chunks(message: &[u8]) -> Vec<[u8; 20]> {
+    message
+        .chunks(20)
+        .map(|chunk| {
+            let mut data = [0; 20];
+            data[..chunk.len()].copy_from_slice(chunk);
+            data
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_remove_message() {
+        assert_eq!(remove_message("rio-1"), b"remove: ID=\"rio-1\"\0");
+        assert_eq!(remove_message("a \"b\\"), b"remove: ID=\"a \\\"b\\\\\"\0");
+
+        let chunks = startup_chunks(&remove_message("rio-1"));
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(&chunks[0][..19], b"remove: ID=\"rio-1\"\0");
+        assert_eq!(chunks[0][19], 0);
+        assert_eq!(startup_chunks(&[b'a'; 41]).len(), 3);
+    }
+}
@@ -0,0 +1,366 @@
+// Playback of recorded sessions, either asciicast v2 files or raw
+// typescripts (e.g. from script(1)), through the regular parser and renderer.
+//
+// While playing, keyboard input controls the playback instead of being
+// written to a PTY:
+// - Space: pause/resume
+// - Left/Right: seek 5 seconds backward/forward
+// - `-` and `+`: halve/double the speed
+
+use crate::layout::Layout;
+use mio_extras::channel;
+use rio_core::crosswords::Crosswords;
+use rio_core::event::sync::FairMutex;
+use rio_core::event::{EventListener, Msg, RioEvent};
+use rio_core::performer::handler::{Handler, ParserProcessor};
+use std::io;
+use std::path::Path;
+use std::str::Chars;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Seconds moved by each seek.
+const SEEK_STEP: f64 = 5.0;
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 16.0;
+/// Max time between checks for control input.
+const TICK: Duration = Duration::from_millis(16);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameKind {
+    Output(Vec<u8>),
+    Resize(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Seconds since the beginning of the recording.
+    pub time: f64,
+    pub kind: FrameKind,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Recording {
+    pub size: Option<(usize, usize)>,
+    pub frames: Vec<Frame>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> io::Result<Recording> {
+        let content = std::fs::read(path)?;
+        if content.first() != Some(&b'{') {
+            // Raw typescripts carry no timing, the output is shown at once.
+            return Ok(Recording {
+                size: None,
+                frames: vec![Frame {
+                    time: 0.0,
+                    kind: FrameKind::Output(content),
+                }],
+            });
+        }
+
+        let content = String::from_utf8_lossy(&content);
+        Self::parse_asciicast(&content).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "invalid asciicast v2 file")
+        })
+    }
+
+    fn parse_asciicast(content: &str) -> Option<Recording> {
+        let mut lines = content.lines();
+        let header = lines.next()?;
+        if header_number(header, "version")? != 2 {
+            return None;
+        }
+
+        let size = header_number(header, "width").zip(header_number(header, "height"));
+        let mut frames = Vec::new();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let (time, code, data) = parse_event(line)?;
+            let kind = match code.as_str() {
+                "o" => FrameKind::Output(data.into_bytes()),
+                "r" => {
+                    let (columns, rows) = data.split_once('x')?;
+                    FrameKind::Resize(columns.parse().ok()?, rows.parse().ok()?)
+                }
+                // Input and marker events are not rendered.
+                _ => continue,
+            };
+            frames.push(Frame { time, kind });
+        }
+
+        Some(Recording { size, frames })
+    }
+
+    #[inline]
+    fn duration(&self) -> f64 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+}
+
+/// Find a numeric field in the asciicast header.
+fn header_number(header: &str, key: &str) -> Option<usize> {
+    let position = header.find(&format!("\"{key}\""))?;
+    let value = header[position + key.len() + 2..]
+        .trim_start()
+        .strip_prefix(':')?
+        .trim_start();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..digits].parse().ok()
+}
+
+/// Parse an event line, formatted as `[time, "code", "data"]`.
+fn parse_event(line: &str) -> Option<(f64, String, String)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (time, rest) = inner.split_once(',')?;
+    let time = time.trim().parse().ok()?;
+
+    let mut chars = rest.chars();
+    let code = parse_string(&mut chars)?;
+    let data = parse_string(&mut chars)?;
+    Some((time, code, data))
+}
+
+/// Parse the next JSON string, skipping separators before it.
+fn parse_string(chars: &mut Chars) -> Option<String> {
+    loop {
+        match chars.next()? {
+            '"' => break,
+            ',' => (),
+            c if c.is_whitespace() => (),
+            _ => return None,
+        }
+    }
+
+    let mut string = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(string),
+            '\\' => match chars.next()? {
+                'n' => string.push('\n'),
+                'r' => string.push('\r'),
+                't' => string.push('\t'),
+                'b' => string.push('\u{8}'),
+                'f' => string.push('\u{c}'),
+                'u' => {
+                    let high = parse_hex(chars)?;
+                    let code = if (0xd800..0xdc00).contains(&high) {
+                        // Surrogate pair.
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = parse_hex(chars)?;
+                        0x10000 + ((high - 0xd800) << 10) + (low.checked_sub(0xdc00)?)
+                    } else {
+                        high
+                    };
+                    string.push(
+                        char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER),
+                    );
+                }
+                c => string.push(c),
+            },
+            c => string.push(c),
+        }
+    }
+}
+
+fn parse_hex(chars: &mut Chars) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).collect();
+    if hex.len() != 4 {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+/// Feeds a recording into the terminal as if it was read from a PTY.
+pub struct Player<U: EventListener> {
+    sender: channel::Sender<Msg>,
+    receiver: channel::Receiver<Msg>,
+    terminal: Arc<FairMutex<Crosswords<U>>>,
+    event_proxy: U,
+    recording: Recording,
+    parser: ParserProcessor,
+    /// Index of the next frame to be played.
+    next: usize,
+    /// Playback position in seconds.
+    position: f64,
+    speed: f64,
+    paused: bool,
+}
+
+impl<U> Player<U>
+where
+    U: EventListener + Clone + Send + 'static,
+{
+    pub fn new(
+        terminal: Arc<FairMutex<Crosswords<U>>>,
+        recording: Recording,
+        event_proxy: U,
+    ) -> Player<U> {
+        let (sender, receiver) = channel::channel();
+
+        Player {
+            sender,
+            receiver,
+            terminal,
+            event_proxy,
+            recording,
+            parser: ParserProcessor::new(),
+            next: 0,
+            position: 0.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    pub fn channel(&self) -> channel::Sender<Msg> {
+        self.sender.clone()
+    }
+
+    /// Handle keyboard input sent to the terminal as playback controls.
+    fn control(&mut self, input: &[u8]) {
+        match input {
+            b" " => self.paused = !self.paused,
+            b"\x1b[C" | b"\x1bOC" => self.seek(self.position + SEEK_STEP),
+            b"\x1b[D" | b"\x1bOD" => self.seek(self.position - SEEK_STEP),
+            b"+" | b"=" => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+            b"-" => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            _ => (),
+        }
+    }
+
+    fn seek(&mut self, position: f64) {
+        let position = position.clamp(0.0, self.recording.duration());
+
+        // Going back replays the recording from the start on a clean terminal,
+        // reset in place so it keeps the settings of the configuration.
+        if position < self.position {
+            let mut terminal = self.terminal.lock();
+            terminal.reset_state();
+            if let Some((columns, rows)) = self.recording.size {
+                terminal.resize::<Layout>(columns, rows);
+            }
+            self.parser = ParserProcessor::new();
+            self.next = 0;
+        }
+
+        self.position = position;
+        self.play_until(position);
+    }
+
+    /// Feed every frame up to `position` into the terminal.
+    fn play_until(&mut self, position: f64) {
+        let start = self.next;
+        let mut terminal = self.terminal.lock();
+        while let Some(frame) = self.recording.frames.get(self.next) {
+            if frame.time > position {
+                break;
+            }
+
+            match &frame.kind {
+                FrameKind::Output(bytes) => {
+                    self.parser.advance_bytes(&mut *terminal, bytes);
+                }
+                FrameKind::Resize(columns, rows) => {
+                    terminal.resize::<Layout>(*columns, *rows);
+                }
+            }
+            self.next += 1;
+        }
+        drop(terminal);
+
+        if self.next > start {
+            self.event_proxy.send_event(RioEvent::Wakeup);
+        }
+    }
+
+    pub fn spawn(mut self) {
+        if let Some((columns, rows)) = self.recording.size {
+            self.terminal.lock().resize::<Layout>(columns, rows);
+        }
+
+        // Sleeping between frames, so it runs on a thread of its own.
+        std::thread::spawn(move || {
+            let mut last_tick = Instant::now();
+
+            loop {
+                while let Ok(msg) = self.receiver.try_recv() {
+                    match msg {
+                        Msg::Input(input) => self.control(&input),
+                        Msg::Shutdown => return,
+                        _ => (),
+                    }
+                }
+
+                let elapsed = last_tick.elapsed().as_secs_f64();
+                last_tick = Instant::now();
+                if !self.paused {
+                    self.position = (self.position + elapsed * self.speed)
+                        .min(self.recording.duration());
+                    let position = self.position;
+                    self.play_until(position);
+                }
+
+                // Sleep until the next frame is due, checking for input regularly.
+                let until_next = self
+                    .recording
+                    .frames
+                    .get(self.next)
+                    .map(|frame| (frame.time - self.position).max(0.0) / self.speed)
+                    .filter(|_| !self.paused)
+                    .map_or(TICK, |seconds| TICK.min(Duration::from_secs_f64(seconds)));
+                std::thread::sleep(until_next);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_asciicast() {
+        let content =
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"timestamp\": 1}\n\
+            [0.5, \"o\", \"ls\\r\\n\\u001b[31m\"]\n\
+            [1.25, \"i\", \"q\"]\n\
+            [2.0, \"r\", \"100x30\"]\n";
+
+        let recording = Recording::parse_asciicast(content).unwrap();
+        assert_eq!(recording.size, Some((80, 24)));
+        assert_eq!(
+            recording.frames,
+            vec![
+                Frame {
+                    time: 0.5,
+                    kind: FrameKind::Output(b"ls\r\n\x1b[31m".to_vec()),
+                },
+                Frame {
+                    time: 2.0,
+                    kind: FrameKind::Resize(100, 30),
+                },
+            ]
+        );
+        assert_eq!(recording.duration(), 2.0);
+    }
+
+    #[test]
+    fn test_parse_asciicast_rejects_other_versions() {
+        assert_eq!(
+            Recording::parse_asciicast("{\"version\": 1, \"width\": 80}"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_string() {
+        let mut chars = r#" "a\"b\\c", "\ud83d\ude00", "é""#.chars();
+        assert_eq!(parse_string(&mut chars), Some(String::from("a\"b\\c")));
+        assert_eq!(parse_string(&mut chars), Some(String::from("😀")));
+        assert_eq!(parse_string(&mut chars), Some(String::from("é")));
+        assert_eq!(parse_string(&mut chars), None);
+    }
+}
@@ -23,6 +23,8 @@ pub enum Topic {
     #[allow(dead_code)]
     SelectionScrolling,
     Frame,
+    DropdownAnimation,
+    Title,
 }
 
 /// Event scheduled to be emitted at a specific time.
@@ -101,12 +103,19 @@ impl Scheduler {
     }
 
     /// Cancel a scheduled event.
-    #[allow(dead_code)]
     pub fn unschedule(&mut self, id: TimerId) -> Option<Timer> {
         let index = self.timers.iter().position(|timer| timer.id == id)?;
         self.timers.remove(index)
     }
 
+    /// Deadline of a scheduled timer.
+    pub fn deadline(&self, id: TimerId) -> Option<Instant> {
+        self.timers
+            .iter()
+            .find(|timer| timer.id == id)
+            .map(|timer| timer.deadline)
+    }
+
     /// Check if a timer is already scheduled.
     pub fn scheduled(&mut self, id: TimerId) -> bool {
         self.timers.iter().any(|timer| timer.id == id)
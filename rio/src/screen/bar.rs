@@ -0,0 +1,315 @@
+use crate::notification::format_duration;
+use config::BarVisibility;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Shown on the left when the application didn't set a title.
+const DEFAULT_TITLE: &str = "Rio";
+
+/// Drawn between right segments.
+const SEPARATOR: &str = "   ";
+
+/// Where the first tab starts, leaving room for the window buttons on macOS,
+/// in logical pixels.
+pub const TABS_X: f32 = 80.0;
+
+/// Width of each tab, close button included, in logical pixels.
+pub const TAB_WIDTH: f32 = 120.0;
+
+/// Width of the close button at the right end of a tab, in logical pixels.
+pub const CLOSE_WIDTH: f32 = 20.0;
+
+/// Width of the new tab button after the last tab, in logical pixels.
+pub const NEW_TAB_WIDTH: f32 = 24.0;
+
+/// Whether the terminal makes room for the bar with `tabs` tabs open,
+/// instead of it being hidden or drawn over the terminal.
+pub fn is_reserved(config: &config::Bar, tabs: usize) -> bool {
+    config.enabled
+        && match config.visibility {
+            BarVisibility::Always => true,
+            BarVisibility::MultipleTabs => tabs > 1,
+            BarVisibility::AutoHide => false,
+        }
+}
+
+/// Whether the bar is drawn with `tabs` tabs open.
+pub fn is_visible(config: &config::Bar, tabs: usize, is_hovered: bool) -> bool {
+    match config.visibility {
+        BarVisibility::AutoHide => config.enabled && is_hovered,
+        _ => is_reserved(config, tabs),
+    }
+}
+
+/// Part of the bar under the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hit {
+    Tab(usize),
+    CloseTab(usize),
+    NewTab,
+    /// Anywhere else, which drags the window when it has no decorations.
+    Empty,
+}
+
+/// What is at `x` logical pixels on a bar showing `tabs` tabs. Tabs can only
+/// be closed when there are more than one.
+pub fn hit(x: f32, tabs: usize) -> Hit {
+    let x = x - TABS_X;
+    if x < 0.0 {
+        return Hit::Empty;
+    }
+
+    let index = (x / TAB_WIDTH) as usize;
+    if index < tabs {
+        let is_close = x - index as f32 * TAB_WIDTH >= TAB_WIDTH - CLOSE_WIDTH;
+        if is_close && tabs > 1 {
+            Hit::CloseTab(index)
+        } else {
+            Hit::Tab(index)
+        }
+    } else if x < tabs as f32 * TAB_WIDTH + NEW_TAB_WIDTH {
+        Hit::NewTab
+    } else {
+        Hit::Empty
+    }
+}
+
+/// Left edge of the tab at `index`, in logical pixels.
+#[inline]
+pub fn tab_x(index: usize) -> f32 {
+    TABS_X + index as f32 * TAB_WIDTH
+}
+
+/// Label of the tab at `index`, the current one also shows the title set by
+/// the application.
+pub fn tab_label(
+    config: &config::Bar,
+    index: usize,
+    is_current: bool,
+    title: Option<&str>,
+) -> String {
+    if !is_current || !config.title {
+        return format!("{}", index + 1);
+    }
+
+    match title {
+        Some(title) if !title.is_empty() => format!("{} {title}", index + 1),
+        _ => format!("{} {DEFAULT_TITLE}", index + 1),
+    }
+}
+
+/// Right segments, from left to right: secure keyboard entry, working
+/// directory, last command, scroll state and clock.
+pub fn status(
+    config: &config::Bar,
+    secure_input: bool,
+    cwd: Option<&Path>,
+    home: Option<&Path>,
+    last_command: Option<(Option<i32>, Duration)>,
+    display_offset: usize,
+    time: (u32, u32),
+) -> String {
+    let mut segments = vec![];
+
+    // Always shown, so it is not left on by accident.
+    if secure_input {
+        segments.push(String::from("SECURE INPUT"));
+    }
+
+    if config.cwd {
+        if let Some(cwd) = cwd {
+            segments.push(shorten_home(cwd, home));
+        }
+    }
+
+    if config.command {
+        if let Some((status, duration)) = last_command {
+            let duration = format_duration(duration);
+            segments.push(match status {
+                Some(status) if status != 0 => format!("✗ {status} {duration}"),
+                _ => format!("✓ {duration}"),
+            });
+        }
+    }
+
+    if config.scrollback && display_offset > 0 {
+        let lines = if display_offset == 1 { "line" } else { "lines" };
+        segments.push(format!("SCROLLBACK {display_offset} {lines}"));
+    }
+
+    if config.clock {
+        segments.push(format!("{:02}:{:02}", time.0, time.1));
+    }
+
+    segments.join(SEPARATOR)
+}
+
+/// Replace the home directory at the start of `path` by `~`.
+fn shorten_home(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(relative) if relative.as_os_str().is_empty() => String::from("~"),
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// Hours and minutes of the local time.
+#[cfg(unix)]
+pub fn local_time() -> (u32, u32) {
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        (tm.tm_hour as u32, tm.tm_min as u32)
+    }
+}
+
+/// Hours and minutes of the time in UTC.
+#[cfg(not(unix))]
+pub fn local_time() -> (u32, u32) {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    (((seconds / 3600) % 24) as u32, ((seconds / 60) % 60) as u32)
+}
+
+/// Milliseconds until the clock shows the next minute.
+pub fn millis_until_next_minute() -> u64 {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default();
+    60_000 - millis % 60_000
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn config() -> config::Bar {
+        config::Bar {
+            enabled: true,
+            title: true,
+            cwd: true,
+            clock: true,
+            command: true,
+            scrollback: true,
+            ..config::Bar::default()
+        }
+    }
+
+    #[test]
+    fn test_visibility() {
+        assert!(is_reserved(&config(), 1));
+        assert!(is_visible(&config(), 1, false));
+
+        let config = config::Bar {
+            visibility: BarVisibility::MultipleTabs,
+            ..config()
+        };
+        assert!(!is_reserved(&config, 1));
+        assert!(is_reserved(&config, 2));
+        assert!(!is_visible(&config, 1, true));
+
+        let config = config::Bar {
+            visibility: BarVisibility::AutoHide,
+            ..config
+        };
+        assert!(!is_reserved(&config, 2));
+        assert!(!is_visible(&config, 2, false));
+        assert!(is_visible(&config, 2, true));
+
+        let config = config::Bar {
+            enabled: false,
+            ..config
+        };
+        assert!(!is_visible(&config, 2, true));
+    }
+
+    #[test]
+    fn test_tab_label() {
+        assert_eq!(tab_label(&config(), 0, true, Some("vim")), "1 vim");
+        assert_eq!(tab_label(&config(), 1, true, None), "2 Rio");
+        assert_eq!(tab_label(&config(), 2, false, Some("vim")), "3");
+
+        let config = config::Bar {
+            title: false,
+            ..config()
+        };
+        assert_eq!(tab_label(&config, 0, true, Some("vim")), "1");
+    }
+
+    #[test]
+    fn test_hit() {
+        assert_eq!(hit(10.0, 2), Hit::Empty);
+        assert_eq!(hit(TABS_X, 2), Hit::Tab(0));
+        assert_eq!(hit(tab_x(1) - 1.0, 2), Hit::CloseTab(0));
+        assert_eq!(hit(tab_x(1) + 10.0, 2), Hit::Tab(1));
+        assert_eq!(hit(tab_x(2) + 1.0, 2), Hit::NewTab);
+        assert_eq!(hit(tab_x(2) + NEW_TAB_WIDTH, 2), Hit::Empty);
+
+        // The last tab can't be closed.
+        assert_eq!(hit(tab_x(1) - 1.0, 1), Hit::Tab(0));
+    }
+
+    #[test]
+    fn test_status() {
+        let home = Path::new("/home/rio");
+        let cwd = Path::new("/home/rio/code");
+        assert_eq!(
+            status(&config(), false, Some(cwd), Some(home), None, 12, (9, 5)),
+            "~/code   SCROLLBACK 12 lines   09:05"
+        );
+        assert_eq!(
+            status(&config(), false, Some(home), Some(home), None, 0, (23, 59)),
+            "~   23:59"
+        );
+        assert_eq!(
+            status(
+                &config(),
+                false,
+                Some(home),
+                Some(home),
+                Some((Some(0), Duration::from_secs(72))),
+                0,
+                (23, 59)
+            ),
+            "~   ✓ 1m 12s   23:59"
+        );
+        assert_eq!(
+            status(
+                &config(),
+                false,
+                None,
+                None,
+                Some((Some(127), Duration::from_millis(20))),
+                0,
+                (23, 59)
+            ),
+            "✗ 127 0s   23:59"
+        );
+
+        let config = config::Bar {
+            clock: false,
+            ..config()
+        };
+        assert_eq!(
+            status(
+                &config,
+                false,
+                Some(Path::new("/tmp")),
+                Some(home),
+                None,
+                1,
+                (0, 0)
+            ),
+            "/tmp   SCROLLBACK 1 line"
+        );
+        assert_eq!(status(&config, false, None, None, None, 0, (0, 0)), "");
+        assert_eq!(
+            status(&config, true, None, None, None, 0, (0, 0)),
+            "SECURE INPUT"
+        );
+    }
+}
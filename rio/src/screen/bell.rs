@@ -0,0 +1,126 @@
+// Audible bell.
+//
+// Sounds are played with `afplay` on macOS and `paplay` (PulseAudio or
+// PipeWire) elsewhere, from a separate thread so the event loop doesn't wait
+// for the sound to end. Bells ringing again during the cooldown are not
+// heard, so programs ringing in a loop don't flood the audio output.
+
+use crate::platform;
+use std::path::PathBuf;
+#[cfg(not(windows))]
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "macos")]
+const DEFAULT_SOUND: &str = "/System/Library/Sounds/Tink.aiff";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_SOUND: &str = "/usr/share/sounds/freedesktop/stereo/bell.oga";
+
+pub struct Bell {
+    sound: PathBuf,
+    volume: f32,
+    cooldown: Duration,
+    is_muted: bool,
+    last_ring: Option<Instant>,
+}
+
+impl Bell {
+    pub fn new(config: &config::Bell) -> Bell {
+        let sound = if config.sound.is_empty() {
+            PathBuf::from(DEFAULT_SOUND)
+        } else {
+            super::expand_home(&config.sound)
+        };
+
+        Bell {
+            sound,
+            volume: config.volume.clamp(0.0, 1.0),
+            cooldown: Duration::from_millis(config.cooldown),
+            is_muted: !config.audible,
+            last_ring: None,
+        }
+    }
+
+    #[inline]
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.is_muted = !self.is_muted;
+    }
+
+    pub fn ring(&mut self) {
+        if self.should_play(Instant::now()) {
+            play(self.sound.clone(), self.volume);
+        }
+    }
+
+    /// Whether a bell ringing at `now` is heard.
+    fn should_play(&mut self, now: Instant) -> bool {
+        if self.is_muted {
+            return false;
+        }
+        if let Some(last_ring) = self.last_ring {
+            if now.saturating_duration_since(last_ring) < self.cooldown {
+                return false;
+            }
+        }
+
+        self.last_ring = Some(now);
+        true
+    }
+}
+
+fn play(sound: PathBuf, volume: f32) {
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut command = Command::new("afplay");
+        command.arg("-v").arg(volume.to_string()).arg(&sound);
+        Some(command)
+    };
+
+    #[cfg(windows)]
+    let command = None;
+
+    // `paplay` volumes go from 0 to 65536.
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let command = {
+        let mut command = Command::new("paplay");
+        command
+            .arg(format!("--volume={}", (volume * 65536.0) as u32))
+            .arg(&sound);
+        Some(command)
+    };
+
+    platform::run_in_background(command, format!("play bell {}", sound.display()));
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_should_play() {
+        let mut bell = Bell::new(&config::Bell {
+            audible: true,
+            ..config::Bell::default()
+        });
+        let now = Instant::now();
+        assert!(bell.should_play(now));
+        assert!(!bell.should_play(now + Duration::from_millis(100)));
+        assert!(bell.should_play(now + Duration::from_millis(600)));
+
+        bell.toggle_mute();
+        assert!(bell.is_muted());
+        assert!(!bell.should_play(now + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_muted_unless_audible() {
+        let mut bell = Bell::new(&config::Bell::default());
+        assert!(!bell.should_play(Instant::now()));
+        bell.toggle_mute();
+        assert!(bell.should_play(Instant::now()));
+    }
+}
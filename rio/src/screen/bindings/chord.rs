@@ -0,0 +1,340 @@
+// Bindings of several keys pressed one after the other, e.g. `Control+A`
+// then `V` like the prefix key of tmux. While a chord is being typed its
+// keys are shown on the screen, and it is abandoned after `TIMEOUT`.
+
+use super::{Action, BindingMode, Key, KeyBinding};
+use std::time::{Duration, Instant};
+use winit::event::ModifiersState;
+use winit::event::VirtualKeyCode::{self, *};
+
+/// Time to press the next key of a chord.
+pub const TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Key pressed with modifiers.
+pub type Stroke = (VirtualKeyCode, ModifiersState);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChordBinding {
+    /// Keys to press in order, the last one being the trigger of `binding`.
+    pub strokes: Vec<Stroke>,
+    pub binding: KeyBinding,
+}
+
+impl ChordBinding {
+    fn is_active(&self, mode: &BindingMode) -> bool {
+        mode.contains(self.binding.mode.clone())
+            && !mode.intersects(self.binding.notmode.clone())
+    }
+}
+
+/// What a key press did to the chords.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChordInput {
+    /// Not part of a chord, handled like any other key.
+    Unbound,
+    /// Started, continued or cancelled a chord, nothing else happens.
+    Swallowed,
+    /// Completed a chord.
+    Run(Action),
+}
+
+#[derive(Debug, Default)]
+pub struct Chords {
+    bindings: Vec<ChordBinding>,
+    pending: Vec<Stroke>,
+    started: Option<Instant>,
+    /// Last key taken by a chord, whose repeats are swallowed too.
+    last_key: Option<VirtualKeyCode>,
+}
+
+impl Chords {
+    pub fn new(bindings: Vec<ChordBinding>) -> Self {
+        Self {
+            bindings,
+            ..Self::default()
+        }
+    }
+
+    /// Handle `key` pressed with `mods` at `now`.
+    pub fn input(
+        &mut self,
+        key: VirtualKeyCode,
+        mods: ModifiersState,
+        mode: BindingMode,
+        repeat: bool,
+        now: Instant,
+    ) -> ChordInput {
+        if is_modifier(key) {
+            return ChordInput::Unbound;
+        }
+        if repeat && self.last_key == Some(key) {
+            return ChordInput::Swallowed;
+        }
+
+        self.last_key = None;
+        if !self.is_pending(now) {
+            self.pending.clear();
+        }
+
+        let mut strokes = std::mem::take(&mut self.pending);
+        let was_pending = !strokes.is_empty();
+        strokes.push((key, mods));
+
+        let completed = self
+            .bindings
+            .iter()
+            .find(|chord| chord.strokes == strokes && chord.is_active(&mode));
+        if let Some(chord) = completed {
+            self.last_key = Some(key);
+            return ChordInput::Run(chord.binding.action.clone());
+        }
+
+        let continued = self.bindings.iter().any(|chord| {
+            chord.strokes.len() > strokes.len()
+                && chord.strokes.starts_with(&strokes)
+                && chord.is_active(&mode)
+        });
+        if continued {
+            self.pending = strokes;
+            self.started = Some(now);
+            self.last_key = Some(key);
+            return ChordInput::Swallowed;
+        }
+
+        // Any other key, e.g. Escape, cancels the chord.
+        if was_pending {
+            self.last_key = Some(key);
+            return ChordInput::Swallowed;
+        }
+
+        ChordInput::Unbound
+    }
+
+    fn is_pending(&self, now: Instant) -> bool {
+        !self.pending.is_empty()
+            && self.started.map_or(false, |started| {
+                now.saturating_duration_since(started) < TIMEOUT
+            })
+    }
+
+    /// Keys of the chord being typed at `now`, e.g. "Control+A …".
+    pub fn text(&self, now: Instant) -> Option<String> {
+        if !self.is_pending(now) {
+            return None;
+        }
+
+        Some(format!("{} …", strokes_name(&self.pending)))
+    }
+
+    /// Milliseconds until the chord being typed times out.
+    pub fn next_frame_in(&self, now: Instant) -> Option<u64> {
+        if !self.is_pending(now) {
+            return None;
+        }
+
+        let elapsed = now.saturating_duration_since(self.started?);
+        TIMEOUT
+            .checked_sub(elapsed)
+            .map(|wait| wait.as_millis() as u64 + 1)
+    }
+}
+
+fn is_modifier(key: VirtualKeyCode) -> bool {
+    matches!(
+        key,
+        LShift | RShift | LControl | RControl | LAlt | RAlt | LWin | RWin
+    )
+}
+
+/// Named like in `rio msg send-key`, e.g. `Control+A`.
+pub fn stroke_name((key, mods): Stroke) -> String {
+    let mut name = String::new();
+    for (modifier, prefix) in [
+        (ModifiersState::CTRL, "Control+"),
+        (ModifiersState::SHIFT, "Shift+"),
+        (ModifiersState::ALT, "Alt+"),
+        (ModifiersState::LOGO, "Super+"),
+    ] {
+        if mods.contains(modifier) {
+            name.push_str(prefix);
+        }
+    }
+
+    let key = match key {
+        Return => String::from("Enter"),
+        Back => String::from("Backspace"),
+        key => {
+            let key = format!("{key:?}");
+            match key.strip_prefix("Key") {
+                Some(digit) => digit.to_string(),
+                None => key,
+            }
+        }
+    };
+    name.push_str(&key);
+    name
+}
+
+fn strokes_name(strokes: &[Stroke]) -> String {
+    let names = strokes.iter().map(|stroke| stroke_name(*stroke));
+    names.collect::<Vec<_>>().join(" ")
+}
+
+/// Chords which can't run as configured: the same keys bound twice, chords
+/// starting with the keys of a shorter one, and chords starting with a key
+/// which has a binding of its own, which is then never triggered.
+pub fn conflicts(chords: &[ChordBinding], bindings: &[KeyBinding]) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    for (index, chord) in chords.iter().enumerate() {
+        let name = strokes_name(&chord.strokes);
+        for other in &chords[..index] {
+            let other_name = strokes_name(&other.strokes);
+            if other.strokes == chord.strokes {
+                if other.binding.triggers_match(&chord.binding) {
+                    conflicts.push(format!("`{name}` is bound twice"));
+                }
+            } else if chord.strokes.starts_with(&other.strokes) {
+                conflicts.push(format!("`{name}` can't be reached after `{other_name}`"));
+            } else if other.strokes.starts_with(&chord.strokes) {
+                conflicts.push(format!("`{other_name}` can't be reached after `{name}`"));
+            }
+        }
+
+        let (key, mods) = chord.strokes[0];
+        let first = KeyBinding {
+            trigger: Key::Keycode(key),
+            mods,
+            ..chord.binding.clone()
+        };
+        if bindings
+            .iter()
+            .any(|binding| binding.triggers_match(&first))
+        {
+            conflicts.push(format!(
+                "`{name}` hides the binding of `{}`",
+                stroke_name((key, mods))
+            ));
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn chord(strokes: &[Stroke], action: Action) -> ChordBinding {
+        let (key, mods) = *strokes.last().unwrap();
+        ChordBinding {
+            strokes: strokes.to_vec(),
+            binding: KeyBinding {
+                trigger: Key::Keycode(key),
+                mods,
+                repeat: action.repeats(),
+                action,
+                mode: BindingMode::empty(),
+                notmode: BindingMode::VI,
+            },
+        }
+    }
+
+    #[test]
+    fn test_input() {
+        let ctrl = ModifiersState::CTRL;
+        let none = ModifiersState::empty();
+        let mut chords = Chords::new(vec![
+            chord(&[(A, ctrl), (V, none)], Action::TabCreateNew),
+            chord(&[(A, ctrl), (Key1, none), (N, none)], Action::TabSwitchNext),
+        ]);
+        let mode = BindingMode::empty;
+        let now = Instant::now();
+
+        assert_eq!(
+            chords.input(V, none, mode(), false, now),
+            ChordInput::Unbound
+        );
+        assert_eq!(chords.text(now), None);
+
+        assert_eq!(
+            chords.input(A, ctrl, mode(), false, now),
+            ChordInput::Swallowed
+        );
+        assert_eq!(chords.text(now), Some(String::from("Control+A …")));
+        assert_eq!(chords.next_frame_in(now), Some(1501));
+        assert_eq!(
+            chords.input(LControl, ctrl, mode(), false, now),
+            ChordInput::Unbound
+        );
+        assert_eq!(
+            chords.input(A, ctrl, mode(), true, now),
+            ChordInput::Swallowed
+        );
+        assert_eq!(
+            chords.input(V, none, mode(), false, now),
+            ChordInput::Run(Action::TabCreateNew)
+        );
+        assert_eq!(chords.text(now), None);
+        // Holding the last key doesn't type it.
+        assert_eq!(
+            chords.input(V, none, mode(), true, now),
+            ChordInput::Swallowed
+        );
+
+        chords.input(A, ctrl, mode(), false, now);
+        chords.input(Key1, none, mode(), false, now);
+        assert_eq!(chords.text(now), Some(String::from("Control+A 1 …")));
+        assert_eq!(
+            chords.input(N, none, mode(), false, now),
+            ChordInput::Run(Action::TabSwitchNext)
+        );
+
+        // Cancelled by another key, or after the timeout.
+        chords.input(A, ctrl, mode(), false, now);
+        assert_eq!(
+            chords.input(Escape, none, mode(), false, now),
+            ChordInput::Swallowed
+        );
+        assert_eq!(
+            chords.input(V, none, mode(), false, now),
+            ChordInput::Unbound
+        );
+
+        chords.input(A, ctrl, mode(), false, now);
+        let later = now + TIMEOUT;
+        assert_eq!(chords.text(later), None);
+        assert_eq!(
+            chords.input(V, none, mode(), false, later),
+            ChordInput::Unbound
+        );
+
+        // Not in the excluded modes.
+        assert_eq!(
+            chords.input(A, ctrl, BindingMode::VI, false, now),
+            ChordInput::Unbound
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let ctrl = ModifiersState::CTRL;
+        let none = ModifiersState::empty();
+        let chords = [
+            chord(&[(A, ctrl), (V, none)], Action::TabCreateNew),
+            chord(&[(A, ctrl), (V, none)], Action::Paste),
+            chord(&[(A, ctrl), (V, none), (X, none)], Action::Copy),
+            chord(&[(L, ctrl), (L, none)], Action::ClearHistory),
+        ];
+        let bindings = [chord(&[(L, ctrl)], Action::ClearLogNotice).binding];
+
+        assert_eq!(
+            conflicts(&chords, &bindings),
+            [
+                "`Control+A V` is bound twice",
+                "`Control+A V X` can't be reached after `Control+A V`",
+                "`Control+A V X` can't be reached after `Control+A V`",
+                "`Control+L L` hides the binding of `Control+L`",
+            ]
+        );
+        assert!(conflicts(&chords[..1], &bindings).is_empty());
+    }
+}
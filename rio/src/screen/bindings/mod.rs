@@ -1,13 +1,20 @@
 // Cria os bindings e usa struct actions
 // https://github.com/alacritty/alacritty/blob/828fdab7470c8d16d2edbe2cec919169524cb2bb/alacritty/src/config/bindings.rs#L43
 
-use crate::crosswords::Mode;
+mod chord;
+
+use crate::screen::hints::HintKind;
 use bitflags::bitflags;
+use rio_core::crosswords::Mode;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use winit::event::ModifiersState;
+use winit::event::MouseButton;
 use winit::event::VirtualKeyCode;
 use winit::event::VirtualKeyCode::*;
 
+pub use chord::{ChordBinding, ChordInput, Chords};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Binding<T> {
     /// Modifier keys required to activate binding.
@@ -26,6 +33,9 @@ pub struct Binding<T> {
     ///
     /// For example, this might be a key like "G", or a mouse button.
     pub trigger: T,
+
+    /// Whether the binding runs again while its key is held down.
+    pub repeat: bool,
 }
 
 impl<T: Eq> Binding<T> {
@@ -46,7 +56,6 @@ impl<T: Eq> Binding<T> {
     }
 
     #[inline]
-    #[allow(unused)]
     pub fn triggers_match(&self, binding: &Binding<T>) -> bool {
         // Check the binding's key and modifiers.
         if self.trigger != binding.trigger || self.mods != binding.mods {
@@ -90,6 +99,9 @@ pub enum Key {
 pub type KeyBindings = Vec<KeyBinding>;
 pub type KeyBinding = Binding<Key>;
 
+pub type MouseBindings = Vec<MouseBinding>;
+pub type MouseBinding = Binding<MouseButton>;
+
 bitflags! {
     /// Modes available for key bindings.
     #[derive(Clone, Debug, Eq, PartialEq)]
@@ -99,6 +111,7 @@ bitflags! {
         const ALT_SCREEN          = 0b0000_0100;
         const VI                  = 0b0000_1000;
         const SEARCH              = 0b0001_0000;
+        const MOUSE_REPORT        = 0b0010_0000;
     }
 }
 
@@ -139,13 +152,18 @@ pub enum Action {
     /// Store current selection into clipboard.
     Copy,
 
+    /// Store current selection into clipboard with colors and styles preserved.
+    CopyRichText,
+
+    /// Store the output of the last command into clipboard.
+    CopyLastOutput,
+
     #[cfg(not(any(target_os = "macos", windows)))]
     #[allow(dead_code)]
     /// Store current selection into selection buffer.
     CopySelection,
 
     /// Paste contents of selection buffer.
-    #[allow(dead_code)]
     PasteSelection,
 
     /// Increase font size.
@@ -184,10 +202,42 @@ pub enum Action {
     /// Scroll all the way to the bottom.
     ScrollToBottom,
 
+    /// Scroll the previous prompt to the top.
+    ScrollToPreviousPrompt,
+
+    /// Scroll the next prompt to the top.
+    ScrollToNextPrompt,
+
+    /// Start or stop recording the session into an asciicast file.
+    ToggleRecording,
+
+    /// Mute or unmute the audible bell.
+    ToggleBellMute,
+
+    /// Keep other applications from reading keystrokes (macOS).
+    ToggleSecureKeyboardEntry,
+
+    /// Save the visible screen into a temporary file.
+    SaveScreen,
+
+    /// Save the whole scrollback into a temporary file.
+    SaveScrollback,
+
+    /// Save the current frame as a PNG file.
+    Screenshot,
+
+    /// Open the whole scrollback with `$PAGER`.
+    OpenScrollbackInPager,
+
+    /// Open the whole scrollback with `$EDITOR`.
+    OpenScrollbackInEditor,
+
     /// Clear the display buffer(s) to remove history.
-    #[allow(dead_code)]
     ClearHistory,
 
+    /// Clear the history and the screen above the cursor.
+    ClearScreenAndHistory,
+
     /// Hide the Rio window.
     #[allow(dead_code)]
     Hide,
@@ -224,6 +274,9 @@ pub enum Action {
     #[allow(dead_code)]
     TabSwitchNext,
 
+    /// Switch to previous tab.
+    TabSwitchPrev,
+
     /// Toggle fullscreen.
     #[allow(dead_code)]
     ToggleFullscreen,
@@ -232,6 +285,35 @@ pub enum Action {
     #[allow(dead_code)]
     ToggleMaximized,
 
+    /// Open or close the command palette.
+    #[allow(dead_code)]
+    ToggleCommandPalette,
+
+    /// Open a new window with a profile from the configuration.
+    OpenProfile(String),
+
+    /// Label targets on the screen, copying the one whose label is typed.
+    Hints(HintKind),
+
+    /// Open the link under the mouse, falling back to the default click
+    /// when there is none.
+    OpenLink,
+
+    /// Select the whole scrollback and screen.
+    SelectAll,
+
+    /// Expand the selection, or start one at the cursor, to whole words.
+    ExpandSelectionToWord,
+
+    /// Expand the selection, or start one at the cursor, to whole lines.
+    ExpandSelectionToLine,
+
+    /// Select the output of the command the selection or cursor is in.
+    ExpandSelectionToOutput,
+
+    /// Open the context menu where the mouse is.
+    ContextMenu,
+
     /// Toggle simple fullscreen on macOS.
     #[cfg(target_os = "macos")]
     #[allow(dead_code)]
@@ -252,6 +334,29 @@ pub enum Action {
     None,
 }
 
+impl Action {
+    /// Whether the action runs again while its key is held down: text for
+    /// the terminal and scrolling do, other actions (e.g. toggling
+    /// fullscreen or opening a tab) only run once per key press.
+    pub fn repeats(&self) -> bool {
+        matches!(
+            self,
+            Action::Esc(_)
+                | Action::ReceiveChar
+                | Action::IncreaseFontSize
+                | Action::DecreaseFontSize
+                | Action::ScrollPageUp
+                | Action::ScrollPageDown
+                | Action::ScrollHalfPageUp
+                | Action::ScrollHalfPageDown
+                | Action::ScrollLineUp
+                | Action::ScrollLineDown
+                | Action::ScrollToPreviousPrompt
+                | Action::ScrollToNextPrompt
+        )
+    }
+}
+
 impl From<&'static str> for Action {
     fn from(s: &'static str) -> Action {
         Action::Esc(s.into())
@@ -302,12 +407,14 @@ macro_rules! bindings {
             let mut _notmode = BindingMode::empty();
             $(_notmode.insert($notmode);)*
 
+            let action: Action = $action.into();
             v.push($ty {
                 trigger: $key,
                 mods: _mods,
                 mode: _mode,
                 notmode: _notmode,
-                action: $action.into(),
+                repeat: action.repeats(),
+                action,
             });
         )*
 
@@ -381,7 +488,6 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         F8,          ~BindingMode::VI; Action::Esc("\x1b[19~".into());
         F9,          ~BindingMode::VI; Action::Esc("\x1b[20~".into());
         F10,         ~BindingMode::VI; Action::Esc("\x1b[21~".into());
-        F11,         ~BindingMode::VI; Action::Esc("\x1b[23~".into());
         F12,         ~BindingMode::VI; Action::Esc("\x1b[24~".into());
         F13,         ~BindingMode::VI; Action::Esc("\x1b[25~".into());
         F14,         ~BindingMode::VI; Action::Esc("\x1b[26~".into());
@@ -483,6 +589,7 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         //     ViMotion::Bracket;
         T, ModifiersState::LOGO; Action::TabCreateNew;
         Tab, ModifiersState::CTRL; Action::TabSwitchNext;
+        Tab, ModifiersState::CTRL | ModifiersState::SHIFT; Action::TabSwitchPrev;
     );
 
     bindings.extend(platform_key_bindings());
@@ -506,17 +613,41 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
             Action::Esc("\x1bb".into());
         Right, ModifiersState::ALT,  ~BindingMode::VI;
             Action::Esc("\x1bf".into());
-        K, ModifiersState::LOGO, ~BindingMode::VI;
-            Action::Esc("\x0c".into());
-        K, ModifiersState::LOGO, ~BindingMode::VI;  Action::ClearHistory;
+        K, ModifiersState::LOGO, ~BindingMode::VI; Action::ClearScreenAndHistory;
+        K, ModifiersState::LOGO | ModifiersState::SHIFT, ~BindingMode::VI;
+            Action::ClearHistory;
         V, ModifiersState::LOGO, ~BindingMode::VI; Action::Paste;
         N, ModifiersState::LOGO; Action::WindowCreateNew;
         F, ModifiersState::CTRL | ModifiersState::LOGO; Action::ToggleFullscreen;
+        Return, ModifiersState::LOGO; Action::ToggleFullscreen;
+        F11, ~BindingMode::VI; Action::Esc("\x1b[23~".into());
         C, ModifiersState::LOGO; Action::Copy;
+        C, ModifiersState::LOGO | ModifiersState::ALT; Action::CopyRichText;
+        O, ModifiersState::LOGO | ModifiersState::SHIFT; Action::CopyLastOutput;
+        S, ModifiersState::LOGO | ModifiersState::SHIFT; Action::SaveScrollback;
+        S, ModifiersState::LOGO | ModifiersState::ALT; Action::ToggleSecureKeyboardEntry;
+        R, ModifiersState::LOGO | ModifiersState::SHIFT; Action::ToggleRecording;
+        P, ModifiersState::LOGO | ModifiersState::SHIFT; Action::Screenshot;
+        A, ModifiersState::LOGO | ModifiersState::SHIFT; Action::ToggleCommandPalette;
+        A, ModifiersState::LOGO; Action::SelectAll;
+        W, ModifiersState::LOGO | ModifiersState::ALT; Action::ExpandSelectionToWord;
+        L, ModifiersState::LOGO | ModifiersState::ALT; Action::ExpandSelectionToLine;
+        O, ModifiersState::LOGO | ModifiersState::ALT, ~BindingMode::ALT_SCREEN;
+            Action::ExpandSelectionToOutput;
+        E, ModifiersState::LOGO | ModifiersState::SHIFT; Action::Hints(HintKind::Url);
+        F, ModifiersState::LOGO | ModifiersState::SHIFT; Action::Hints(HintKind::Path);
+        W, ModifiersState::LOGO | ModifiersState::SHIFT; Action::Hints(HintKind::Word);
+        L, ModifiersState::LOGO | ModifiersState::SHIFT; Action::Hints(HintKind::Line);
+        Up, ModifiersState::LOGO, ~BindingMode::ALT_SCREEN; Action::ScrollToPreviousPrompt;
+        Down, ModifiersState::LOGO, ~BindingMode::ALT_SCREEN; Action::ScrollToNextPrompt;
+        H, ModifiersState::LOGO | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN;
+            Action::OpenScrollbackInPager;
         C, ModifiersState::LOGO, +BindingMode::VI; Action::ClearSelection;
         H, ModifiersState::LOGO; Action::Hide;
         H, ModifiersState::LOGO | ModifiersState::ALT; Action::HideOtherApplications;
         M, ModifiersState::LOGO; Action::Minimize;
+        LBracket, ModifiersState::LOGO | ModifiersState::SHIFT; Action::TabSwitchPrev;
+        RBracket, ModifiersState::LOGO | ModifiersState::SHIFT; Action::TabSwitchNext;
         Q, ModifiersState::LOGO; Action::Quit;
         W, ModifiersState::LOGO; Action::Quit;
         // N, ModifiersState::ALT,  ~BindingMode::VI;
@@ -530,6 +661,35 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         KeyBinding;
         V,        ModifiersState::CTRL | ModifiersState::SHIFT, ~BindingMode::VI; Action::Paste;
         C,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::Copy;
+        C,        ModifiersState::CTRL | ModifiersState::SHIFT | ModifiersState::ALT;
+            Action::CopyRichText;
+        O,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::CopyLastOutput;
+        S,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::SaveScrollback;
+        R,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::ToggleRecording;
+        P,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::Screenshot;
+        A,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::ToggleCommandPalette;
+        A,        ModifiersState::CTRL | ModifiersState::SHIFT | ModifiersState::ALT;
+            Action::SelectAll;
+        W,        ModifiersState::CTRL | ModifiersState::SHIFT | ModifiersState::ALT;
+            Action::ExpandSelectionToWord;
+        L,        ModifiersState::CTRL | ModifiersState::SHIFT | ModifiersState::ALT;
+            Action::ExpandSelectionToLine;
+        O,        ModifiersState::CTRL | ModifiersState::SHIFT | ModifiersState::ALT,
+            ~BindingMode::ALT_SCREEN; Action::ExpandSelectionToOutput;
+        E,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::Hints(HintKind::Url);
+        F,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::Hints(HintKind::Path);
+        W,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::Hints(HintKind::Word);
+        L,        ModifiersState::CTRL | ModifiersState::SHIFT; Action::Hints(HintKind::Line);
+        Z,        ModifiersState::CTRL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN;
+            Action::ScrollToPreviousPrompt;
+        X,        ModifiersState::CTRL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN;
+            Action::ScrollToNextPrompt;
+        H,        ModifiersState::CTRL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN;
+            Action::OpenScrollbackInPager;
+        K,        ModifiersState::CTRL | ModifiersState::SHIFT, ~BindingMode::VI;
+            Action::ClearScreenAndHistory;
+        K,        ModifiersState::CTRL | ModifiersState::SHIFT | ModifiersState::ALT,
+            ~BindingMode::VI; Action::ClearHistory;
         C,        ModifiersState::CTRL | ModifiersState::SHIFT,
             +BindingMode::VI; Action::ClearSelection;
         Insert,   ModifiersState::SHIFT, ~BindingMode::VI; Action::PasteSelection;
@@ -539,6 +699,7 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         NumpadAdd,      ModifiersState::CTRL;  Action::IncreaseFontSize;
         Minus,          ModifiersState::CTRL;  Action::DecreaseFontSize;
         NumpadSubtract, ModifiersState::CTRL;  Action::DecreaseFontSize;
+        F11;      Action::ToggleFullscreen;
     )
 }
 
@@ -546,3 +707,576 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
 pub fn platform_key_bindings() -> Vec<KeyBinding> {
     vec![]
 }
+
+const LETTERS: [VirtualKeyCode; 26] = [
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+];
+const DIGITS: [VirtualKeyCode; 10] =
+    [Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+
+/// Parse a key sent with `rio msg send-key`, e.g. `Up` or `Control+C`.
+pub fn parse_key(name: &str) -> Result<(VirtualKeyCode, ModifiersState), String> {
+    let mut parts: Vec<&str> = name.split('+').collect();
+    let key = parts.pop().unwrap_or_default();
+
+    let mut mods = ModifiersState::empty();
+    for modifier in parts {
+        mods |= parse_modifier(modifier)?;
+    }
+
+    Ok((parse_keycode(key)?, mods))
+}
+
+fn parse_modifier(modifier: &str) -> Result<ModifiersState, String> {
+    match modifier {
+        "Shift" => Ok(ModifiersState::SHIFT),
+        "Control" => Ok(ModifiersState::CTRL),
+        "Alt" | "Option" => Ok(ModifiersState::ALT),
+        "Super" | "Command" => Ok(ModifiersState::LOGO),
+        modifier => Err(format!("unknown modifier `{modifier}`")),
+    }
+}
+
+/// Modifiers of a binding, e.g. `Control|Shift`.
+fn parse_modifiers(with: &str) -> Result<ModifiersState, String> {
+    let mut mods = ModifiersState::empty();
+    for modifier in with.split('|').map(str::trim) {
+        if !modifier.is_empty() {
+            mods |= parse_modifier(modifier)?;
+        }
+    }
+    Ok(mods)
+}
+
+/// Key named like in `parse_key`, without modifiers.
+fn parse_keycode(key: &str) -> Result<VirtualKeyCode, String> {
+    let keycode = match key {
+        "Enter" | "Return" => Return,
+        "Tab" => Tab,
+        "Escape" => Escape,
+        "Backspace" => Back,
+        "Space" => Space,
+        "Insert" => Insert,
+        "Delete" => Delete,
+        "Home" => Home,
+        "End" => End,
+        "PageUp" => PageUp,
+        "PageDown" => PageDown,
+        "Up" => Up,
+        "Down" => Down,
+        "Left" => Left,
+        "Right" => Right,
+        "F1" => F1,
+        "F2" => F2,
+        "F3" => F3,
+        "F4" => F4,
+        "F5" => F5,
+        "F6" => F6,
+        "F7" => F7,
+        "F8" => F8,
+        "F9" => F9,
+        "F10" => F10,
+        "F11" => F11,
+        "F12" => F12,
+        key => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c @ 'A'..='Z'), None) => LETTERS[(c as u8 - b'A') as usize],
+                (Some(c @ '0'..='9'), None) => DIGITS[(c as u8 - b'0') as usize],
+                _ => return Err(format!("unknown key `{key}`")),
+            }
+        }
+    };
+
+    Ok(keycode)
+}
+
+/// Bytes written to the PTY when `key` is pressed with `mods`, taken from
+/// the escape sequences of `bindings` when one of them is triggered.
+pub fn key_to_bytes(
+    bindings: &[KeyBinding],
+    mode: BindingMode,
+    key: VirtualKeyCode,
+    mods: ModifiersState,
+) -> Option<Vec<u8>> {
+    let trigger = Key::Keycode(key);
+    for binding in bindings {
+        if let Action::Esc(s) = &binding.action {
+            if binding.is_triggered_by(mode.clone(), mods, &trigger) {
+                return Some(s.replace("\r\n", "\r").replace('\n', "\r").into_bytes());
+            }
+        }
+    }
+
+    let byte = if let Some(index) = LETTERS.iter().position(|letter| *letter == key) {
+        let letter = b'a' + index as u8;
+        if mods.ctrl() {
+            letter & 0x1f
+        } else if mods.shift() {
+            letter.to_ascii_uppercase()
+        } else {
+            letter
+        }
+    } else if let Some(index) = DIGITS.iter().position(|digit| *digit == key) {
+        b'0' + index as u8
+    } else {
+        match key {
+            Return => b'\r',
+            Tab => b'\t',
+            Escape => b'\x1b',
+            Back => b'\x7f',
+            Space if mods.ctrl() => b'\0',
+            Space => b' ',
+            _ => return None,
+        }
+    };
+
+    if mods.alt() {
+        Some(vec![b'\x1b', byte])
+    } else {
+        Some(vec![byte])
+    }
+}
+
+pub fn default_mouse_bindings() -> Vec<MouseBinding> {
+    bindings!(
+        MouseBinding;
+        MouseButton::Middle, ~BindingMode::MOUSE_REPORT; Action::PasteSelection;
+        MouseButton::Right, ~BindingMode::MOUSE_REPORT; Action::ContextMenu;
+        MouseButton::Left, ModifiersState::CTRL, ~BindingMode::MOUSE_REPORT;
+            Action::OpenLink;
+    )
+}
+
+/// Default mouse bindings with the ones of `config` replacing those with
+/// the same trigger. Invalid ones are logged and skipped.
+pub fn mouse_bindings(config: &[config::MouseBinding]) -> Vec<MouseBinding> {
+    let mut bindings = default_mouse_bindings();
+    for binding in config {
+        match parse_mouse_binding(binding) {
+            Ok(binding) => {
+                bindings.retain(|default| !default.triggers_match(&binding));
+                bindings.push(binding);
+            }
+            Err(err) => log::warn!("invalid mouse binding: {err}"),
+        }
+    }
+    bindings
+}
+
+fn parse_mouse_binding(binding: &config::MouseBinding) -> Result<MouseBinding, String> {
+    let trigger = match binding.button.as_str() {
+        "Left" => MouseButton::Left,
+        "Middle" => MouseButton::Middle,
+        "Right" => MouseButton::Right,
+        button => return Err(format!("unknown button `{button}`")),
+    };
+    let mods = parse_modifiers(&binding.with)?;
+    let (mode, notmode) = parse_mode(&binding.mode)?;
+    let action = parse_action(&binding.action)?;
+
+    Ok(MouseBinding {
+        mods,
+        repeat: action.repeats(),
+        action,
+        mode,
+        notmode,
+        trigger,
+    })
+}
+
+/// Default key bindings with the ones of `config` replacing those with the
+/// same trigger. Invalid ones are logged and skipped.
+pub fn key_bindings(config: &[config::KeyBinding]) -> Vec<KeyBinding> {
+    let mut bindings = default_key_bindings();
+    for binding in config.iter().filter(|binding| binding.chord.is_empty()) {
+        match parse_key_binding(binding) {
+            Ok(binding) => {
+                bindings.retain(|default| !default.triggers_match(&binding));
+                bindings.push(binding);
+            }
+            Err(err) => log::warn!("invalid key binding: {err}"),
+        }
+    }
+    bindings
+}
+
+/// Chords of `config`, logging the invalid ones and their conflicts with
+/// each other and with `bindings`.
+pub fn chords(config: &[config::KeyBinding], bindings: &[KeyBinding]) -> Chords {
+    let mut chords = Vec::new();
+    for binding in config.iter().filter(|binding| !binding.chord.is_empty()) {
+        match parse_chord_binding(binding) {
+            Ok(chord) => chords.push(chord),
+            Err(err) => log::warn!("invalid key binding: {err}"),
+        }
+    }
+
+    for conflict in chord::conflicts(&chords, bindings) {
+        log::warn!("conflicting key bindings: {conflict}");
+    }
+    Chords::new(chords)
+}
+
+fn parse_chord_binding(binding: &config::KeyBinding) -> Result<ChordBinding, String> {
+    let mut strokes = binding
+        .chord
+        .split_whitespace()
+        .map(parse_key)
+        .collect::<Result<Vec<_>, _>>()?;
+    let binding = parse_key_binding(binding)?;
+    if let Key::Keycode(key) = binding.trigger {
+        strokes.push((key, binding.mods));
+    }
+
+    Ok(ChordBinding { strokes, binding })
+}
+
+fn parse_key_binding(binding: &config::KeyBinding) -> Result<KeyBinding, String> {
+    let trigger = Key::Keycode(parse_keycode(&binding.key)?);
+    let mods = parse_modifiers(&binding.with)?;
+    let (mode, notmode) = parse_mode(&binding.mode)?;
+    let action = match &binding.text {
+        Some(text) => Action::Esc(text.clone()),
+        None => parse_action(&binding.action)?,
+    };
+
+    Ok(KeyBinding {
+        mods,
+        repeat: binding.repeat.unwrap_or_else(|| action.repeats()),
+        action,
+        mode,
+        notmode,
+        trigger,
+    })
+}
+
+/// Modes required and excluded by a binding, e.g. `~AltScreen|~Vi`.
+fn parse_mode(mode: &str) -> Result<(BindingMode, BindingMode), String> {
+    let mut required = BindingMode::empty();
+    let mut excluded = BindingMode::empty();
+    for name in mode
+        .split('|')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let (modes, name) = match name.strip_prefix('~') {
+            Some(name) => (&mut excluded, name),
+            None => (&mut required, name),
+        };
+        modes.insert(match name {
+            "AppCursor" => BindingMode::APP_CURSOR,
+            "AppKeypad" => BindingMode::APP_KEYPAD,
+            "AltScreen" => BindingMode::ALT_SCREEN,
+            "Vi" => BindingMode::VI,
+            "Search" => BindingMode::SEARCH,
+            "MouseReport" => BindingMode::MOUSE_REPORT,
+            name => return Err(format!("unknown mode `{name}`")),
+        });
+    }
+    Ok((required, excluded))
+}
+
+fn parse_action(action: &str) -> Result<Action, String> {
+    let action = match action {
+        "Paste" => Action::Paste,
+        "PasteSelection" => Action::PasteSelection,
+        "Copy" => Action::Copy,
+        "CopyRichText" => Action::CopyRichText,
+        "CopyLastOutput" => Action::CopyLastOutput,
+        "ClearSelection" => Action::ClearSelection,
+        "OpenLink" => Action::OpenLink,
+        "SelectAll" => Action::SelectAll,
+        "ExpandSelectionToWord" => Action::ExpandSelectionToWord,
+        "ExpandSelectionToLine" => Action::ExpandSelectionToLine,
+        "ExpandSelectionToOutput" => Action::ExpandSelectionToOutput,
+        "ContextMenu" => Action::ContextMenu,
+        "ToggleCommandPalette" => Action::ToggleCommandPalette,
+        "ToggleFullscreen" => Action::ToggleFullscreen,
+        "ToggleMaximized" => Action::ToggleMaximized,
+        "ToggleRecording" => Action::ToggleRecording,
+        "ToggleBellMute" => Action::ToggleBellMute,
+        "ToggleSecureKeyboardEntry" => Action::ToggleSecureKeyboardEntry,
+        "TabCreateNew" => Action::TabCreateNew,
+        "TabSwitchNext" => Action::TabSwitchNext,
+        "TabSwitchPrev" => Action::TabSwitchPrev,
+        "ScrollPageUp" => Action::ScrollPageUp,
+        "ScrollPageDown" => Action::ScrollPageDown,
+        "ScrollHalfPageUp" => Action::ScrollHalfPageUp,
+        "ScrollHalfPageDown" => Action::ScrollHalfPageDown,
+        "ScrollLineUp" => Action::ScrollLineUp,
+        "ScrollLineDown" => Action::ScrollLineDown,
+        "ScrollToTop" => Action::ScrollToTop,
+        "ScrollToBottom" => Action::ScrollToBottom,
+        "ScrollToPreviousPrompt" => Action::ScrollToPreviousPrompt,
+        "ScrollToNextPrompt" => Action::ScrollToNextPrompt,
+        "ClearHistory" => Action::ClearHistory,
+        "ClearScreenAndHistory" => Action::ClearScreenAndHistory,
+        "SaveScreen" => Action::SaveScreen,
+        "SaveScrollback" => Action::SaveScrollback,
+        "Screenshot" => Action::Screenshot,
+        "OpenScrollbackInPager" => Action::OpenScrollbackInPager,
+        "OpenScrollbackInEditor" => Action::OpenScrollbackInEditor,
+        "Quit" => Action::Quit,
+        "ReceiveChar" => Action::ReceiveChar,
+        "None" => Action::None,
+        action => return Err(format!("unknown action `{action}`")),
+    };
+    Ok(action)
+}
+
+/// Keys held down, telling the first press of a key from the presses the
+/// platform repeats while it is held, which winit doesn't report.
+#[derive(Debug, Default)]
+pub struct HeldKeys(HashSet<u32>);
+
+impl HeldKeys {
+    /// The key with `scancode` was pressed, returns whether it was already
+    /// held, i.e. the press is a repeat.
+    pub fn press(&mut self, scancode: u32) -> bool {
+        !self.0.insert(scancode)
+    }
+
+    pub fn release(&mut self, scancode: u32) {
+        self.0.remove(&scancode);
+    }
+
+    /// Forget the keys held, e.g. when the window loses the focus and
+    /// their release won't be reported.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn config(
+        button: &str,
+        with: &str,
+        action: &str,
+        mode: &str,
+    ) -> config::MouseBinding {
+        config::MouseBinding {
+            button: button.to_string(),
+            with: with.to_string(),
+            action: action.to_string(),
+            mode: mode.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_mouse_binding() {
+        let binding = parse_mouse_binding(&config(
+            "Right",
+            "Control|Shift",
+            "Paste",
+            "~MouseReport",
+        ))
+        .unwrap();
+        assert_eq!(binding.trigger, MouseButton::Right);
+        assert_eq!(binding.mods, ModifiersState::CTRL | ModifiersState::SHIFT);
+        assert_eq!(binding.action, Action::Paste);
+        assert_eq!(binding.notmode, BindingMode::MOUSE_REPORT);
+
+        assert!(parse_mouse_binding(&config("Back", "", "Paste", "")).is_err());
+        assert!(parse_mouse_binding(&config("Left", "Hyper", "Paste", "")).is_err());
+        assert!(parse_mouse_binding(&config("Left", "", "Explode", "")).is_err());
+    }
+
+    fn key_config(
+        key: &str,
+        with: &str,
+        action: &str,
+        repeat: Option<bool>,
+    ) -> config::KeyBinding {
+        config::KeyBinding {
+            key: key.to_string(),
+            with: with.to_string(),
+            action: action.to_string(),
+            text: None,
+            mode: String::new(),
+            repeat,
+            chord: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        let binding =
+            parse_key_binding(&key_config("T", "Control|Shift", "TabCreateNew", None))
+                .unwrap();
+        assert_eq!(binding.trigger, Key::Keycode(T));
+        assert_eq!(binding.mods, ModifiersState::CTRL | ModifiersState::SHIFT);
+        assert_eq!(binding.action, Action::TabCreateNew);
+        assert!(!binding.repeat);
+
+        let binding = parse_key_binding(&config::KeyBinding {
+            text: Some(String::from("\x1b[1;3A")),
+            mode: String::from("~Vi|AppCursor"),
+            ..key_config("Up", "Alt", "", None)
+        })
+        .unwrap();
+        assert_eq!(binding.action, Action::Esc(String::from("\x1b[1;3A")));
+        assert_eq!(binding.mode, BindingMode::APP_CURSOR);
+        assert_eq!(binding.notmode, BindingMode::VI);
+        assert!(binding.repeat);
+
+        let binding =
+            parse_key_binding(&key_config("F11", "", "ToggleFullscreen", Some(true)))
+                .unwrap();
+        assert!(binding.repeat);
+
+        assert!(parse_key_binding(&key_config("t", "", "Copy", None)).is_err());
+        assert!(parse_key_binding(&key_config("T", "", "Explode", None)).is_err());
+        assert!(parse_key_binding(&config::KeyBinding {
+            mode: String::from("Insert"),
+            ..key_config("T", "", "Copy", None)
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_key_bindings() {
+        let bindings = key_bindings(&[
+            key_config("T", "Super", "TabSwitchNext", None),
+            key_config("J", "Control|Alt", "Paste", Some(true)),
+        ]);
+        assert_eq!(bindings.len(), default_key_bindings().len() + 1);
+
+        let new_tab = bindings
+            .iter()
+            .find(|binding| binding.action == Action::TabCreateNew);
+        assert!(new_tab.is_none());
+        assert!(bindings.last().unwrap().repeat);
+
+        // Text for the terminal repeats, actions don't.
+        let defaults = default_key_bindings();
+        assert!(defaults
+            .iter()
+            .filter(|binding| matches!(binding.action, Action::Esc(_)))
+            .all(|binding| binding.repeat));
+        assert!(defaults
+            .iter()
+            .filter(|binding| binding.action == Action::TabCreateNew)
+            .all(|binding| !binding.repeat));
+    }
+
+    #[test]
+    fn test_chords() {
+        let bindings = key_bindings(&[
+            config::KeyBinding {
+                chord: String::from("Control+A"),
+                ..key_config("V", "", "TabCreateNew", None)
+            },
+            config::KeyBinding {
+                chord: String::from("Control+A Shift+1"),
+                ..key_config("N", "", "TabSwitchNext", None)
+            },
+            config::KeyBinding {
+                chord: String::from("Control+"),
+                ..key_config("V", "", "Paste", None)
+            },
+        ]);
+        assert_eq!(bindings, default_key_bindings());
+
+        let chords = [
+            config::KeyBinding {
+                chord: String::from("Control+A Shift+1"),
+                ..key_config("N", "", "TabSwitchNext", None)
+            },
+            config::KeyBinding {
+                chord: String::from("Control+"),
+                ..key_config("V", "", "Paste", None)
+            },
+        ];
+        let chord = parse_chord_binding(&chords[0]).unwrap();
+        assert_eq!(
+            chord.strokes,
+            [
+                (A, ModifiersState::CTRL),
+                (Key1, ModifiersState::SHIFT),
+                (N, ModifiersState::empty())
+            ]
+        );
+        assert_eq!(chord.binding.action, Action::TabSwitchNext);
+        assert!(parse_chord_binding(&chords[1]).is_err());
+    }
+
+    #[test]
+    fn test_held_keys() {
+        let mut held = HeldKeys::default();
+        assert!(!held.press(30));
+        assert!(held.press(30));
+        assert!(!held.press(31));
+        held.release(30);
+        assert!(!held.press(30));
+        held.clear();
+        assert!(!held.press(31));
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("Up"), Ok((Up, ModifiersState::empty())));
+        assert_eq!(parse_key("Control+C"), Ok((C, ModifiersState::CTRL)));
+        assert_eq!(
+            parse_key("Shift+Alt+7"),
+            Ok((Key7, ModifiersState::SHIFT | ModifiersState::ALT))
+        );
+        assert!(parse_key("Hyper+C").is_err());
+        assert!(parse_key("Control+").is_err());
+        assert!(parse_key("c").is_err());
+    }
+
+    #[test]
+    fn test_key_to_bytes() {
+        let bindings = default_key_bindings();
+        let bytes = |mode: BindingMode, name: &str| {
+            let (key, mods) = parse_key(name).unwrap();
+            key_to_bytes(&bindings, mode, key, mods)
+        };
+
+        assert_eq!(bytes(BindingMode::empty(), "Up"), Some(b"\x1b[A".to_vec()));
+        assert_eq!(
+            bytes(BindingMode::APP_CURSOR, "Up"),
+            Some(b"\x1bOA".to_vec())
+        );
+        assert_eq!(bytes(BindingMode::empty(), "Control+C"), Some(vec![0x03]));
+        assert_eq!(bytes(BindingMode::empty(), "Control+L"), Some(vec![0x0c]));
+        assert_eq!(bytes(BindingMode::empty(), "Shift+A"), Some(b"A".to_vec()));
+        assert_eq!(
+            bytes(BindingMode::empty(), "Alt+B"),
+            Some(b"\x1bb".to_vec())
+        );
+        assert_eq!(bytes(BindingMode::empty(), "Enter"), Some(b"\r".to_vec()));
+        assert_eq!(bytes(BindingMode::empty(), "F11"), None);
+    }
+
+    #[test]
+    fn test_mouse_bindings() {
+        let bindings = mouse_bindings(&[
+            config("Middle", "", "None", ""),
+            config("Right", "", "Paste", ""),
+            config("Right", "", "Oops", ""),
+        ]);
+        assert_eq!(bindings.len(), 3);
+        assert!(!bindings
+            .iter()
+            .any(|binding| binding.action == Action::ContextMenu));
+
+        let middle: Vec<&MouseBinding> = bindings
+            .iter()
+            .filter(|binding| binding.trigger == MouseButton::Middle)
+            .collect();
+        assert_eq!(middle.len(), 1);
+        assert_eq!(middle[0].action, Action::None);
+
+        let right = bindings.last().unwrap();
+        assert!(right.is_triggered_by(
+            BindingMode::MOUSE_REPORT,
+            ModifiersState::empty(),
+            &MouseButton::Right
+        ));
+    }
+}
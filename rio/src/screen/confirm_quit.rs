@@ -0,0 +1,45 @@
+/// Program keeping the window open until closing it is confirmed, from the
+/// one in the `foreground` of the shell.
+pub fn blocking_program(
+    config: &config::ConfirmQuit,
+    foreground: Option<String>,
+) -> Option<String> {
+    if !config.enabled {
+        return None;
+    }
+
+    foreground.filter(|name| !config.ignore.iter().any(|ignored| ignored == name))
+}
+
+/// Lines of the prompt shown over the terminal.
+pub fn prompt(program: &str) -> [String; 2] {
+    [
+        format!("{program} is still running."),
+        String::from("Press Enter to quit or Escape to cancel."),
+    ]
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_blocking_program() {
+        let config = config::ConfirmQuit {
+            enabled: true,
+            ignore: vec![String::from("tmux")],
+        };
+        assert_eq!(
+            blocking_program(&config, Some(String::from("vim"))),
+            Some(String::from("vim"))
+        );
+        assert_eq!(blocking_program(&config, Some(String::from("tmux"))), None);
+        assert_eq!(blocking_program(&config, None), None);
+
+        let config = config::ConfirmQuit {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(blocking_program(&config, Some(String::from("vim"))), None);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::screen::bindings::Action;
+use rio_core::crosswords::pos::Pos;
+
+/// Width of the menu, in columns.
+pub const COLUMNS: usize = 14;
+
+/// Entries of the menu, with the action each runs.
+pub const ENTRIES: &[(&str, Action)] = &[
+    ("Copy", Action::Copy),
+    ("Paste", Action::Paste),
+    ("Select All", Action::SelectAll),
+    ("Open Link", Action::OpenLink),
+    ("New Tab", Action::TabCreateNew),
+];
+
+/// Menu opened by right-clicking, drawn over the terminal.
+#[derive(Debug)]
+pub struct ContextMenu {
+    /// Top-left corner, kept inside the window.
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub line_height: f32,
+    /// Square which was clicked, where "Open Link" looks for a link.
+    pub point: Pos,
+    pub selected: Option<usize>,
+}
+
+impl ContextMenu {
+    /// Menu opened at `position` in a window of `size`.
+    pub fn new(
+        position: (f32, f32),
+        size: (f32, f32),
+        width: f32,
+        line_height: f32,
+        point: Pos,
+    ) -> ContextMenu {
+        let height = ENTRIES.len() as f32 * line_height;
+        ContextMenu {
+            left: position.0.min(size.0 - width).max(0.0),
+            top: position.1.min(size.1 - height).max(0.0),
+            width,
+            line_height,
+            point,
+            selected: None,
+        }
+    }
+
+    #[inline]
+    pub fn height(&self) -> f32 {
+        ENTRIES.len() as f32 * self.line_height
+    }
+
+    /// Index of the entry at `(x, y)`, if any.
+    pub fn entry_at(&self, x: f32, y: f32) -> Option<usize> {
+        if x < self.left || x >= self.left + self.width || y < self.top {
+            return None;
+        }
+
+        let index = ((y - self.top) / self.line_height) as usize;
+        (index < ENTRIES.len()).then_some(index)
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected =
+            Some(self.selected.map_or(0, |index| (index + 1) % ENTRIES.len()));
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = Some(match self.selected {
+            Some(index) if index > 0 => index - 1,
+            _ => ENTRIES.len() - 1,
+        });
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use rio_core::crosswords::pos::{Column, Line};
+
+    fn menu(position: (f32, f32)) -> ContextMenu {
+        ContextMenu::new(
+            position,
+            (400.0, 300.0),
+            100.0,
+            20.0,
+            Pos::new(Line(0), Column(0)),
+        )
+    }
+
+    #[test]
+    fn test_position() {
+        let opened = menu((50.0, 60.0));
+        assert_eq!((opened.left, opened.top), (50.0, 60.0));
+
+        // Opened at the bottom right corner, the menu moves inside the window.
+        let opened = menu((390.0, 290.0));
+        assert_eq!((opened.left, opened.top), (300.0, 200.0));
+    }
+
+    #[test]
+    fn test_entry_at() {
+        let menu = menu((50.0, 60.0));
+        assert_eq!(menu.entry_at(60.0, 65.0), Some(0));
+        assert_eq!(menu.entry_at(60.0, 105.0), Some(2));
+        assert_eq!(menu.entry_at(60.0, 165.0), None);
+        assert_eq!(menu.entry_at(40.0, 65.0), None);
+        assert_eq!(menu.entry_at(160.0, 65.0), None);
+    }
+
+    #[test]
+    fn test_select() {
+        let mut menu = menu((0.0, 0.0));
+        menu.select_previous();
+        assert_eq!(menu.selected, Some(ENTRIES.len() - 1));
+        menu.select_next();
+        assert_eq!(menu.selected, Some(0));
+        menu.select_next();
+        assert_eq!(menu.selected, Some(1));
+    }
+}
@@ -0,0 +1,145 @@
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Path followed by a line number and optionally a column, as printed by
+/// compilers and linters (e.g. `src/main.rs:42:5`).
+const FILE_LINK: &str = r"[\w.~+-]*(/[\w.+-]+)*:(\d+)(:(\d+))?";
+
+/// Command used when `links.editor` is empty.
+const DEFAULT_EDITOR: &str = "vi";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLink {
+    pub path: String,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+/// File link in `text` over the character at `column`.
+pub fn find(text: &str, column: usize) -> Option<FileLink> {
+    let pattern = Regex::new(FILE_LINK).ok()?;
+    for captures in pattern.captures_iter(text) {
+        let found = captures.get(0)?;
+        let start = text[..found.start()].chars().count();
+        let end = start + found.as_str().chars().count();
+        if column < start || column >= end {
+            continue;
+        }
+
+        // Not a time or a port number.
+        let path = &text[found.start()..captures.get(2)?.start() - 1];
+        if path.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        return Some(FileLink {
+            path: path.to_string(),
+            line: captures[2].parse().ok()?,
+            column: captures
+                .get(4)
+                .and_then(|column| column.as_str().parse().ok()),
+        });
+    }
+
+    None
+}
+
+/// Absolute path of `path`, relative to `cwd` (the directory reported by
+/// the shell with OSC 7) unless it starts with `/` or `~/`.
+pub fn resolve(path: &str, cwd: Option<&Path>, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix("~/"), home) {
+        (Some(relative), Some(home)) => home.join(relative),
+        _ => match cwd {
+            Some(cwd) => cwd.join(path),
+            None => PathBuf::from(path),
+        },
+    }
+}
+
+/// Program and arguments opening `path` at `link`, from the `links.editor`
+/// template split on whitespace, with `{file}`, `{line}` and `{column}`
+/// replaced in each argument. An empty template opens the file with
+/// `$EDITOR`.
+pub fn command(template: &str, path: &str, link: &FileLink) -> Vec<String> {
+    let template = if template.is_empty() {
+        let editor =
+            std::env::var("EDITOR").unwrap_or_else(|_| String::from(DEFAULT_EDITOR));
+        format!("{editor} +{{line}} -- {{file}}")
+    } else {
+        template.to_string()
+    };
+
+    template
+        .split_whitespace()
+        .map(|argument| {
+            argument
+                .replace("{file}", path)
+                .replace("{line}", &link.line.to_string())
+                .replace("{column}", &link.column.unwrap_or(1).to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn link(path: &str, line: usize, column: Option<usize>) -> FileLink {
+        FileLink {
+            path: String::from(path),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let text = "  --> src/main.rs:42:5";
+        assert_eq!(find(text, 6), Some(link("src/main.rs", 42, Some(5))));
+        assert_eq!(find(text, 21), Some(link("src/main.rs", 42, Some(5))));
+        assert_eq!(find(text, 2), None);
+
+        assert_eq!(
+            find("error in ~/code/app.py:7", 12),
+            Some(link("~/code/app.py", 7, None))
+        );
+        assert_eq!(
+            find("/tmp/a.c:1: warning", 0),
+            Some(link("/tmp/a.c", 1, None))
+        );
+        assert_eq!(find("at 12:30", 4), None);
+        assert_eq!(find("no links here", 3), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let cwd = Path::new("/home/rio/code");
+        let home = Path::new("/home/rio");
+        assert_eq!(
+            resolve("src/main.rs", Some(cwd), Some(home)),
+            PathBuf::from("/home/rio/code/src/main.rs")
+        );
+        assert_eq!(
+            resolve("/etc/hosts", Some(cwd), Some(home)),
+            PathBuf::from("/etc/hosts")
+        );
+        assert_eq!(
+            resolve("~/notes.md", Some(cwd), Some(home)),
+            PathBuf::from("/home/rio/notes.md")
+        );
+        assert_eq!(resolve("a.rs", None, None), PathBuf::from("a.rs"));
+    }
+
+    #[test]
+    fn test_command() {
+        let link = link("src/main.rs", 42, Some(5));
+        assert_eq!(
+            command("code --goto {file}:{line}:{column}", "/src/main.rs", &link),
+            vec!["code", "--goto", "/src/main.rs:42:5"]
+        );
+        assert_eq!(
+            command("hx {file}:{line}", "/a b;.rs", &link),
+            vec!["hx", "/a b;.rs:42"]
+        );
+    }
+}
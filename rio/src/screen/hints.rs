@@ -0,0 +1,219 @@
+use regex::Regex;
+
+/// Characters used in labels, home row first.
+const ALPHABET: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+const URL: &str = r#"(https?|ftp|file)://[^\s<>"'`]+"#;
+const PATH: &str = r"(~|\.{1,2})?(/[\w.+@-]+)+|[\w.+@-]+(/[\w.+@-]+)+";
+const WORD: &str = r#"[^\s,│`|"'()\[\]{}<>]{3,}"#;
+
+/// What is labeled on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintKind {
+    Url,
+    Path,
+    Word,
+    Line,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    /// Index of the visible line.
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub label: String,
+}
+
+/// `count` labels of the same length, so none is the start of another.
+pub fn labels(count: usize) -> Vec<String> {
+    let alphabet: Vec<char> = ALPHABET.chars().collect();
+    let mut length = 1;
+    while alphabet.len().pow(length) < count {
+        length += 1;
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = vec![' '; length as usize];
+            for c in label.iter_mut().rev() {
+                *c = alphabet[index % alphabet.len()];
+                index /= alphabet.len();
+            }
+            label.into_iter().collect()
+        })
+        .collect()
+}
+
+/// Targets of `kind` in the visible `lines`. The bottom ones, closer to
+/// the prompt, get the first labels.
+pub fn find(lines: &[String], kind: HintKind) -> Vec<Hint> {
+    let pattern = match kind {
+        HintKind::Url => Regex::new(URL).ok(),
+        HintKind::Path => Regex::new(PATH).ok(),
+        HintKind::Word => Regex::new(WORD).ok(),
+        HintKind::Line => None,
+    };
+
+    let mut found = vec![];
+    for (line, text) in lines.iter().enumerate() {
+        match &pattern {
+            Some(pattern) => {
+                for target in pattern.find_iter(text) {
+                    let mut target_text = target.as_str();
+                    // Punctuation ending a sentence isn't part of the URL.
+                    if kind == HintKind::Url {
+                        target_text = target_text
+                            .trim_end_matches(['.', ',', ':', ';', '!', '?', ')']);
+                    }
+                    let column = text[..target.start()].chars().count();
+                    found.push((line, column, target_text.to_string()));
+                }
+            }
+            None => {
+                let trimmed = text.trim();
+                if !trimmed.is_empty() {
+                    let column = text.chars().take_while(|c| c.is_whitespace()).count();
+                    found.push((line, column, trimmed.to_string()));
+                }
+            }
+        }
+    }
+
+    let labels = labels(found.len());
+    found
+        .into_iter()
+        .rev()
+        .zip(labels)
+        .map(|((line, column, text), label)| Hint {
+            line,
+            column,
+            text,
+            label,
+        })
+        .collect()
+}
+
+/// Labels drawn over the screen, picked by typing them.
+#[derive(Debug)]
+pub struct Hints {
+    hints: Vec<Hint>,
+    typed: String,
+}
+
+impl Hints {
+    /// Hints for the visible `lines`, `None` when there is nothing to pick.
+    pub fn new(lines: &[String], kind: HintKind) -> Option<Hints> {
+        let hints = find(lines, kind);
+        if hints.is_empty() {
+            return None;
+        }
+
+        Some(Hints {
+            hints,
+            typed: String::new(),
+        })
+    }
+
+    #[inline]
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// Hints whose label starts with what was typed so far.
+    pub fn visible(&self) -> impl Iterator<Item = &Hint> {
+        self.hints
+            .iter()
+            .filter(|hint| hint.label.starts_with(&self.typed))
+    }
+
+    /// Type `c`, returning the text of the hint once its whole label is
+    /// typed. Characters not leading to any label are ignored.
+    pub fn push(&mut self, c: char) -> Option<String> {
+        let typed = format!("{}{c}", self.typed);
+        if !self.hints.iter().any(|hint| hint.label.starts_with(&typed)) {
+            return None;
+        }
+
+        self.typed = typed;
+        self.hints
+            .iter()
+            .find(|hint| hint.label == self.typed)
+            .map(|hint| hint.text.clone())
+    }
+
+    pub fn pop(&mut self) {
+        self.typed.pop();
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(labels(3), vec!["a", "s", "d"]);
+        let labels = labels(30);
+        assert_eq!(labels[0], "aa");
+        assert_eq!(labels[1], "as");
+        assert_eq!(labels[26], "sa");
+        assert!(labels.iter().all(|label| label.len() == 2));
+    }
+
+    #[test]
+    fn test_find() {
+        let lines = lines(&[
+            "see https://example.com/docs.",
+            "  edit ~/code/app/main.rs now",
+            "",
+        ]);
+
+        let urls = find(&lines, HintKind::Url);
+        assert_eq!(
+            urls,
+            vec![Hint {
+                line: 0,
+                column: 4,
+                text: String::from("https://example.com/docs"),
+                label: String::from("a"),
+            }]
+        );
+
+        let paths = find(&lines, HintKind::Path);
+        assert_eq!(paths[0].text, "~/code/app/main.rs");
+        assert_eq!(paths[0].column, 7);
+
+        let words: Vec<String> = find(&lines, HintKind::Word)
+            .into_iter()
+            .map(|hint| hint.text)
+            .collect();
+        assert_eq!(words[0], "now");
+        assert!(words.contains(&String::from("see")));
+
+        let lines = find(&lines, HintKind::Line);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "edit ~/code/app/main.rs now");
+        assert_eq!(lines[0].column, 2);
+        assert_eq!(lines[0].label, "a");
+    }
+
+    #[test]
+    fn test_hints() {
+        assert!(Hints::new(&lines(&["", "  "]), HintKind::Line).is_none());
+
+        let lines: Vec<String> = (0..30).map(|line| format!("line {line}")).collect();
+        let mut hints = Hints::new(&lines, HintKind::Line).unwrap();
+        assert_eq!(hints.push('z'), None);
+        assert_eq!(hints.typed(), "");
+        assert_eq!(hints.push('s'), None);
+        assert_eq!(hints.visible().count(), 4);
+        hints.pop();
+        assert_eq!(hints.push('a'), None);
+        assert_eq!(hints.push('s'), Some(String::from("line 28")));
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+/// How long frames wait for the echo of a keystroke.
+pub const ECHO_TIMEOUT: Duration = Duration::from_millis(8);
+
+/// Keystroke sent to the PTY whose echo was not drawn yet, so the frame
+/// showing it is drawn as soon as it's parsed rather than on the next tick.
+#[derive(Debug, Default)]
+pub struct Latency {
+    enabled: bool,
+    keystroke: Option<Instant>,
+}
+
+impl Latency {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            keystroke: None,
+        }
+    }
+
+    /// A keystroke was written to the PTY.
+    #[inline]
+    pub fn keystroke(&mut self) {
+        if self.enabled {
+            self.keystroke = Some(Instant::now());
+        }
+    }
+
+    /// Whether the echo of a keystroke may still arrive at `now`.
+    pub fn is_awaiting_echo(&self, now: Instant) -> bool {
+        self.keystroke.map_or(false, |keystroke| {
+            now.saturating_duration_since(keystroke) < ECHO_TIMEOUT
+        })
+    }
+
+    /// Time from the keystroke to `now`, when its echo is drawn.
+    pub fn echo(&mut self, now: Instant) -> Option<Duration> {
+        let keystroke = self.keystroke.take()?;
+        Some(now.saturating_duration_since(keystroke))
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_echo() {
+        let mut latency = Latency::new(true);
+        assert!(!latency.is_awaiting_echo(Instant::now()));
+        assert_eq!(latency.echo(Instant::now()), None);
+
+        latency.keystroke();
+        let keystroke = latency.keystroke.unwrap();
+        assert!(latency.is_awaiting_echo(keystroke));
+        assert!(!latency.is_awaiting_echo(keystroke + ECHO_TIMEOUT));
+
+        let echo = keystroke + Duration::from_millis(2);
+        assert_eq!(latency.echo(echo), Some(Duration::from_millis(2)));
+        assert!(!latency.is_awaiting_echo(echo));
+
+        let mut disabled = Latency::new(false);
+        disabled.keystroke();
+        assert!(!disabled.is_awaiting_echo(Instant::now()));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::event::Msg;
+use rio_core::event::Msg;
+use rio_core::recorder::Recorder;
 use std::borrow::Cow;
 use teletypewriter::WinsizeBuilder;
 use winit::event::ModifiersState;
@@ -32,6 +33,10 @@ impl Messenger {
         let _ = self.channel.send(Msg::Input(data.into()));
     }
 
+    pub fn send_recorder(&self, recorder: Option<Recorder>) {
+        let _ = self.channel.send(Msg::Record(recorder));
+    }
+
     #[inline]
     pub fn send_resize(
         &self,
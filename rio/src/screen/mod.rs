@@ -1,43 +1,184 @@
+pub mod bar;
+mod bell;
 mod bindings;
+mod confirm_quit;
+mod context_menu;
+mod file_link;
+mod hints;
+pub mod latency;
 mod messenger;
+mod palette;
+mod paste;
+mod prompt;
+mod rich_text;
+mod scroll_indicator;
+mod size_overlay;
+pub mod smooth_scroll;
 mod state;
+mod url;
 pub mod window;
 
-use crate::clipboard::{Clipboard, ClipboardType};
-use crate::crosswords::{
-    grid::Scroll,
-    pos::{Pos, Side},
-    Crosswords, Mode,
-};
-use crate::event::sync::FairMutex;
-use crate::event::{ClickState, EventProxy};
+use crate::cli::Cli;
+use crate::clipboard::Clipboard;
+use crate::crash;
+use crate::event::{ClickState, EventProxy, RioEventType};
 use crate::ime::Ime;
+use crate::ipc::{Request, Response, TextExtent, TextFormat, NEW_INSTANCE_ENV};
 use crate::layout::Layout;
-use crate::performer::Machine;
-use crate::screen::bindings::{Action as Act, BindingMode, Key};
-use crate::selection::{Selection, SelectionType};
+use crate::logger::Span;
+use crate::player::{Player, Recording};
+use crate::screen::bell::Bell;
+use crate::screen::bindings::{Action as Act, BindingMode, ChordInput, Key};
+use crate::screen::hints::{HintKind, Hints};
+use crate::session;
+use crate::speech;
 use crate::tabs::TabsControl;
+use context_menu::ContextMenu;
+use latency::Latency;
 use messenger::Messenger;
+use palette::Palette;
+use rio_core::clipboard::ClipboardType;
+use rio_core::crosswords::{
+    grid::{Dimensions, Scroll},
+    pos::{Column, Line, Pos, Side},
+    square::Square,
+    Crosswords, Mode, Snapshot,
+};
+use rio_core::event::sync::FairMutex;
+use rio_core::performer::handler::ParserProcessor;
+use rio_core::performer::{FlowControl, Machine};
+use rio_core::recorder::Recorder;
+use rio_core::selection::{Selection, SelectionType};
+use scroll_indicator::ScrollIndicator;
+use size_overlay::SizeOverlay;
+use smooth_scroll::SmoothScroll;
 use state::State;
 use std::borrow::Cow;
 use std::error::Error;
+use std::ffi::{CString, OsString};
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::FromRawFd;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sugarloaf::components::background_image::ImageFit;
+use sugarloaf::components::post_process::{self, ColorBlindness};
+use sugarloaf::components::rect::Rect;
+use sugarloaf::components::text::Antialiasing;
+use sugarloaf::context::AdapterOptions;
+use sugarloaf::core::SugarloafStyle;
+use sugarloaf::font::rasterizer::Rasterizer;
 use sugarloaf::Sugarloaf;
-use teletypewriter::create_pty;
+use teletypewriter::{create_pty, create_pty_with_command, Foreground};
+use winit::event::{ElementState, ModifiersState, MouseButton};
 
 pub struct Screen {
+    bar: config::Bar,
+    bar_hovered: bool,
     bindings: bindings::KeyBindings,
+    chords: bindings::Chords,
+    held_keys: bindings::HeldKeys,
+    mouse_bindings: bindings::MouseBindings,
+    confirm_quit: config::ConfirmQuit,
+    /// Program shown in the prompt confirming to quit, while it is open.
+    quit_prompt: Option<String>,
+    paste_config: config::Paste,
+    /// Text waiting for the prompt confirming to paste it.
+    paste_prompt: Option<String>,
+    foreground: Option<Foreground>,
+    palette: Palette,
+    hints: Option<Hints>,
+    context_menu: Option<ContextMenu>,
+    /// Matches prompts when the shell doesn't mark them.
+    prompt_pattern: Option<regex::Regex>,
+    command_marks: bool,
+    /// Command template opening `path:line` links.
+    link_editor: String,
+    link_schemes: Vec<String>,
+    event_proxy: EventProxy,
     clipboard: Clipboard,
+    primary_selection: bool,
+    recording: Option<PathBuf>,
+    /// Whether `send-text` and `send-key` requests may write to the PTY.
+    allow_remote_input: bool,
+    /// Whether hyperlinks set by applications are highlighted and opened.
+    hyperlinks: bool,
+    /// Whether Secure Keyboard Entry is on (macOS).
+    secure_input: bool,
     ignore_chars: bool,
     layout: Layout,
     pub ime: Ime,
     pub messenger: Messenger,
+    smooth_scroll: SmoothScroll,
+    scroll_indicator: ScrollIndicator,
+    size_overlay: SizeOverlay,
+    /// Whether interactive resizing snaps to whole cells.
+    resize_increments: bool,
+    latency: Latency,
+    click_to_jump: bool,
+    animate_post_processing: bool,
     state: State,
+    style: config::Style,
     sugarloaf: Sugarloaf,
     terminal: Arc<FairMutex<Crosswords<EventProxy>>>,
-    #[allow(unused)]
+    /// Terminal drawn in the last frame, drawn again while the parser holds
+    /// the terminal.
+    snapshot: Option<Snapshot>,
+    flow: Arc<FlowControl>,
     tabs: TabsControl,
+    bell: Bell,
+    speaker: speech::Speaker,
+}
+
+/// Path from the configuration, which may start with `~/`.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(relative), Some(home)) => home.join(relative),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Read post-processing shaders, skipping the ones that can't be read, and
+/// add the color filter after them.
+fn load_post_processing_shaders(post_processing: &config::PostProcessing) -> Vec<String> {
+    let mut shaders: Vec<String> = post_processing
+        .shaders
+        .iter()
+        .filter_map(|path| {
+            let path = expand_home(path);
+            match std::fs::read_to_string(&path) {
+                Ok(source) => Some(source),
+                Err(err) => {
+                    log::error!("unable to read shader {}: {err}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let color_filter = match post_processing.color_filter {
+        config::ColorFilter::None => None,
+        config::ColorFilter::Protanopia => Some((ColorBlindness::Protanopia, false)),
+        config::ColorFilter::Deuteranopia => Some((ColorBlindness::Deuteranopia, false)),
+        config::ColorFilter::Tritanopia => Some((ColorBlindness::Tritanopia, false)),
+        config::ColorFilter::DaltonizeProtanopia => {
+            Some((ColorBlindness::Protanopia, true))
+        }
+        config::ColorFilter::DaltonizeDeuteranopia => {
+            Some((ColorBlindness::Deuteranopia, true))
+        }
+        config::ColorFilter::DaltonizeTritanopia => {
+            Some((ColorBlindness::Tritanopia, true))
+        }
+    };
+    if let Some((color_blindness, daltonize)) = color_filter {
+        shaders.push(post_process::color_filter(color_blindness, daltonize));
+    }
+
+    shaders
 }
 
 impl Screen {
@@ -45,8 +186,8 @@ impl Screen {
         winit_window: &winit::window::Window,
         config: &Rc<config::Config>,
         event_proxy: EventProxy,
+        cli: &Cli,
     ) -> Result<Screen, Box<dyn Error>> {
-        let shell = std::env::var("SHELL")?;
         let size = winit_window.inner_size();
         let scale = winit_window.scale_factor();
 
@@ -56,49 +197,221 @@ impl Screen {
             scale as f32,
             config.style.font_size,
         );
+        layout.mouse_mut().multiplier = config.scroll.multiplier;
+        layout.set_bar(config.bar.position, config.bar.height);
+        layout.set_bar_reserved(bar::is_reserved(&config.bar, 1));
+        layout.update();
         let (columns, rows) = layout.compute();
-        let pty = create_pty(&Cow::Borrowed(&shell), columns as u16, rows as u16);
 
         let power_preference: wgpu::PowerPreference = match config.performance {
             config::Performance::High => wgpu::PowerPreference::HighPerformance,
             config::Performance::Low => wgpu::PowerPreference::LowPower,
         };
 
-        let sugarloaf = Sugarloaf::new(
-            winit_window,
+        let rasterizer = match config.style.font_rasterizer {
+            config::FontRasterizer::AbGlyph => Rasterizer::AbGlyph,
+            config::FontRasterizer::Swash => Rasterizer::Swash,
+        };
+
+        let adapter_options = AdapterOptions {
             power_preference,
+            name: config.advanced.adapter_name.clone(),
+            force_fallback: config.advanced.force_fallback_adapter,
+        };
+
+        let mut sugarloaf = Sugarloaf::new(
+            winit_window,
+            adapter_options,
             config.style.font.to_string(),
+            rasterizer,
         )
         .await?;
+        sugarloaf
+            .set_text_blending(config.style.text_gamma, config.style.text_contrast)
+            .set_sample_count(config.advanced.sample_count);
+        sugarloaf.warm_up(layout.styles.term.text_scale);
+        let shaders = load_post_processing_shaders(&config.post_processing);
+        if let Err(err) = sugarloaf.set_post_processing(&shaders) {
+            log::error!("unable to enable post-processing: {err}");
+        }
+        if let Some(path) = &config.background_image.path {
+            let fit = match config.background_image.mode {
+                config::BackgroundImageMode::Fit => ImageFit::Fit,
+                config::BackgroundImageMode::Fill => ImageFit::Fill,
+                config::BackgroundImageMode::Tile => ImageFit::Tile,
+            };
+            let result = std::fs::read(expand_home(path))
+                .map_err(|err| err.to_string())
+                .and_then(|data| {
+                    sugarloaf.set_background_image(
+                        &data,
+                        config.background_image.opacity,
+                        fit,
+                    )
+                });
+            if let Err(err) = result {
+                log::error!("unable to load background image {path}: {err}");
+            }
+        }
+        crash::set_renderer(&sugarloaf.ctx.adapter_info, sugarloaf.ctx.format);
+        crash::set_window_size(sugarloaf.ctx.size.width, sugarloaf.ctx.size.height);
 
         let state = State::new(config);
 
         let event_proxy_clone = event_proxy.clone();
+        let window_events = event_proxy.clone();
         let terminal: Arc<FairMutex<Crosswords<EventProxy>>> =
             Arc::new(FairMutex::new(Crosswords::new(columns, rows, event_proxy)));
 
-        let machine = Machine::new(Arc::clone(&terminal), pty, event_proxy_clone)?;
-        let channel = machine.channel();
-        machine.spawn();
-        let messenger = Messenger::new(channel);
+        terminal
+            .lock()
+            .set_reader_mode(config.accessibility.reader_mode && !cli.bench);
+        terminal
+            .lock()
+            .set_semantic_escape_chars(&config.selection.word_separators);
+        terminal
+            .lock()
+            .set_copy_hidden_text(config.selection.copy_hidden_text);
+        terminal
+            .lock()
+            .set_term_name(teletypewriter::terminfo(&config.term));
+        terminal.lock().set_truecolor(config.features.truecolor);
+        terminal
+            .lock()
+            .set_clipboard_write(config.features.clipboard_write);
+        terminal.lock().set_history_limit(
+            config.scroll.history,
+            config.scroll.history_memory_limit * 1024 * 1024,
+        );
+
+        if config.session.restore
+            && cli.play.is_none()
+            && !cli.bench
+            && cli.command.is_none()
+        {
+            if let Err(err) = session::restore(&mut terminal.lock()) {
+                log::warn!("unable to restore session: {err}");
+            }
+        }
+
+        let (channel, flow, foreground) = match &cli.play {
+            // Benchmarks feed the parser directly, there is nothing to read from.
+            _ if cli.bench => {
+                let (channel, _) = mio_extras::channel::channel();
+                (channel, Arc::new(FlowControl::default()), None)
+            }
+            Some(path) => {
+                let recording = Recording::load(path)?;
+                let player =
+                    Player::new(Arc::clone(&terminal), recording, event_proxy_clone);
+                let channel = player.channel();
+                player.spawn();
+                (channel, Arc::new(FlowControl::default()), None)
+            }
+            None => {
+                let pty = match cli.command.as_deref() {
+                    Some([program, args @ ..]) => create_pty_with_command(
+                        program,
+                        args,
+                        columns as u16,
+                        rows as u16,
+                    ),
+                    _ => {
+                        let shell = std::env::var("SHELL")?;
+                        create_pty(&Cow::Borrowed(&shell), columns as u16, rows as u16)
+                    }
+                };
+                let foreground = pty.foreground();
+                let machine =
+                    Machine::new(Arc::clone(&terminal), pty, event_proxy_clone)?;
+                let channel = machine.channel();
+                let flow = machine.flow();
+                machine.spawn();
+                (channel, flow, Some(foreground))
+            }
+        };
+        let mut messenger = Messenger::new(channel);
+        // Commands from the session file, read by the shell once it starts.
+        if cli.play.is_none() && !cli.bench {
+            for command in &cli.commands {
+                messenger.send_bytes(format!("{command}\r").into_bytes());
+            }
+        }
 
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        let clipboard = {
+            use winit::platform::wayland::WindowExtWayland;
+            unsafe { Clipboard::new(winit_window.wayland_display()) }
+        };
+        #[cfg(any(not(feature = "wayland"), target_os = "macos", windows))]
         let clipboard = Clipboard::new();
-        let bindings = bindings::default_key_bindings();
+        let bindings = bindings::key_bindings(&config.key_bindings);
+        let chords = bindings::chords(&config.key_bindings, &bindings);
         let ime = Ime::new();
         let tabs = TabsControl::new();
-
-        Ok(Screen {
+        let smooth_scroll = SmoothScroll::new(config.scroll.smooth);
+        let animate_post_processing =
+            config.post_processing.animate && sugarloaf.has_post_processing();
+
+        let mut profiles: Vec<String> = config.profiles.keys().cloned().collect();
+        profiles.sort();
+
+        let mut screen = Screen {
+            bar: config.bar.clone(),
+            bar_hovered: false,
+            confirm_quit: config.confirm_quit.clone(),
+            quit_prompt: None,
+            paste_config: config.paste.clone(),
+            paste_prompt: None,
+            foreground,
+            palette: Palette::new(&profiles),
+            hints: None,
+            context_menu: None,
+            prompt_pattern: prompt::pattern(&config.shell_integration),
+            command_marks: config.shell_integration.command_marks,
+            link_editor: config.links.editor.clone(),
+            link_schemes: config.links.schemes.clone(),
+            bell: Bell::new(&config.bell),
+            speaker: speech::Speaker::default(),
+            event_proxy: window_events,
             tabs,
             ime,
             sugarloaf,
             terminal,
+            snapshot: None,
+            flow,
             layout,
             messenger,
+            smooth_scroll,
+            scroll_indicator: ScrollIndicator::new(config.scroll.indicator),
+            size_overlay: SizeOverlay::new(columns, rows),
+            resize_increments: config.window.resize_increments,
+            latency: Latency::new(config.advanced.latency_mode),
+            click_to_jump: config.scroll.click_to_jump,
+            animate_post_processing,
             state,
+            style: config.style.clone(),
             bindings,
+            chords,
+            held_keys: bindings::HeldKeys::default(),
+            mouse_bindings: bindings::mouse_bindings(&config.mouse_bindings),
             clipboard,
+            primary_selection: config.selection.primary,
+            recording: None,
+            allow_remote_input: config.remote_control.allow_input,
+            hyperlinks: config.features.hyperlinks,
+            secure_input: false,
             ignore_chars: false,
-        })
+        };
+
+        // Playback does not read from a PTY, so there is nothing to record.
+        if let (Some(path), None) = (&cli.record, &cli.play) {
+            screen.start_recording(path.clone());
+        }
+
+        screen.update_display(winit_window);
+
+        Ok(screen)
     }
 
     #[inline]
@@ -111,6 +424,11 @@ impl Screen {
         self.clipboard.get(clipboard_type)
     }
 
+    #[inline]
+    pub fn clipboard_store(&mut self, clipboard_type: ClipboardType, text: String) {
+        self.clipboard.set(clipboard_type, text);
+    }
+
     pub fn input_character(&mut self, character: char) {
         if self.ime.preedit().is_some() {
             return;
@@ -122,6 +440,25 @@ impl Screen {
             return;
         }
 
+        if self.palette.is_open() {
+            if !character.is_control() {
+                self.palette.push(character);
+                self.render();
+            }
+            return;
+        }
+
+        if let Some(hints) = &mut self.hints {
+            if !character.is_control() {
+                if let Some(text) = hints.push(character) {
+                    self.clipboard.set(ClipboardType::Clipboard, text);
+                    self.hints = None;
+                }
+                self.render();
+            }
+            return;
+        }
+
         let utf8_len = character.len_utf8();
         let mut bytes = vec![0; utf8_len];
         character.encode_utf8(&mut bytes[..]);
@@ -137,15 +474,17 @@ impl Screen {
         }
 
         self.messenger.send_bytes(bytes);
+        self.latency.keystroke();
     }
 
     #[inline]
-    pub fn scroll_bottom_when_cursor_not_visible(&self) {
+    pub fn scroll_bottom_when_cursor_not_visible(&mut self) {
         let mut terminal = self.terminal.lock();
         if terminal.display_offset() != 0 {
             terminal.scroll_display(Scroll::Bottom);
         }
         drop(terminal);
+        self.smooth_scroll.reset();
     }
 
     #[inline]
@@ -175,12 +514,59 @@ impl Screen {
         virtual_keycode: Option<winit::event::VirtualKeyCode>,
         scancode: u32,
     ) {
+        let repeat = self.held_keys.press(scancode);
         if self.ime.preedit().is_some() {
             return;
         }
 
+        if self.quit_prompt.is_some() {
+            self.answer_quit_prompt(virtual_keycode);
+            self.ignore_chars = true;
+            return;
+        }
+
+        if self.paste_prompt.is_some() {
+            self.answer_paste_prompt(virtual_keycode);
+            self.ignore_chars = true;
+            return;
+        }
+
+        if self.context_menu.is_some() {
+            self.input_context_menu(virtual_keycode);
+            self.ignore_chars = true;
+            return;
+        }
+
+        if self.palette.is_open() {
+            self.ignore_chars = self.input_palette(virtual_keycode);
+            return;
+        }
+
+        if self.hints.is_some() {
+            self.ignore_chars = self.input_hints(virtual_keycode);
+            return;
+        }
+
         let mode = BindingMode::new(&self.get_mode());
         let mods = self.messenger.get_modifiers();
+        if let Some(key) = virtual_keycode {
+            let now = Instant::now();
+            match self.chords.input(key, mods, mode.clone(), repeat, now) {
+                ChordInput::Unbound => (),
+                ChordInput::Swallowed => {
+                    self.ignore_chars = true;
+                    self.render();
+                    return;
+                }
+                ChordInput::Run(action) => {
+                    self.ignore_chars = true;
+                    self.run_key_action(&action);
+                    self.render();
+                    return;
+                }
+            }
+        }
+
         let mut ignore_chars = None;
 
         for i in 0..self.bindings.len() {
@@ -194,161 +580,1101 @@ impl Screen {
 
             if binding.is_triggered_by(mode.clone(), mods, &key) {
                 *ignore_chars.get_or_insert(true) &= binding.action != Act::ReceiveChar;
-
-                match &binding.action {
-                    Act::Esc(s) => {
-                        self.messenger.send_bytes(
-                            s.replace("\r\n", "\r").replace('\n', "\r").into_bytes(),
-                        );
-                    }
-                    Act::Paste => {
-                        let content = self.clipboard.get(ClipboardType::Clipboard);
-                        self.paste(&content, true);
-                    }
-                    Act::PasteSelection => {
-                        let content = self.clipboard.get(ClipboardType::Selection);
-                        self.paste(&content, true);
-                    }
-                    Act::Copy => {
-                        self.copy_selection(ClipboardType::Clipboard);
-                    }
-                    Act::TabCreateNew => {
-                        self.tabs.add_tab(true);
-                        self.render();
-                    }
-                    Act::TabSwitchNext => {
-                        self.tabs.switch_to_next();
-                        self.render();
-                    }
-                    Act::ReceiveChar | Act::None => (),
-                    _ => (),
+                if repeat && !binding.repeat {
+                    continue;
                 }
+
+                let action = binding.action.clone();
+                self.run_key_action(&action);
             }
         }
 
         self.ignore_chars = ignore_chars.unwrap_or(false);
     }
 
-    pub fn copy_selection(&mut self, ty: ClipboardType) {
-        let terminal = self.terminal.lock();
-        let text = match terminal.selection_to_string().filter(|s| !s.is_empty()) {
-            Some(text) => text,
-            None => return,
-        };
-        drop(terminal);
-
-        if ty == ClipboardType::Selection {
-            self.clipboard.set(ClipboardType::Clipboard, text.clone());
+    /// Run the action of a key binding, whose text is typed input.
+    fn run_key_action(&mut self, action: &Act) {
+        self.run_action(action);
+        if let Act::Esc(_) = action {
+            self.latency.keystroke();
         }
-        self.clipboard.set(ty, text);
     }
 
-    // fn on_mouse_release(&mut self, button: MouseButton) {
-    //     if !self.ctx.modifiers().shift() && self.ctx.mouse_mode() {
-    //         let code = match button {
-    //             MouseButton::Left => 0,
-    //             MouseButton::Middle => 1,
-    //             MouseButton::Right => 2,
-    //             // Can't properly report more than three buttons.
-    //             MouseButton::Other(_) => return,
-    //         };
-    //         self.mouse_report(code, ElementState::Released);
-    //         return;
-    //     }
+    /// The key with `scancode` was released, so pressing it again is not a
+    /// repeat.
+    #[inline]
+    pub fn release_keycode(&mut self, scancode: u32) {
+        self.held_keys.release(scancode);
+    }
 
-    //     // Trigger hints highlighted by the mouse.
-    //     let hint = self.ctx.display().highlighted_hint.take();
-    //     if let Some(hint) = hint.as_ref().filter(|_| button == MouseButton::Left) {
-    //         self.ctx.trigger_hint(hint);
-    //     }
-    //     self.ctx.display().highlighted_hint = hint;
+    fn run_action(&mut self, action: &Act) {
+        match action {
+            Act::Esc(s) => {
+                self.messenger.send_bytes(
+                    s.replace("\r\n", "\r").replace('\n', "\r").into_bytes(),
+                );
+            }
+            Act::Paste => {
+                let content = self.clipboard.get(ClipboardType::Clipboard);
+                self.paste(&content, true);
+            }
+            Act::PasteSelection => {
+                self.paste_primary_selection();
+            }
+            Act::Copy => {
+                self.copy_selection(ClipboardType::Clipboard);
+            }
+            Act::CopyRichText => {
+                self.copy_selection_as_rich_text();
+            }
+            Act::CopyLastOutput => {
+                self.copy_last_output();
+            }
+            Act::ToggleRecording => {
+                self.toggle_recording();
+            }
+            Act::ToggleBellMute => {
+                self.bell.toggle_mute();
+                log::info!("bell muted: {}", self.bell.is_muted());
+            }
+            Act::ToggleSecureKeyboardEntry => {
+                if let Err(err) = self.set_secure_input(!self.secure_input) {
+                    log::error!("{err}");
+                }
+            }
+            Act::SaveScreen => {
+                self.save_buffer(false);
+            }
+            Act::SaveScrollback => {
+                self.save_buffer(true);
+            }
+            Act::Screenshot => {
+                if let Err(err) = self.screenshot(None) {
+                    log::error!("unable to save screenshot: {err}");
+                }
+            }
+            Act::OpenScrollbackInPager => {
+                let pager =
+                    std::env::var("PAGER").unwrap_or(String::from("less"));
+                self.open_scrollback_with(&pager);
+            }
+            Act::OpenScrollbackInEditor => {
+                let editor =
+                    std::env::var("EDITOR").unwrap_or(String::from("vi"));
+                self.open_scrollback_with(&editor);
+            }
+            Act::TabCreateNew => {
+                self.tabs.add_tab(true);
+                self.update_bar_space();
+                self.render();
+            }
+            Act::Quit => {
+                if self.request_quit() {
+                    self.event_proxy.send_event(RioEventType::Quit);
+                }
+            }
+            Act::ToggleFullscreen => {
+                self.event_proxy.send_event(RioEventType::ToggleFullscreen);
+            }
+            Act::ToggleMaximized => {
+                self.event_proxy.send_event(RioEventType::ToggleMaximized);
+            }
+            Act::TabSwitchNext => {
+                self.tabs.switch_to_next();
+                self.render();
+            }
+            Act::TabSwitchPrev => {
+                self.tabs.switch_to_prev();
+                self.render();
+            }
+            Act::ScrollPageUp => {
+                self.scroll_display(Scroll::PageUp);
+            }
+            Act::ScrollPageDown => {
+                self.scroll_display(Scroll::PageDown);
+            }
+            Act::ScrollHalfPageUp => {
+                let scroll_amount = self.layout.rows as i32 / 2;
+                self.scroll_display(Scroll::Delta(scroll_amount));
+            }
+            Act::ScrollHalfPageDown => {
+                let scroll_amount = -(self.layout.rows as i32 / 2);
+                self.scroll_display(Scroll::Delta(scroll_amount));
+            }
+            Act::ScrollLineUp => {
+                self.scroll_display(Scroll::Delta(1));
+            }
+            Act::ScrollLineDown => {
+                self.scroll_display(Scroll::Delta(-1));
+            }
+            Act::ScrollToTop => {
+                self.scroll_display(Scroll::Top);
+            }
+            Act::ScrollToBottom => {
+                self.scroll_display(Scroll::Bottom);
+            }
+            Act::ScrollToPreviousPrompt => {
+                self.scroll_to_prompt(true);
+            }
+            Act::ScrollToNextPrompt => {
+                self.scroll_to_prompt(false);
+            }
+            Act::ToggleCommandPalette => {
+                self.palette.toggle();
+                self.render();
+            }
+            Act::Hints(kind) => {
+                self.open_hints(*kind);
+            }
+            Act::OpenProfile(profile) => {
+                let result = std::env::current_exe().and_then(|program| {
+                    std::process::Command::new(program)
+                        .arg("--profile")
+                        .arg(profile)
+                        .env(NEW_INSTANCE_ENV, "1")
+                        .spawn()
+                });
+                if let Err(err) = result {
+                    log::error!("unable to open profile {profile}: {err}");
+                }
+            }
+            Act::OpenLink => {
+                let point = self.layout.mouse_position(self.display_offset());
+                self.open_link(point);
+            }
+            Act::ClearHistory => {
+                self.terminal.lock().clear_history();
+                self.render();
+            }
+            Act::ClearScreenAndHistory => {
+                self.terminal.lock().clear_screen_and_history();
+                self.clear_selection();
+                self.render();
+            }
+            Act::SelectAll => {
+                self.select_all();
+                self.render();
+            }
+            Act::ExpandSelectionToWord
+            | Act::ExpandSelectionToLine
+            | Act::ExpandSelectionToOutput => {
+                self.expand_selection(action);
+                self.render();
+            }
+            Act::ContextMenu => {
+                self.open_context_menu();
+            }
+            Act::ReceiveChar | Act::None => (),
+            _ => (),
+        }
+    }
 
-    //     let timer_id = TimerId::new(Topic::SelectionScrolling, self.ctx.window().id());
-    //     self.ctx.scheduler_mut().unschedule(timer_id);
+    /// Handle a key pressed while the command palette is open. Returns
+    /// whether the character it produces goes to the query.
+    fn input_palette(&mut self, key: Option<winit::event::VirtualKeyCode>) -> bool {
+        use winit::event::VirtualKeyCode;
 
-    //     if let MouseButton::Left | MouseButton::Right = button {
-    //         // Copy selection on release, to prevent flooding the display server.
-    //         self.ctx.copy_selection(ClipboardType::Selection);
-    //     }
-    // }
+        match key {
+            Some(VirtualKeyCode::Escape) => self.palette.close(),
+            Some(VirtualKeyCode::Up) => self.palette.select_previous(),
+            Some(VirtualKeyCode::Down | VirtualKeyCode::Tab) => {
+                self.palette.select_next()
+            }
+            Some(VirtualKeyCode::Back) => self.palette.pop(),
+            Some(VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter) => {
+                if let Some(action) = self.palette.accept() {
+                    self.run_action(&action);
+                }
+            }
+            _ => return false,
+        }
 
-    pub fn clear_selection(&mut self) {
-        // Clear the selection on the terminal.
-        let mut terminal = self.terminal.lock();
-        terminal.selection.take();
-        drop(terminal);
-        self.state.set_selection(None);
+        self.render();
+        true
     }
 
-    fn start_selection(&mut self, ty: SelectionType, point: Pos, side: Side) {
-        self.copy_selection(ClipboardType::Selection);
-        let mut terminal = self.terminal.lock();
-        terminal.selection = Some(Selection::new(ty, point, side));
-        drop(terminal);
+    fn open_context_menu(&mut self) {
+        let line_height = self.layout.styles.term.text_scale;
+        let mouse = &self.layout.mouse;
+        let position = (mouse.x as f32, mouse.y as f32);
+        let point = self.layout.mouse_position(self.display_offset());
+        self.context_menu = Some(ContextMenu::new(
+            position,
+            (self.layout.width, self.layout.height),
+            (context_menu::COLUMNS + 2) as f32 * line_height / 2.0,
+            line_height,
+            point,
+        ));
+        self.render();
     }
 
-    #[allow(dead_code)]
-    pub fn update_selection_scrolling(&self, _mouse_y: f64) {
-        // println!("{:?}", mouse_y);
+    /// Run the entry `index` of the context menu and close it.
+    fn run_context_menu_entry(&mut self, index: usize) {
+        let menu = match self.context_menu.take() {
+            Some(menu) => menu,
+            None => return,
+        };
+
+        match &context_menu::ENTRIES[index].1 {
+            Act::OpenLink => {
+                self.open_link(menu.point);
+            }
+            action => self.run_action(action),
+        }
+        self.render();
     }
 
-    // pub fn update_selection(&mut self, mut point: Pos, side: Side) {
-    pub fn update_selection(&mut self, mut point: Pos) {
-        let mut terminal = self.terminal.lock();
-        let mut selection = match terminal.selection.take() {
-            Some(selection) => selection,
+    fn input_context_menu(&mut self, key: Option<winit::event::VirtualKeyCode>) {
+        use winit::event::VirtualKeyCode;
+
+        let menu = match &mut self.context_menu {
+            Some(menu) => menu,
             None => return,
         };
 
-        // Treat motion over message bar like motion over the last line.
-        point.row = std::cmp::min(point.row, terminal.bottommost_line());
+        match key {
+            Some(VirtualKeyCode::Escape) => self.context_menu = None,
+            Some(VirtualKeyCode::Up) => menu.select_previous(),
+            Some(VirtualKeyCode::Down) => menu.select_next(),
+            Some(VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter) => {
+                if let Some(index) = menu.selected {
+                    self.run_context_menu_entry(index);
+                }
+                return;
+            }
+            _ => return,
+        }
+        self.render();
+    }
 
-        // Update selection.
-        // selection.update(point, side);
-        selection.update(point, Side::Left);
+    /// Handle a mouse press while the context menu is open, running the
+    /// entry under the mouse or closing the menu. Returns whether the menu
+    /// was open.
+    pub fn click_context_menu(&mut self, button: MouseButton) -> bool {
+        let menu = match &self.context_menu {
+            Some(menu) => menu,
+            None => return false,
+        };
 
-        // Move vi cursor and expand selection.
-        if terminal.mode().contains(Mode::VI) {
-            terminal.vi_mode_cursor = point;
-            selection.include_all();
+        let mouse = &self.layout.mouse;
+        match menu.entry_at(mouse.x as f32, mouse.y as f32) {
+            Some(index) if button == MouseButton::Left => {
+                self.run_context_menu_entry(index)
+            }
+            _ => self.context_menu = None,
         }
+        true
+    }
 
-        self.state.set_selection(selection.to_range(&terminal));
-        terminal.selection = Some(selection);
-        drop(terminal);
+    /// Highlight the entry of the context menu under the mouse. Returns
+    /// whether it changed.
+    pub fn update_context_menu_hover(&mut self) -> bool {
+        let mouse = &self.layout.mouse;
+        let menu = match &mut self.context_menu {
+            Some(menu) => menu,
+            None => return false,
+        };
+
+        let hovered = menu.entry_at(mouse.x as f32, mouse.y as f32);
+        let changed = hovered.is_some() && menu.selected != hovered;
+        if changed {
+            menu.selected = hovered;
+        }
+        changed
     }
 
-    #[inline]
-    #[allow(unused)]
-    pub fn selection_is_empty(&self) -> bool {
+    fn queue_context_menu(&mut self) {
+        let menu = match &self.context_menu {
+            Some(menu) => menu,
+            None => return,
+        };
+
+        let scale = self.sugarloaf.get_scale();
+        let mut background = self.state.background_color();
+        background[3] = 0.95;
+        self.sugarloaf.add_rect(Rect::from_pixels(
+            [menu.left, menu.top],
+            [menu.width, menu.height()],
+            background,
+            scale,
+        ));
+
+        let style = self.layout.styles.term;
+        let color = self.state.foreground_color();
+        let mut highlight = color;
+        highlight[3] = 0.2;
+        for (index, (name, _)) in context_menu::ENTRIES.iter().enumerate() {
+            let top = menu.top + index as f32 * menu.line_height;
+            if menu.selected == Some(index) {
+                self.sugarloaf.add_rect(Rect::from_pixels(
+                    [menu.left, top],
+                    [menu.width, menu.line_height],
+                    highlight,
+                    scale,
+                ));
+            }
+            let style = SugarloafStyle {
+                screen_position: (menu.left + menu.line_height / 2.0, top),
+                ..style
+            };
+            self.sugarloaf.label(name, style, color, false);
+        }
+    }
+
+    /// Label the targets of `kind` on the screen.
+    fn open_hints(&mut self, kind: HintKind) {
         let terminal = self.terminal.lock();
-        let is_empty = terminal.selection.is_none();
+        let display_offset = terminal.grid.display_offset() as i32;
+        let lines: Vec<String> = (0..terminal.grid.screen_lines() as i32)
+            .map(|line| terminal.line_text(Line(line - display_offset)))
+            .collect();
         drop(terminal);
-        is_empty
+
+        self.hints = Hints::new(&lines, kind);
+        self.render();
     }
 
-    pub fn on_left_click(&mut self, point: Pos) {
-        let side = self.layout.mouse.square_side;
+    /// Handle a key pressed while hints are shown. Returns whether the
+    /// character it produces picks a hint.
+    fn input_hints(&mut self, key: Option<winit::event::VirtualKeyCode>) -> bool {
+        use winit::event::VirtualKeyCode;
 
-        match self.layout.mouse.click_state {
-            ClickState::Click => {
-                self.clear_selection();
+        match (key, &mut self.hints) {
+            (Some(VirtualKeyCode::Escape), _) => self.hints = None,
+            (Some(VirtualKeyCode::Back), Some(hints)) => hints.pop(),
+            _ => return false,
+        }
 
-                // Start new empty selection.
-                if self.messenger.get_modifiers().ctrl() {
-                    self.start_selection(SelectionType::Block, point, side);
-                } else {
-                    self.start_selection(SelectionType::Simple, point, side);
-                }
-            }
-            ClickState::DoubleClick => {
-                self.start_selection(SelectionType::Semantic, point, side);
-            }
-            ClickState::TripleClick => {
-                self.start_selection(SelectionType::Lines, point, side);
-            }
-            ClickState::None => (),
-        };
+        self.render();
+        true
+    }
+
+    fn queue_hints(&mut self) {
+        let hints = match &self.hints {
+            Some(hints) => hints,
+            None => return,
+        };
+
+        let scale = self.sugarloaf.get_scale();
+        let style = self.layout.styles.term;
+        let line_height = style.text_scale;
+        let column_width = style.text_scale / 2.0;
+        let (background, foreground) = self.state.hint_colors();
+        let typed = hints.typed().len();
+
+        for hint in hints.visible() {
+            let label = &hint.label[typed..];
+            let position = (
+                style.screen_position.0 + hint.column as f32 * column_width,
+                style.screen_position.1 + hint.line as f32 * line_height,
+            );
+            self.sugarloaf.add_rect(Rect::from_pixels(
+                [position.0, position.1],
+                [label.len() as f32 * column_width, line_height],
+                background,
+                scale,
+            ));
+            self.sugarloaf.label(
+                label,
+                SugarloafStyle {
+                    screen_position: position,
+                    ..style
+                },
+                foreground,
+                false,
+            );
+        }
+    }
+
+    fn queue_palette(&mut self) {
+        let scale = self.sugarloaf.get_scale();
+        let line_height = self.layout.styles.term.text_scale;
+        let matches = self.palette.matches();
+        let visible = matches.len().min(palette::MAX_VISIBLE);
+        let left = self.layout.width * 0.2;
+        let width = self.layout.width * 0.6;
+        let top = self.layout.height * 0.1;
+
+        let mut background = self.state.background_color();
+        background[3] = 0.95;
+        self.sugarloaf.add_rect(Rect::from_pixels(
+            [left, top],
+            [width, (visible + 2) as f32 * line_height],
+            background,
+            scale,
+        ));
+
+        let style = self.layout.styles.term;
+        let color = self.state.foreground_color();
+        let mut highlight = color;
+        highlight[3] = 0.2;
+        let style_at = |line: usize| SugarloafStyle {
+            screen_position: (
+                left + 8.0 * scale,
+                top + (line + 1) as f32 * line_height,
+            ),
+            ..style
+        };
+
+        let query = format!("> {}", self.palette.query());
+        self.sugarloaf.label(&query, style_at(0), color, false);
+
+        // Keep the selected command in view.
+        let first = (self.palette.selected() + 1).saturating_sub(visible);
+        for (line, (name, _)) in matches.iter().skip(first).take(visible).enumerate() {
+            if first + line == self.palette.selected() {
+                self.sugarloaf.add_rect(Rect::from_pixels(
+                    [left, top + (line + 1) as f32 * line_height],
+                    [width, line_height],
+                    highlight,
+                    scale,
+                ));
+            }
+            self.sugarloaf.label(name, style_at(line + 1), color, false);
+        }
+    }
+
+    /// Whether the window can be closed right away, otherwise the prompt
+    /// confirming it is shown.
+    pub fn request_quit(&mut self) -> bool {
+        let foreground = self.foreground.and_then(|fg| fg.process_name());
+        match confirm_quit::blocking_program(&self.confirm_quit, foreground) {
+            Some(program) => {
+                self.quit_prompt = Some(program);
+                self.render();
+                false
+            }
+            None => true,
+        }
+    }
+
+    fn answer_quit_prompt(&mut self, key: Option<winit::event::VirtualKeyCode>) {
+        use winit::event::VirtualKeyCode;
+
+        match key {
+            Some(VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter) => {
+                self.event_proxy.send_event(RioEventType::Quit);
+            }
+            Some(VirtualKeyCode::Escape) => {
+                self.quit_prompt = None;
+                self.render();
+            }
+            _ => {}
+        }
+    }
+
+    fn answer_paste_prompt(&mut self, key: Option<winit::event::VirtualKeyCode>) {
+        use winit::event::VirtualKeyCode;
+
+        match key {
+            Some(VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter) => {
+                if let Some(text) = self.paste_prompt.take() {
+                    self.write_paste(&text, true);
+                }
+                self.render();
+            }
+            Some(VirtualKeyCode::Escape) => {
+                self.paste_prompt = None;
+                self.render();
+            }
+            _ => {}
+        }
+    }
+
+    /// Draw the `lines` of a prompt in a band across the middle of the
+    /// window.
+    fn queue_prompt(&mut self, lines: &[String]) {
+        let scale = self.sugarloaf.get_scale();
+        let line_height = self.layout.styles.term.text_scale;
+        let height = (lines.len() + 2) as f32 * line_height;
+        let top = (self.layout.height - height) / 2.0;
+        let mut background = self.state.background_color();
+        background[3] = 0.9;
+        self.sugarloaf.add_rect(Rect::from_pixels(
+            [0.0, top],
+            [self.layout.width, height],
+            background,
+            scale,
+        ));
+
+        let style = self.layout.styles.term;
+        let color = self.state.foreground_color();
+        for (index, line) in lines.iter().enumerate() {
+            let style = SugarloafStyle {
+                screen_position: (
+                    style.screen_position.0,
+                    top + (index + 1) as f32 * line_height,
+                ),
+                ..style
+            };
+            self.sugarloaf.label(line, style, color, false);
+        }
+    }
+
+    pub fn copy_selection(&mut self, ty: ClipboardType) {
+        let terminal = self.terminal.lock();
+        let text = match terminal.selection_to_string().filter(|s| !s.is_empty()) {
+            Some(text) => text,
+            None => return,
+        };
+        drop(terminal);
+
+        if ty == ClipboardType::Selection {
+            // Platforms without a primary selection (e.g. macOS) copy on select
+            // straight into the clipboard instead.
+            if !self.clipboard.has_selection() {
+                self.clipboard.set(ClipboardType::Clipboard, text);
+                return;
+            }
+
+            if !self.primary_selection {
+                return;
+            }
+        }
+
+        self.clipboard.set(ty, text);
+    }
+
+    /// Copy the selection to the clipboard as HTML (and RTF on macOS),
+    /// keeping the colors and styles it is rendered with.
+    pub fn copy_selection_as_rich_text(&mut self) {
+        let terminal = self.terminal.lock();
+        let text = match terminal.selection_to_string().filter(|s| !s.is_empty()) {
+            Some(text) => text,
+            None => return,
+        };
+        let squares = terminal.selection_to_squares().unwrap_or_default();
+        drop(terminal);
+
+        let lines = self.squares_to_spans(&squares);
+        let background = self.state.background_color();
+        let html = rich_text::to_html(&lines, background);
+        let rtf = if cfg!(target_os = "macos") {
+            rich_text::to_rtf(&lines, background)
+        } else {
+            String::new()
+        };
+
+        self.clipboard.set_rich_text(text, html, rtf);
+    }
+
+    /// Runs of the squares with the colors and styles they are rendered with.
+    fn squares_to_spans(&self, squares: &[Vec<Square>]) -> Vec<Vec<rich_text::Span>> {
+        squares
+            .iter()
+            .map(|row| {
+                let mut line = Vec::new();
+                for square in row {
+                    let sugar = self.state.create_sugar_from_square(square);
+                    let (fg, bg) = (sugar.foreground_color, sugar.background_color);
+                    rich_text::push(&mut line, square.c, fg, bg, square.flags);
+                    for c in square.zerowidth().into_iter().flatten() {
+                        rich_text::push(&mut line, *c, fg, bg, square.flags);
+                    }
+                }
+                line
+            })
+            .collect()
+    }
+
+    /// Copy the output of the last command to the clipboard.
+    pub fn copy_last_output(&mut self) {
+        let terminal = self.terminal.lock();
+        let output = prompt::last_output(&terminal, self.prompt_pattern.as_ref());
+        drop(terminal);
+
+        match output {
+            Some(output) => self.clipboard.set(ClipboardType::Clipboard, output),
+            None => log::info!("no output of a last command to copy"),
+        }
+    }
+
+    /// Write the visible screen or the whole scrollback into a temporary file.
+    fn export_buffer(&mut self, scrollback: bool) -> Option<PathBuf> {
+        let terminal = self.terminal.lock();
+        let content = if scrollback {
+            terminal.scrollback_to_string()
+        } else {
+            terminal.screen_to_string()
+        };
+        drop(terminal);
+
+        let name = if scrollback { "scrollback" } else { "screen" };
+        let prefix = format!("rio-{name}-");
+        let result = create_private_file(&prefix, ".txt").and_then(|(path, mut file)| {
+            file.write_all(content.as_bytes())?;
+            Ok(path)
+        });
+
+        match result {
+            Ok(path) => {
+                log::info!("{name} saved to {}", path.display());
+                Some(path)
+            }
+            Err(err) => {
+                log::warn!("unable to save {name}: {err}");
+                None
+            }
+        }
+    }
+
+    /// Save the buffer into a temporary file and copy its path to the clipboard.
+    pub fn save_buffer(&mut self, scrollback: bool) {
+        if let Some(path) = self.export_buffer(scrollback) {
+            self.clipboard
+                .set(ClipboardType::Clipboard, path.to_string_lossy());
+        }
+    }
+
+    /// Save the scrollback into a temporary file and open it with `program`
+    /// (e.g. `$PAGER`) in a new window.
+    pub fn open_scrollback_with(&mut self, program: &str) {
+        let mut command: Vec<String> =
+            program.split_whitespace().map(String::from).collect();
+        if command.is_empty() {
+            log::warn!("no program to open the scrollback with");
+            return;
+        }
+
+        if let Some(path) = self.export_buffer(true) {
+            command.push(String::from("--"));
+            command.push(path.to_string_lossy().to_string());
+            if let Err(err) = open_window_with(&command) {
+                log::error!("unable to open scrollback with {program}: {err}");
+            }
+        }
+    }
+
+    /// Open the hyperlink, URL or file link at `point`. Returns whether
+    /// there was one, even when its scheme isn't allowed.
+    pub fn open_link(&mut self, point: Pos) -> bool {
+        let terminal = self.terminal.lock();
+        let hyperlink = if self.hyperlinks && point.col.0 < terminal.grid.columns() {
+            terminal.grid[point].hyperlink()
+        } else {
+            None
+        };
+        let text = terminal.line_text(point.row);
+        drop(terminal);
+
+        let link = match hyperlink {
+            Some(hyperlink) => hyperlink.uri().to_string(),
+            None => match url::find(&text, point.col.0) {
+                Some(link) => link,
+                None => return self.open_file_link(point),
+            },
+        };
+
+        if url::is_allowed(&link, &self.link_schemes) {
+            url::open(link);
+        } else {
+            log::warn!("not opening {link}, its scheme isn't in links.schemes");
+        }
+        true
+    }
+
+    /// Open the `path:line` link under `point` in the editor. Returns whether
+    /// there was a link to an existing file.
+    fn open_file_link(&mut self, point: Pos) -> bool {
+        let terminal = self.terminal.lock();
+        let text = terminal.line_text(point.row);
+        let cwd = terminal.current_directory().map(PathBuf::from);
+        drop(terminal);
+
+        let link = match file_link::find(&text, point.col.0) {
+            Some(link) => link,
+            None => return false,
+        };
+        let home = dirs::home_dir();
+        let path = file_link::resolve(&link.path, cwd.as_deref(), home.as_deref());
+        if !path.is_file() {
+            return false;
+        }
+
+        let command =
+            file_link::command(&self.link_editor, &path.to_string_lossy(), &link);
+        // `$EDITOR` usually needs a terminal, a configured editor may not.
+        let result = if self.link_editor.is_empty() {
+            open_window_with(&command)
+        } else {
+            match command.split_first() {
+                Some((program, args)) => std::process::Command::new(program)
+                    .args(args)
+                    .spawn()
+                    .map(|_| ()),
+                None => Ok(()),
+            }
+        };
+        if let Err(err) = result {
+            log::error!("unable to open {}: {err}", link.path);
+        }
+        true
+    }
+
+    /// Start recording the session into an asciicast file.
+    pub fn start_recording(&mut self, path: PathBuf) {
+        match Recorder::new(&path, self.layout.columns, self.layout.rows) {
+            Ok(recorder) => {
+                log::info!("recording session to {}", path.display());
+                self.messenger.send_recorder(Some(recorder));
+                self.recording = Some(path);
+            }
+            Err(err) => {
+                log::warn!("unable to record session to {}: {err}", path.display());
+            }
+        }
+    }
+
+    /// Stop the current recording and copy its path to the clipboard,
+    /// otherwise start a new one in the home directory.
+    pub fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(path) => {
+                self.messenger.send_recorder(None);
+                log::info!("recording saved to {}", path.display());
+                self.clipboard
+                    .set(ClipboardType::Clipboard, path.to_string_lossy());
+            }
+            None => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default();
+                let directory = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+                self.start_recording(directory.join(format!("rio-{timestamp}.cast")));
+            }
+        }
+    }
+
+    /// Handle a remote control request.
+    pub fn handle_request(&mut self, request: Request) -> Response {
+        match request {
+            Request::CreateWindow(launch) => {
+                let program = std::env::current_exe().map_err(|err| err.to_string())?;
+                let mut command = std::process::Command::new(program);
+                if let Some(launch) = launch {
+                    command.current_dir(launch.cwd).env_clear().envs(launch.env);
+                }
+                command
+                    .env(NEW_INSTANCE_ENV, "1")
+                    .spawn()
+                    .map_err(|err| err.to_string())?;
+            }
+            Request::CreateTab => self.run_action(&Act::TabCreateNew),
+            Request::SetColors(colors) => {
+                for (name, value) in colors {
+                    self.state
+                        .set_color(&name, colors::hex_to_color_arr(&value))?;
+                    if name == "background" {
+                        self.init(colors::hex_to_color_wgpu(&value));
+                    }
+                }
+                self.render();
+            }
+            Request::SetTheme(theme) => {
+                let colors = config::Config::theme_colors(&theme)
+                    .map_err(|err| format!("unable to load theme {theme}: {err}"))?;
+                self.state.set_colors(colors);
+                self.init(colors.background.1);
+                self.mark_fully_damaged();
+                self.render();
+            }
+            Request::GetText(extent, format) => {
+                let terminal = self.terminal.lock();
+                let bounds = match extent {
+                    TextExtent::Screen => Some(terminal.screen_bounds()),
+                    TextExtent::Scrollback => Some(terminal.scrollback_bounds()),
+                    TextExtent::Lines(first, last) => terminal.lines_bounds(first, last),
+                    TextExtent::Selection => None,
+                };
+
+                if format == TextFormat::Plain {
+                    let text = match (extent, bounds) {
+                        (TextExtent::Selection, _) => {
+                            terminal.selection_to_string().unwrap_or_default()
+                        }
+                        (_, Some((start, end))) => terminal.bounds_to_string(start, end),
+                        (_, None) => String::new(),
+                    };
+                    return Ok(text);
+                }
+
+                let squares = match (extent, bounds) {
+                    (TextExtent::Selection, _) => {
+                        terminal.selection_to_squares().unwrap_or_default()
+                    }
+                    (_, Some((start, end))) => terminal.bounds_to_squares(start, end),
+                    (_, None) => Vec::new(),
+                };
+                drop(terminal);
+
+                return Ok(rich_text::to_json(&self.squares_to_spans(&squares)));
+            }
+            Request::Screenshot(path) => {
+                let path = self.screenshot(path)?;
+                return Ok(path.to_string_lossy().to_string());
+            }
+            Request::SecureInput(enabled) => {
+                self.set_secure_input(enabled.unwrap_or(!self.secure_input))?;
+                let state = if self.secure_input { "on" } else { "off" };
+                return Ok(state.to_string());
+            }
+            Request::SendText(_) | Request::SendKeys(_) if !self.allow_remote_input => {
+                return Err(String::from(
+                    "input is disabled, set allow-input in [remote-control]",
+                ))
+            }
+            Request::SendText(text) => {
+                self.scroll_bottom_when_cursor_not_visible();
+                self.messenger.send_bytes(text.into_bytes());
+            }
+            Request::SendKeys(names) => {
+                let mode = BindingMode::new(&self.get_mode());
+                let mut bytes = Vec::new();
+                for name in names {
+                    let (key, mods) = bindings::parse_key(&name)?;
+                    let key_bytes =
+                        bindings::key_to_bytes(&self.bindings, mode.clone(), key, mods)
+                            .ok_or_else(|| format!("key `{name}` has no input"))?;
+                    bytes.extend(key_bytes);
+                }
+
+                self.scroll_bottom_when_cursor_not_visible();
+                self.messenger.send_bytes(bytes);
+            }
+            // The window is owned by the sequencer, which answers it directly.
+            Request::ToggleDropdown => {
+                return Err(String::from("toggle-dropdown is not handled by the screen"))
+            }
+            // The configuration is owned by the sequencer as well.
+            Request::ReloadConfig => {
+                return Err(String::from("reload-config is not handled by the screen"))
+            }
+        }
+
+        Ok(String::new())
+    }
+
+    /// Save the terminal contents to be restored on the next startup.
+    pub fn save_session(&self) {
+        if let Err(err) = session::save(&self.terminal.lock()) {
+            log::warn!("unable to save session: {err}");
+        }
+    }
+
+    /// Run the mouse bindings triggered by pressing `button`, returning
+    /// whether one handled the click.
+    pub fn process_mouse_bindings(&mut self, button: MouseButton) -> bool {
+        let mut mods = self.messenger.get_modifiers();
+        let mut mode = BindingMode::new(&self.get_mode());
+        // Shift bypasses the mouse reporting of the application.
+        if self.mouse_mode() {
+            mode.set(BindingMode::MOUSE_REPORT, !mods.shift());
+            mods.remove(ModifiersState::SHIFT);
+        }
+
+        let mut handled = false;
+        for i in 0..self.mouse_bindings.len() {
+            let binding = &self.mouse_bindings[i];
+            if !binding.is_triggered_by(mode.clone(), mods, &button) {
+                continue;
+            }
+
+            let action = binding.action.clone();
+            if action == Act::OpenLink {
+                let point = self.layout.mouse_position(self.display_offset());
+                handled |= self.open_link(point);
+            } else {
+                self.run_action(&action);
+                handled = true;
+            }
+        }
+        handled
+    }
+
+    /// Paste the primary selection, usually triggered by middle-click.
+    pub fn paste_primary_selection(&mut self) {
+        if !self.primary_selection || !self.clipboard.has_selection() {
+            return;
+        }
+
+        let content = self.clipboard.get(ClipboardType::Selection);
+        self.paste(&content, true);
+    }
+
+    // fn on_mouse_release(&mut self, button: MouseButton) {
+    //     if !self.ctx.modifiers().shift() && self.ctx.mouse_mode() {
+    //         let code = match button {
+    //             MouseButton::Left => 0,
+    //             MouseButton::Middle => 1,
+    //             MouseButton::Right => 2,
+    //             // Can't properly report more than three buttons.
+    //             MouseButton::Other(_) => return,
+    //         };
+    //         self.mouse_report(code, ElementState::Released);
+    //         return;
+    //     }
+
+    //     // Trigger hints highlighted by the mouse.
+    //     let hint = self.ctx.display().highlighted_hint.take();
+    //     if let Some(hint) = hint.as_ref().filter(|_| button == MouseButton::Left) {
+    //         self.ctx.trigger_hint(hint);
+    //     }
+    //     self.ctx.display().highlighted_hint = hint;
+
+    //     let timer_id = TimerId::new(Topic::SelectionScrolling, self.ctx.window().id());
+    //     self.ctx.scheduler_mut().unschedule(timer_id);
+
+    //     if let MouseButton::Left | MouseButton::Right = button {
+    //         // Copy selection on release, to prevent flooding the display server.
+    //         self.ctx.copy_selection(ClipboardType::Selection);
+    //     }
+    // }
+
+    pub fn clear_selection(&mut self) {
+        // Clear the selection on the terminal.
+        let mut terminal = self.terminal.lock();
+        terminal.selection.take();
+        drop(terminal);
+        self.state.set_selection(None);
+    }
+
+    /// Select everything from the top of the scrollback to the bottom of
+    /// the screen.
+    fn select_all(&mut self) {
+        let terminal = self.terminal.lock();
+        let start = Pos::new(terminal.grid.topmost_line(), Column(0));
+        let end = Pos::new(terminal.grid.bottommost_line(), terminal.grid.last_column());
+        drop(terminal);
+
+        self.select(SelectionType::Simple, start, end);
+    }
+
+    /// Expand the selection, or start one at the cursor, for one of the
+    /// `ExpandSelectionTo` actions.
+    fn expand_selection(&mut self, action: &Act) {
+        let terminal = self.terminal.lock();
+        let range = terminal
+            .selection
+            .as_ref()
+            .and_then(|selection| selection.to_range(&terminal));
+        let (start, end) = match range {
+            Some(range) => (range.start, range.end),
+            None => (terminal.grid.cursor.pos, terminal.grid.cursor.pos),
+        };
+        let output = terminal.output_lines_at(start.row);
+        drop(terminal);
+
+        match action {
+            Act::ExpandSelectionToWord => {
+                self.select(SelectionType::Semantic, start, end)
+            }
+            Act::ExpandSelectionToLine => self.select(SelectionType::Lines, start, end),
+            Act::ExpandSelectionToOutput => {
+                if let Some((first, last)) = output {
+                    let start = Pos::new(first, Column(0));
+                    let end = Pos::new(last, Column(0));
+                    self.select(SelectionType::Lines, start, end);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Replace the selection by one of `ty` from `start` to `end`.
+    fn select(&mut self, ty: SelectionType, start: Pos, end: Pos) {
+        let mut terminal = self.terminal.lock();
+        let mut selection = Selection::new(ty, start, Side::Left);
+        selection.update(end, Side::Right);
+        self.state.set_selection(selection.to_range(&terminal));
+        terminal.selection = Some(selection);
+        drop(terminal);
+
+        self.copy_selection(ClipboardType::Selection);
+    }
+
+    fn start_selection(&mut self, ty: SelectionType, point: Pos, side: Side) {
+        self.copy_selection(ClipboardType::Selection);
+        let mut terminal = self.terminal.lock();
+        terminal.selection = Some(Selection::new(ty, point, side));
+        drop(terminal);
+    }
+
+    #[allow(dead_code)]
+    pub fn update_selection_scrolling(&self, _mouse_y: f64) {
+        // println!("{:?}", mouse_y);
+    }
+
+    // pub fn update_selection(&mut self, mut point: Pos, side: Side) {
+    pub fn update_selection(&mut self, mut point: Pos) {
+        let mut terminal = self.terminal.lock();
+        let mut selection = match terminal.selection.take() {
+            Some(selection) => selection,
+            None => return,
+        };
+
+        // Treat motion over message bar like motion over the last line.
+        point.row = std::cmp::min(point.row, terminal.bottommost_line());
+
+        // Update selection.
+        // selection.update(point, side);
+        selection.update(point, Side::Left);
+
+        // Move vi cursor and expand selection.
+        if terminal.mode().contains(Mode::VI) {
+            terminal.vi_mode_cursor = point;
+            selection.include_all();
+        }
+
+        self.state.set_selection(selection.to_range(&terminal));
+        terminal.selection = Some(selection);
+        drop(terminal);
+    }
+
+    /// Highlight the hyperlink under the mouse, returns whether it changed.
+    pub fn update_hovered_hyperlink(&mut self, point: Pos) -> bool {
+        let terminal = self.terminal.lock();
+        let hyperlink = if self.hyperlinks && point.col.0 < terminal.grid.columns() {
+            terminal.grid[point].hyperlink()
+        } else {
+            None
+        };
+        drop(terminal);
+
+        self.state.set_hovered_hyperlink(hyperlink)
+    }
+
+    #[inline]
+    #[allow(unused)]
+    pub fn selection_is_empty(&self) -> bool {
+        let terminal = self.terminal.lock();
+        let is_empty = terminal.selection.is_none();
+        drop(terminal);
+        is_empty
+    }
+
+    pub fn on_left_click(&mut self, point: Pos) {
+        let side = self.layout.mouse.square_side;
+
+        match self.layout.mouse.click_state {
+            ClickState::Click => {
+                self.clear_selection();
+
+                // Start new empty selection.
+                if self.messenger.get_modifiers().ctrl() {
+                    self.start_selection(SelectionType::Block, point, side);
+                } else {
+                    self.start_selection(SelectionType::Simple, point, side);
+                }
+            }
+            ClickState::DoubleClick => {
+                self.start_selection(SelectionType::Semantic, point, side);
+            }
+            ClickState::TripleClick => {
+                self.start_selection(SelectionType::Lines, point, side);
+            }
+            ClickState::None => (),
+        };
 
         // Move vi mode cursor to mouse click position.
         // if self.ctx.terminal().mode().contains(TermMode::VI) && !self.ctx.search_active() {
@@ -357,8 +1683,29 @@ impl Screen {
         // }
     }
 
-    #[inline]
+    /// Paste `text`, as bracketed paste when it is `bracketed` and the
+    /// application enabled it. Text with newlines or control characters is
+    /// only sent once confirmed when the application didn't.
     pub fn paste(&mut self, text: &str, bracketed: bool) {
+        if !bracketed {
+            self.write_paste(text, false);
+            return;
+        }
+
+        let text = paste::sanitize(text, &self.paste_config);
+        if self.paste_config.confirm
+            && !self.get_mode().contains(Mode::BRACKETED_PASTE)
+            && paste::needs_confirmation(&text)
+        {
+            self.paste_prompt = Some(text);
+            self.render();
+            return;
+        }
+
+        self.write_paste(&text, true);
+    }
+
+    fn write_paste(&mut self, text: &str, bracketed: bool) {
         if bracketed && self.get_mode().contains(Mode::BRACKETED_PASTE) {
             self.messenger.send_bytes(b"\x1b[200~"[..].to_vec());
 
@@ -377,6 +1724,90 @@ impl Screen {
         }
     }
 
+    /// Paste dropped files as shell-escaped paths separated by spaces.
+    pub fn paste_files(&mut self, paths: &[PathBuf]) {
+        let mut content = paths
+            .iter()
+            .map(|path| shell_escape(&path.to_string_lossy()))
+            .collect::<Vec<String>>()
+            .join(" ");
+        content.push(' ');
+
+        self.paste(&content, true);
+    }
+
+    /// Dim unfocused windows by `style.unfocused-dim` and draw a hollow
+    /// cursor in them.
+    pub fn set_focused(&mut self, is_focused: bool) {
+        self.state.set_focused(is_focused);
+        if !is_focused {
+            self.held_keys.clear();
+        }
+        let brightness = if is_focused {
+            1.0
+        } else {
+            1.0 - self.style.unfocused_dim
+        };
+        self.sugarloaf.set_brightness(brightness);
+    }
+
+    #[inline]
+    pub fn ring_bell(&mut self) {
+        self.bell.ring();
+    }
+
+    /// Read aloud the lines of output printed since the last call, in
+    /// reader mode.
+    pub fn announce_output(&mut self) {
+        let lines = self.terminal.lock().take_announcements();
+        if let Some(text) = speech::announcement(&lines) {
+            self.speaker.speak(text);
+        }
+    }
+
+    /// Apply the settings of a reloaded configuration which don't need the
+    /// window or the terminal to be created again.
+    pub fn update_config(&mut self, config: &config::Config) {
+        self.state.set_colors(config.colors);
+        self.init(config.colors.background.1);
+        self.mark_fully_damaged();
+
+        self.bell = Bell::new(&config.bell);
+        self.confirm_quit = config.confirm_quit.clone();
+        self.paste_config = config.paste.clone();
+        self.link_editor = config.links.editor.clone();
+        self.link_schemes = config.links.schemes.clone();
+        self.bindings = bindings::key_bindings(&config.key_bindings);
+        self.chords = bindings::chords(&config.key_bindings, &self.bindings);
+        self.mouse_bindings = bindings::mouse_bindings(&config.mouse_bindings);
+        self.primary_selection = config.selection.primary;
+        self.click_to_jump = config.scroll.click_to_jump;
+        self.allow_remote_input = config.remote_control.allow_input;
+        self.hyperlinks = config.features.hyperlinks;
+
+        let mut terminal = self.terminal.lock();
+        terminal.set_truecolor(config.features.truecolor);
+        terminal.set_clipboard_write(config.features.clipboard_write);
+    }
+
+    /// Follow the system appearance, switching to the colors of
+    /// `light-theme` or `dark-theme` when set.
+    pub fn set_appearance(&mut self, is_dark: bool, colors: Option<colors::Colors>) {
+        if let Some(colors) = colors {
+            self.state.set_colors(colors);
+            self.init(colors.background.1);
+            self.mark_fully_damaged();
+        }
+        self.terminal.lock().set_dark(is_dark);
+    }
+
+    /// Repaint everything on the next render, e.g. once the window is
+    /// visible again.
+    #[inline]
+    pub fn mark_fully_damaged(&mut self) {
+        self.terminal.lock().mark_fully_damaged();
+    }
+
     #[inline]
     pub fn init(&mut self, color: colors::ColorWGPU) {
         self.sugarloaf.init(color, self.layout.styles.term);
@@ -384,80 +1815,678 @@ impl Screen {
 
     #[inline]
     pub fn render(&mut self) {
-        let mut terminal = self.terminal.lock();
-        let visible_rows = terminal.visible_rows();
-        let cursor = terminal.cursor();
-        drop(terminal);
+        self.queue_frame();
+
+        let _span = Span::new("render");
+        self.sugarloaf.render();
+        self.flow.frame_rendered();
+    }
+
+    /// Whether frames should wait for the echo of a keystroke.
+    #[inline]
+    pub fn is_awaiting_echo(&self) -> bool {
+        self.latency.is_awaiting_echo(Instant::now())
+    }
+
+    /// Draw the echo of the last keystroke right away, returns whether a
+    /// keystroke was waiting for it.
+    pub fn render_echo(&mut self) -> bool {
+        if !self.is_awaiting_echo() {
+            return false;
+        }
+
+        self.render();
+        if let Some(latency) = self.latency.echo(Instant::now()) {
+            log::debug!("keystroke echo drawn after {latency:?}");
+        }
+        true
+    }
+
+    /// Save the next frame as a PNG file, into the home directory unless
+    /// `path` is given, and copy its path to the clipboard.
+    pub fn screenshot(&mut self, path: Option<PathBuf>) -> Result<PathBuf, String> {
+        let path = path.unwrap_or_else(|| {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            let directory = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+            directory.join(format!("rio-{timestamp}.png"))
+        });
+
+        self.queue_frame();
+        let image = self.sugarloaf.screenshot()?;
+        image.save(&path).map_err(|err| err.to_string())?;
+
+        log::info!("screenshot saved to {}", path.display());
+        self.clipboard
+            .set(ClipboardType::Clipboard, path.to_string_lossy());
+        Ok(path)
+    }
+
+    /// Lay out the visible rows, to be drawn by sugarloaf.
+    fn queue_frame(&mut self) {
+        let snapshot = self
+            .terminal
+            .try_lock_unfair()
+            .map(|mut terminal| terminal.snapshot());
+        let mut snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            // The parser holds the terminal and asks for a frame once done,
+            // meanwhile the last snapshot is drawn again.
+            None => match self.snapshot.take() {
+                Some(snapshot) => Snapshot {
+                    damaged_lines: Some(vec![]),
+                    ..snapshot
+                },
+                // Nothing was drawn yet.
+                None => return,
+            },
+        };
+
+        let command_marks = if self.command_marks {
+            prompt::command_marks(&snapshot.rows)
+        } else {
+            vec![]
+        };
 
         self.state.set_ime(self.ime.preedit());
+        self.state.set_vi_mode(snapshot.is_vi_mode);
+        self.state.set_cursor_color(snapshot.cursor_color);
 
+        let layout_span = Span::new("layout");
         self.state.update(
-            visible_rows,
-            cursor,
+            &snapshot.rows,
+            snapshot.damaged_lines.take(),
+            snapshot.cursor.clone(),
             &mut self.sugarloaf,
             self.layout.styles.term,
             self.layout.styles.tabs,
             &self.tabs,
         );
+        drop(layout_span);
+
+        if bar::is_visible(&self.bar, self.tabs.len(), self.bar_hovered) {
+            self.queue_bar(
+                snapshot.title.as_deref(),
+                snapshot.current_directory.as_deref(),
+                snapshot.last_command,
+                snapshot.display_offset,
+            );
+        }
 
-        self.sugarloaf.render();
+        if !command_marks.is_empty() {
+            self.queue_command_marks(&command_marks);
+        }
+
+        if snapshot.display_offset > 0 {
+            self.queue_scroll_indicator(snapshot.display_offset, snapshot.history_size);
+        }
+
+        // Kept to be drawn again while the parser holds the terminal.
+        self.snapshot = Some(snapshot);
+
+        self.queue_size_overlay();
+        self.queue_chord_indicator();
+
+        self.queue_hints();
+
+        if self.palette.is_open() {
+            self.queue_palette();
+        }
+
+        self.queue_context_menu();
+
+        if let Some(program) = &self.quit_prompt {
+            self.queue_prompt(&confirm_quit::prompt(program));
+        } else if let Some(text) = &self.paste_prompt {
+            self.queue_prompt(&paste::prompt(text));
+        }
+
+        let scroll_offset = self.smooth_scroll.step();
+        self.sugarloaf.set_scroll_offset(scroll_offset);
+    }
+
+    fn queue_bar(
+        &mut self,
+        title: Option<&str>,
+        cwd: Option<&Path>,
+        last_command: Option<(Option<i32>, Duration)>,
+        display_offset: usize,
+    ) {
+        let scale = self.sugarloaf.get_scale();
+        let (background, title_color, status_color) = self.state.bar_colors();
+        let (bar_top, bar_height) = self.layout.bar_area();
+        self.sugarloaf.add_rect(Rect::from_pixels(
+            [0.0, bar_top],
+            [self.layout.width, bar_height],
+            background,
+            scale,
+        ));
+
+        let style = self.layout.styles.tabs;
+        let (close_color, current_background) =
+            (self.state.foreground_color(), self.state.background_color());
+        let current = self.tabs.position(self.tabs.current());
+        for index in 0..self.tabs.len() {
+            let x = bar::tab_x(index) * scale;
+            let is_current = current == Some(index);
+            if is_current {
+                self.sugarloaf.add_rect(Rect::from_pixels(
+                    [x, bar_top],
+                    [bar::TAB_WIDTH * scale, bar_height],
+                    current_background,
+                    scale,
+                ));
+            }
+
+            let label = bar::tab_label(&self.bar, index, is_current, title);
+            let label_style = SugarloafStyle {
+                screen_position: (x + 8.0 * scale, style.screen_position.1),
+                bounds: (
+                    (bar::TAB_WIDTH - bar::CLOSE_WIDTH - 8.0) * scale,
+                    style.bounds.1,
+                ),
+                ..style
+            };
+            let color = if is_current {
+                self.state.bar_active_color()
+            } else {
+                title_color
+            };
+            self.sugarloaf.label(&label, label_style, color, false);
+
+            if self.tabs.len() > 1 {
+                let close_style = SugarloafStyle {
+                    screen_position: (
+                        x + (bar::TAB_WIDTH - bar::CLOSE_WIDTH) * scale,
+                        style.screen_position.1,
+                    ),
+                    ..style
+                };
+                self.sugarloaf.label("×", close_style, close_color, false);
+            }
+        }
+
+        let new_tab_style = SugarloafStyle {
+            screen_position: (
+                bar::tab_x(self.tabs.len()) * scale + 6.0 * scale,
+                style.screen_position.1,
+            ),
+            ..style
+        };
+        self.sugarloaf.label("+", new_tab_style, title_color, false);
+
+        let home = dirs::home_dir();
+        let status = bar::status(
+            &self.bar,
+            self.secure_input,
+            cwd,
+            home.as_deref(),
+            last_command,
+            display_offset,
+            bar::local_time(),
+        );
+        let style = SugarloafStyle {
+            screen_position: (self.layout.width - 10.0 * scale, style.screen_position.1),
+            ..style
+        };
+        self.sugarloaf.label(&status, style, status_color, true);
+    }
+
+    /// Turn Secure Keyboard Entry on or off, the bar shows it while on.
+    fn set_secure_input(&mut self, enabled: bool) -> Result<(), String> {
+        #[cfg(target_os = "macos")]
+        crate::platform::macos::set_secure_keyboard_entry(enabled)?;
+        #[cfg(not(target_os = "macos"))]
+        if enabled {
+            return Err(String::from(
+                "secure keyboard entry is only available on macOS",
+            ));
+        }
+
+        self.secure_input = enabled;
+        log::info!("secure keyboard entry: {enabled}");
+        self.render();
+        Ok(())
+    }
+
+    /// Act on a left click on the bar. Returns what was clicked, or `None`
+    /// when the click is not on the bar.
+    pub fn click_bar(&mut self) -> Option<bar::Hit> {
+        if !bar::is_visible(&self.bar, self.tabs.len(), self.bar_hovered)
+            || !self.is_mouse_over_bar()
+        {
+            return None;
+        }
+
+        let mouse = &self.layout.mouse;
+
+        let hit = bar::hit(mouse.x as f32 / self.sugarloaf.get_scale(), self.tabs.len());
+        match hit {
+            bar::Hit::Tab(index) => {
+                if let Some(id) = self.tabs.id_at(index) {
+                    self.tabs.set_current(id);
+                }
+            }
+            bar::Hit::CloseTab(index) => {
+                if let Some(id) = self.tabs.id_at(index) {
+                    self.tabs.close_tab(id);
+                    self.update_bar_space();
+                }
+            }
+            bar::Hit::NewTab => {
+                self.tabs.add_tab(true);
+                self.update_bar_space();
+            }
+            bar::Hit::Empty => {}
+        }
+
+        Some(hit)
     }
 
     #[inline]
-    pub fn scroll(&mut self, _new_scroll_x_px: f64, new_scroll_y_px: f64) {
-        // let width = self.layout.width as f64;
-        // let height = self.layout.height as f64;
-
-        // if self
-        //     .ctx
-        //     .terminal()
-        //     .mode()
-        //     .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
-        //     && !self.ctx.modifiers().shift()
-        // {
-        // // let multiplier = f64::from(self.ctx.config().terminal_config.scrolling.multiplier);
-
-        // // self.layout.mouse_mut().accumulated_scroll.x += new_scroll_x_px;//* multiplier;
-        // // self.layout.mouse_mut().accumulated_scroll.y += new_scroll_y_px;// * multiplier;
-
-        // // // The chars here are the same as for the respective arrow keys.
-        // let line_cmd = if new_scroll_y_px > 0. { b'A' } else { b'B' };
-        // let column_cmd = if new_scroll_x_px > 0. { b'D' } else { b'C' };
-
-        // // let lines = (self.layout.cursor.accumulated_scroll.y / self.layout.font_size as f64).abs() as usize;
-        // let lines = 1;
-        // let columns = (self.layout.cursor.accumulated_scroll.x / width).abs() as usize;
-
-        // let mut content = Vec::with_capacity(3 * (lines + columns));
-
-        // for _ in 0..lines {
-        //     content.push(0x1b);
-        //     content.push(b'O');
-        //     content.push(line_cmd);
-        // }
+    fn is_mouse_over_bar(&self) -> bool {
+        let (top, height) = self.layout.bar_area();
+        let y = self.layout.mouse.y as f32;
+        y >= top && y < top + height
+    }
 
-        // for _ in 0..columns {
-        //     content.push(0x1b);
-        //     content.push(b'O');
-        //     content.push(column_cmd);
-        // }
+    /// With an auto-hidden bar, show it while the mouse is over its edge.
+    /// Returns whether it appeared or disappeared.
+    pub fn update_bar_hover(&mut self) -> bool {
+        let is_hovered = self.bar.visibility == config::BarVisibility::AutoHide
+            && self.is_mouse_over_bar();
+        let changed = self.bar_hovered != is_hovered;
+        self.bar_hovered = is_hovered;
+        changed
+    }
 
-        // println!("{:?} {:?} {:?} {:?}", content, lines, columns, self.layout.cursor);
-        // if content.len() > 0 {
-        //     self.messenger.write_to_pty(content);
-        // }
-        // }
+    /// Make room for the bar when it shows up, or give the room back to the
+    /// terminal when it is hidden, since it depends on the number of tabs.
+    fn update_bar_space(&mut self) {
+        let is_reserved = bar::is_reserved(&self.bar, self.tabs.len());
+        if self.layout.set_bar_reserved(is_reserved) {
+            let size = winit::dpi::PhysicalSize::new(
+                self.layout.width_u32,
+                self.layout.height_u32,
+            );
+            self.resize(size);
+        }
+    }
+
+    /// Mark the rows where commands were entered in the left padding, with
+    /// `marks` from `prompt::command_marks`.
+    fn queue_command_marks(&mut self, marks: &[(usize, bool)]) {
+        let scale = self.sugarloaf.get_scale();
+        let line_height = self.layout.styles.term.text_scale;
+        let top = self.layout.styles.term.screen_position.1;
+        for (line, succeeded) in marks {
+            self.sugarloaf.add_rect(Rect::from_pixels(
+                [prompt::MARK_X * scale, top + *line as f32 * line_height],
+                [prompt::MARK_WIDTH * scale, line_height],
+                self.state.command_mark_color(*succeeded),
+                scale,
+            ));
+        }
+    }
+
+    /// Draw the grid size in the middle of the window, while it is resized.
+    fn queue_size_overlay(&mut self) {
+        let text = match self.size_overlay.text(Instant::now()) {
+            Some(text) => text,
+            None => return,
+        };
+
+        let (width, height) = self.boxed_label_size(&text);
+        let left = ((self.layout.width - width) / 2.0).max(0.0);
+        let top = ((self.layout.height - height) / 2.0).max(0.0);
+        self.queue_boxed_label(&text, left, top);
+    }
+
+    /// Keys of the chord being typed, in the bottom left corner.
+    fn queue_chord_indicator(&mut self) {
+        let text = match self.chords.text(Instant::now()) {
+            Some(text) => text,
+            None => return,
+        };
+
+        let (_, height) = self.boxed_label_size(&text);
+        let margin = self.layout.font_size / 2.0 * self.sugarloaf.get_scale();
+        let top = (self.layout.height - height - margin).max(0.0);
+        self.queue_boxed_label(&text, margin, top);
+    }
+
+    /// Size of `text` drawn by `queue_boxed_label`.
+    fn boxed_label_size(&self, text: &str) -> (f32, f32) {
+        let cell_width = self.layout.font_size / 2.0 * self.sugarloaf.get_scale();
+        let width = (text.chars().count() + 2) as f32 * cell_width;
+        (width, 2.0 * self.layout.styles.term.text_scale)
+    }
+
+    /// Draw `text` over a box of the background color at `left` and `top`.
+    fn queue_boxed_label(&mut self, text: &str, left: f32, top: f32) {
+        let scale = self.sugarloaf.get_scale();
+        let style = self.layout.styles.term;
+        let cell_width = self.layout.font_size / 2.0 * scale;
+        let (width, height) = self.boxed_label_size(text);
+
+        let mut background = self.state.background_color();
+        background[3] = 0.9;
+        self.sugarloaf.add_rect(Rect::from_pixels(
+            [left, top],
+            [width, height],
+            background,
+            scale,
+        ));
+
+        let style = SugarloafStyle {
+            screen_position: (left + cell_width, top + style.text_scale / 2.0),
+            ..style
+        };
+        let color = self.state.foreground_color();
+        self.sugarloaf.label(text, style, color, false);
+    }
 
-        self.layout.mouse_mut().accumulated_scroll.y +=
-            new_scroll_y_px * self.layout.mouse.multiplier;
-        let lines = (self.layout.mouse.accumulated_scroll.y
-            / self.layout.font_size as f64) as i32;
+    fn queue_scroll_indicator(&mut self, display_offset: usize, history_size: usize) {
+        let opacity = self.scroll_indicator.opacity(Instant::now());
+        if opacity == 0.0 {
+            return;
+        }
+
+        let scale = self.sugarloaf.get_scale();
+        let (top, height) =
+            scroll_indicator::thumb(display_offset, history_size, self.layout.rows);
+        let track = self.layout.height;
+        let height = (height * track).max(scroll_indicator::MIN_HEIGHT * scale);
+        let top = (top * track).min(track - height).max(0.0);
+
+        let mut color = self.state.foreground_color();
+        color[3] = 0.5 * opacity;
+        self.sugarloaf.add_rect(Rect::from_pixels(
+            [self.layout.width - (scroll_indicator::WIDTH + 2.0) * scale, top],
+            [scroll_indicator::WIDTH * scale, height],
+            color,
+            scale,
+        ));
+    }
+
+    /// With `click-to-jump`, scroll to the position of the scrollback under a
+    /// click on the right edge. Returns whether the click was handled.
+    pub fn jump_to_scroll_position(&mut self) -> bool {
+        let scale = self.sugarloaf.get_scale() as f64;
+        let mouse = &self.layout.mouse;
+        let hit_width = scroll_indicator::HIT_WIDTH as f64 * scale;
+        if !self.click_to_jump
+            || (mouse.x as f64) < self.layout.width as f64 - hit_width
+        {
+            return false;
+        }
+
+        let fraction = mouse.y as f64 / (self.layout.height as f64).max(1.0);
+        let terminal = self.terminal.lock();
+        let history_size = terminal.history_size();
+        drop(terminal);
+        if history_size == 0 {
+            return false;
+        }
+
+        let offset = scroll_indicator::offset_at(fraction, history_size, self.layout.rows);
+        let lines = offset as i32 - self.display_offset() as i32;
+        self.scroll_display(Scroll::Delta(lines));
+        self.scroll_indicator.touch();
+        true
+    }
+
+    /// Feed `content` through the parser in chunks, rendering after each one.
+    /// Returns the time spent parsing and the time of each frame.
+    pub fn replay(
+        &mut self,
+        content: &[u8],
+        chunk_size: usize,
+    ) -> (Duration, Vec<Duration>) {
+        let mut parser = ParserProcessor::new();
+        let mut parse_time = Duration::ZERO;
+        let mut frame_times = Vec::new();
+
+        for chunk in content.chunks(chunk_size) {
+            let started_at = Instant::now();
+            parser.advance_bytes(&mut *self.terminal.lock(), chunk);
+            parse_time += started_at.elapsed();
+
+            let started_at = Instant::now();
+            self.render();
+            frame_times.push(started_at.elapsed());
+        }
+
+        (parse_time, frame_times)
+    }
+
+    /// Scroll by a number of lines and columns (e.g. mouse wheel line deltas).
+    #[inline]
+    pub fn scroll_lines(&mut self, columns: f32, lines: f32) {
+        let new_scroll_x_px = columns * self.layout.font_size / 2.;
+        let new_scroll_y_px = lines * self.layout.font_size;
+        self.scroll(new_scroll_x_px as f64, new_scroll_y_px as f64);
+    }
+
+    #[inline]
+    pub fn scroll(&mut self, new_scroll_x_px: f64, new_scroll_y_px: f64) {
+        let width = (self.layout.font_size / 2.) as f64;
+        let height = self.layout.font_size as f64;
+        let multiplier = self.layout.mouse.multiplier;
+
+        if self.mouse_mode() {
+            self.layout.mouse_mut().accumulated_scroll.x += new_scroll_x_px;
+            self.layout.mouse_mut().accumulated_scroll.y += new_scroll_y_px;
+
+            let code = if new_scroll_y_px > 0. { 64 } else { 65 };
+            let lines = (self.layout.mouse.accumulated_scroll.y / height).abs() as usize;
+
+            for _ in 0..lines {
+                self.mouse_report(code, ElementState::Pressed);
+            }
+
+            let code = if new_scroll_x_px > 0. { 66 } else { 67 };
+            let columns = (self.layout.mouse.accumulated_scroll.x / width).abs() as usize;
+
+            for _ in 0..columns {
+                self.mouse_report(code, ElementState::Pressed);
+            }
+        } else if self
+            .get_mode()
+            .contains(Mode::ALT_SCREEN | Mode::ALTERNATE_SCROLL)
+            && !self.messenger.get_modifiers().shift()
+        {
+            self.layout.mouse_mut().accumulated_scroll.x += new_scroll_x_px * multiplier;
+            self.layout.mouse_mut().accumulated_scroll.y += new_scroll_y_px * multiplier;
+
+            // The chars here are the same as for the respective arrow keys.
+            let line_cmd = if new_scroll_y_px > 0. { b'A' } else { b'B' };
+            let column_cmd = if new_scroll_x_px > 0. { b'D' } else { b'C' };
+
+            let lines = (self.layout.mouse.accumulated_scroll.y / height).abs() as usize;
+            let columns = (self.layout.mouse.accumulated_scroll.x / width).abs() as usize;
+
+            let mut content = Vec::with_capacity(3 * (lines + columns));
+
+            for _ in 0..lines {
+                content.push(0x1b);
+                content.push(b'O');
+                content.push(line_cmd);
+            }
+
+            for _ in 0..columns {
+                content.push(0x1b);
+                content.push(b'O');
+                content.push(column_cmd);
+            }
+
+            if !content.is_empty() {
+                self.messenger.send_bytes(content);
+            }
+        } else {
+            self.layout.mouse_mut().accumulated_scroll.y += new_scroll_y_px * multiplier;
+            let lines = (self.layout.mouse.accumulated_scroll.y / height) as i32;
+
+            if lines != 0 {
+                self.scroll_display(Scroll::Delta(lines));
+            }
+        }
+
+        // Keep only the remainder that did not produce a full line or column.
+        let accumulated_scroll = &mut self.layout.mouse_mut().accumulated_scroll;
+        accumulated_scroll.x %= width;
+        accumulated_scroll.y %= height;
+    }
+
+    /// Scroll the display offset, animating the change when smooth scroll is enabled.
+    pub fn scroll_display(&mut self, scroll: Scroll) {
+        let mut terminal = self.terminal.lock();
+        let old_display_offset = terminal.display_offset() as i32;
+        terminal.scroll_display(scroll);
+        let lines = terminal.display_offset() as i32 - old_display_offset;
+        drop(terminal);
 
         if lines != 0 {
-            let mut terminal = self.terminal.lock();
-            terminal.scroll_display(Scroll::Delta(lines));
-            drop(terminal);
+            self.scroll_indicator.touch();
+        }
+
+        let line_height = self.layout.styles.term.text_scale;
+        let max_offset = line_height * self.layout.rows as f32;
+        self.smooth_scroll.push(lines, line_height, max_offset);
+    }
+
+    /// Scroll the closest prompt above the top of the viewport, or below it,
+    /// to the top.
+    fn scroll_to_prompt(&mut self, up: bool) {
+        let terminal = self.terminal.lock();
+        let display_offset = terminal.grid.display_offset() as i32;
+        let target = prompt::find(
+            &terminal,
+            Line(-display_offset),
+            up,
+            self.prompt_pattern.as_ref(),
+        );
+        drop(terminal);
+
+        if let Some(line) = target {
+            // Prompts on the screen are reached by scrolling to the bottom.
+            let lines = (-line.0).max(0) - display_offset;
+            self.scroll_display(Scroll::Delta(lines));
+        }
+    }
+
+    /// Milliseconds until another frame has to be drawn, if anything is
+    /// animating.
+    #[inline]
+    pub fn next_frame_in(&self) -> Option<u64> {
+        if self.smooth_scroll.is_animating() || self.animate_post_processing {
+            return Some(self.smooth_scroll.frame_ms());
+        }
+
+        let clock = if self.bar.enabled && self.bar.clock {
+            Some(bar::millis_until_next_minute())
+        } else {
+            None
+        };
+
+        let scroll_indicator = if self.display_offset() > 0 {
+            self.scroll_indicator.next_frame_in(Instant::now())
+        } else {
+            None
+        };
+
+        [
+            clock,
+            scroll_indicator,
+            self.size_overlay.next_frame_in(Instant::now()),
+            self.chords.next_frame_in(Instant::now()),
+            self.state.next_blink_in(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// Report a mouse event to the PTY, using the encoding requested by the application.
+    pub fn mouse_report(&mut self, button: u8, state: ElementState) {
+        let display_offset = self.display_offset();
+        let point = self.layout.mouse_position(display_offset);
+
+        // Assure the mouse point is not in the scrollback.
+        if point.row < 0 {
+            return;
+        }
+
+        // Calculate modifiers value.
+        let mut mods = 0;
+        let modifiers = self.messenger.get_modifiers();
+        if modifiers.shift() {
+            mods += 4;
+        }
+        if modifiers.alt() {
+            mods += 8;
+        }
+        if modifiers.ctrl() {
+            mods += 16;
+        }
+
+        // Report mouse events.
+        let mode = self.get_mode();
+        if mode.contains(Mode::SGR_MOUSE) {
+            self.sgr_mouse_report(point, button + mods, state);
+        } else if let ElementState::Released = state {
+            self.normal_mouse_report(point, 3 + mods, mode.contains(Mode::UTF8_MOUSE));
+        } else {
+            self.normal_mouse_report(
+                point,
+                button + mods,
+                mode.contains(Mode::UTF8_MOUSE),
+            );
+        }
+    }
+
+    fn normal_mouse_report(&mut self, point: Pos, button: u8, utf8: bool) {
+        let Pos { row, col } = point;
+        let max_point = if utf8 { 2015 } else { 223 };
+
+        if row >= max_point || col >= max_point {
+            return;
         }
+
+        let mut msg = vec![b'\x1b', b'[', b'M', 32 + button];
+
+        let mouse_pos_encode = |pos: usize| -> Vec<u8> {
+            let pos = 32 + 1 + pos as u32;
+            let first = 0xC0 + pos / 64;
+            let second = 0x80 + (pos & 63);
+            vec![first as u8, second as u8]
+        };
+
+        if utf8 && col >= Column(95) {
+            msg.append(&mut mouse_pos_encode(col.0));
+        } else {
+            msg.push(32 + 1 + col.0 as u8);
+        }
+
+        if utf8 && row >= 95 {
+            msg.append(&mut mouse_pos_encode(row.0 as usize));
+        } else {
+            msg.push(32 + 1 + row.0 as u8);
+        }
+
+        self.messenger.send_bytes(msg);
+    }
+
+    fn sgr_mouse_report(&mut self, point: Pos, button: u8, state: ElementState) {
+        let c = match state {
+            ElementState::Pressed => 'M',
+            ElementState::Released => 'm',
+        };
+
+        let msg = format!("\x1b[<{};{};{}{}", button, point.col + 1, point.row + 1, c);
+        self.messenger.send_bytes(msg.into_bytes());
     }
 
     #[inline]
@@ -471,8 +2500,15 @@ impl Screen {
     }
 
     #[inline]
+    /// Show the grid size for a moment, when the window was resized.
+    pub fn show_size(&mut self) {
+        self.size_overlay
+            .resized(self.layout.columns, self.layout.rows, Instant::now());
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> &mut Self {
         self.sugarloaf.resize(new_size.width, new_size.height);
+        crash::set_window_size(new_size.width, new_size.height);
         self.layout
             .set_size(new_size.width, new_size.height)
             .update();
@@ -491,19 +2527,134 @@ impl Screen {
         self
     }
 
+    /// Use the text antialiasing configured for the display the window is
+    /// in, and animate at its refresh rate. Both change when the window is
+    /// moved to another monitor.
+    pub fn update_display(&mut self, winit_window: &winit::window::Window) -> &mut Self {
+        // Cells depend on the scale factor, which changes with the monitor.
+        if self.resize_increments {
+            let (width, height) = self.layout.cell_size();
+            winit_window.set_resize_increments(Some(winit::dpi::PhysicalSize::new(
+                width, height,
+            )));
+        }
+
+        let monitor = winit_window.current_monitor();
+        self.smooth_scroll.set_refresh_rate(
+            monitor
+                .as_ref()
+                .and_then(|monitor| monitor.refresh_rate_millihertz()),
+        );
+        let display = monitor.and_then(|monitor| monitor.name());
+        let antialiasing = match self.style.text_antialiasing_for(display.as_deref()) {
+            config::TextAntialiasing::Grayscale => Antialiasing::Grayscale,
+            config::TextAntialiasing::Rgb => Antialiasing::Rgb,
+            config::TextAntialiasing::Bgr => Antialiasing::Bgr,
+        };
+
+        self.sugarloaf.set_antialiasing(antialiasing);
+        self
+    }
+
     pub fn set_scale(
         &mut self,
         new_scale: f32,
         new_size: winit::dpi::PhysicalSize<u32>,
     ) -> &mut Self {
-        self.sugarloaf
-            .resize(new_size.width, new_size.height)
-            .rescale(new_scale);
+        self.sugarloaf.rescale(new_scale);
+        self.layout.set_scale(new_scale);
+        // Minimized windows can change monitor as well, the grid is kept
+        // until they are resized back.
+        if new_size.width == 0 || new_size.height == 0 {
+            self.layout.update();
+            return self;
+        }
 
-        self.layout
-            .set_scale(new_scale)
-            .set_size(new_size.width, new_size.height)
-            .update();
+        // Lines are whole pixels high, so the grid may fit more or fewer of
+        // them at the new scale (e.g. moving to a 125% monitor).
+        self.resize(new_size);
+        self.mark_fully_damaged();
         self
     }
 }
+
+/// Open a new Rio window running `command` instead of a shell.
+fn open_window_with(command: &[String]) -> std::io::Result<()> {
+    let program = std::env::current_exe()?;
+    std::process::Command::new(program)
+        .arg("--command")
+        .args(command)
+        .env(NEW_INSTANCE_ENV, "1")
+        .spawn()
+        .map(|_| ())
+}
+
+/// Create a file in the temporary directory that only the user can read,
+/// named `prefix`, random characters and `suffix`.
+fn create_private_file(prefix: &str, suffix: &str) -> std::io::Result<(PathBuf, File)> {
+    let template = std::env::temp_dir().join(format!("{prefix}XXXXXX{suffix}"));
+    let mut template =
+        CString::new(template.into_os_string().into_vec())?.into_bytes_with_nul();
+    // Opened with `O_EXCL` and mode 0600, unlike `std::fs::write`.
+    let fd = unsafe {
+        libc::mkstemps(
+            template.as_mut_ptr() as *mut libc::c_char,
+            suffix.len() as libc::c_int,
+        )
+    };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    template.pop();
+    let path = PathBuf::from(OsString::from_vec(template));
+    Ok((path, unsafe { File::from_raw_fd(fd) }))
+}
+
+/// Quote a string to be used as a single shell argument.
+///
+/// Strings only containing safe characters are kept as they are, otherwise
+/// they are wrapped in single quotes (e.g. paths with spaces).
+fn shell_escape(content: &str) -> String {
+    let is_safe = |c: char| {
+        c.is_ascii_alphanumeric()
+            || matches!(c, '/' | '.' | '-' | '_' | ',' | ':' | '@' | '%' | '+' | '=')
+    };
+
+    if !content.is_empty() && content.chars().all(is_safe) {
+        return content.to_string();
+    }
+
+    format!("'{}'", content.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_create_private_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (first, _) = create_private_file("rio-test-", ".txt").unwrap();
+        let (second, _) = create_private_file("rio-test-", ".txt").unwrap();
+        assert_ne!(first, second);
+
+        let name = first.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("rio-test-") && name.ends_with(".txt"));
+        let mode = std::fs::metadata(&first).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!(shell_escape("/tmp/file.txt"), "/tmp/file.txt");
+        assert_eq!(shell_escape("/tmp/my file.txt"), "'/tmp/my file.txt'");
+        assert_eq!(shell_escape("/tmp/it's.txt"), "'/tmp/it'\\''s.txt'");
+        assert_eq!(shell_escape("/tmp/$HOME"), "'/tmp/$HOME'");
+        assert_eq!(shell_escape(""), "''");
+    }
+}
@@ -0,0 +1,249 @@
+use crate::screen::bindings::Action;
+use crate::screen::hints::HintKind;
+
+/// Most commands listed at once.
+pub const MAX_VISIBLE: usize = 8;
+
+/// Actions listed by the palette, with the name they are searched by.
+const COMMANDS: &[(&str, Action)] = &[
+    ("Copy", Action::Copy),
+    ("Copy as Rich Text", Action::CopyRichText),
+    ("Copy Last Output", Action::CopyLastOutput),
+    ("Copy URL on Screen", Action::Hints(HintKind::Url)),
+    ("Copy Path on Screen", Action::Hints(HintKind::Path)),
+    ("Copy Word on Screen", Action::Hints(HintKind::Word)),
+    ("Copy Line on Screen", Action::Hints(HintKind::Line)),
+    ("Paste", Action::Paste),
+    ("Paste Selection", Action::PasteSelection),
+    ("Select All", Action::SelectAll),
+    ("Expand Selection to Word", Action::ExpandSelectionToWord),
+    ("Expand Selection to Line", Action::ExpandSelectionToLine),
+    ("Select Command Output", Action::ExpandSelectionToOutput),
+    ("New Tab", Action::TabCreateNew),
+    ("Next Tab", Action::TabSwitchNext),
+    ("Previous Tab", Action::TabSwitchPrev),
+    ("Toggle Fullscreen", Action::ToggleFullscreen),
+    ("Toggle Maximized", Action::ToggleMaximized),
+    ("Scroll Page Up", Action::ScrollPageUp),
+    ("Scroll Page Down", Action::ScrollPageDown),
+    ("Scroll to Top", Action::ScrollToTop),
+    ("Scroll to Bottom", Action::ScrollToBottom),
+    ("Scroll to Previous Prompt", Action::ScrollToPreviousPrompt),
+    ("Scroll to Next Prompt", Action::ScrollToNextPrompt),
+    ("Clear Scrollback", Action::ClearHistory),
+    ("Clear Screen and Scrollback", Action::ClearScreenAndHistory),
+    ("Save Screen", Action::SaveScreen),
+    ("Save Scrollback", Action::SaveScrollback),
+    ("Open Scrollback in Pager", Action::OpenScrollbackInPager),
+    ("Open Scrollback in Editor", Action::OpenScrollbackInEditor),
+    ("Toggle Recording", Action::ToggleRecording),
+    ("Toggle Bell Mute", Action::ToggleBellMute),
+    #[cfg(target_os = "macos")]
+    (
+        "Toggle Secure Keyboard Entry",
+        Action::ToggleSecureKeyboardEntry,
+    ),
+    ("Screenshot", Action::Screenshot),
+    ("Quit", Action::Quit),
+];
+
+/// Score of `name` for `query`, higher is better, or `None` when the query
+/// characters don't all appear in order. Consecutive characters and
+/// characters starting a word score more.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in name.chars() {
+        let expected = match query.peek() {
+            Some(expected) => *expected,
+            None => break,
+        };
+
+        let is_match = c.to_lowercase().eq(std::iter::once(expected));
+        if is_match {
+            query.next();
+            score += 1;
+            if previous_matched {
+                score += 5;
+            }
+            if previous.map_or(true, |previous| previous == ' ') {
+                score += 3;
+            }
+        }
+
+        previous_matched = is_match;
+        previous = Some(c);
+    }
+
+    if query.peek().is_some() {
+        return None;
+    }
+
+    // Prefer shorter names among equally good matches.
+    Some(score * 100 - name.len() as i32)
+}
+
+/// Searchable list of actions drawn over the terminal.
+#[derive(Debug)]
+pub struct Palette {
+    commands: Vec<(String, Action)>,
+    is_open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl Palette {
+    /// Palette listing the actions and opening each of `profiles`.
+    pub fn new(profiles: &[String]) -> Palette {
+        let mut commands: Vec<(String, Action)> = COMMANDS
+            .iter()
+            .map(|(name, action)| (name.to_string(), action.clone()))
+            .collect();
+        commands.extend(profiles.iter().map(|profile| {
+            (
+                format!("Open Profile {profile}"),
+                Action::OpenProfile(profile.clone()),
+            )
+        }));
+
+        Palette {
+            commands,
+            is_open: false,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn toggle(&mut self) {
+        if self.is_open {
+            self.close();
+        } else {
+            self.is_open = true;
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    #[inline]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    #[inline]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn select_next(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    /// Commands matching the query, best first.
+    pub fn matches(&self) -> Vec<&(String, Action)> {
+        let mut matches: Vec<(i32, &(String, Action))> = self
+            .commands
+            .iter()
+            .filter_map(|command| {
+                fuzzy_score(&self.query, &command.0).map(|score| (score, command))
+            })
+            .collect();
+        matches.sort_by(|a, b| b.0.cmp(&a.0));
+        matches.into_iter().map(|(_, command)| command).collect()
+    }
+
+    /// Close the palette, returning the selected action.
+    pub fn accept(&mut self) -> Option<Action> {
+        let action = self
+            .matches()
+            .get(self.selected)
+            .map(|(_, action)| action.clone());
+        self.close();
+        action
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("", "Copy").is_some());
+        assert!(fuzzy_score("cpy", "Copy").is_some());
+        assert!(fuzzy_score("ypc", "Copy").is_none());
+        assert!(
+            fuzzy_score("tf", "Toggle Fullscreen") > fuzzy_score("tf", "Scroll to Top")
+        );
+        assert!(fuzzy_score("copy", "Copy") > fuzzy_score("copy", "Copy as Rich Text"));
+    }
+
+    #[test]
+    fn test_palette() {
+        let mut palette = Palette::new(&[]);
+        palette.toggle();
+        assert!(palette.is_open());
+
+        for c in "new tab".chars() {
+            palette.push(c);
+        }
+        assert_eq!(palette.matches()[0].1, Action::TabCreateNew);
+
+        palette.select_next();
+        palette.select_previous();
+        assert_eq!(palette.accept(), Some(Action::TabCreateNew));
+        assert!(!palette.is_open());
+        assert_eq!(palette.query(), "");
+
+        palette.toggle();
+        for c in "zzz".chars() {
+            palette.push(c);
+        }
+        assert!(palette.matches().is_empty());
+        assert_eq!(palette.accept(), None);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut palette = Palette::new(&[String::from("staging")]);
+        palette.toggle();
+        for c in "staging".chars() {
+            palette.push(c);
+        }
+        assert_eq!(
+            palette.accept(),
+            Some(Action::OpenProfile(String::from("staging")))
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use config::PasteTransform;
+
+/// Most lines of the pasted text shown in the prompt.
+const PREVIEW_LINES: usize = 5;
+/// Most characters shown of each line of the preview.
+const PREVIEW_COLUMNS: usize = 60;
+
+/// `text` as configured to be pasted by `config`.
+pub fn sanitize(text: &str, config: &config::Paste) -> String {
+    let mut text = if config.strip_escapes {
+        text.chars()
+            .filter(|&c| c != '\x1b' && !('\u{80}'..='\u{9f}').contains(&c))
+            .collect()
+    } else {
+        text.to_string()
+    };
+
+    if config.normalize_newlines {
+        text = text.replace("\r\n", "\n");
+    }
+
+    for transform in &config.transforms {
+        text = match transform {
+            PasteTransform::TrimTrailingWhitespace => text
+                .split('\n')
+                .map(|line| line.trim_end_matches([' ', '\t']))
+                .collect::<Vec<&str>>()
+                .join("\n"),
+            PasteTransform::TrimTrailingNewline => {
+                text.trim_end_matches(['\r', '\n']).to_string()
+            }
+            PasteTransform::JoinLines => text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" "),
+        };
+    }
+
+    text
+}
+
+/// Whether pasting `text` outside of bracketed paste could run commands,
+/// because it has newlines or control characters.
+pub fn needs_confirmation(text: &str) -> bool {
+    text.trim_end_matches(['\r', '\n'])
+        .chars()
+        .any(|c| c.is_control() && c != '\t')
+}
+
+/// `line` with control characters in caret notation (e.g. `^[` for ESC),
+/// so they are visible in the preview.
+fn visible(line: &str) -> String {
+    let mut text = String::new();
+    for c in line.chars() {
+        match c {
+            '\t' => text.push(' '),
+            '\x7f' => text.push_str("^?"),
+            c if (c as u32) < 0x20 => {
+                text.push('^');
+                text.push((c as u8 + b'@') as char);
+            }
+            c if c.is_control() => text.push_str(&format!("<{:02x}>", c as u32)),
+            c => text.push(c),
+        }
+    }
+    text
+}
+
+/// Lines of the prompt shown over the terminal before pasting `text`.
+pub fn prompt(text: &str) -> Vec<String> {
+    let lines: Vec<&str> = text.trim_end_matches(['\r', '\n']).lines().collect();
+    let mut prompt = vec![match lines.len() {
+        1 => String::from("Paste text with control characters?"),
+        count => format!("Paste {count} lines?"),
+    }];
+
+    for line in lines.iter().take(PREVIEW_LINES) {
+        let line = visible(line);
+        if line.chars().count() > PREVIEW_COLUMNS {
+            let line: String = line.chars().take(PREVIEW_COLUMNS - 1).collect();
+            prompt.push(format!("  {line}…"));
+        } else {
+            prompt.push(format!("  {line}"));
+        }
+    }
+    if lines.len() > PREVIEW_LINES {
+        prompt.push(format!("  … {} more lines", lines.len() - PREVIEW_LINES));
+    }
+
+    prompt.push(String::from("Press Enter to paste or Escape to cancel."));
+    prompt
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        let config = config::Paste::default();
+        assert_eq!(sanitize("a\x1b[201~b\u{9b}c", &config), "a[201~bc");
+        assert_eq!(sanitize("a\r\nb", &config), "a\r\nb");
+
+        let config = config::Paste {
+            strip_escapes: false,
+            normalize_newlines: true,
+            ..config
+        };
+        assert_eq!(sanitize("a\x1b\r\nb", &config), "a\x1b\nb");
+
+        let config = config::Paste {
+            transforms: vec![
+                PasteTransform::TrimTrailingWhitespace,
+                PasteTransform::TrimTrailingNewline,
+            ],
+            ..config
+        };
+        assert_eq!(sanitize("a  \nb\t\n\n", &config), "a\nb");
+
+        let config = config::Paste {
+            transforms: vec![PasteTransform::JoinLines],
+            ..config
+        };
+        assert_eq!(sanitize("cd /tmp &&\n  ls\n\n", &config), "cd /tmp && ls");
+    }
+
+    #[test]
+    fn test_needs_confirmation() {
+        assert!(!needs_confirmation("ls -la"));
+        assert!(!needs_confirmation("ls -la\n"));
+        assert!(!needs_confirmation("a\tb"));
+        assert!(needs_confirmation("cd /\nrm -rf *"));
+        assert!(needs_confirmation("echo \x1b[31m"));
+    }
+
+    #[test]
+    fn test_prompt() {
+        assert_eq!(
+            prompt("echo \x1b[31m\x03"),
+            vec![
+                "Paste text with control characters?",
+                "  echo ^[[31m^C",
+                "Press Enter to paste or Escape to cancel.",
+            ]
+        );
+
+        let text: String = (0..8).map(|line| format!("line {line}\n")).collect();
+        let lines = prompt(&text);
+        assert_eq!(lines[0], "Paste 8 lines?");
+        assert_eq!(lines[1], "  line 0");
+        assert_eq!(lines[6], "  … 3 more lines");
+        assert_eq!(lines.len(), 8);
+
+        let long = "x".repeat(100);
+        assert_eq!(prompt(&format!("{long}\n{long}"))[1].chars().count(), 62);
+    }
+}
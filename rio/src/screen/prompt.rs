@@ -0,0 +1,168 @@
+use regex::Regex;
+use rio_core::crosswords::grid::row::{Marks, Row};
+use rio_core::crosswords::grid::Dimensions;
+use rio_core::crosswords::pos::{Column, Line, Pos};
+use rio_core::crosswords::square::Square;
+use rio_core::crosswords::Crosswords;
+use rio_core::event::EventListener;
+
+/// Distance of the command marks from the left edge, in logical pixels.
+pub const MARK_X: f32 = 3.0;
+
+/// Width of the command marks, in logical pixels.
+pub const MARK_WIDTH: f32 = 3.0;
+
+/// Pattern matching the lines where a prompt starts, for shells which don't
+/// mark them with OSC 133.
+pub fn pattern(config: &config::ShellIntegration) -> Option<Regex> {
+    if config.prompt_pattern.is_empty() {
+        return None;
+    }
+
+    match Regex::new(&config.prompt_pattern) {
+        Ok(pattern) => Some(pattern),
+        Err(err) => {
+            log::error!("invalid prompt pattern {}: {err}", config.prompt_pattern);
+            None
+        }
+    }
+}
+
+/// Closest line above `line`, or below it, where a prompt starts. Marks
+/// sent by the shell are used when there are any, otherwise lines are
+/// matched against `pattern`.
+pub fn find<U: EventListener>(
+    terminal: &Crosswords<U>,
+    line: Line,
+    up: bool,
+    pattern: Option<&Regex>,
+) -> Option<Line> {
+    let is_prompt = |line: &Line| {
+        if terminal.has_prompt_marks() {
+            terminal.is_prompt(*line)
+        } else {
+            pattern.map_or(false, |pattern| {
+                pattern.is_match(&terminal.line_text(*line))
+            })
+        }
+    };
+
+    if up {
+        (terminal.grid.topmost_line().0..line.0)
+            .rev()
+            .map(Line)
+            .find(is_prompt)
+    } else {
+        (line.0 + 1..=terminal.grid.bottommost_line().0)
+            .map(Line)
+            .find(is_prompt)
+    }
+}
+
+/// Text of the output of the last command, without the prompt and the
+/// command line.
+pub fn last_output<U: EventListener>(
+    terminal: &Crosswords<U>,
+    pattern: Option<&Regex>,
+) -> Option<String> {
+    let (start, end) = if terminal.has_prompt_marks() {
+        terminal.last_output_lines()?
+    } else {
+        // Between the prompt of the last command and the current one.
+        let below_bottom = terminal.grid.bottommost_line() + 1i32;
+        let current = find(terminal, below_bottom, true, pattern)?;
+        let last = find(terminal, current, true, pattern)?;
+        if current.0 - last.0 < 2 {
+            return None;
+        }
+        (last + 1i32, current - 1i32)
+    };
+
+    let start = Pos::new(start, Column(0));
+    let end = Pos::new(end, terminal.grid.last_column());
+    Some(terminal.bounds_to_string(start, end))
+}
+
+/// Rows where a finished command was entered, by their index in `rows`,
+/// and whether the command succeeded.
+pub fn command_marks(rows: &[Row<Square>]) -> Vec<(usize, bool)> {
+    rows.iter()
+        .enumerate()
+        .filter_map(|(line, row)| {
+            if row.marks.contains(Marks::SUCCEEDED) {
+                Some((line, true))
+            } else if row.marks.contains(Marks::FAILED) {
+                Some((line, false))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use rio_core::event::VoidListener;
+    use rio_core::performer::handler::ParserProcessor;
+
+    #[test]
+    fn test_find_marked_prompts() {
+        let mut terminal = Crosswords::new(10, 6, VoidListener {});
+        let mut parser = ParserProcessor::new();
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07> ls\r\nout\r\n");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07> pwd\r\nout\r\n");
+
+        // The pattern is ignored once the shell marks prompts.
+        let pattern = Regex::new("^out").ok();
+        assert_eq!(find(&terminal, Line(3), true, pattern.as_ref()), Some(Line(2)));
+        assert_eq!(find(&terminal, Line(2), true, pattern.as_ref()), Some(Line(0)));
+        assert_eq!(find(&terminal, Line(0), true, pattern.as_ref()), None);
+        assert_eq!(find(&terminal, Line(0), false, pattern.as_ref()), Some(Line(2)));
+        assert_eq!(find(&terminal, Line(2), false, pattern.as_ref()), None);
+    }
+
+    #[test]
+    fn test_find_matching_prompts() {
+        let mut terminal = Crosswords::new(10, 6, VoidListener {});
+        let mut parser = ParserProcessor::new();
+        parser.advance_bytes(&mut terminal, b"~$ ls\r\nout\r\n~$ pwd\r\n");
+
+        let config = config::ShellIntegration::default();
+        let pattern = pattern(&config);
+        assert_eq!(find(&terminal, Line(5), true, pattern.as_ref()), Some(Line(2)));
+        assert_eq!(find(&terminal, Line(2), true, pattern.as_ref()), Some(Line(0)));
+        assert_eq!(find(&terminal, Line(0), false, None), None);
+    }
+
+    #[test]
+    fn test_last_output() {
+        let mut terminal = Crosswords::new(10, 6, VoidListener {});
+        let mut parser = ParserProcessor::new();
+        parser.advance_bytes(&mut terminal, b"~$ ls\r\na\r\nb\r\n~$ ");
+
+        let pattern = pattern(&config::ShellIntegration::default());
+        assert_eq!(last_output(&terminal, pattern.as_ref()), Some(String::from("a\nb")));
+
+        parser.advance_bytes(&mut terminal, b"true\r\n~$ ");
+        assert_eq!(last_output(&terminal, pattern.as_ref()), None);
+
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07~$ \x1b]133;B\x07ls\r\n");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;C\x07c\r\n\x1b]133;D;0\x07");
+        assert_eq!(last_output(&terminal, None), Some(String::from("c")));
+    }
+
+    #[test]
+    fn test_command_marks() {
+        let mut terminal = Crosswords::new(10, 6, VoidListener {});
+        let mut parser = ParserProcessor::new();
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;C\x07a\r\n\x1b]133;D;0\x07");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07$ \x1b]133;B\x07no\r\n");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;C\x07\x1b]133;D;127\x07");
+        parser.advance_bytes(&mut terminal, b"\x1b]133;A\x07$ ");
+
+        let rows = terminal.visible_rows();
+        assert_eq!(command_marks(&rows), vec![(0, true), (2, false)]);
+    }
+}
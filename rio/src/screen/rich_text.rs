@@ -0,0 +1,299 @@
+// Export of styled terminal content to HTML and RTF, used to copy a
+// selection with its colors preserved, and to JSON for `rio msg get-text`.
+
+use rio_core::crosswords::square::Flags;
+
+/// Flags that affect how an exported span is styled.
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::STRIKEOUT);
+
+/// A run of consecutive characters sharing the same style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub fg: [f32; 4],
+    pub bg: [f32; 4],
+    pub flags: Flags,
+}
+
+/// Append a character to the line, extending the last span when the style matches.
+#[inline]
+pub fn push(line: &mut Vec<Span>, c: char, fg: [f32; 4], bg: [f32; 4], flags: Flags) {
+    let flags = flags & STYLE_FLAGS;
+    if let Some(span) = line.last_mut() {
+        if span.fg == fg && span.bg == bg && span.flags == flags {
+            span.text.push(c);
+            return;
+        }
+    }
+
+    line.push(Span {
+        text: c.to_string(),
+        fg,
+        bg,
+        flags,
+    });
+}
+
+#[inline]
+fn to_rgb(color: [f32; 4]) -> (u8, u8, u8) {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (channel(color[0]), channel(color[1]), channel(color[2]))
+}
+
+fn to_hex(color: [f32; 4]) -> String {
+    let (r, g, b) = to_rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape_html(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+pub fn to_html(lines: &[Vec<Span>], background: [f32; 4]) -> String {
+    let mut html = format!(
+        "<pre style=\"font-family: monospace; background-color: {};\">",
+        to_hex(background)
+    );
+
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            html.push('\n');
+        }
+
+        for span in line {
+            html.push_str(&format!(
+                "<span style=\"color: {}; background-color: {};",
+                to_hex(span.fg),
+                to_hex(span.bg)
+            ));
+            if span.flags.contains(Flags::BOLD) {
+                html.push_str(" font-weight: bold;");
+            }
+            if span.flags.contains(Flags::ITALIC) {
+                html.push_str(" font-style: italic;");
+            }
+            match (
+                span.flags.intersects(Flags::ALL_UNDERLINES),
+                span.flags.contains(Flags::STRIKEOUT),
+            ) {
+                (true, true) => {
+                    html.push_str(" text-decoration: underline line-through;")
+                }
+                (true, false) => html.push_str(" text-decoration: underline;"),
+                (false, true) => html.push_str(" text-decoration: line-through;"),
+                (false, false) => (),
+            }
+            html.push_str("\">");
+            escape_html(&span.text, &mut html);
+            html.push_str("</span>");
+        }
+    }
+
+    html.push_str("</pre>");
+    html
+}
+
+fn escape_rtf(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                output.push('\\');
+                output.push(c);
+            }
+            c if c.is_ascii() => output.push(c),
+            c => {
+                // RTF expects signed 16-bit UTF-16 code units.
+                let mut buffer = [0; 2];
+                for unit in c.encode_utf16(&mut buffer) {
+                    output.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+}
+
+pub fn to_rtf(lines: &[Vec<Span>], background: [f32; 4]) -> String {
+    let mut colors: Vec<(u8, u8, u8)> = vec![to_rgb(background)];
+    let mut color_index = |color: [f32; 4]| {
+        let rgb = to_rgb(color);
+        // Index 0 is reserved for the automatic color.
+        match colors.iter().position(|c| *c == rgb) {
+            Some(position) => position + 1,
+            None => {
+                colors.push(rgb);
+                colors.len()
+            }
+        }
+    };
+
+    let mut body = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            body.push_str("\\line\n");
+        }
+
+        for span in line {
+            body.push_str(&format!(
+                "{{\\cf{} \\highlight{} \\cb{}",
+                color_index(span.fg),
+                color_index(span.bg),
+                color_index(span.bg)
+            ));
+            if span.flags.contains(Flags::BOLD) {
+                body.push_str("\\b");
+            }
+            if span.flags.contains(Flags::ITALIC) {
+                body.push_str("\\i");
+            }
+            if span.flags.intersects(Flags::ALL_UNDERLINES) {
+                body.push_str("\\ul");
+            }
+            if span.flags.contains(Flags::STRIKEOUT) {
+                body.push_str("\\strike");
+            }
+            body.push(' ');
+            escape_rtf(&span.text, &mut body);
+            body.push('}');
+        }
+    }
+
+    let mut rtf = String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Menlo;}}");
+    rtf.push_str("{\\colortbl;");
+    for (r, g, b) in colors {
+        rtf.push_str(&format!("\\red{r}\\green{g}\\blue{b};"));
+    }
+    rtf.push_str("}\n\\f0\\cb1 ");
+    rtf.push_str(&body);
+    rtf.push('}');
+    rtf
+}
+
+fn escape_json(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+}
+
+/// Lines as an array of arrays of runs, e.g.
+/// `[[{"text":"ls","fg":"#ffffff","bg":"#000000","styles":["bold"]}]]`.
+pub fn to_json(lines: &[Vec<Span>]) -> String {
+    let mut json = String::from("[");
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+
+        json.push('[');
+        for (index, span) in line.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+
+            json.push_str("{\"text\":\"");
+            escape_json(&span.text, &mut json);
+            json.push_str(&format!(
+                "\",\"fg\":\"{}\",\"bg\":\"{}\",\"styles\":[",
+                to_hex(span.fg),
+                to_hex(span.bg)
+            ));
+            let styles = [
+                (span.flags.contains(Flags::BOLD), "bold"),
+                (span.flags.contains(Flags::ITALIC), "italic"),
+                (span.flags.intersects(Flags::ALL_UNDERLINES), "underline"),
+                (span.flags.contains(Flags::STRIKEOUT), "strikeout"),
+            ];
+            let styles: Vec<String> = styles
+                .iter()
+                .filter(|(is_set, _)| *is_set)
+                .map(|(_, style)| format!("\"{style}\""))
+                .collect();
+            json.push_str(&styles.join(","));
+            json.push_str("]}");
+        }
+        json.push(']');
+    }
+
+    json.push(']');
+    json
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+    #[test]
+    fn test_push_merges_spans_with_same_style() {
+        let mut line = vec![];
+        push(&mut line, 'a', WHITE, BLACK, Flags::empty());
+        push(&mut line, 'b', WHITE, BLACK, Flags::WRAPLINE);
+        push(&mut line, 'c', RED, BLACK, Flags::empty());
+        push(&mut line, 'd', RED, BLACK, Flags::BOLD);
+
+        assert_eq!(line.len(), 3);
+        assert_eq!(line[0].text, "ab");
+        assert_eq!(line[1].text, "c");
+        assert_eq!(line[2].text, "d");
+    }
+
+    #[test]
+    fn test_to_html() {
+        let mut line = vec![];
+        push(&mut line, '<', RED, BLACK, Flags::BOLD);
+        push(&mut line, 'a', WHITE, BLACK, Flags::empty());
+
+        assert_eq!(
+            to_html(&[line, vec![]], BLACK),
+            "<pre style=\"font-family: monospace; background-color: #000000;\">\
+            <span style=\"color: #ff0000; background-color: #000000; font-weight: bold;\">&lt;</span>\
+            <span style=\"color: #ffffff; background-color: #000000;\">a</span>\n</pre>"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut line = vec![];
+        push(&mut line, '"', RED, BLACK, Flags::BOLD | Flags::ITALIC);
+        push(&mut line, 'a', WHITE, BLACK, Flags::empty());
+
+        assert_eq!(
+            to_json(&[line, vec![]]),
+            "[[{\"text\":\"\\\"\",\"fg\":\"#ff0000\",\"bg\":\"#000000\",\"styles\":[\"bold\",\"italic\"]},\
+            {\"text\":\"a\",\"fg\":\"#ffffff\",\"bg\":\"#000000\",\"styles\":[]}],[]]"
+        );
+    }
+
+    #[test]
+    fn test_to_rtf() {
+        let mut line = vec![];
+        push(&mut line, '{', RED, BLACK, Flags::UNDERLINE);
+        push(&mut line, 'é', WHITE, BLACK, Flags::empty());
+
+        let rtf = to_rtf(&[line], BLACK);
+        assert!(rtf.contains("{\\colortbl;\\red0\\green0\\blue0;\\red255\\green0\\blue0;\\red255\\green255\\blue255;}"));
+        assert!(rtf.contains("{\\cf2 \\highlight1 \\cb1\\ul \\{}"));
+        assert!(rtf.contains("{\\cf3 \\highlight1 \\cb1 \\u233?}"));
+    }
+}
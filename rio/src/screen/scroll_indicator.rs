@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+
+/// How long the indicator stays visible after the last scroll.
+const VISIBLE: Duration = Duration::from_millis(1000);
+
+/// How long it takes to fade out afterwards.
+const FADE: Duration = Duration::from_millis(300);
+
+/// Interval between frames while fading.
+const FADE_FRAME_MS: u64 = 16;
+
+/// Thumb width, in logical pixels.
+pub const WIDTH: f32 = 4.0;
+
+/// Clicks this close to the right edge jump through the scrollback, in
+/// logical pixels.
+pub const HIT_WIDTH: f32 = 12.0;
+
+/// Shortest thumb, in logical pixels.
+pub const MIN_HEIGHT: f32 = 16.0;
+
+/// Slim scrollbar on the right edge, shown while the viewport is scrolled
+/// into history and faded out after a while without scrolling.
+#[derive(Debug, Default)]
+pub struct ScrollIndicator {
+    enabled: bool,
+    last_scroll: Option<Instant>,
+}
+
+impl ScrollIndicator {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last_scroll: None,
+        }
+    }
+
+    /// Show the indicator again, after the viewport was scrolled.
+    #[inline]
+    pub fn touch(&mut self) {
+        if self.enabled {
+            self.last_scroll = Some(Instant::now());
+        }
+    }
+
+    /// Opacity at `now`, 0.0 once faded out.
+    pub fn opacity(&self, now: Instant) -> f32 {
+        let elapsed = match self.last_scroll {
+            Some(last_scroll) => now.saturating_duration_since(last_scroll),
+            None => return 0.0,
+        };
+
+        if elapsed <= VISIBLE {
+            1.0
+        } else if elapsed < VISIBLE + FADE {
+            1.0 - (elapsed - VISIBLE).as_secs_f32() / FADE.as_secs_f32()
+        } else {
+            0.0
+        }
+    }
+
+    /// Milliseconds until the opacity changes, if it will.
+    pub fn next_frame_in(&self, now: Instant) -> Option<u64> {
+        let elapsed = now.saturating_duration_since(self.last_scroll?);
+        if elapsed < VISIBLE {
+            Some((VISIBLE - elapsed).as_millis() as u64 + 1)
+        } else if elapsed < VISIBLE + FADE {
+            Some(FADE_FRAME_MS)
+        } else {
+            None
+        }
+    }
+}
+
+/// Top and height of the thumb, as fractions of the track, with
+/// `display_offset` lines scrolled into `history` lines above `screen_lines`.
+pub fn thumb(display_offset: usize, history: usize, screen_lines: usize) -> (f32, f32) {
+    let total = (history + screen_lines).max(1) as f32;
+    let top = history.saturating_sub(display_offset) as f32 / total;
+    (top, screen_lines as f32 / total)
+}
+
+/// Display offset centering the viewport at `fraction` of the track.
+pub fn offset_at(fraction: f64, history: usize, screen_lines: usize) -> usize {
+    let total = (history + screen_lines) as f64;
+    let top = fraction * total - screen_lines as f64 / 2.0;
+    history - top.clamp(0.0, history as f64).round() as usize
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_fade_out() {
+        let now = Instant::now();
+        let mut indicator = ScrollIndicator::new(true);
+        assert_eq!(indicator.opacity(now), 0.0);
+        assert_eq!(indicator.next_frame_in(now), None);
+
+        indicator.touch();
+        let last_scroll = indicator.last_scroll.unwrap();
+        assert_eq!(indicator.opacity(last_scroll), 1.0);
+        assert_eq!(indicator.next_frame_in(last_scroll), Some(1001));
+
+        let fading = last_scroll + VISIBLE + FADE / 2;
+        assert!((indicator.opacity(fading) - 0.5).abs() < 0.01);
+        assert_eq!(indicator.next_frame_in(fading), Some(FADE_FRAME_MS));
+
+        let hidden = last_scroll + VISIBLE + FADE;
+        assert_eq!(indicator.opacity(hidden), 0.0);
+        assert_eq!(indicator.next_frame_in(hidden), None);
+
+        let mut disabled = ScrollIndicator::new(false);
+        disabled.touch();
+        assert_eq!(disabled.opacity(Instant::now()), 0.0);
+    }
+
+    #[test]
+    fn test_thumb() {
+        assert_eq!(thumb(0, 75, 25), (0.75, 0.25));
+        assert_eq!(thumb(75, 75, 25), (0.0, 0.25));
+        assert_eq!(thumb(0, 0, 0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_offset_at() {
+        assert_eq!(offset_at(0.0, 75, 25), 75);
+        assert_eq!(offset_at(1.0, 75, 25), 0);
+        // Centered on the click.
+        assert_eq!(offset_at(0.5, 75, 25), 37);
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+/// How long the size stays visible after the last resize.
+const VISIBLE: Duration = Duration::from_millis(1000);
+
+/// Grid size, e.g. "80×24", shown in the middle of the window while it is
+/// being resized.
+#[derive(Debug)]
+pub struct SizeOverlay {
+    size: (usize, usize),
+    last_resize: Option<Instant>,
+}
+
+impl SizeOverlay {
+    /// Overlay of a window which opened with `columns` and `rows`.
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            size: (columns, rows),
+            last_resize: None,
+        }
+    }
+
+    /// The window was resized to `columns` and `rows` at `now`, which is
+    /// only shown when the grid changed.
+    pub fn resized(&mut self, columns: usize, rows: usize, now: Instant) {
+        if self.size != (columns, rows) {
+            self.size = (columns, rows);
+            self.last_resize = Some(now);
+        }
+    }
+
+    /// Text to show at `now`, if any.
+    pub fn text(&self, now: Instant) -> Option<String> {
+        let (columns, rows) = self.size;
+        let elapsed = now.saturating_duration_since(self.last_resize?);
+        if elapsed < VISIBLE {
+            Some(format!("{columns}×{rows}"))
+        } else {
+            None
+        }
+    }
+
+    /// Milliseconds until the size is hidden, while it is shown.
+    pub fn next_frame_in(&self, now: Instant) -> Option<u64> {
+        let elapsed = now.saturating_duration_since(self.last_resize?);
+        VISIBLE
+            .checked_sub(elapsed)
+            .filter(|wait| !wait.is_zero())
+            .map(|wait| wait.as_millis() as u64 + 1)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_resized() {
+        let now = Instant::now();
+        let mut overlay = SizeOverlay::new(80, 24);
+        assert_eq!(overlay.text(now), None);
+        assert_eq!(overlay.next_frame_in(now), None);
+
+        // Same grid, e.g. a resize by less than a cell.
+        overlay.resized(80, 24, now);
+        assert_eq!(overlay.text(now), None);
+
+        overlay.resized(100, 30, now);
+        assert_eq!(overlay.text(now), Some(String::from("100×30")));
+        assert_eq!(overlay.next_frame_in(now), Some(1001));
+
+        let later = now + VISIBLE;
+        assert_eq!(overlay.text(later), None);
+        assert_eq!(overlay.next_frame_in(later), None);
+    }
+}
@@ -0,0 +1,160 @@
+/// Interval between animation frames, when the refresh rate of the display
+/// is unknown.
+pub const SMOOTH_SCROLL_FRAME_MS: u64 = 8;
+
+/// Shortest and longest interval between animation frames.
+const MIN_FRAME_MS: u64 = 4;
+const MAX_FRAME_MS: u64 = 33;
+
+/// Fraction of the remaining offset kept after a frame of
+/// `SMOOTH_SCROLL_FRAME_MS`.
+const DECAY: f32 = 0.55;
+
+/// Offsets below half a pixel are not visible, so the animation stops.
+const MIN_OFFSET: f32 = 0.5;
+
+/// Animates display offset changes at sub-line granularity.
+///
+/// The grid is always scrolled by whole lines, the animation only renders
+/// the new frame shifted back to where the content was and moves it towards
+/// its final position over a few frames.
+#[derive(Debug)]
+pub struct SmoothScroll {
+    enabled: bool,
+    offset: f32,
+    frame_ms: u64,
+    /// Fraction of the remaining offset kept after each frame.
+    decay: f32,
+}
+
+impl SmoothScroll {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            offset: 0.0,
+            frame_ms: SMOOTH_SCROLL_FRAME_MS,
+            decay: DECAY,
+        }
+    }
+
+    /// Draw frames at the pace of a display refreshing at
+    /// `refresh_rate_millihertz`, the animation lasts as long at any pace.
+    pub fn set_refresh_rate(&mut self, refresh_rate_millihertz: Option<u32>) {
+        self.frame_ms = frame_interval_ms(refresh_rate_millihertz);
+        self.decay = DECAY.powf(self.frame_ms as f32 / SMOOTH_SCROLL_FRAME_MS as f32);
+    }
+
+    /// Milliseconds between animation frames.
+    #[inline]
+    pub fn frame_ms(&self) -> u64 {
+        self.frame_ms
+    }
+
+    /// Start (or extend) an animation after the display offset changed by `lines`.
+    ///
+    /// The offset is limited to `max_offset`, so jumps through the scrollback
+    /// (e.g. scroll to top) do not animate through thousands of lines.
+    #[inline]
+    pub fn push(&mut self, lines: i32, line_height: f32, max_offset: f32) {
+        if !self.enabled || lines == 0 {
+            return;
+        }
+
+        self.offset -= lines as f32 * line_height;
+        self.offset = self.offset.clamp(-max_offset, max_offset);
+    }
+
+    /// Advance one frame and return the offset that should be rendered.
+    #[inline]
+    pub fn step(&mut self) -> f32 {
+        let current = self.offset;
+        self.offset *= self.decay;
+        if self.offset.abs() < MIN_OFFSET {
+            self.offset = 0.0;
+        }
+
+        current
+    }
+
+    #[inline]
+    pub fn is_animating(&self) -> bool {
+        self.offset != 0.0
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.offset = 0.0;
+    }
+}
+
+/// Milliseconds between frames of a display refreshing at
+/// `refresh_rate_millihertz`.
+fn frame_interval_ms(refresh_rate_millihertz: Option<u32>) -> u64 {
+    match refresh_rate_millihertz {
+        Some(millihertz) if millihertz > 0 => {
+            (1_000_000 / u64::from(millihertz)).clamp(MIN_FRAME_MS, MAX_FRAME_MS)
+        }
+        _ => SMOOTH_SCROLL_FRAME_MS,
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_disabled() {
+        let mut smooth_scroll = SmoothScroll::new(false);
+        smooth_scroll.push(3, 20.0, 1000.0);
+        assert!(!smooth_scroll.is_animating());
+        assert_eq!(smooth_scroll.step(), 0.0);
+    }
+
+    #[test]
+    fn test_animation_converges() {
+        let mut smooth_scroll = SmoothScroll::new(true);
+        smooth_scroll.push(2, 20.0, 1000.0);
+        assert!(smooth_scroll.is_animating());
+        assert_eq!(smooth_scroll.step(), -40.0);
+
+        let mut frames = 0;
+        while smooth_scroll.is_animating() {
+            let offset = smooth_scroll.step();
+            assert!(offset < 0.0 && offset > -40.0);
+            frames += 1;
+        }
+
+        assert!(frames < 20);
+        assert_eq!(smooth_scroll.step(), 0.0);
+    }
+
+    #[test]
+    fn test_offset_is_clamped() {
+        let mut smooth_scroll = SmoothScroll::new(true);
+        smooth_scroll.push(-10_000, 20.0, 500.0);
+        assert_eq!(smooth_scroll.step(), 500.0);
+    }
+
+    #[test]
+    fn test_frame_interval_ms() {
+        assert_eq!(frame_interval_ms(None), SMOOTH_SCROLL_FRAME_MS);
+        assert_eq!(frame_interval_ms(Some(0)), SMOOTH_SCROLL_FRAME_MS);
+        assert_eq!(frame_interval_ms(Some(60_000)), 16);
+        assert_eq!(frame_interval_ms(Some(144_000)), 6);
+        assert_eq!(frame_interval_ms(Some(500_000)), MIN_FRAME_MS);
+        assert_eq!(frame_interval_ms(Some(24_000)), MAX_FRAME_MS);
+    }
+
+    #[test]
+    fn test_refresh_rate() {
+        let mut smooth_scroll = SmoothScroll::new(true);
+        smooth_scroll.set_refresh_rate(Some(60_000));
+        assert_eq!(smooth_scroll.frame_ms(), 16);
+
+        // Two frames of 8ms at 120Hz decay as much as one of 16ms at 60Hz.
+        smooth_scroll.push(1, 100.0, 1000.0);
+        smooth_scroll.step();
+        let offset = smooth_scroll.step();
+        assert!((offset + 100.0 * DECAY * DECAY).abs() < 0.01);
+    }
+}
@@ -1,24 +1,42 @@
-use crate::crosswords::grid::row::Row;
-use crate::crosswords::pos;
-use crate::crosswords::pos::CursorState;
-use crate::crosswords::square::{Flags, Square};
 use crate::ime::Preedit;
-use crate::selection::SelectionRange;
 use crate::tabs::TabsControl;
 use colors::{
     term::{List, TermColors},
-    AnsiColor, Colors, NamedColor,
+    AnsiColor, ColorArray, ColorBuilder, ColorRgb, Colors, Format, NamedColor,
 };
-use config::Config;
+use config::{BlinkingText, Config};
+use rio_core::crosswords::grid::row::Row;
+use rio_core::crosswords::pos;
+use rio_core::crosswords::pos::CursorState;
+use rio_core::crosswords::square::{Flags, Hyperlink, Square};
+use rio_core::selection::SelectionRange;
 use std::rc::Rc;
-use sugarloaf::core::{Sugar, SugarStack, SugarStyle};
+use std::time::Instant;
+use sugarloaf::core::{
+    queue_highlight, Highlight, HighlightLayer, Sugar, SugarStack, SugarStyle,
+};
 use sugarloaf::Sugarloaf;
 
+/// Cursor drawn as the background of the square under it.
+const BLOCK_CURSOR: char = '█';
+
+/// Time blinking text is shown, then hidden.
+const BLINK_INTERVAL_MS: u128 = 500;
+
+/// How much faint text without a dim color of its own is darkened.
+const DIM_FACTOR: f32 = 0.66;
+
+/// Least contrast between text and its background in high-contrast mode,
+/// the WCAG AAA level.
+const HIGH_CONTRAST_RATIO: f32 = 7.0;
+
 #[derive(Default)]
 struct Cursor {
     state: CursorState,
     content: char,
     content_ref: char,
+    /// Color set by the application, overriding the configured one.
+    color: Option<ColorArray>,
 }
 
 pub struct State {
@@ -28,32 +46,28 @@ pub struct State {
     cursor: Cursor,
     colors: List,
     selection_range: Option<SelectionRange>,
-}
-
-// TODO: Finish from
-impl From<Square> for Sugar {
-    #[inline]
-    fn from(square: Square) -> Sugar {
-        let mut style: Option<SugarStyle> = None;
-        let is_italic = square.flags.contains(Flags::ITALIC);
-        let is_bold_italic = square.flags.contains(Flags::BOLD_ITALIC);
-        let is_bold = square.flags.contains(Flags::BOLD);
-
-        if is_bold || is_bold_italic || is_italic {
-            style = Some(SugarStyle {
-                is_italic,
-                is_bold_italic,
-                is_bold,
-            });
-        }
-
-        Sugar {
-            content: square.c,
-            foreground_color: [0.0, 0.0, 0.0, 1.0],
-            background_color: [0.0, 0.0, 0.0, 1.0],
-            style,
-        }
-    }
+    search_matches: Vec<SelectionRange>,
+    hovered_hyperlink: Option<Hyperlink>,
+    is_vi_mode: bool,
+    has_background_image: bool,
+    is_focused: bool,
+    high_contrast: bool,
+    blinking_text: BlinkingText,
+    bold_brightens_colors: bool,
+    /// Start of the blink cycle, shared by all blinking text.
+    blink_start: Instant,
+    /// Whether blinking text is hidden in the current frame.
+    is_blink_hidden: bool,
+    /// Whether the current frame has blinking text.
+    has_blinking_text: bool,
+    /// Stacks and highlights of the lines in the last frame, reused until
+    /// the lines are damaged.
+    lines: Vec<(SugarStack, Vec<Highlight>)>,
+    /// Whether every line is laid out again in the next frame.
+    is_fully_damaged: bool,
+    /// Lines laid out again in the next frame, besides the ones damaged in
+    /// the terminal.
+    damaged_lines: Vec<usize>,
 }
 
 impl State {
@@ -71,21 +85,49 @@ impl State {
             is_ime_enabled: false,
             colors,
             selection_range: None,
+            search_matches: vec![],
+            hovered_hyperlink: None,
+            is_vi_mode: false,
+            has_background_image: config.background_image.path.is_some(),
+            is_focused: true,
+            high_contrast: config.accessibility.high_contrast,
+            blinking_text: config.style.blinking_text,
+            bold_brightens_colors: config.style.bold_brightens_colors,
+            blink_start: Instant::now(),
+            is_blink_hidden: false,
+            has_blinking_text: false,
+            lines: vec![],
+            is_fully_damaged: true,
+            damaged_lines: vec![],
             named_colors: config.colors,
             cursor: Cursor {
                 content: config.cursor,
                 content_ref: config.cursor,
                 state: CursorState::default(),
+                color: None,
             },
         }
     }
 
     // TODO: Square.into()
     #[inline]
-    fn create_sugar_from_square(&self, square: &Square) -> Sugar {
+    pub fn create_sugar_from_square(&self, square: &Square) -> Sugar {
         let flags = square.flags;
 
-        let foreground_color = match square.fg {
+        let fg = match (square.fg, flags & Flags::DIM_BOLD) {
+            (AnsiColor::Named(color), Flags::DIM) => AnsiColor::Named(color.to_dim()),
+            (AnsiColor::Named(color), Flags::BOLD) if self.bold_brightens_colors => {
+                AnsiColor::Named(color.to_light())
+            }
+            (AnsiColor::Indexed(index @ 0..=7), Flags::BOLD)
+                if self.bold_brightens_colors =>
+            {
+                AnsiColor::Indexed(index + 8)
+            }
+            (color, _) => color,
+        };
+
+        let foreground_color = match fg {
             AnsiColor::Named(NamedColor::Black) => self.named_colors.black,
             AnsiColor::Named(NamedColor::Background) => self.named_colors.background.0,
             AnsiColor::Named(NamedColor::Blue) => self.named_colors.blue,
@@ -131,10 +173,20 @@ impl State {
             }
         };
 
+        // Colors without a dim variant are darkened instead.
+        let foreground_color = match fg {
+            AnsiColor::Spec(_) | AnsiColor::Indexed(16..)
+                if flags & Flags::DIM_BOLD == Flags::DIM =>
+            {
+                colors::dim(foreground_color, DIM_FACTOR)
+            }
+            _ => foreground_color,
+        };
+
         let background_color = match square.bg {
             AnsiColor::Spec(_rgb) => self.named_colors.foreground,
             AnsiColor::Named(NamedColor::Black) => self.named_colors.black,
-            AnsiColor::Named(NamedColor::Background) => self.named_colors.background.0,
+            AnsiColor::Named(NamedColor::Background) => self.default_background(),
             AnsiColor::Named(NamedColor::Blue) => self.named_colors.blue,
             AnsiColor::Named(NamedColor::LightBlack) => self.named_colors.light_black,
             AnsiColor::Named(NamedColor::LightBlue) => self.named_colors.light_blue,
@@ -169,10 +221,23 @@ impl State {
             AnsiColor::Indexed(idx) => self.colors[idx as usize],
         };
 
+        // Applications may still pick colors that are hard to read.
+        let foreground_color = if self.high_contrast {
+            colors::readable_foreground(
+                foreground_color,
+                background_color,
+                HIGH_CONTRAST_RATIO,
+            )
+        } else {
+            foreground_color
+        };
+
         let mut style: Option<SugarStyle> = None;
         let is_italic = flags.contains(Flags::ITALIC);
         let is_bold_italic = flags.contains(Flags::BOLD_ITALIC);
-        let is_bold = flags.contains(Flags::BOLD);
+        let is_blinking = flags.contains(Flags::BLINK);
+        let is_bold = flags.contains(Flags::BOLD)
+            || (is_blinking && self.blinking_text == BlinkingText::Bold);
 
         if is_bold || is_bold_italic || is_italic {
             style = Some(SugarStyle {
@@ -182,8 +247,15 @@ impl State {
             });
         }
 
+        let is_hidden = flags.contains(Flags::HIDDEN);
+        let content = if is_hidden || (is_blinking && self.is_blink_hidden) {
+            ' '
+        } else {
+            square.c
+        };
+
         Sugar {
-            content: square.c,
+            content,
             foreground_color,
             background_color,
             style,
@@ -191,22 +263,74 @@ impl State {
     }
 
     #[inline]
-    fn create_sugar_stack_with_selection(
+    fn create_sugar_stack(
         &mut self,
         row: &Row<Square>,
         has_cursor: bool,
-        range: &SelectionRange,
-        line: pos::Line,
-    ) -> SugarStack {
+        line: usize,
+    ) -> (SugarStack, Vec<Highlight>) {
         let mut stack: Vec<Sugar> = vec![];
+        let mut highlights: Vec<Highlight> = vec![];
         let columns: usize = row.len();
         for column in 0..columns {
-            let is_selected = range.contains(pos::Pos::new(line, pos::Column(column)));
             let square = &row.inner[column];
+            self.has_blinking_text |= square.flags.contains(Flags::BLINK);
+            let pos = pos::Pos::new(pos::Line(line as i32), pos::Column(column));
+            let is_selected = self
+                .selection_range
+                .map_or(false, |range| range.contains(pos));
+            let is_cursor = has_cursor && column == self.cursor.state.pos.col;
+            // Only outlined while unfocused, leaving the text as it is.
+            let is_hollow = is_cursor && !self.is_focused && !self.is_ime_enabled;
+            let cursor_color = self.cursor.color.unwrap_or(self.named_colors.cursor);
+
+            let mut highlight = |layer, color| {
+                queue_highlight(
+                    &mut highlights,
+                    Highlight {
+                        layer,
+                        line,
+                        columns: column..column + 1,
+                        color,
+                    },
+                );
+            };
+            if self.search_matches.iter().any(|range| range.contains(pos)) {
+                highlight(
+                    HighlightLayer::SearchMatch,
+                    self.named_colors.search_match_background,
+                );
+            }
+            if self.hovered_hyperlink.is_some()
+                && square.hyperlink() == self.hovered_hyperlink
+            {
+                highlight(
+                    HighlightLayer::Hyperlink,
+                    self.named_colors.hyperlink_hover_background,
+                );
+            }
+            if is_selected {
+                highlight(
+                    HighlightLayer::Selection,
+                    self.named_colors.selection_background,
+                );
+            }
+            if is_cursor && self.is_vi_mode {
+                let layer = if is_hollow {
+                    HighlightLayer::CursorOutline
+                } else {
+                    HighlightLayer::ViCursor
+                };
+                highlight(layer, self.named_colors.vi_cursor_background);
+            } else if is_hollow {
+                highlight(HighlightLayer::CursorOutline, cursor_color);
+            }
 
-            if has_cursor && column == self.cursor.state.pos.col {
-                let mut foreground_color = self.named_colors.cursor;
+            if is_cursor && !self.is_vi_mode && !is_hollow {
+                let mut content = self.cursor.content;
+                let mut foreground_color = cursor_color;
                 let mut background_color = self.named_colors.background.0;
+                let mut style = None;
 
                 if is_selected {
                     foreground_color = self.named_colors.yellow;
@@ -215,24 +339,36 @@ impl State {
                 if self.is_ime_enabled {
                     foreground_color = self.named_colors.background.0;
                     background_color = self.named_colors.yellow;
+                } else if content == BLOCK_CURSOR {
+                    // The text stays readable under the block, in the color of
+                    // its background unless configured.
+                    let sugar = self.create_sugar_from_square(square);
+                    content = sugar.content;
+                    background_color = foreground_color;
+                    foreground_color = self
+                        .named_colors
+                        .cursor_text
+                        .unwrap_or(sugar.background_color);
+                    style = sugar.style;
                 }
 
                 stack.push(Sugar {
-                    content: self.cursor.content,
+                    content,
                     foreground_color,
                     background_color,
-                    style: None,
+                    style,
                 });
-            } else if is_selected {
-                let selected_sugar = Sugar {
-                    content: square.c,
-                    foreground_color: self.named_colors.background.0,
-                    background_color: self.named_colors.light_blue,
-                    style: None,
-                };
-                stack.push(selected_sugar);
             } else {
-                stack.push(self.create_sugar_from_square(square));
+                let mut sugar = self.create_sugar_from_square(square);
+                if is_selected {
+                    sugar.foreground_color = self.named_colors.selection_foreground;
+                }
+                if square.flags.contains(Flags::STRIKEOUT)
+                    && !square.flags.contains(Flags::HIDDEN)
+                {
+                    highlight(HighlightLayer::Strikethrough, sugar.foreground_color);
+                }
+                stack.push(sugar);
             }
 
             // Render last column and break row
@@ -241,96 +377,269 @@ impl State {
             }
         }
 
-        stack
+        (stack, highlights)
     }
 
+    /// Lay out `line` again in the next frame.
     #[inline]
-    fn create_sugar_stack(&mut self, row: &Row<Square>, has_cursor: bool) -> SugarStack {
-        let mut stack: Vec<Sugar> = vec![];
-        let columns: usize = row.len();
-        for column in 0..columns {
-            let square = &row.inner[column];
+    fn damage_line(&mut self, line: pos::Line) {
+        if line.0 >= 0 {
+            self.damaged_lines.push(line.0 as usize);
+        }
+    }
 
-            if has_cursor && column == self.cursor.state.pos.col {
-                let mut foreground_color = self.named_colors.cursor;
-                let mut background_color = self.named_colors.background.0;
+    #[inline]
+    pub fn set_colors(&mut self, colors: Colors) {
+        self.named_colors = colors;
+        self.is_fully_damaged = true;
+    }
 
-                if self.is_ime_enabled {
-                    foreground_color = self.named_colors.background.0;
-                    background_color = self.named_colors.yellow;
-                }
+    /// Change a color by the name used in the config file (e.g. `light-red`).
+    pub fn set_color(&mut self, name: &str, color: ColorArray) -> Result<(), String> {
+        self.is_fully_damaged = true;
+        let colors = &mut self.named_colors;
+        if name == "cursor-text" {
+            colors.cursor_text = Some(color);
+            return Ok(());
+        }
 
-                stack.push(Sugar {
-                    content: self.cursor.content,
-                    foreground_color,
-                    background_color,
-                    style: None,
-                });
-            } else {
-                stack.push(self.create_sugar_from_square(square));
-            }
+        let target = match name {
+            "background" => &mut colors.background.0,
+            "foreground" => &mut colors.foreground,
+            "cursor" => &mut colors.cursor,
+            "tabs" => &mut colors.tabs,
+            "tabs-active" => &mut colors.tabs_active,
+            "selection-background" => &mut colors.selection_background,
+            "selection-foreground" => &mut colors.selection_foreground,
+            "search-match-background" => &mut colors.search_match_background,
+            "hyperlink-hover-background" => &mut colors.hyperlink_hover_background,
+            "vi-cursor-background" => &mut colors.vi_cursor_background,
+            "bar-background" => &mut colors.bar_background,
+            "bar-foreground" => &mut colors.bar_foreground,
+            "black" => &mut colors.black,
+            "red" => &mut colors.red,
+            "green" => &mut colors.green,
+            "yellow" => &mut colors.yellow,
+            "blue" => &mut colors.blue,
+            "magenta" => &mut colors.magenta,
+            "cyan" => &mut colors.cyan,
+            "white" => &mut colors.white,
+            "dim-black" => &mut colors.dim_black,
+            "dim-red" => &mut colors.dim_red,
+            "dim-green" => &mut colors.dim_green,
+            "dim-yellow" => &mut colors.dim_yellow,
+            "dim-blue" => &mut colors.dim_blue,
+            "dim-magenta" => &mut colors.dim_magenta,
+            "dim-cyan" => &mut colors.dim_cyan,
+            "dim-white" => &mut colors.dim_white,
+            "dim-foreground" => &mut colors.dim_foreground,
+            "light-black" => &mut colors.light_black,
+            "light-red" => &mut colors.light_red,
+            "light-green" => &mut colors.light_green,
+            "light-yellow" => &mut colors.light_yellow,
+            "light-blue" => &mut colors.light_blue,
+            "light-magenta" => &mut colors.light_magenta,
+            "light-cyan" => &mut colors.light_cyan,
+            "light-white" => &mut colors.light_white,
+            "light-foreground" => &mut colors.light_foreground,
+            other => return Err(format!("unknown color {other:?}")),
+        };
 
-            // Render last column and break row
-            if column == (columns - 1) {
-                break;
-            }
+        *target = color;
+        Ok(())
+    }
+
+    /// Background of cells without a color of their own, transparent so a
+    /// background image shows through.
+    #[inline]
+    fn default_background(&self) -> ColorArray {
+        if self.has_background_image {
+            [0.0, 0.0, 0.0, 0.0]
+        } else {
+            self.named_colors.background.0
         }
+    }
 
-        stack
+    /// Cursor color set by the application with OSC 12, `None` once reset.
+    #[inline]
+    pub fn set_cursor_color(&mut self, color: Option<ColorRgb>) {
+        let color =
+            color.map(|rgb| ColorBuilder::from_rgb(rgb, Format::SRGB0_1).to_arr());
+        if self.cursor.color != color {
+            self.cursor.color = color;
+            self.damage_line(self.cursor.state.pos.row);
+        }
     }
 
-    pub fn set_ime(&mut self, ime_preedit: Option<&Preedit>) {
-        if let Some(preedit) = ime_preedit {
-            if let Some(content) = preedit.text.chars().next() {
-                self.cursor.content = content;
-                self.is_ime_enabled = true;
-                return;
-            }
+    /// Milliseconds until blinking text in the last frame is shown or
+    /// hidden.
+    pub fn next_blink_in(&self) -> Option<u64> {
+        if !self.has_blinking_text || self.blinking_text != BlinkingText::Blink {
+            return None;
+        }
+
+        let elapsed = self.blink_start.elapsed().as_millis();
+        Some((BLINK_INTERVAL_MS - elapsed % BLINK_INTERVAL_MS) as u64)
+    }
+
+    #[inline]
+    pub fn set_focused(&mut self, is_focused: bool) {
+        if self.is_focused != is_focused {
+            self.damage_line(self.cursor.state.pos.row);
+        }
+        self.is_focused = is_focused;
+    }
+
+    #[inline]
+    pub fn foreground_color(&self) -> [f32; 4] {
+        self.named_colors.foreground
+    }
+
+    #[inline]
+    pub fn background_color(&self) -> [f32; 4] {
+        self.named_colors.background.0
+    }
+
+    /// Background, title and status colors of the bar.
+    #[inline]
+    pub fn bar_colors(&self) -> ([f32; 4], [f32; 4], [f32; 4]) {
+        (
+            self.named_colors.bar_background,
+            self.named_colors.tabs,
+            self.named_colors.bar_foreground,
+        )
+    }
+
+    /// Background and text colors of the labels of hints.
+    #[inline]
+    pub fn hint_colors(&self) -> ([f32; 4], [f32; 4]) {
+        (self.named_colors.yellow, self.named_colors.background.0)
+    }
+
+    /// Color of the mark of a command which succeeded or failed.
+    #[inline]
+    pub fn command_mark_color(&self, succeeded: bool) -> [f32; 4] {
+        if succeeded {
+            self.named_colors.green
+        } else {
+            self.named_colors.red
         }
+    }
+
+    /// Label color of the current tab in the bar.
+    #[inline]
+    pub fn bar_active_color(&self) -> [f32; 4] {
+        self.named_colors.tabs_active
+    }
 
-        self.is_ime_enabled = false;
-        self.cursor.content = self.cursor.content_ref;
+    pub fn set_ime(&mut self, ime_preedit: Option<&Preedit>) {
+        let (content, is_ime_enabled) = match ime_preedit
+            .and_then(|preedit| preedit.text.chars().next())
+        {
+            Some(content) => (content, true),
+            None => (self.cursor.content_ref, false),
+        };
+
+        if (content, is_ime_enabled) != (self.cursor.content, self.is_ime_enabled) {
+            self.damage_line(self.cursor.state.pos.row);
+        }
+        self.cursor.content = content;
+        self.is_ime_enabled = is_ime_enabled;
     }
 
     #[inline]
     pub fn set_selection(&mut self, selection_range: Option<SelectionRange>) {
+        if self.selection_range == selection_range {
+            return;
+        }
+
+        for range in [self.selection_range, selection_range].into_iter().flatten() {
+            for line in range.start.row.0.max(0)..=range.end.row.0 {
+                self.damage_line(pos::Line(line));
+            }
+        }
         self.selection_range = selection_range;
     }
 
     #[inline]
+    #[allow(unused)]
+    pub fn set_search_matches(&mut self, search_matches: Vec<SelectionRange>) {
+        self.is_fully_damaged |= self.search_matches != search_matches;
+        self.search_matches = search_matches;
+    }
+
+    /// Returns whether the hovered hyperlink changed.
+    #[inline]
+    pub fn set_hovered_hyperlink(&mut self, hyperlink: Option<Hyperlink>) -> bool {
+        let changed = self.hovered_hyperlink != hyperlink;
+        self.hovered_hyperlink = hyperlink;
+        self.is_fully_damaged |= changed;
+        changed
+    }
+
+    #[inline]
+    pub fn set_vi_mode(&mut self, is_vi_mode: bool) {
+        self.is_fully_damaged |= self.is_vi_mode != is_vi_mode;
+        self.is_vi_mode = is_vi_mode;
+    }
+
+    /// Queue `rows` into `sugarloaf`, laying out again only the lines in
+    /// `damaged_lines`, or all of them when it is `None`, and the lines
+    /// changed by the state since the last frame.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
-        rows: Vec<Row<Square>>,
+        rows: &[Row<Square>],
+        damaged_lines: Option<Vec<usize>>,
         cursor: CursorState,
         sugarloaf: &mut Sugarloaf,
         style: sugarloaf::core::SugarloafStyle,
         _tab_style: sugarloaf::core::SugarloafStyle,
         _tabs: &TabsControl,
     ) {
-        self.cursor.state = cursor;
+        if self.cursor.state != cursor {
+            self.damage_line(self.cursor.state.pos.row);
+            self.damage_line(cursor.pos.row);
+            self.cursor.state = cursor;
+        }
+
+        let is_blink_hidden = self.blinking_text == BlinkingText::Blink
+            && (self.blink_start.elapsed().as_millis() / BLINK_INTERVAL_MS) % 2 == 1;
+        self.is_fully_damaged |=
+            self.has_blinking_text && self.is_blink_hidden != is_blink_hidden;
+        self.is_blink_hidden = is_blink_hidden;
+
+        let is_fully_damaged = self.is_fully_damaged
+            || damaged_lines.is_none()
+            || self.lines.len() != rows.len();
+        let damaged_lines = damaged_lines.unwrap_or_default();
+        if is_fully_damaged {
+            self.lines.clear();
+            self.has_blinking_text = false;
+        }
 
         let is_cursor_visible = self.cursor.state.is_visible();
 
-        if let Some(sel) = self.selection_range {
-            for (i, row) in rows.iter().enumerate() {
+        for (i, row) in rows.iter().enumerate() {
+            if is_fully_damaged {
                 let has_cursor = is_cursor_visible && self.cursor.state.pos.row == i;
-                let sugar_stack = self.create_sugar_stack_with_selection(
-                    row,
-                    has_cursor,
-                    &sel,
-                    pos::Line(i as i32),
-                );
-                sugarloaf.stack(sugar_stack, style);
+                let line = self.create_sugar_stack(row, has_cursor, i);
+                self.lines.push(line);
+            } else if damaged_lines.contains(&i) || self.damaged_lines.contains(&i) {
+                let has_cursor = is_cursor_visible && self.cursor.state.pos.row == i;
+                self.lines[i] = self.create_sugar_stack(row, has_cursor, i);
             }
 
-            return;
+            let (stack, highlights) = &self.lines[i];
+            for highlight in highlights {
+                sugarloaf.highlight(highlight.clone());
+            }
+            sugarloaf.stack(stack.clone(), style);
         }
 
-        for (i, row) in rows.iter().enumerate() {
-            let has_cursor = is_cursor_visible && self.cursor.state.pos.row == i;
-            let sugar_stack = self.create_sugar_stack(row, has_cursor);
-            sugarloaf.stack(sugar_stack, style);
-        }
+        self.is_fully_damaged = false;
+        self.damaged_lines.clear();
 
         // if tabs.len() > 1 {
         //     sugarloaf.tabs(
@@ -0,0 +1,99 @@
+// URLs opened with Ctrl+click, from hyperlinks set by applications (OSC 8)
+// or recognized in the text of a line.
+//
+// Only URLs with a scheme from `links.schemes` are handed to `open` on macOS
+// and `xdg-open` elsewhere, since those also launch whatever application
+// registered a scheme.
+
+use crate::platform;
+use regex::Regex;
+#[cfg(not(windows))]
+use std::process::Command;
+
+/// URLs recognized in the text, hyperlinks can have any scheme.
+const URL: &str = r#"(https?|ftp|file)://[^\s<>"'`]+|mailto:[^\s<>"'`]+"#;
+
+/// Scheme of `url`, e.g. `https`.
+pub fn scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(':')?;
+    let mut chars = scheme.chars();
+    let is_valid = chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'));
+    is_valid.then_some(scheme)
+}
+
+/// Whether the scheme of `url` is one of `schemes`.
+pub fn is_allowed(url: &str, schemes: &[String]) -> bool {
+    scheme(url).map_or(false, |scheme| {
+        schemes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// URL in `text` over the character at `column`.
+pub fn find(text: &str, column: usize) -> Option<String> {
+    let pattern = Regex::new(URL).ok()?;
+    let url = pattern.find_iter(text).find_map(|found| {
+        // Punctuation ending a sentence isn't part of the URL.
+        let url = found
+            .as_str()
+            .trim_end_matches(['.', ',', ':', ';', '!', '?', ')']);
+        let start = text[..found.start()].chars().count();
+        let end = start + url.chars().count();
+        (start..end).contains(&column).then(|| url.to_string())
+    });
+    url
+}
+
+pub fn open(url: String) {
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut command = Command::new("open");
+        command.arg(&url);
+        Some(command)
+    };
+
+    #[cfg(windows)]
+    let command = None;
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(&url);
+        Some(command)
+    };
+
+    platform::run_in_background(command, format!("open {url}"));
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_scheme() {
+        assert_eq!(scheme("https://example.com"), Some("https"));
+        assert_eq!(scheme("mailto:rio@example.com"), Some("mailto"));
+        assert_eq!(scheme("-o:x"), None);
+        assert_eq!(scheme("example.com"), None);
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let schemes = vec![String::from("https"), String::from("mailto")];
+        assert!(is_allowed("https://example.com", &schemes));
+        assert!(is_allowed("HTTPS://example.com", &schemes));
+        assert!(!is_allowed("smb://host/share", &schemes));
+        assert!(!is_allowed("javascript:alert(1)", &schemes));
+    }
+
+    #[test]
+    fn test_find() {
+        let text = "see https://example.com/docs. or mailto:rio@example.com";
+        assert_eq!(find(text, 2), None);
+        assert_eq!(find(text, 4).as_deref(), Some("https://example.com/docs"));
+        assert_eq!(find(text, 28), None);
+        assert_eq!(find(text, 40).as_deref(), Some("mailto:rio@example.com"));
+    }
+}
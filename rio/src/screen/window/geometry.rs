@@ -0,0 +1,142 @@
+// Window placement at startup.
+//
+// The window is placed on the monitor named in the configuration, or the
+// primary one, at the configured position or centered. With
+// `window.remember` the last position and size are saved on exit and used
+// instead.
+
+use std::io;
+use std::path::PathBuf;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::MonitorHandle;
+
+/// Position and size of the window, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+pub fn path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".rio").join("window"))
+}
+
+impl Geometry {
+    pub fn load() -> Option<Geometry> {
+        let content = std::fs::read_to_string(path()?).ok()?;
+        Geometry::parse(&content)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "unable to find home directory")
+        })?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, self.serialize())
+    }
+
+    /// Read `x y width height`.
+    fn parse(content: &str) -> Option<Geometry> {
+        let mut values = content.split_whitespace();
+        let x = values.next()?.parse().ok()?;
+        let y = values.next()?.parse().ok()?;
+        let width = values.next()?.parse().ok()?;
+        let height = values.next()?.parse().ok()?;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some(Geometry {
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+        })
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "{} {} {} {}\n",
+            self.position.x, self.position.y, self.size.width, self.size.height
+        )
+    }
+}
+
+/// First monitor whose name contains `name`, ignoring case.
+pub fn find_monitor(
+    mut monitors: impl Iterator<Item = MonitorHandle>,
+    name: &str,
+) -> Option<MonitorHandle> {
+    let name = name.to_lowercase();
+    monitors.find(|monitor| {
+        monitor
+            .name()
+            .map_or(false, |monitor_name| monitor_name.to_lowercase().contains(&name))
+    })
+}
+
+/// Where a window of `size` logical pixels goes on `monitor`, at `position`
+/// logical pixels from its top left corner or centered.
+pub fn position_on(
+    monitor: &MonitorHandle,
+    position: Option<[i32; 2]>,
+    size: (u16, u16),
+) -> PhysicalPosition<i32> {
+    let origin = monitor.position();
+    let monitor_size = monitor.size();
+    let (x, y) = offset(
+        (monitor_size.width, monitor_size.height),
+        monitor.scale_factor(),
+        position,
+        size,
+    );
+    PhysicalPosition::new(origin.x + x, origin.y + y)
+}
+
+/// Offset from the top left corner of a monitor of `monitor_size` physical
+/// pixels, in physical pixels.
+fn offset(
+    monitor_size: (u32, u32),
+    scale: f64,
+    position: Option<[i32; 2]>,
+    size: (u16, u16),
+) -> (i32, i32) {
+    match position {
+        Some([x, y]) => ((x as f64 * scale) as i32, (y as f64 * scale) as i32),
+        None => {
+            let width = size.0 as f64 * scale;
+            let height = size.1 as f64 * scale;
+            (
+                ((monitor_size.0 as f64 - width) / 2.0).max(0.0) as i32,
+                ((monitor_size.1 as f64 - height) / 2.0).max(0.0) as i32,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let geometry = Geometry {
+            position: PhysicalPosition::new(-20, 40),
+            size: PhysicalSize::new(1200, 800),
+        };
+        assert_eq!(Geometry::parse(&geometry.serialize()), Some(geometry));
+        assert_eq!(Geometry::parse("10 20 0 800"), None);
+        assert_eq!(Geometry::parse("10 20"), None);
+        assert_eq!(Geometry::parse(""), None);
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(offset((1920, 1080), 1.0, Some([100, 50]), (800, 600)), (100, 50));
+        assert_eq!(offset((3840, 2160), 2.0, Some([100, 50]), (800, 600)), (200, 100));
+        assert_eq!(offset((1920, 1080), 1.0, None, (800, 600)), (560, 240));
+        // Larger than the monitor.
+        assert_eq!(offset((640, 480), 1.0, None, (800, 600)), (0, 0));
+    }
+}
@@ -1,11 +1,22 @@
+pub mod geometry;
+pub mod resize;
+pub mod title;
+
 pub const LOGO_ICON: &[u8; 20264] = include_bytes!("./resources/images/logo-macos.ico");
 // Terminal W/H contraints
 pub const DEFAULT_MINIMUM_WINDOW_HEIGHT: i32 = 150;
 pub const DEFAULT_MINIMUM_WINDOW_WIDTH: i32 = 300;
 
+/// Builder of a window named `class` and `name` for window manager rules,
+/// as the two parts of `WM_CLASS` on X11 (`class` is also the Wayland app ID).
+/// It can't be resized below `min_size`, or the default minimum if larger.
+#[allow(unused_variables)]
 pub fn create_window_builder(
     title: &str,
     size: (u16, u16),
+    min_size: (u16, u16),
+    class: &str,
+    name: &str,
 ) -> winit::window::WindowBuilder {
     use winit::window::Icon;
 
@@ -25,23 +36,27 @@ pub fn create_window_builder(
             height: size.1,
         })
         .with_min_inner_size(winit::dpi::LogicalSize {
-            width: DEFAULT_MINIMUM_WINDOW_WIDTH,
-            height: DEFAULT_MINIMUM_WINDOW_HEIGHT,
+            width: DEFAULT_MINIMUM_WINDOW_WIDTH.max(min_size.0.into()),
+            height: DEFAULT_MINIMUM_WINDOW_HEIGHT.max(min_size.1.into()),
         })
         .with_resizable(true)
         .with_decorations(true)
         .with_window_icon(Some(icon));
 
-    #[cfg(any(
-        target_os = "linux",
-        target_os = "dragonfly",
-        target_os = "freebsd",
-        target_os = "netbsd",
-        target_os = "openbsd"
+    // winit keeps one name, read by whichever backend the window uses.
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    {
+        use winit::platform::x11::WindowBuilderExtX11;
+        window_builder = window_builder.with_name(class, name);
+    }
+    #[cfg(all(
+        feature = "wayland",
+        not(feature = "x11"),
+        not(any(target_os = "macos", windows))
     ))]
     {
-        // use winit::platform::unix::WindowBuilderExtUnix;
-        // window_builder = window_builder.with_name(title);
+        use winit::platform::wayland::WindowBuilderExtWayland;
+        window_builder = window_builder.with_name(class, name);
     }
 
     #[cfg(target_os = "macos")]
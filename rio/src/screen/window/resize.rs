@@ -0,0 +1,92 @@
+use winit::window::CursorIcon;
+
+/// Width of the area along the edges resizing a window without decorations,
+/// in logical pixels.
+const EDGE_WIDTH: f64 = 6.0;
+
+/// Edge of the window being dragged. Only edges which keep the window in
+/// place are handled, since moving windows isn't supported everywhere
+/// (e.g. Wayland).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Right,
+    Bottom,
+    BottomRight,
+}
+
+impl Edge {
+    /// Edge of a `size` window under `position`, both in physical pixels.
+    pub fn at(position: (f64, f64), size: (f64, f64), scale: f64) -> Option<Edge> {
+        let edge_width = EDGE_WIDTH * scale;
+        let is_right = position.0 >= size.0 - edge_width;
+        let is_bottom = position.1 >= size.1 - edge_width;
+        match (is_right, is_bottom) {
+            (true, true) => Some(Edge::BottomRight),
+            (true, false) => Some(Edge::Right),
+            (false, true) => Some(Edge::Bottom),
+            (false, false) => None,
+        }
+    }
+
+    pub fn cursor_icon(self) -> CursorIcon {
+        match self {
+            Edge::Right => CursorIcon::EResize,
+            Edge::Bottom => CursorIcon::SResize,
+            Edge::BottomRight => CursorIcon::SeResize,
+        }
+    }
+}
+
+/// Resize in progress, from a press on an edge until the button is released.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeResize {
+    edge: Edge,
+    /// Distance between the mouse and the edges when grabbed.
+    grab: (f64, f64),
+}
+
+impl EdgeResize {
+    pub fn new(edge: Edge, position: (f64, f64), size: (f64, f64)) -> EdgeResize {
+        EdgeResize {
+            edge,
+            grab: (size.0 - position.0, size.1 - position.1),
+        }
+    }
+
+    /// Size of a `size` window with the mouse moved to `position`.
+    pub fn size(&self, position: (f64, f64), size: (f64, f64)) -> (f64, f64) {
+        let width = position.0 + self.grab.0;
+        let height = position.1 + self.grab.1;
+        match self.edge {
+            Edge::Right => (width, size.1),
+            Edge::Bottom => (size.0, height),
+            Edge::BottomRight => (width, height),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_edge_at() {
+        let size = (800.0, 600.0);
+        assert_eq!(Edge::at((400.0, 300.0), size, 1.0), None);
+        assert_eq!(Edge::at((797.0, 300.0), size, 1.0), Some(Edge::Right));
+        assert_eq!(Edge::at((400.0, 599.0), size, 1.0), Some(Edge::Bottom));
+        assert_eq!(Edge::at((799.0, 599.0), size, 1.0), Some(Edge::BottomRight));
+        // Wider on high density displays.
+        assert_eq!(Edge::at((790.0, 300.0), size, 2.0), Some(Edge::Right));
+    }
+
+    #[test]
+    fn test_edge_resize() {
+        let size = (800.0, 600.0);
+        let resize = EdgeResize::new(Edge::Right, (797.0, 300.0), size);
+        assert_eq!(resize.size((897.0, 350.0), size), (900.0, 600.0));
+
+        let resize = EdgeResize::new(Edge::BottomRight, (799.0, 599.0), size);
+        assert_eq!(resize.size((699.0, 499.0), size), (700.0, 500.0));
+    }
+}
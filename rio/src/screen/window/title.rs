@@ -0,0 +1,75 @@
+// Window title set by applications with OSC 0 and 2.
+//
+// Shells setting the title on every prompt, or programs on every line of
+// output, would otherwise call into the platform for each change. Changes
+// are applied at most every `INTERVAL`, the last one wins.
+
+use std::time::{Duration, Instant};
+
+/// Title of the window when no application set one.
+pub const DEFAULT_TITLE: &str = "Rio";
+
+/// Shortest time between two changes of the window title.
+pub const INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+pub struct TitleThrottle {
+    pending: Option<String>,
+    last_change: Option<Instant>,
+}
+
+impl TitleThrottle {
+    /// Title to set at `now`, or `None` when it has to `wait`.
+    pub fn update(&mut self, title: String, now: Instant) -> Option<String> {
+        self.pending = Some(title);
+        self.flush(now)
+    }
+
+    /// Pending title, once `INTERVAL` passed since the last change.
+    pub fn flush(&mut self, now: Instant) -> Option<String> {
+        if self.wait(now).is_some() {
+            return None;
+        }
+
+        let title = self.pending.take()?;
+        self.last_change = Some(now);
+        Some(title)
+    }
+
+    /// Time until the pending title can be set.
+    pub fn wait(&self, now: Instant) -> Option<Duration> {
+        self.pending.as_ref()?;
+        let elapsed = now.saturating_duration_since(self.last_change?);
+        INTERVAL.checked_sub(elapsed).filter(|wait| !wait.is_zero())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_title_throttle() {
+        let mut throttle = TitleThrottle::default();
+        let now = Instant::now();
+        assert_eq!(throttle.flush(now), None);
+
+        assert_eq!(
+            throttle.update(String::from("a"), now),
+            Some(String::from("a"))
+        );
+        assert_eq!(throttle.wait(now), None);
+
+        // Held back, the last title wins.
+        let soon = now + INTERVAL / 2;
+        assert_eq!(throttle.update(String::from("b"), soon), None);
+        assert_eq!(throttle.update(String::from("c"), soon), None);
+        assert_eq!(throttle.wait(soon), Some(INTERVAL / 2));
+        assert_eq!(throttle.flush(soon), None);
+
+        let later = now + INTERVAL;
+        assert_eq!(throttle.flush(later), Some(String::from("c")));
+        assert_eq!(throttle.flush(later), None);
+        assert_eq!(throttle.wait(later), None);
+    }
+}
@@ -1,9 +1,25 @@
-use crate::clipboard::ClipboardType;
-use crate::event::{ClickState, EventP, EventProxy, RioEvent, RioEventType};
+use crate::appearance;
+use crate::bench;
+use crate::cli::Cli;
+use crate::dropdown::{self, Dropdown, Hotkey, ANIMATION_FRAME_MS};
+use crate::event::{ClickState, EventP, EventProxy, RioEventType};
 use crate::ime::Preedit;
+use crate::ipc;
+use crate::layout::Layout;
+use crate::notification;
 use crate::scheduler::{Scheduler, TimerId, Topic};
-use crate::screen::{window::create_window_builder, Screen};
+use crate::screen::latency::ECHO_TIMEOUT;
+use crate::screen::window::create_window_builder;
+use crate::screen::window::geometry::{self, Geometry};
+use crate::screen::window::resize::{Edge, EdgeResize};
+use crate::screen::window::title::{self, TitleThrottle};
+use crate::screen::{bar, Screen};
+use crate::startup::StartupToken;
+use config::WindowMode;
+use rio_core::clipboard::ClipboardType;
+use rio_core::event::RioEvent;
 use std::error::Error;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
 use winit::event::{
@@ -11,18 +27,86 @@ use winit::event::{
 };
 use winit::event_loop::{DeviceEventFilter, EventLoop};
 use winit::platform::run_return::EventLoopExtRunReturn;
-#[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
-use winit::platform::wayland::EventLoopWindowTargetExtWayland;
-use winit::window::ImePurpose;
+use winit::window::{Fullscreen, ImePurpose, Theme, Window, WindowLevel};
+
+/// Set the window title, or once `title::INTERVAL` passed since the last
+/// change.
+fn update_title(
+    window: &Window,
+    scheduler: &mut Scheduler,
+    throttle: &mut TitleThrottle,
+    title: String,
+) {
+    let now = Instant::now();
+    if let Some(title) = throttle.update(title, now) {
+        window.set_title(&title);
+        return;
+    }
+
+    let timer_id = TimerId::new(Topic::Title, 0);
+    if let Some(wait) = throttle.wait(now) {
+        if !scheduler.scheduled(timer_id) {
+            scheduler.schedule(
+                EventP::new(RioEventType::UpdateTitle),
+                wait,
+                false,
+                timer_id,
+            );
+        }
+    }
+}
+
+/// Layout of the configured font and bar, to measure windows in logical
+/// pixels.
+fn logical_layout(config: &config::Config) -> Layout {
+    let mut layout = Layout::new(0.0, 0.0, 1.0, config.style.font_size);
+    layout.set_bar(config.bar.position, config.bar.height);
+    layout.set_bar_reserved(bar::is_reserved(&config.bar, 1));
+    layout
+}
+
+/// Logical size of the window at startup, fitting `window.columns` and
+/// `window.lines` when set.
+fn startup_size(config: &config::Config) -> (u16, u16) {
+    let layout = logical_layout(config);
+    let columns = config.window.columns.unwrap_or_default();
+    let lines = config.window.lines.unwrap_or_default();
+    let (width, height) = layout.size_for(columns, lines);
+
+    (
+        config.window.columns.map_or(config.width, |_| width as u16),
+        config.window.lines.map_or(config.height, |_| height as u16),
+    )
+}
+
+/// Read the configuration file again, with the profile, accessibility and
+/// feature settings applied at startup.
+fn reload_config(cli: &Cli) -> Result<config::Config, String> {
+    let mut config = config::Config::try_load()?;
+    if let Some(name) = &cli.profile {
+        config.apply_profile(name)?;
+    }
+    config.apply_high_contrast();
+    config.apply_features_env();
+    Ok(config)
+}
 
 pub struct Sequencer {
     config: Rc<config::Config>,
+    cli: Cli,
+    startup: Option<StartupToken>,
 }
 
 impl Sequencer {
-    pub fn new(config: config::Config) -> Sequencer {
+    pub fn new(
+        config: config::Config,
+        cli: Cli,
+        startup: Option<StartupToken>,
+    ) -> Sequencer {
         Sequencer {
             config: Rc::new(config),
+            cli,
+            startup,
         }
     }
 
@@ -33,11 +117,101 @@ impl Sequencer {
         let proxy = event_loop.create_proxy();
         let event_proxy = EventProxy::new(proxy.clone());
         let mut scheduler = Scheduler::new(proxy);
-        let window_builder =
-            create_window_builder("Rio", (self.config.width, self.config.height));
+        let size = startup_size(&self.config);
+        let (min_width, min_height) = logical_layout(&self.config).minimum_size();
+        let mut window_builder = create_window_builder(
+            title::DEFAULT_TITLE,
+            size,
+            (min_width as u16, min_height as u16),
+            &self.config.window.class,
+            &self.config.window.name,
+        );
+
+        // Where the window was last time, or on the configured monitor and
+        // position, otherwise the system decides.
+        let window_config = &self.config.window;
+        let monitor = window_config.monitor.as_ref().and_then(|name| {
+            geometry::find_monitor(event_loop.available_monitors(), name)
+        });
+        let remembered = if window_config.remember {
+            Geometry::load()
+        } else {
+            None
+        };
+        if let Some(remembered) = remembered {
+            window_builder = window_builder
+                .with_position(remembered.position)
+                .with_inner_size(remembered.size);
+        } else if monitor.is_some() || window_config.position.is_some() {
+            if let Some(target) = monitor.clone().or_else(|| event_loop.primary_monitor())
+            {
+                window_builder = window_builder.with_position(geometry::position_on(
+                    &target,
+                    window_config.position,
+                    size,
+                ));
+            }
+        }
+
+        #[allow(unused_mut)]
+        let mut has_decorations =
+            self.config.window.decorations && !self.config.dropdown.enabled;
+
+        // Compositors without server-side decorations would get the ones
+        // drawn by winit, an enabled bar takes their place instead.
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        if has_decorations && self.config.bar.enabled {
+            use winit::platform::wayland::EventLoopWindowTargetExtWayland;
+            if let Some(display) = event_loop.wayland_display() {
+                if !crate::platform::wayland::has_server_decorations(display) {
+                    log::info!("no server-side decorations, using the bar as title bar");
+                    has_decorations = false;
+                }
+            }
+        }
+
+        if !has_decorations {
+            window_builder = window_builder.with_decorations(false);
+        }
+        if self.config.dropdown.enabled {
+            window_builder = window_builder
+                .with_decorations(false)
+                .with_window_level(WindowLevel::AlwaysOnTop);
+        } else {
+            window_builder = match self.config.window.mode {
+                WindowMode::Windowed => window_builder,
+                WindowMode::Maximized => window_builder.with_maximized(true),
+                WindowMode::Fullscreen => {
+                    window_builder.with_fullscreen(Some(Fullscreen::Borderless(monitor)))
+                }
+            };
+        }
+        // The tabs are in the bar, macOS can't group windows of different
+        // processes in native tabs.
+        #[cfg(target_os = "macos")]
+        if !self.config.window.native_tabs {
+            crate::platform::macos::disable_window_tabbing();
+        }
         let winit_window = window_builder.build(&event_loop).unwrap();
+        let mut edge_resize: Option<EdgeResize> = None;
+
+        let mut dropdown = if self.config.dropdown.enabled {
+            let mut dropdown = Dropdown::new(self.config.dropdown.height);
+            dropdown.place(&winit_window);
+
+            let registered =
+                Hotkey::parse(&self.config.dropdown.hotkey).and_then(|hotkey| {
+                    dropdown::register_hotkey(hotkey, event_proxy.clone())
+                });
+            if let Err(err) = registered {
+                log::warn!("unable to register the dropdown hotkey: {err}");
+            }
+            Some(dropdown)
+        } else {
+            None
+        };
 
-        let current_mouse_cursor = winit::window::CursorIcon::Text;
+        let mut current_mouse_cursor = winit::window::CursorIcon::Text;
         winit_window.set_cursor_icon(current_mouse_cursor);
 
         // https://docs.rs/winit/latest/winit/window/enum.ImePurpose.html#variant.Terminal
@@ -63,65 +237,249 @@ impl Sequencer {
                 "both" => winit_window.set_option_as_alt(OptionAsAlt::Both),
                 "left" => winit_window.set_option_as_alt(OptionAsAlt::OnlyLeft),
                 "right" => winit_window.set_option_as_alt(OptionAsAlt::OnlyRight),
-                _ => {}
+                "none" => winit_window.set_option_as_alt(OptionAsAlt::None),
+                other => {
+                    log::warn!("unknown option-as-alt value {other:?}, using \"none\"");
+                    winit_window.set_option_as_alt(OptionAsAlt::None);
+                }
             }
         }
 
-        let mut screen = Screen::new(&winit_window, &self.config, event_proxy).await?;
+        // Started before the shell so it inherits the socket path.
+        #[cfg(unix)]
+        let socket_path = {
+            let path = ipc::socket_path(self.config.session.single_instance);
+            match ipc::spawn_server(&path, event_proxy.clone()) {
+                Ok(()) => {
+                    std::env::set_var(ipc::SOCKET_ENV, &path);
+                    Some(path)
+                }
+                Err(err) => {
+                    log::warn!("unable to start remote control socket: {err}");
+                    None
+                }
+            }
+        };
+
+        let mut screen =
+            Screen::new(&winit_window, &self.config, event_proxy.clone(), &self.cli)
+                .await?;
         let mut is_window_focused = false;
+        // Nothing is drawn while the window can't be seen, the PTY is still
+        // read and parsed meanwhile.
+        let mut is_occluded = false;
+        let mut is_minimized = false;
         let mut should_render = false;
+        let mut dropped_files: Vec<PathBuf> = vec![];
+        let mut title_throttle = TitleThrottle::default();
         screen.init(self.config.colors.background.1);
+        if let Some(is_dark) = appearance::is_dark(winit_window.theme()) {
+            screen.set_appearance(is_dark, self.config.appearance_colors(is_dark));
+        }
+        // The window is drawn, the launcher can stop waiting for it.
+        if let Some(startup) = self.startup.take() {
+            startup.complete(&winit_window);
+        }
+
+        if self.cli.bench {
+            bench::print(&bench::run(&mut screen));
+            #[cfg(unix)]
+            if let Some(path) = &socket_path {
+                let _ = std::fs::remove_file(path);
+            }
+            return Ok(());
+        }
+
         event_loop.set_device_event_filter(DeviceEventFilter::Always);
         event_loop.run_return(move |event, _, control_flow| {
             match event {
-                Event::UserEvent(EventP { payload, .. }) => {
-                    if let RioEventType::Rio(event) = payload {
-                        match event {
-                            RioEvent::Wakeup => {
+                Event::UserEvent(EventP { payload, .. }) => match payload {
+                    RioEventType::Rio(event) => match event {
+                        RioEvent::Wakeup => {
+                            screen.announce_output();
+                            // The echo of a keystroke is drawn without waiting
+                            // for the other events.
+                            if !is_occluded && !is_minimized && screen.render_echo() {
+                                should_render = false;
+                                if let Some(millis) = screen.next_frame_in() {
+                                    event_proxy.send_event(RioEventType::Rio(
+                                        RioEvent::PrepareRender(millis),
+                                    ));
+                                }
+                            } else {
                                 should_render = true;
                             }
-                            RioEvent::Render => {
-                                if self.config.advanced.disable_render_when_unfocused
-                                    && is_window_focused
-                                {
-                                    return;
-                                }
-                                screen.render();
+                        }
+                        RioEvent::Render => {
+                            if self.config.advanced.disable_render_when_unfocused
+                                && is_window_focused
+                            {
+                                return;
                             }
-                            RioEvent::PrepareRender(millis) => {
-                                let timer_id = TimerId::new(Topic::Frame, 0);
-                                let event =
-                                    EventP::new(RioEventType::Rio(RioEvent::Render));
-
-                                if !scheduler.scheduled(timer_id) {
-                                    scheduler.schedule(
-                                        event,
-                                        Duration::from_millis(millis),
-                                        false,
-                                        timer_id,
-                                    );
-                                }
+                            if is_occluded || is_minimized {
+                                return;
                             }
-                            RioEvent::Title(_title) => {
-                                // if !self.ctx.preserve_title && self.ctx.config.window.dynamic_title {
-                                // self.ctx.window().set_title(title);
-                                // }
+                            screen.render();
+                            should_render = false;
+
+                            if let Some(millis) = screen.next_frame_in() {
+                                event_proxy.send_event(RioEventType::Rio(
+                                    RioEvent::PrepareRender(millis),
+                                ));
+                            }
+                        }
+                        RioEvent::PrepareRender(millis) => {
+                            let timer_id = TimerId::new(Topic::Frame, 0);
+                            let event = EventP::new(RioEventType::Rio(RioEvent::Render));
+
+                            let interval = Duration::from_millis(millis);
+
+                            // An earlier frame replaces a later one, e.g. smooth
+                            // scrolling while the scroll indicator waits to fade.
+                            if scheduler
+                                .deadline(timer_id)
+                                .map_or(true, |deadline| {
+                                    Instant::now() + interval < deadline
+                                })
+                            {
+                                scheduler.unschedule(timer_id);
+                                scheduler.schedule(event, interval, false, timer_id);
+                            }
+                        }
+                        RioEvent::Bell => {
+                            screen.ring_bell();
+                        }
+                        // Windows running a program with `-e` close with it.
+                        RioEvent::ChildExit(_) if self.cli.command.is_some() => {
+                            *control_flow = winit::event_loop::ControlFlow::Exit;
+                        }
+                        RioEvent::Title(title) => {
+                            update_title(
+                                &winit_window,
+                                &mut scheduler,
+                                &mut title_throttle,
+                                title,
+                            );
+                        }
+                        RioEvent::ResetTitle => {
+                            update_title(
+                                &winit_window,
+                                &mut scheduler,
+                                &mut title_throttle,
+                                String::from(title::DEFAULT_TITLE),
+                            );
+                        }
+                        RioEvent::MouseCursorDirty => {
+                            screen.layout_mut().reset_mouse();
+                        }
+                        RioEvent::ClipboardStore(clipboard_type, text) => {
+                            screen.clipboard_store(clipboard_type, text);
+                        }
+                        RioEvent::ClipboardLoad(clipboard_type, format) => {
+                            if is_window_focused {
+                                let text =
+                                    format(screen.clipboard_get(clipboard_type).as_str());
+                                screen.messenger.send_bytes(text.into_bytes());
                             }
-                            RioEvent::MouseCursorDirty => {
-                                screen.layout_mut().reset_mouse();
+                        }
+                        RioEvent::CommandFinished {
+                            command,
+                            status,
+                            duration,
+                        } => {
+                            let notify_after = self.config.shell_integration.notify_after;
+                            if !is_window_focused
+                                && notify_after > 0
+                                && duration >= Duration::from_secs(notify_after)
+                            {
+                                let (title, body) = notification::command_finished(
+                                    &command, status, duration,
+                                );
+                                notification::send(title, body);
                             }
-                            RioEvent::ClipboardLoad(clipboard_type, format) => {
-                                if is_window_focused {
-                                    let text = format(
-                                        screen.clipboard_get(clipboard_type).as_str(),
+                        }
+                        _ => {}
+                    },
+                    RioEventType::Ipc(ipc::Request::ToggleDropdown, reply) => {
+                        let response = if dropdown.is_some() {
+                            event_proxy.send_event(RioEventType::ToggleDropdown);
+                            Ok(String::new())
+                        } else {
+                            Err(String::from("dropdown is not enabled"))
+                        };
+                        let _ = reply.send(response);
+                    }
+                    RioEventType::Ipc(ipc::Request::ReloadConfig, reply) => {
+                        let response = match reload_config(&self.cli) {
+                            Ok(config) => {
+                                screen.update_config(&config);
+                                let theme = winit_window.theme();
+                                if let Some(is_dark) = appearance::is_dark(theme) {
+                                    screen.set_appearance(
+                                        is_dark,
+                                        config.appearance_colors(is_dark),
                                     );
-                                    screen.messenger.send_bytes(text.into_bytes());
                                 }
+                                screen.render();
+                                self.config = Rc::new(config);
+                                Ok(String::new())
                             }
-                            _ => {}
+                            Err(err) => Err(err),
+                        };
+                        let _ = reply.send(response);
+                    }
+                    RioEventType::Ipc(request, reply) => {
+                        // Tabs requested from another invocation should be visible.
+                        if request == ipc::Request::CreateTab {
+                            winit_window.focus_window();
                         }
+                        let _ = reply.send(screen.handle_request(request));
                     }
-                }
+                    RioEventType::ToggleDropdown => {
+                        if let Some(dropdown) = dropdown.as_mut() {
+                            dropdown.toggle(&winit_window);
+
+                            let timer_id = TimerId::new(Topic::DropdownAnimation, 0);
+                            if !scheduler.scheduled(timer_id) {
+                                scheduler.schedule(
+                                    EventP::new(RioEventType::DropdownFrame),
+                                    Duration::from_millis(ANIMATION_FRAME_MS),
+                                    true,
+                                    timer_id,
+                                );
+                            }
+                        }
+                    }
+                    // The grid and the PTY follow with the `Resized` event.
+                    RioEventType::ToggleFullscreen => {
+                        let fullscreen = match winit_window.fullscreen() {
+                            Some(_) => None,
+                            None => Some(Fullscreen::Borderless(None)),
+                        };
+                        winit_window.set_fullscreen(fullscreen);
+                    }
+                    RioEventType::Quit => {
+                        *control_flow = winit::event_loop::ControlFlow::Exit;
+                    }
+                    RioEventType::ToggleMaximized => {
+                        winit_window.set_maximized(!winit_window.is_maximized());
+                    }
+                    RioEventType::UpdateTitle => {
+                        if let Some(title) = title_throttle.flush(Instant::now()) {
+                            winit_window.set_title(&title);
+                        }
+                    }
+                    RioEventType::DropdownFrame => {
+                        let is_animating = dropdown
+                            .as_mut()
+                            .map_or(false, |dropdown| dropdown.animate(&winit_window));
+                        if !is_animating {
+                            scheduler
+                                .unschedule(TimerId::new(Topic::DropdownAnimation, 0));
+                        }
+                    }
+                    _ => {}
+                },
                 Event::Resumed => {
                     // Should render once the loop is resumed for first time
                     // Then wait for instructions or user inputs
@@ -131,7 +489,9 @@ impl Sequencer {
                     event: winit::event::WindowEvent::CloseRequested,
                     ..
                 } => {
-                    *control_flow = winit::event_loop::ControlFlow::Exit;
+                    if screen.request_quit() {
+                        *control_flow = winit::event_loop::ControlFlow::Exit;
+                    }
                 }
 
                 Event::WindowEvent {
@@ -158,6 +518,50 @@ impl Sequencer {
                         _ => (),
                     }
 
+                    // Without decorations, the edges resize the window.
+                    if !has_decorations && button == MouseButton::Left {
+                        let size = winit_window.inner_size();
+                        let size = (size.width as f64, size.height as f64);
+                        let mouse = &screen.layout().mouse;
+                        let position = (mouse.x as f64, mouse.y as f64);
+                        let scale = winit_window.scale_factor();
+                        match state {
+                            ElementState::Pressed => {
+                                if let Some(edge) = Edge::at(position, size, scale) {
+                                    edge_resize =
+                                        Some(EdgeResize::new(edge, position, size));
+                                    return;
+                                }
+                            }
+                            ElementState::Released => {
+                                if edge_resize.take().is_some() {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+
+                    // The bar takes clicks even when the application reports the mouse.
+                    if state == ElementState::Pressed && button == MouseButton::Left {
+                        if let Some(hit) = screen.click_bar() {
+                            if hit == bar::Hit::Empty && !has_decorations {
+                                if let Err(err) = winit_window.drag_window() {
+                                    log::warn!("unable to drag the window: {err}");
+                                }
+                            }
+                            should_render = true;
+                            return;
+                        }
+                    }
+
+                    if state == ElementState::Pressed
+                        && (screen.click_context_menu(button)
+                            || screen.process_mouse_bindings(button))
+                    {
+                        should_render = true;
+                        return;
+                    }
+
                     match state {
                         ElementState::Pressed => {
                             // Process mouse press before bindings to update the `click_state`.
@@ -209,7 +613,9 @@ impl Sequencer {
                                 // Load mouse point, treating message bar and padding as the closest square.
                                 let display_offset = screen.display_offset();
 
-                                if let MouseButton::Left = button {
+                                if button == MouseButton::Left
+                                    && !screen.jump_to_scroll_position()
+                                {
                                     let point =
                                         screen.layout().mouse_position(display_offset);
                                     screen.on_left_click(point);
@@ -217,7 +623,6 @@ impl Sequencer {
 
                                 should_render = true;
                             }
-                            // screen.process_mouse_bindings(button);
                         }
                         ElementState::Released => {
                             if !screen.messenger.get_modifiers().shift()
@@ -250,6 +655,27 @@ impl Sequencer {
                     let x = position.x;
                     let y = position.y;
 
+                    let size = winit_window.inner_size();
+                    let size = (size.width as f64, size.height as f64);
+                    if let Some(resize) = edge_resize {
+                        let (width, height) = resize.size((x, y), size);
+                        winit_window.set_inner_size(winit::dpi::PhysicalSize::new(
+                            width.max(1.0) as u32,
+                            height.max(1.0) as u32,
+                        ));
+                        return;
+                    }
+
+                    if !has_decorations {
+                        let scale = winit_window.scale_factor();
+                        let cursor = Edge::at((x, y), size, scale)
+                            .map_or(winit::window::CursorIcon::Text, Edge::cursor_icon);
+                        if cursor != current_mouse_cursor {
+                            winit_window.set_cursor_icon(cursor);
+                            current_mouse_cursor = cursor;
+                        }
+                    }
+
                     let lmb_pressed =
                         screen.layout().mouse.left_button_state == ElementState::Pressed;
                     let rmb_pressed =
@@ -270,6 +696,14 @@ impl Sequencer {
                     let point = screen.layout().mouse_position(display_offset);
                     let square_changed = old_point != point;
 
+                    if square_changed && screen.update_hovered_hyperlink(point) {
+                        should_render = true;
+                    }
+
+                    if screen.update_bar_hover() || screen.update_context_menu_hover() {
+                        should_render = true;
+                    }
+
                     // If the mouse hasn't changed cells, do nothing.
                     if !square_changed
                     // && screen.layout().mouse.square_side == square_side
@@ -315,8 +749,9 @@ impl Sequencer {
                 } => {
                     winit_window.set_cursor_visible(true);
                     match delta {
-                        MouseScrollDelta::LineDelta(_x, _y) => {
-                            // scroll_y = y;
+                        MouseScrollDelta::LineDelta(columns, lines) => {
+                            screen.scroll_lines(columns, lines);
+                            should_render = true;
                         }
 
                         MouseScrollDelta::PixelDelta(mut lpos) => {
@@ -339,6 +774,7 @@ impl Sequencer {
                                     // Should implement update_selection_scrolling later
                                     screen.clear_selection();
                                     screen.scroll(lpos.x, lpos.y);
+                                    should_render = true;
                                 }
                                 _ => (),
                             }
@@ -375,7 +811,7 @@ impl Sequencer {
                     }
 
                     ElementState::Released => {
-                        // winit_window.request_redraw();
+                        screen.release_keycode(scancode);
                     }
                 },
 
@@ -384,7 +820,11 @@ impl Sequencer {
                     ..
                 } => match ime {
                     Ime::Commit(text) => {
-                        screen.paste(&text, true);
+                        // Committed text (e.g. from dead keys composition) is typed
+                        // input, so it should not be wrapped as bracketed paste.
+                        screen.scroll_bottom_when_cursor_not_visible();
+                        screen.clear_selection();
+                        screen.paste(&text, false);
                     }
                     Ime::Preedit(text, cursor_offset) => {
                         let preedit = if text.is_empty() {
@@ -411,28 +851,58 @@ impl Sequencer {
                     ..
                 } => {
                     is_window_focused = focused;
+                    screen.set_focused(focused);
+                    should_render = true;
+                }
+
+                Event::WindowEvent {
+                    event: winit::event::WindowEvent::ThemeChanged(theme),
+                    ..
+                } => {
+                    let is_dark = theme == Theme::Dark;
+                    screen
+                        .set_appearance(is_dark, self.config.appearance_colors(is_dark));
+                    should_render = true;
                 }
 
                 Event::WindowEvent {
                     event: winit::event::WindowEvent::DroppedFile(path),
                     ..
                 } => {
-                    let path: String = path.to_string_lossy().into();
-                    screen.paste(&(path + " "), true);
+                    // Multiple files are dropped as consecutive events,
+                    // those are pasted together once all events are processed.
+                    dropped_files.push(path);
                 }
 
                 Event::WindowEvent {
                     event: winit::event::WindowEvent::Resized(new_size),
                     ..
                 } => {
+                    // Minimized windows are resized to zero on some platforms.
                     if new_size.width == 0 || new_size.height == 0 {
+                        is_minimized = true;
                         return;
                     }
 
-                    screen.resize(new_size);
+                    if is_minimized {
+                        is_minimized = false;
+                        screen.mark_fully_damaged();
+                    }
+                    screen.resize(new_size).show_size();
                     should_render = true;
                 }
 
+                Event::WindowEvent {
+                    event: winit::event::WindowEvent::Occluded(occluded),
+                    ..
+                } => {
+                    is_occluded = occluded;
+                    if !occluded {
+                        screen.mark_fully_damaged();
+                        should_render = true;
+                    }
+                }
+
                 Event::WindowEvent {
                     event:
                         winit::event::WindowEvent::ScaleFactorChanged {
@@ -443,28 +913,87 @@ impl Sequencer {
                 } => {
                     screen
                         .set_scale(scale_factor as f32, *new_inner_size)
+                        .update_display(&winit_window)
                         .render();
                 }
 
+                Event::WindowEvent {
+                    event: winit::event::WindowEvent::Moved(_),
+                    ..
+                } => {
+                    screen.update_display(&winit_window);
+                }
+
                 // Emitted when the event loop is being shut down.
                 // This is irreversible - if this event is emitted, it is guaranteed to be the last event that gets emitted.
                 // You generally want to treat this as an “do on quit” event.
                 Event::LoopDestroyed { .. } => {
+                    if self.config.session.restore {
+                        screen.save_session();
+                    }
+
+                    // Keep the last windowed geometry, to return to it.
+                    let is_windowed = winit_window.fullscreen().is_none()
+                        && !winit_window.is_maximized();
+                    if self.config.window.remember && is_windowed {
+                        if let Ok(position) = winit_window.outer_position() {
+                            let geometry = Geometry {
+                                position,
+                                size: winit_window.inner_size(),
+                            };
+                            if let Err(err) = geometry.save() {
+                                log::warn!("unable to save the window geometry: {err}");
+                            }
+                        }
+                    }
+
+                    #[cfg(unix)]
+                    if let Some(path) = &socket_path {
+                        let _ = std::fs::remove_file(path);
+                    }
+
                     // TODO: Now we are forcing an exit operation
                     // but it should be revaluated since CloseRequested in MacOs
                     // not necessarily exit the process
                     std::process::exit(0);
                 }
                 Event::MainEventsCleared { .. } => {
-                    if should_render {
+                    if !dropped_files.is_empty() {
+                        screen.paste_files(&dropped_files);
+                        dropped_files.clear();
+                    }
+
+                    // Held back while the echo of a keystroke may arrive,
+                    // the timer draws it at the latest when it doesn't.
+                    if should_render && screen.is_awaiting_echo() {
+                        event_proxy.send_event(RioEventType::Rio(
+                            RioEvent::PrepareRender(ECHO_TIMEOUT.as_millis() as u64),
+                        ));
+                        should_render = false;
+                    } else if should_render && !is_occluded && !is_minimized {
                         screen.render();
                         should_render = false;
+
+                        if let Some(millis) = screen.next_frame_in() {
+                            event_proxy.send_event(RioEventType::Rio(
+                                RioEvent::PrepareRender(millis),
+                            ));
+                        }
                         return;
                     }
 
                     scheduler.update();
                 }
                 Event::RedrawRequested { .. } => {}
+                Event::RedrawEventsCleared { .. } => {
+                    // Wake up in time for the next scheduled timer.
+                    *control_flow = match scheduler.update() {
+                        Some(deadline) => {
+                            winit::event_loop::ControlFlow::WaitUntil(deadline)
+                        }
+                        None => winit::event_loop::ControlFlow::Wait,
+                    };
+                }
                 _ => {
                     *control_flow = winit::event_loop::ControlFlow::Wait;
                 }
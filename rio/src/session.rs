@@ -0,0 +1,277 @@
+// Terminal contents saved on exit and restored on startup.
+//
+// The primary screen and its scrollback are written as text with SGR
+// sequences, so restoring is a matter of feeding the file through the
+// parser before the shell starts writing. When a program was using the
+// alternate screen (e.g. an editor), its last screen follows as plain
+// output. Modes requested by programs (e.g. mouse reporting or application
+// cursor keys) and their cursor are not kept, since those programs are gone
+// once Rio restarts and the new shell starts below the restored contents.
+
+use colors::AnsiColor;
+use rio_core::crosswords::grid::{Dimensions, Grid};
+use rio_core::crosswords::pos::{Column, Line};
+use rio_core::crosswords::square::{Flags, LineLength, Square};
+use rio_core::crosswords::{Crosswords, Mode};
+use rio_core::event::EventListener;
+use rio_core::performer::handler::ParserProcessor;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// Flags that are written as SGR attributes.
+const SGR_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+pub fn path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".rio").join("session"))
+}
+
+pub fn save<U: EventListener>(terminal: &Crosswords<U>) -> io::Result<()> {
+    let path = path().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "unable to find home directory")
+    })?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    write_private(&path, serialize(terminal).as_bytes())
+}
+
+/// Replace `path` with a file only the user can read, since the scrollback
+/// may hold secrets. It is written aside first, so a failed write keeps the
+/// previous session.
+fn write_private(path: &Path, content: &[u8]) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    match std::fs::remove_file(&temporary) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => (),
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temporary)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+
+    std::fs::rename(temporary, path)
+}
+
+pub fn restore<U: EventListener>(terminal: &mut Crosswords<U>) -> io::Result<()> {
+    let path = path().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "unable to find home directory")
+    })?;
+    let content = std::fs::read(path)?;
+
+    ParserProcessor::new().advance_bytes(terminal, &content);
+
+    Ok(())
+}
+
+/// Write the primary screen and its scrollback, up to the cursor line,
+/// followed by the alternate screen when it is active.
+pub fn serialize<U: EventListener>(terminal: &Crosswords<U>) -> String {
+    let mut output = String::new();
+    let grid = terminal.primary_grid();
+    let lines = grid.topmost_line().0..=grid.cursor.pos.row.0;
+    serialize_lines(&mut output, grid, lines);
+
+    if terminal.mode().contains(Mode::ALT_SCREEN) {
+        let grid = &terminal.grid;
+        let last_line = (0..grid.screen_lines() as i32)
+            .rev()
+            .find(|line| grid[Line(*line)].line_length().0 > 0);
+        if let Some(last_line) = last_line {
+            serialize_lines(&mut output, grid, 0..=last_line);
+        }
+    }
+
+    output
+}
+
+fn serialize_lines(output: &mut String, grid: &Grid<Square>, lines: RangeInclusive<i32>) {
+    let last_column = grid.last_column();
+    let default = Square::default();
+    let mut style = (default.fg, default.bg, Flags::empty());
+    for line in lines.map(Line) {
+        let row = &grid[line];
+        let is_wrapped = row[last_column].flags.contains(Flags::WRAPLINE);
+        let length = if is_wrapped {
+            grid.columns()
+        } else {
+            row.line_length().0
+        };
+
+        for square in (0..length).map(|column| &row[Column(column)]) {
+            if square
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+
+            let square_style = (square.fg, square.bg, square.flags & SGR_FLAGS);
+            if square_style != style {
+                push_sgr(output, square);
+                style = square_style;
+            }
+
+            // Tabs were already expanded into the following squares.
+            output.push(if square.c == '\t' { ' ' } else { square.c });
+            for c in square.zerowidth().into_iter().flatten() {
+                output.push(*c);
+            }
+        }
+
+        if !is_wrapped {
+            output.push_str("\r\n");
+        }
+    }
+
+    output.push_str("\x1b[0m");
+}
+
+fn push_sgr(output: &mut String, square: &Square) {
+    output.push_str("\x1b[0");
+
+    let flags = square.flags;
+    for (flag, code) in [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::ALL_UNDERLINES, "4"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ] {
+        if flags.intersects(flag) {
+            output.push(';');
+            output.push_str(code);
+        }
+    }
+
+    push_color(output, square.fg, 30, 90, 38);
+    push_color(output, square.bg, 40, 100, 48);
+    output.push('m');
+}
+
+fn push_color(
+    output: &mut String,
+    color: AnsiColor,
+    base: usize,
+    light: usize,
+    extended: u8,
+) {
+    match color {
+        AnsiColor::Named(named) if (named as usize) < 8 => {
+            output.push_str(&format!(";{}", base + named as usize));
+        }
+        AnsiColor::Named(named) if (named as usize) < 16 => {
+            output.push_str(&format!(";{}", light + named as usize - 8));
+        }
+        // Foreground, background and the remaining named colors are the defaults.
+        AnsiColor::Named(_) => (),
+        AnsiColor::Indexed(index) => {
+            output.push_str(&format!(";{extended};5;{index}"));
+        }
+        AnsiColor::Spec(rgb) => {
+            output.push_str(&format!(";{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b));
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use colors::NamedColor;
+    use rio_core::event::VoidListener;
+
+    #[test]
+    fn test_serialize() {
+        let mut terminal = Crosswords::new(5, 3, VoidListener {});
+        terminal.grid[Line(0)][Column(0)].c = 'a';
+        terminal.grid[Line(0)][Column(1)].c = 'b';
+        terminal.grid[Line(0)][Column(1)].fg = AnsiColor::Named(NamedColor::Red);
+        terminal.grid[Line(0)][Column(1)].flags = Flags::BOLD;
+        terminal.grid[Line(1)][Column(0)].c = 'c';
+        terminal.grid[Line(1)][Column(0)].bg = AnsiColor::Indexed(200);
+        terminal.grid.cursor.pos.row = Line(1);
+
+        assert_eq!(
+            serialize(&terminal),
+            "a\x1b[0;1;31mb\r\n\x1b[0;48;5;200mc\r\n\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_serialize_and_restore() {
+        let mut terminal = Crosswords::new(5, 3, VoidListener {});
+        terminal.grid[Line(0)][Column(0)].c = 'a';
+        terminal.grid[Line(0)][Column(4)].c = 'b';
+        terminal.grid[Line(0)][Column(4)].flags = Flags::WRAPLINE;
+        terminal.grid[Line(1)][Column(0)].c = 'c';
+        terminal.grid.cursor.pos.row = Line(1);
+
+        let content = serialize(&terminal);
+        let mut restored = Crosswords::new(5, 3, VoidListener {});
+        ParserProcessor::new().advance_bytes(&mut restored, content.as_bytes());
+
+        assert_eq!(restored.grid[Line(0)][Column(0)].c, 'a');
+        assert_eq!(restored.grid[Line(0)][Column(4)].c, 'b');
+        assert_eq!(restored.grid[Line(1)][Column(0)].c, 'c');
+        assert_eq!(restored.grid.cursor.pos.row, Line(2));
+        assert_eq!(restored.grid.cursor.pos.col, Column(0));
+    }
+
+    #[test]
+    fn test_serialize_alternate_screen() {
+        let mut terminal = Crosswords::new(5, 3, VoidListener {});
+        let mut parser = ParserProcessor::new();
+        parser.advance_bytes(&mut terminal, b"$ vi\r\n\x1b[?1049h\x1b[?1000hab\r\n\r\n");
+
+        assert_eq!(serialize(&terminal), "$ vi\r\n\r\n\x1b[0mab\r\n\x1b[0m");
+        parser.advance_bytes(&mut terminal, b"\x1b[2;2Hcd");
+        assert_eq!(
+            serialize(&terminal),
+            "$ vi\r\n\r\n\x1b[0mab\r\n cd\r\n\x1b[0m"
+        );
+
+        let mut restored = Crosswords::new(5, 3, VoidListener {});
+        ParserProcessor::new()
+            .advance_bytes(&mut restored, serialize(&terminal).as_bytes());
+        assert!(!restored
+            .mode()
+            .intersects(Mode::ALT_SCREEN | Mode::MOUSE_REPORT_CLICK));
+        assert_eq!(restored.grid[Line(0)][Column(0)].c, 'a');
+        assert_eq!(restored.grid[Line(1)][Column(2)].c, 'd');
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path =
+            std::env::temp_dir().join(format!("rio-session-{}", std::process::id()));
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
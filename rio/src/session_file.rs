@@ -0,0 +1,118 @@
+// Session files describing the windows opened by `rio --session <path>`.
+//
+// The process started from the command line takes the first window and
+// starts one process per other window, telling each which one it is with
+// `RIO_SESSION_WINDOW`. Commands are typed into the shell once it starts,
+// so they end up in its history like commands typed by hand.
+
+use crate::ipc::NEW_INSTANCE_ENV;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Index of the window of the session file taken by a process.
+pub const WINDOW_ENV: &str = "RIO_SESSION_WINDOW";
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct SessionFile {
+    #[serde(default = "Vec::default")]
+    pub windows: Vec<SessionWindow>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct SessionWindow {
+    /// Profile from the configuration applied to the window.
+    #[serde(default = "Option::default")]
+    pub profile: Option<String>,
+    /// Working directory of the shell, may start with `~/`.
+    #[serde(default = "Option::default")]
+    pub cwd: Option<String>,
+    /// Typed into the shell once it starts.
+    #[serde(default = "Vec::default")]
+    pub commands: Vec<String>,
+}
+
+impl SessionFile {
+    pub fn load(path: &Path) -> Result<SessionFile, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+        SessionFile::parse(&content)
+    }
+
+    fn parse(content: &str) -> Result<SessionFile, String> {
+        let session: SessionFile =
+            toml::from_str(content).map_err(|err| err.to_string())?;
+        if session.windows.is_empty() {
+            return Err(String::from("the session has no windows"));
+        }
+        Ok(session)
+    }
+
+    /// Start a process for each window after the first one.
+    pub fn open_other_windows(&self, path: &Path) {
+        let program = match std::env::current_exe() {
+            Ok(program) => program,
+            Err(err) => {
+                log::error!("unable to open the session windows: {err}");
+                return;
+            }
+        };
+
+        for index in 1..self.windows.len() {
+            let result = std::process::Command::new(&program)
+                .arg("--session")
+                .arg(path)
+                .env(NEW_INSTANCE_ENV, "1")
+                .env(WINDOW_ENV, index.to_string())
+                .spawn();
+            if let Err(err) = result {
+                log::error!("unable to open window {index} of the session: {err}");
+            }
+        }
+    }
+
+    /// Window taken by this process, from `RIO_SESSION_WINDOW`.
+    pub fn window(&self, index: Option<&str>) -> Option<&SessionWindow> {
+        let index = index.map_or(Some(0), |index| index.parse().ok())?;
+        self.windows.get(index)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let session = SessionFile::parse(
+            r#"
+            [[windows]]
+            cwd = "~/code/app"
+            commands = ["git status"]
+
+            [[windows]]
+            profile = "staging"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(session.windows.len(), 2);
+        assert_eq!(session.windows[0].cwd.as_deref(), Some("~/code/app"));
+        assert_eq!(session.windows[0].commands, [String::from("git status")]);
+        assert_eq!(session.windows[1].profile.as_deref(), Some("staging"));
+        assert!(session.windows[1].commands.is_empty());
+
+        assert!(SessionFile::parse("").is_err());
+        assert!(SessionFile::parse("[[windows]]\ncwd = 1").is_err());
+    }
+
+    #[test]
+    fn test_window() {
+        let session = SessionFile {
+            windows: vec![SessionWindow::default(), SessionWindow::default()],
+        };
+        assert_eq!(session.window(None), Some(&session.windows[0]));
+        assert_eq!(session.window(Some("1")), Some(&session.windows[1]));
+        assert_eq!(session.window(Some("2")), None);
+        assert_eq!(session.window(Some("x")), None);
+    }
+}
@@ -0,0 +1,108 @@
+// Output read aloud by the reader mode.
+//
+// Lines are spoken with `say` on macOS and `spd-say` (speech-dispatcher)
+// elsewhere, one announcement after the other from a worker thread so the
+// event loop doesn't wait for the speech to finish.
+
+use std::process::ExitStatus;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Most lines spoken at once, the others are only counted.
+const MAX_LINES: usize = 10;
+
+/// Text announcing `lines`, `None` when there is nothing to say.
+pub fn announcement(lines: &[String]) -> Option<String> {
+    if lines.is_empty() {
+        return None;
+    }
+
+    let mut text = lines[..lines.len().min(MAX_LINES)].join("\n");
+    if lines.len() > MAX_LINES {
+        text.push_str(&format!("\n{} more lines", lines.len() - MAX_LINES));
+    }
+    Some(text)
+}
+
+/// Worker reading announcements aloud in order, started with the first one.
+#[derive(Debug, Default)]
+pub struct Speaker {
+    sender: Option<Sender<String>>,
+}
+
+impl Speaker {
+    /// Read `text` aloud once the previous announcements were spoken.
+    pub fn speak(&mut self, text: String) {
+        let sender = self.sender.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || work(receiver));
+            sender
+        });
+        let _ = sender.send(text);
+    }
+}
+
+fn work(receiver: Receiver<String>) {
+    while let Ok(mut text) = receiver.recv() {
+        // Announcements queued while speaking are read together.
+        for next in receiver.try_iter() {
+            text.push('\n');
+            text.push_str(&next);
+        }
+
+        if let Err(err) = say(&text) {
+            log::warn!("unable to read output aloud: {err}");
+        }
+    }
+}
+
+/// Speak `text` and wait until it's done. The text is output of programs,
+/// so it's never passed where it could be taken for an option.
+#[cfg(target_os = "macos")]
+fn say(text: &str) -> std::io::Result<ExitStatus> {
+    use std::io::Write;
+
+    let mut child = std::process::Command::new("say")
+        .args(["-f", "-"])
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    child.wait()
+}
+
+#[cfg(windows)]
+fn say(_text: &str) -> std::io::Result<ExitStatus> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "unsupported platform",
+    ))
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn say(text: &str) -> std::io::Result<ExitStatus> {
+    std::process::Command::new("spd-say")
+        .args(["--application-name=Rio", "--wait", "--"])
+        .arg(text)
+        .status()
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_announcement() {
+        assert_eq!(announcement(&[]), None);
+        assert_eq!(
+            announcement(&[String::from("a"), String::from("b")]),
+            Some(String::from("a\nb"))
+        );
+
+        let lines: Vec<String> = (0..12).map(|line| line.to_string()).collect();
+        assert_eq!(
+            announcement(&lines),
+            Some(String::from("0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n2 more lines"))
+        );
+    }
+}
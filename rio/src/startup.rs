@@ -0,0 +1,58 @@
+// Startup notification. Launchers show a busy cursor until the program they
+// started reports its window, with the X11 startup notification protocol or
+// with Wayland's xdg-activation, which also gives the window the focus.
+
+use winit::window::Window;
+
+/// Token of the launcher which started Rio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartupToken {
+    /// `DESKTOP_STARTUP_ID`, for X11 startup notification.
+    X11(String),
+    /// `XDG_ACTIVATION_TOKEN`, for Wayland's xdg-activation.
+    Wayland(String),
+}
+
+impl StartupToken {
+    /// Take the token out of the environment, so programs started from the
+    /// terminal don't report it again.
+    pub fn take() -> Option<StartupToken> {
+        let x11 = take_var("DESKTOP_STARTUP_ID").map(StartupToken::X11);
+        let wayland = take_var("XDG_ACTIVATION_TOKEN").map(StartupToken::Wayland);
+        wayland.or(x11)
+    }
+
+    /// Report `window` to the launcher.
+    #[allow(unused_variables)]
+    pub fn complete(&self, window: &Window) {
+        #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+        if let StartupToken::X11(id) = self {
+            use winit::platform::x11::WindowExtX11;
+            if window.xlib_window().is_some() {
+                if let Err(err) = crate::platform::x11::complete_startup(id) {
+                    log::warn!("unable to complete the startup notification: {err}");
+                }
+            }
+        }
+
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        if let StartupToken::Wayland(token) = self {
+            use winit::platform::wayland::WindowExtWayland;
+            if let (Some(display), Some(surface)) =
+                (window.wayland_display(), window.wayland_surface())
+            {
+                if let Err(err) =
+                    crate::platform::wayland::activate(display, surface, token)
+                {
+                    log::warn!("unable to activate the window: {err}");
+                }
+            }
+        }
+    }
+}
+
+fn take_var(name: &str) -> Option<String> {
+    let value = std::env::var(name).ok().filter(|value| !value.is_empty());
+    std::env::remove_var(name);
+    value
+}
@@ -36,7 +36,6 @@ impl TabsControl {
     }
 
     #[inline]
-    #[allow(unused)]
     pub fn len(&self) -> usize {
         self.tabs.len()
     }
@@ -47,7 +46,6 @@ impl TabsControl {
     }
 
     #[inline]
-    #[allow(unused)]
     pub fn set_current(&mut self, tab_id: u8) {
         if self.contains(tab_id) {
             self.current = tab_id;
@@ -55,7 +53,6 @@ impl TabsControl {
     }
 
     #[inline]
-    #[allow(unused)]
     pub fn contains(&self, tab_id: u8) -> bool {
         self.tabs.iter().any(|i| i.id == tab_id)
     }
@@ -66,7 +63,11 @@ impl TabsControl {
     }
 
     #[inline]
-    #[allow(unused)]
+    pub fn id_at(&self, position: usize) -> Option<u8> {
+        self.tabs.get(position).map(|t| t.id)
+    }
+
+    #[inline]
     pub fn close_tab(&mut self, tab_id: u8) {
         if self.tabs.len() <= 1 {
             return;
@@ -88,7 +89,6 @@ impl TabsControl {
     }
 
     #[inline]
-    #[allow(unused)]
     pub fn current(&self) -> u8 {
         self.current
     }
@@ -105,6 +105,17 @@ impl TabsControl {
         }
     }
 
+    #[inline]
+    pub fn switch_to_prev(&mut self) {
+        if let Some(current_position) = self.position(self.current) {
+            let previous = match current_position {
+                0 => self.tabs.len() - 1,
+                position => position - 1,
+            };
+            self.current = self.tabs[previous].id;
+        }
+    }
+
     #[inline]
     pub fn add_tab(&mut self, redirect: bool) {
         let size = self.tabs.len();
@@ -262,6 +273,20 @@ pub mod test {
         assert_eq!(tabs_control.len(), 1);
     }
 
+    #[test]
+    fn test_id_at() {
+        let mut tabs_control = TabsControl::with_capacity(5);
+        let should_redirect = false;
+
+        tabs_control.add_tab(should_redirect);
+        tabs_control.add_tab(should_redirect);
+        tabs_control.close_tab(1);
+
+        assert_eq!(tabs_control.id_at(0), Some(0));
+        assert_eq!(tabs_control.id_at(1), Some(2));
+        assert_eq!(tabs_control.id_at(2), None);
+    }
+
     #[test]
     fn test_switch_to_next() {
         let mut tabs_control = TabsControl::with_capacity(5);
@@ -288,4 +313,20 @@ pub mod test {
         tabs_control.switch_to_next();
         assert_eq!(tabs_control.current, 1);
     }
+
+    #[test]
+    fn test_switch_to_prev() {
+        let mut tabs_control = TabsControl::with_capacity(5);
+        tabs_control.add_tab(false);
+        tabs_control.add_tab(false);
+        assert_eq!(tabs_control.len(), 3);
+        assert_eq!(tabs_control.current, 0);
+
+        tabs_control.switch_to_prev();
+        assert_eq!(tabs_control.current, 2);
+        tabs_control.switch_to_prev();
+        assert_eq!(tabs_control.current, 1);
+        tabs_control.switch_to_prev();
+        assert_eq!(tabs_control.current, 0);
+    }
 }
@@ -50,8 +50,9 @@ async fn main() {
 
     let mut sugarloaf = Sugarloaf::new(
         &window,
-        wgpu::PowerPreference::HighPerformance,
+        wgpu::PowerPreference::HighPerformance.into(),
         sugarloaf::font::DEFAULT_FONT_NAME.to_string(),
+        sugarloaf::font::rasterizer::Rasterizer::default(),
     )
     .await
     .expect("Sugarloaf instance should be created");
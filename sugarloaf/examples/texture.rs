@@ -0,0 +1,80 @@
+extern crate tokio;
+
+use std::sync::Arc;
+use sugarloaf::context::Target;
+use sugarloaf::core::{Sugar, SugarloafStyle};
+use sugarloaf::Sugarloaf;
+
+// Renders into a texture owned by the application instead of a window, the
+// way a game engine or an editor would embed the terminal.
+#[tokio::main]
+async fn main() {
+    let width = 800;
+    let height = 200;
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions::default())
+        .await
+        .expect("Request adapter");
+    let (device, queue) = adapter
+        .request_device(&wgpu::DeviceDescriptor::default(), None)
+        .await
+        .expect("Request device");
+    let (device, queue) = (Arc::new(device), Arc::new(queue));
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("host texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut sugarloaf = Sugarloaf::with_device(
+        device.clone(),
+        queue,
+        adapter.get_info(),
+        Target {
+            format,
+            width,
+            height,
+            scale: 1.0,
+        },
+        sugarloaf::font::DEFAULT_FONT_NAME.to_string(),
+        sugarloaf::font::rasterizer::Rasterizer::default(),
+    )
+    .expect("Sugarloaf instance should be created");
+
+    let style = SugarloafStyle {
+        screen_position: (20., 60.),
+        text_scale: 40.,
+        bounds: (width as f32, height as f32),
+    };
+    let sugar = "sugarloaf"
+        .chars()
+        .map(|content| Sugar {
+            content,
+            foreground_color: [1.0, 1.0, 1.0, 1.0],
+            background_color: [0.0, 0.0, 0.0, 1.0],
+            style: None,
+        })
+        .collect();
+
+    sugarloaf.init(wgpu::Color::BLACK, style);
+    sugarloaf.stack(sugar, style);
+    sugarloaf.render_to_view(&view);
+    device.poll(wgpu::Maintain::Wait);
+
+    println!("rendered {width}x{height} into the host texture");
+}
@@ -0,0 +1,34 @@
+struct Uniforms {
+    // Left, top, right and bottom edges in clip space.
+    rect: vec4<f32>,
+    uv_scale: vec2<f32>,
+    opacity: f32,
+    brightness: f32,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var image_texture: texture_2d<f32>;
+@group(0) @binding(2) var image_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Triangle strip over the corners: top left, bottom left, top right and
+// bottom right.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let corner = vec2<f32>(f32(vertex_index / 2u), f32(vertex_index % 2u));
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(mix(uniforms.rect.xy, uniforms.rect.zw, corner), 0.0, 1.0);
+    output.uv = corner * uniforms.uv_scale;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(image_texture, image_sampler, input.uv);
+    return vec4<f32>(color.rgb * uniforms.brightness, color.a * uniforms.opacity);
+}
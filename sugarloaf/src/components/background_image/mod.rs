@@ -0,0 +1,329 @@
+// Image drawn behind the terminal.
+//
+// The image is drawn right after the frame is cleared, in the same pass and
+// with the same sample count as rects, so cell backgrounds blend over it.
+// Cells using the default background are expected to be transparent.
+
+use crate::components::rect::BLEND;
+use crate::context::Context;
+use bytemuck::{Pod, Zeroable};
+use std::borrow::Cow;
+use std::mem;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFit {
+    /// Scale the image to be fully visible, leaving bars around it.
+    Fit,
+    /// Scale the image to cover the window, cropping what doesn't fit.
+    #[default]
+    Fill,
+    /// Repeat the image at its own size.
+    Tile,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Zeroable, Pod)]
+struct Uniforms {
+    rect: [f32; 4],
+    uv_scale: [f32; 2],
+    opacity: f32,
+    brightness: f32,
+}
+
+/// Where the image is drawn on a surface of `surface` size, in pixels.
+fn placement(fit: ImageFit, surface: (f32, f32), image: (f32, f32)) -> Uniforms {
+    let (surface_width, surface_height) = surface;
+    let (image_width, image_height) = image;
+
+    let scale = match fit {
+        ImageFit::Tile => {
+            return Uniforms {
+                rect: [-1.0, 1.0, 1.0, -1.0],
+                uv_scale: [surface_width / image_width, surface_height / image_height],
+                opacity: 1.0,
+                brightness: 1.0,
+            }
+        }
+        ImageFit::Fit => (surface_width / image_width).min(surface_height / image_height),
+        ImageFit::Fill => {
+            (surface_width / image_width).max(surface_height / image_height)
+        }
+    };
+
+    let width = image_width * scale;
+    let height = image_height * scale;
+    let x = (surface_width - width) / 2.0;
+    let y = (surface_height - height) / 2.0;
+
+    Uniforms {
+        rect: [
+            x / surface_width * 2.0 - 1.0,
+            1.0 - y / surface_height * 2.0,
+            (x + width) / surface_width * 2.0 - 1.0,
+            1.0 - (y + height) / surface_height * 2.0,
+        ],
+        uv_scale: [1.0, 1.0],
+        opacity: 1.0,
+        brightness: 1.0,
+    }
+}
+
+pub struct BackgroundImage {
+    pipeline: wgpu::RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    bind_group: wgpu::BindGroup,
+    uniforms: wgpu::Buffer,
+    current_uniforms: Option<Uniforms>,
+    image_size: (u32, u32),
+    opacity: f32,
+    fit: ImageFit,
+    generation: u64,
+}
+
+impl BackgroundImage {
+    /// Decode a PNG or JPEG image, downscaled when larger than the device
+    /// supports.
+    pub fn new(
+        ctx: &Context,
+        data: &[u8],
+        opacity: f32,
+        fit: ImageFit,
+    ) -> Result<BackgroundImage, String> {
+        let mut image = image::load_from_memory(data).map_err(|err| err.to_string())?;
+        let max_size = ctx.device.limits().max_texture_dimension_2d;
+        if image.width() > max_size || image.height() > max_size {
+            image =
+                image.resize(max_size, max_size, image::imageops::FilterType::Triangle);
+        }
+        let image = image.to_rgba8();
+        let (width, height) = image.dimensions();
+
+        let device = &ctx.device;
+        let format = if ctx.format.describe().srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("background_image::Pipeline texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        ctx.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &image,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(4 * width),
+                rows_per_image: std::num::NonZeroU32::new(height),
+            },
+            size,
+        );
+
+        let address_mode = if fit == ImageFit::Tile {
+            wgpu::AddressMode::Repeat
+        } else {
+            wgpu::AddressMode::ClampToEdge
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("background_image::Pipeline uniforms"),
+            size: mem::size_of::<Uniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("background_image::Pipeline uniforms"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            mem::size_of::<Uniforms>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("background_image::Pipeline uniforms"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("background_image shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "background_image.wgsl"
+            ))),
+        });
+        let pipeline = create_pipeline(ctx, &pipeline_layout, &shader);
+
+        Ok(BackgroundImage {
+            pipeline,
+            pipeline_layout,
+            shader,
+            bind_group,
+            uniforms,
+            current_uniforms: None,
+            image_size: (width, height),
+            opacity: opacity.clamp(0.0, 1.0),
+            fit,
+            generation: ctx.generation,
+        })
+    }
+
+    /// Draw the image into a pass attached with `RectBrush::color_attachment`.
+    pub fn render<'a>(
+        &'a mut self,
+        ctx: &mut Context,
+        brightness: f32,
+        render_pass: &mut wgpu::RenderPass<'a>,
+    ) {
+        // The pipeline has to match the sample count of rects.
+        if self.generation != ctx.generation {
+            self.pipeline = create_pipeline(ctx, &self.pipeline_layout, &self.shader);
+            self.generation = ctx.generation;
+            ctx.rebuilds.pipelines += 1;
+        }
+
+        let mut uniforms = placement(
+            self.fit,
+            (ctx.size.width as f32, ctx.size.height as f32),
+            (self.image_size.0 as f32, self.image_size.1 as f32),
+        );
+        uniforms.opacity = self.opacity;
+        uniforms.brightness = brightness;
+        if self.current_uniforms != Some(uniforms) {
+            ctx.queue
+                .write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+            self.current_uniforms = Some(uniforms);
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..4, 0..1);
+    }
+}
+
+fn create_pipeline(
+    ctx: &Context,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+) -> wgpu::RenderPipeline {
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("background_image::Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.format,
+                    blend: BLEND,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: ctx.sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+        })
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_placement() {
+        // Wider than the surface.
+        let fit = placement(ImageFit::Fit, (100.0, 100.0), (200.0, 100.0));
+        assert_eq!(fit.rect, [-1.0, 0.5, 1.0, -0.5]);
+        assert_eq!(fit.uv_scale, [1.0, 1.0]);
+
+        let fill = placement(ImageFit::Fill, (100.0, 100.0), (200.0, 100.0));
+        assert_eq!(fill.rect, [-2.0, 1.0, 2.0, -1.0]);
+
+        let tile = placement(ImageFit::Tile, (100.0, 50.0), (25.0, 25.0));
+        assert_eq!(tile.rect, [-1.0, 1.0, 1.0, -1.0]);
+        assert_eq!(tile.uv_scale, [4.0, 2.0]);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod background_image;
+pub mod post_process;
 pub mod rect;
 pub mod text;
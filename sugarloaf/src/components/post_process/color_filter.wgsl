@@ -0,0 +1,25 @@
+// Color blindness filter, with `{rows}` and `{daltonize}` replaced by
+// `post_process::color_filter`.
+//
+// The simulation matrices are from Machado et al. (2009), at full severity.
+// Daltonizing shifts the difference between the frame and its simulation to
+// the channels still perceived (Fidaner et al.).
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, input.uv);
+    let rows = array<vec3<f32>, 3>({rows});
+    let simulated = vec3<f32>(
+        dot(rows[0], color.rgb),
+        dot(rows[1], color.rgb),
+        dot(rows[2], color.rgb),
+    );
+
+    var filtered = simulated;
+    if ({daltonize}) {
+        let error = color.rgb - simulated;
+        filtered = color.rgb + vec3<f32>(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+    }
+
+    return vec4<f32>(clamp(filtered, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
@@ -0,0 +1,370 @@
+// Post-processing passes (CRT, scanlines, bloom and so on).
+//
+// When enabled the frame is drawn into an intermediate texture instead of the
+// surface. Every pass samples the output of the previous one and the last
+// pass writes into the surface. Passes are WGSL fragment shaders, appended to
+// `prelude.wgsl` which declares the bindings and the vertex shader.
+
+use crate::context::Context;
+use bytemuck::{Pod, Zeroable};
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+use std::borrow::Cow;
+use std::mem;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+const PRELUDE: &str = include_str!("prelude.wgsl");
+const COLOR_FILTER: &str = include_str!("color_filter.wgsl");
+
+/// Color blindness simulated or corrected by `color_filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorBlindness {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+/// Pass showing the frame as seen with `color_blindness`, or with colors
+/// shifted to stay distinguishable with it when `daltonize` is set.
+pub fn color_filter(color_blindness: ColorBlindness, daltonize: bool) -> String {
+    let rows = match color_blindness {
+        ColorBlindness::Protanopia => [
+            [0.152286, 1.052583, -0.204868],
+            [0.114503, 0.786281, 0.099216],
+            [-0.003882, -0.048116, 1.051998],
+        ],
+        ColorBlindness::Deuteranopia => [
+            [0.367322, 0.860646, -0.227968],
+            [0.280085, 0.672501, 0.047413],
+            [-0.011820, 0.042940, 0.968881],
+        ],
+        ColorBlindness::Tritanopia => [
+            [1.255528, -0.076749, -0.178779],
+            [-0.078411, 0.930809, 0.147602],
+            [0.004733, 0.691367, 0.303900],
+        ],
+    };
+    let rows = rows
+        .iter()
+        .map(|[r, g, b]| format!("vec3<f32>({r:?}, {g:?}, {b:?})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    COLOR_FILTER
+        .replace("{rows}", &rows)
+        .replace("{daltonize}", &daltonize.to_string())
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct Uniforms {
+    resolution: [f32; 2],
+    time: f32,
+    _padding: f32,
+}
+
+/// Texture a pass draws into and the bind group of the pass reading it.
+struct Target {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Target {
+    fn new(
+        ctx: &Context,
+        layout: &wgpu::BindGroupLayout,
+        uniforms: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+    ) -> Target {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("post_process::Target"),
+            size: wgpu::Extent3d {
+                width: ctx.size.width.max(1),
+                height: ctx.size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ctx.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_process::Target uniforms"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Target { view, bind_group }
+    }
+}
+
+pub struct PostProcess {
+    passes: Vec<wgpu::RenderPipeline>,
+    layout: wgpu::BindGroupLayout,
+    uniforms: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    /// Passes alternate between both targets.
+    targets: [Target; 2],
+    size: (u32, u32),
+    started: Instant,
+}
+
+impl PostProcess {
+    /// Check that `source` is a valid pass, since wgpu aborts on invalid
+    /// shaders.
+    pub fn validate(source: &str) -> Result<(), String> {
+        let source = format!("{PRELUDE}\n{source}");
+        let module = naga::front::wgsl::parse_str(&source)
+            .map_err(|err| err.emit_to_string(&source))?;
+
+        let has_fs_main = module.entry_points.iter().any(|entry_point| {
+            entry_point.name == "fs_main"
+                && entry_point.stage == naga::ShaderStage::Fragment
+        });
+        if !has_fs_main {
+            return Err(String::from("missing `@fragment fn fs_main`"));
+        }
+
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .map(|_| ())
+        .map_err(|err| err.into_inner().to_string())
+    }
+
+    pub fn new(ctx: &Context, sources: &[String]) -> Result<PostProcess, String> {
+        for (index, source) in sources.iter().enumerate() {
+            PostProcess::validate(source)
+                .map_err(|err| format!("post-processing shader {index}: {err}"))?;
+        }
+
+        let device = &ctx.device;
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post_process::Pipeline uniforms"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            mem::size_of::<Uniforms>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+
+        let passes = sources
+            .iter()
+            .map(|source| {
+                let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("post_process shader"),
+                    source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!(
+                        "{PRELUDE}\n{source}"
+                    ))),
+                });
+
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("post_process::Pipeline"),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: ctx.format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                })
+            })
+            .collect();
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("post_process::Pipeline uniforms"),
+            size: mem::size_of::<Uniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let targets = [
+            Target::new(ctx, &layout, &uniforms, &sampler),
+            Target::new(ctx, &layout, &uniforms, &sampler),
+        ];
+
+        Ok(PostProcess {
+            passes,
+            layout,
+            uniforms,
+            sampler,
+            targets,
+            size: (ctx.size.width, ctx.size.height),
+            started: Instant::now(),
+        })
+    }
+
+    /// Where the frame has to be drawn before `render`.
+    #[inline]
+    pub fn target(&self) -> &wgpu::TextureView {
+        &self.targets[0].view
+    }
+
+    /// Recreate the targets if the surface size changed.
+    pub fn resize(&mut self, ctx: &mut Context) {
+        if self.size == (ctx.size.width, ctx.size.height) {
+            return;
+        }
+
+        self.targets = [
+            Target::new(ctx, &self.layout, &self.uniforms, &self.sampler),
+            Target::new(ctx, &self.layout, &self.uniforms, &self.sampler),
+        ];
+        self.size = (ctx.size.width, ctx.size.height);
+        ctx.rebuilds.bind_groups += 2;
+    }
+
+    /// Run every pass, the last one writes into `view`.
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        let uniforms = Uniforms {
+            resolution: [ctx.size.width as f32, ctx.size.height as f32],
+            time: self.started.elapsed().as_secs_f32(),
+            _padding: 0.0,
+        };
+        ctx.queue
+            .write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+
+        let last = self.passes.len().saturating_sub(1);
+        for (index, pass) in self.passes.iter().enumerate() {
+            let input = &self.targets[index % 2];
+            let output = if index == last {
+                view
+            } else {
+                &self.targets[(index + 1) % 2].view
+            };
+
+            let mut render_pass =
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("post_process::render"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: output,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+            render_pass.set_pipeline(pass);
+            render_pass.set_bind_group(0, &input.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let scanlines = r#"
+            @fragment
+            fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+                var color: vec4<f32> = textureSample(input_texture, input_sampler, input.uv);
+                var line: f32 = floor(input.uv.y * uniforms.resolution.y);
+                return color * (0.8 + 0.2 * (line % 2.0));
+            }
+        "#;
+        assert_eq!(PostProcess::validate(scanlines), Ok(()));
+
+        let missing_entry_point =
+            "fn tint(color: vec4<f32>) -> vec4<f32> { return color; }";
+        assert!(PostProcess::validate(missing_entry_point).is_err());
+        assert!(PostProcess::validate("@fragment fn fs_main(").is_err());
+    }
+
+    #[test]
+    fn test_color_filter() {
+        for color_blindness in [
+            ColorBlindness::Protanopia,
+            ColorBlindness::Deuteranopia,
+            ColorBlindness::Tritanopia,
+        ] {
+            for daltonize in [false, true] {
+                let source = color_filter(color_blindness, daltonize);
+                assert_eq!(PostProcess::validate(&source), Ok(()));
+            }
+        }
+    }
+}
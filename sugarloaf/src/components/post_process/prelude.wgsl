@@ -0,0 +1,33 @@
+// Prepended to every post-processing shader, which has to define:
+//
+//     @fragment
+//     fn fs_main(input: VertexOutput) -> @location(0) vec4<f32>
+//
+// `input_texture` holds the frame drawn so far, sampled at `input.uv`.
+
+struct Uniforms {
+    // Size of the frame in physical pixels.
+    resolution: vec2<f32>,
+    // Seconds since post-processing started.
+    time: f32,
+    _padding: f32,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var input_texture: texture_2d<f32>;
+@group(0) @binding(2) var input_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Single triangle covering the frame.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    var uv: vec2<f32> = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
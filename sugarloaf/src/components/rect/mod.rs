@@ -11,18 +11,20 @@ const MAX_INSTANCES: usize = 10_000;
 struct Uniforms {
     transform: [f32; 16],
     scale: f32,
-    _padding: [f32; 3],
+    brightness: f32,
+    _padding: [f32; 2],
 }
 
 impl Uniforms {
-    fn new(transformation: [f32; 16], scale: f32) -> Uniforms {
+    fn new(transformation: [f32; 16], scale: f32, brightness: f32) -> Uniforms {
         Self {
             transform: transformation,
             scale,
+            brightness,
             // Ref: https://github.com/iced-rs/iced/blob/bc62013b6cde52174bf4c4286939cf170bfa7760/wgpu/src/quad.rs#LL295C6-L296C68
             // Uniforms must be aligned to their largest member,
             // this uses a mat4x4<f32> which aligns to 16, so align to that
-            _padding: [0.0; 3],
+            _padding: [0.0; 2],
         }
     }
 }
@@ -36,7 +38,8 @@ impl Default for Uniforms {
         Self {
             transform: identity_matrix,
             scale: 1.0,
-            _padding: [0.0; 3],
+            brightness: 1.0,
+            _padding: [0.0; 2],
         }
     }
 }
@@ -64,6 +67,66 @@ pub struct Rect {
     pub size: [f32; 2],
 }
 
+impl Rect {
+    /// Rect covering `size` physical pixels at `position`, on a surface with
+    /// `scale` physical pixels per logical pixel.
+    pub fn from_pixels(
+        position: [f32; 2],
+        size: [f32; 2],
+        color: [f32; 4],
+        scale: f32,
+    ) -> Rect {
+        // The shader draws half of `size * scale + 1` pixels, moved by half
+        // a pixel.
+        Rect {
+            position: [(position[0] + 0.5) / scale, (position[1] + 0.5) / scale],
+            color,
+            size: [(size[0] * 2.0 - 1.0) / scale, (size[1] * 2.0 - 1.0) / scale],
+        }
+    }
+
+    /// Borders of this rect, `width` thick in the units of `size`.
+    pub fn outline(&self, width: f32) -> [Rect; 4] {
+        // Only half of `size` is drawn, in the units of `position`.
+        let [x, y] = self.position;
+        let [w, h] = self.size;
+        let horizontal = [w, width];
+        let vertical = [width, h];
+        [
+            Rect {
+                size: horizontal,
+                ..*self
+            },
+            Rect {
+                position: [x, y + (h - width) / 2.0],
+                size: horizontal,
+                ..*self
+            },
+            Rect {
+                size: vertical,
+                ..*self
+            },
+            Rect {
+                position: [x + (w - width) / 2.0, y],
+                size: vertical,
+                ..*self
+            },
+        ]
+    }
+
+    /// Horizontal line through the middle of this rect, `width` thick in
+    /// the units of `size`.
+    pub fn middle_line(&self, width: f32) -> Rect {
+        let [x, y] = self.position;
+        let [w, h] = self.size;
+        Rect {
+            position: [x, y + (h - width) / 4.0],
+            size: [w, width],
+            ..*self
+        }
+    }
+}
+
 #[allow(unsafe_code)]
 unsafe impl bytemuck::Zeroable for Rect {}
 
@@ -95,6 +158,46 @@ pub const BLEND: Option<wgpu::BlendState> = Some(wgpu::BlendState {
     },
 });
 
+/// Sample counts every renderable format supports, see
+/// https://www.w3.org/TR/webgpu/#texture-format-caps
+pub const SUPPORTED_SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
+/// Multisampled target rects are drawn into, resolved into the frame.
+struct Multisample {
+    view: wgpu::TextureView,
+    size: (u32, u32),
+}
+
+impl Multisample {
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        width: u32,
+        height: u32,
+    ) -> Multisample {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("rect::Pipeline multisample"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        Multisample {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            size: (width, height),
+        }
+    }
+}
+
 pub struct RectBrush {
     vertex_buf: wgpu::Buffer,
     index_buf: wgpu::Buffer,
@@ -105,6 +208,64 @@ pub struct RectBrush {
     pipeline: wgpu::RenderPipeline,
     current_transform: [f32; 16],
     scale: f32,
+    brightness: f32,
+    next_brightness: f32,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    multisample: Option<Multisample>,
+    /// `Context::generation` the pipeline was built for.
+    pub generation: u64,
+}
+
+impl RectBrush {
+    /// Attachment for passes drawing into `view`. With multisampling they
+    /// draw into the multisampled target, which is resolved into `view`.
+    pub fn color_attachment<'a>(
+        &'a self,
+        view: &'a wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let ops = wgpu::Operations { load, store: true };
+        match &self.multisample {
+            Some(multisample) => wgpu::RenderPassColorAttachment {
+                view: &multisample.view,
+                resolve_target: Some(view),
+                ops,
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
+
+    /// Rect colors are multiplied by `brightness`, uploaded with the next
+    /// render.
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.next_brightness = brightness.clamp(0.0, 1.0);
+    }
+
+    /// Recreate the multisampled target for the new surface size.
+    pub fn resize_multisample(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if self.sample_count <= 1 {
+            return;
+        }
+
+        let is_same_size = self
+            .multisample
+            .as_ref()
+            .map_or(false, |multisample| multisample.size == (width, height));
+        if !is_same_size {
+            self.multisample = Some(Multisample::new(
+                device,
+                self.format,
+                self.sample_count,
+                width,
+                height,
+            ));
+        }
+    }
 }
 
 impl Renderable for RectBrush {
@@ -219,7 +380,10 @@ impl Renderable for RectBrush {
                 ..Default::default()
             },
             depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
+            multisample: wgpu::MultisampleState {
+                count: context.sample_count,
+                ..Default::default()
+            },
             multiview: None,
         });
 
@@ -230,6 +394,16 @@ impl Renderable for RectBrush {
             mapped_at_creation: false,
         });
 
+        let multisample = (context.sample_count > 1).then(|| {
+            Multisample::new(
+                device,
+                context.format,
+                context.sample_count,
+                context.size.width,
+                context.size.height,
+            )
+        });
+
         // Done
         RectBrush {
             scale: context.scale,
@@ -240,7 +414,13 @@ impl Renderable for RectBrush {
             transform,
             pipeline,
             current_transform: [0.0; 16],
+            brightness: 1.0,
+            next_brightness: 1.0,
             instances,
+            format: context.format,
+            sample_count: context.sample_count,
+            multisample,
+            generation: context.generation,
         }
     }
 
@@ -270,21 +450,23 @@ impl Renderable for RectBrush {
         let device = &ctx.device;
         let staging_belt = &mut ctx.staging_belt;
 
-        if transform != self.current_transform || scale != self.scale {
-            let uniforms = Uniforms::new(transform, scale);
+        if transform != self.current_transform
+            || scale != self.scale
+            || self.next_brightness != self.brightness
+        {
+            let uniforms = Uniforms::new(transform, scale, self.next_brightness);
 
-            let mut transform_view = staging_belt.write_buffer(
+            staging_belt.write(
                 encoder,
                 &self.transform,
                 0,
-                wgpu::BufferSize::new(mem::size_of::<Uniforms>() as u64).unwrap(),
+                bytemuck::bytes_of(&uniforms),
                 device,
             );
 
-            transform_view.copy_from_slice(bytemuck::bytes_of(&uniforms));
-
             self.current_transform = transform;
             self.scale = scale;
+            self.brightness = self.next_brightness;
         }
 
         let mut i = 0;
@@ -297,27 +479,14 @@ impl Renderable for RectBrush {
             let instance_bytes = bytemuck::cast_slice(&instances[i..end]);
 
             // queue.write_buffer(&self.instances, 0, instance_bytes);
-            let mut instance_buffer = staging_belt.write_buffer(
-                encoder,
-                &self.instances,
-                0,
-                wgpu::BufferSize::new(instance_bytes.len() as u64).unwrap(),
-                device,
-            );
-
-            instance_buffer.copy_from_slice(instance_bytes);
+            staging_belt.write(encoder, &self.instances, 0, instance_bytes, device);
 
             {
                 let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: None,
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Load,
-                            store: true,
-                        },
-                    })],
+                    color_attachments: &[Some(
+                        self.color_attachment(view, wgpu::LoadOp::Load),
+                    )],
                     depth_stencil_attachment: None,
                 });
                 // rpass.push_debug_group("Prepare data for draw.");
@@ -374,3 +543,33 @@ impl Renderable for RectBrush {
 //         max_outliers: 1250, // Bounded by swiftshader
 //     });
 // }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_outline() {
+        let rect = Rect {
+            position: [10.0, 20.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            size: [16.0, 32.0],
+        };
+        let [top, bottom, left, right] = rect.outline(2.0);
+        assert_eq!((top.position, top.size), ([10.0, 20.0], [16.0, 2.0]));
+        assert_eq!((bottom.position, bottom.size), ([10.0, 35.0], [16.0, 2.0]));
+        assert_eq!((left.position, left.size), ([10.0, 20.0], [2.0, 32.0]));
+        assert_eq!((right.position, right.size), ([17.0, 20.0], [2.0, 32.0]));
+    }
+
+    #[test]
+    fn test_middle_line() {
+        let rect = Rect {
+            position: [10.0, 20.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            size: [16.0, 32.0],
+        };
+        let line = rect.middle_line(2.0);
+        assert_eq!((line.position, line.size), ([10.0, 27.5], [16.0, 2.0]));
+    }
+}
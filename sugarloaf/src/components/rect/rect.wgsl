@@ -1,6 +1,7 @@
 struct Globals {
     transform: mat4x4<f32>,
     scale: f32,
+    brightness: f32,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
@@ -29,7 +30,7 @@ fn vs_main(
         vec4<f32>(pos - vec2<f32>(0.5, 0.5), 0.0, 1.0)
     );
 
-    output.color = color;
+    output.color = vec4<f32>(color.rgb * globals.brightness, color.a);
     output.position = globals.transform * transform * vec4<f32>(vertex_position, 0.0, 1.0);
     return output;
 }
@@ -3,6 +3,7 @@
 // #[deny(unused_results)]
 mod builder;
 mod pipeline;
+mod subpixel;
 
 /// A region of the screen.
 pub struct Region {
@@ -12,6 +13,7 @@ pub struct Region {
     pub height: u32,
 }
 
+use crate::context::{Rebuilds, Staging};
 use pipeline::{Instance, Pipeline};
 
 pub use builder::GlyphBrushBuilder;
@@ -21,12 +23,14 @@ pub use glyph_brush::{
     Layout, LineBreak, LineBreaker, OwnedSection, OwnedText, Section, SectionGeometry,
     SectionGlyph, SectionGlyphIter, SectionText, Text, VerticalAlign,
 };
+pub use subpixel::Antialiasing;
 
 use ab_glyph::{Font, Rect};
 use core::hash::BuildHasher;
 use std::borrow::Cow;
 
 use glyph_brush::{BrushAction, BrushError, DefaultSectionHasher};
+use subpixel::SUBPIXELS;
 
 /// Object allowing glyph drawing, containing cache state. Manages glyph positioning cacheing,
 /// glyph draw caching & efficient GPU texture cache updating and re-sizing on demand.
@@ -48,9 +52,59 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     where
         S: Into<Cow<'a, Section<'a>>>,
     {
+        if !self.pipeline.antialiasing().is_subpixel() {
+            return self.glyph_brush.queue(section);
+        }
+
+        let mut section = section.into().into_owned();
+        section.screen_position.0 *= SUBPIXELS;
+        section.bounds.0 *= SUBPIXELS;
+        for text in section.text.iter_mut() {
+            text.scale.x *= SUBPIXELS;
+        }
         self.glyph_brush.queue(section)
     }
 
+    #[inline]
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.pipeline.antialiasing()
+    }
+
+    /// Adjust glyph coverage, see `text.wgsl`.
+    #[inline]
+    pub fn set_blending(&mut self, gamma: f32, contrast: f32) {
+        self.pipeline.set_blending(gamma, contrast);
+    }
+
+    #[inline]
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.pipeline.set_brightness(brightness);
+    }
+
+    /// Pipelines and bind groups rebuilt since the brush was built, when the
+    /// antialiasing changed or the glyph cache grew.
+    #[inline]
+    pub fn rebuilds(&self) -> Rebuilds {
+        self.pipeline.rebuilds()
+    }
+
+    /// Switch between grayscale and subpixel antialiasing, cached glyphs are
+    /// dropped.
+    pub fn set_antialiasing(
+        &mut self,
+        device: &wgpu::Device,
+        antialiasing: Antialiasing,
+    ) {
+        if antialiasing == self.pipeline.antialiasing() {
+            return;
+        }
+
+        let (width, height) = self.glyph_brush.texture_dimensions();
+        self.pipeline
+            .set_antialiasing(device, antialiasing, width, height);
+        self.glyph_brush.resize_texture(width, height);
+    }
+
     /// Queues a section/layout to be drawn by the next call of
     /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued). Can be
     /// called multiple times to queue multiple sections for drawing.
@@ -134,10 +188,17 @@ where
     fn process_queued(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
     ) {
         let pipeline = &mut self.pipeline;
+        let antialiasing = pipeline.antialiasing();
+        let to_vertex: fn(glyph_brush::GlyphVertex) -> Instance =
+            if antialiasing.is_subpixel() {
+                Instance::from_subpixel_vertex
+            } else {
+                Instance::from_vertex
+            };
 
         let mut brush_action;
 
@@ -147,16 +208,26 @@ where
                     let offset = [rect.min[0] as u16, rect.min[1] as u16];
                     let size = [rect.width() as u16, rect.height() as u16];
 
+                    let tex_data: Cow<[u8]> = if antialiasing.is_subpixel() {
+                        Cow::Owned(subpixel::to_rgba(
+                            tex_data,
+                            rect.width() as usize,
+                            antialiasing,
+                        ))
+                    } else {
+                        Cow::Borrowed(tex_data)
+                    };
+
                     pipeline.update_cache(
                         device,
                         staging_belt,
                         encoder,
                         offset,
                         size,
-                        tex_data,
+                        &tex_data,
                     );
                 },
-                Instance::from_vertex,
+                to_vertex,
             );
 
             match brush_action {
@@ -191,6 +262,17 @@ where
             BrushAction::ReDraw => {}
         };
     }
+
+    /// Rasterize the glyphs of the queued sections into the cache without
+    /// drawing them.
+    pub fn process(
+        &mut self,
+        device: &wgpu::Device,
+        staging_belt: &mut Staging,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        self.process_queued(device, staging_belt, encoder);
+    }
 }
 
 impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
@@ -230,7 +312,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
     pub fn draw_queued(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
         w_h: (u32, u32),
@@ -259,7 +341,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
     pub fn draw_queued_with_transform(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
         transform: [f32; 16],
@@ -286,7 +368,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
     pub fn _draw_queued_with_transform_and_scissoring(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
         transform: [f32; 16],
@@ -345,7 +427,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     pub fn _draw_queued(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
         depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment,
@@ -377,7 +459,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     pub fn draw_queued_with_transform(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
         depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment,
@@ -411,7 +493,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
         // config: (device, staging_belt, encoder, target),
         config: (
             &wgpu::Device,
-            &mut wgpu::util::StagingBelt,
+            &mut Staging,
             &mut wgpu::CommandEncoder,
             &wgpu::TextureView,
         ),
@@ -1,11 +1,12 @@
 mod cache;
 
+use crate::components::text::subpixel::{Antialiasing, SUBPIXELS};
 use crate::components::text::Region;
+use crate::context::{Rebuilds, Staging};
 use cache::Cache;
 use std::borrow::Cow;
 
 use bytemuck::{Pod, Zeroable};
-use core::num::NonZeroU64;
 use glyph_brush::ab_glyph::{point, Rect};
 use std::marker::PhantomData;
 use std::mem;
@@ -23,17 +24,61 @@ pub const BLEND: Option<wgpu::BlendState> = Some(wgpu::BlendState {
     },
 });
 
+/// First subpixel pass, darkens the destination per channel:
+/// `dst * (1 - coverage)`.
+const SUBPIXEL_MASK_BLEND: Option<wgpu::BlendState> = Some(wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::OneMinusSrc,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+});
+
+/// Second subpixel pass, adds `color * coverage`. Together with the mask pass
+/// this is per channel alpha blending without dual-source blending.
+const SUBPIXEL_COLOR_BLEND: Option<wgpu::BlendState> = Some(wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+});
+
+/// Gamma and contrast of glyph coverage and brightness of text colors,
+/// padded to 16 bytes.
+const DEFAULT_BLENDING: [f32; 4] = [1.0, 0.0, 1.0, 0.0];
+
 pub struct Pipeline<Depth> {
     transform: wgpu::Buffer,
+    blending: wgpu::Buffer,
     sampler: wgpu::Sampler,
     cache: Cache,
     uniform_layout: wgpu::BindGroupLayout,
     uniforms: wgpu::BindGroup,
     raw: wgpu::RenderPipeline,
+    /// Mask pass drawn before `raw` with subpixel antialiasing.
+    subpixel_mask: Option<wgpu::RenderPipeline>,
+    antialiasing: Antialiasing,
+    multisample: wgpu::MultisampleState,
+    render_format: wgpu::TextureFormat,
+    depth_stencil: Option<wgpu::DepthStencilState>,
     instances: wgpu::Buffer,
     current_instances: usize,
     supported_instances: usize,
     current_transform: [f32; 16],
+    next_blending: [f32; 4],
+    current_blending: [f32; 4],
+    rebuilds: Rebuilds,
     depth: PhantomData<Depth>,
 }
 
@@ -60,7 +105,7 @@ impl Pipeline<()> {
     pub fn draw(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
         transform: [f32; 16],
@@ -102,7 +147,7 @@ impl Pipeline<wgpu::DepthStencilState> {
         &mut self,
         config: (
             &wgpu::Device,
-            &mut wgpu::util::StagingBelt,
+            &mut Staging,
             &mut wgpu::CommandEncoder,
             &wgpu::TextureView,
         ),
@@ -125,7 +170,7 @@ impl<Depth> Pipeline<Depth> {
     pub fn update_cache(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         offset: [u16; 2],
         size: [u16; 2],
@@ -135,27 +180,75 @@ impl<Depth> Pipeline<Depth> {
             .update(device, staging_belt, encoder, offset, size, data);
     }
 
+    /// Coverage is raised by `contrast`, then to the power of `1 / gamma`.
+    /// Uploaded with the next draw.
+    pub fn set_blending(&mut self, gamma: f32, contrast: f32) {
+        self.next_blending[0] = gamma.max(0.1);
+        self.next_blending[1] = contrast.max(0.0);
+    }
+
+    /// Text colors are multiplied by `brightness`. Uploaded with the next draw.
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.next_blending[2] = brightness.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    #[inline]
+    pub fn rebuilds(&self) -> Rebuilds {
+        self.rebuilds
+    }
+
+    /// Rebuild the render pipelines and the glyph cache for `antialiasing`,
+    /// glyphs have to be rasterized again afterwards.
+    pub fn set_antialiasing(
+        &mut self,
+        device: &wgpu::Device,
+        antialiasing: Antialiasing,
+        cache_width: u32,
+        cache_height: u32,
+    ) {
+        self.antialiasing = antialiasing;
+        let (raw, subpixel_mask) = create_render_pipelines(
+            device,
+            &self.uniform_layout,
+            self.multisample,
+            self.render_format,
+            self.depth_stencil.clone(),
+            antialiasing,
+        );
+        self.raw = raw;
+        self.subpixel_mask = subpixel_mask;
+        self.rebuilds.pipelines += 1 + u64::from(self.subpixel_mask.is_some());
+        self.increase_cache_size(device, cache_width, cache_height);
+    }
+
     pub fn increase_cache_size(
         &mut self,
         device: &wgpu::Device,
         width: u32,
         height: u32,
     ) {
-        self.cache = Cache::new(device, width, height);
+        self.cache = Cache::new(device, width, height, self.antialiasing);
 
         self.uniforms = create_uniforms(
             device,
             &self.uniform_layout,
             &self.transform,
+            &self.blending,
             &self.sampler,
             &self.cache.view,
         );
+        self.rebuilds.bind_groups += 1;
     }
 
     pub fn upload(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         instances: &[Instance],
     ) {
@@ -175,14 +268,13 @@ impl<Depth> Pipeline<Depth> {
             self.supported_instances = instances.len();
         }
 
-        let instances_bytes = bytemuck::cast_slice(instances);
-
-        if let Some(size) = NonZeroU64::new(instances_bytes.len() as u64) {
-            let mut instances_view =
-                staging_belt.write_buffer(encoder, &self.instances, 0, size, device);
-
-            instances_view.copy_from_slice(instances_bytes);
-        }
+        staging_belt.write(
+            encoder,
+            &self.instances,
+            0,
+            bytemuck::cast_slice(instances),
+            device,
+        );
 
         self.current_instances = instances.len();
     }
@@ -209,6 +301,12 @@ fn build<D>(
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
     });
 
+    let blending = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("text::Pipeline blending"),
+        contents: bytemuck::cast_slice(&DEFAULT_BLENDING),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
     let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
         address_mode_u: wgpu::AddressMode::ClampToEdge,
         address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -219,7 +317,8 @@ fn build<D>(
         ..Default::default()
     });
 
-    let cache = Cache::new(device, cache_width, cache_height);
+    let antialiasing = Antialiasing::default();
+    let cache = Cache::new(device, cache_width, cache_height, antialiasing);
 
     let uniform_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -253,11 +352,29 @@ fn build<D>(
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            mem::size_of::<[f32; 4]>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
             ],
         });
 
-    let uniforms =
-        create_uniforms(device, &uniform_layout, &transform, &sampler, &cache.view);
+    let uniforms = create_uniforms(
+        device,
+        &uniform_layout,
+        &transform,
+        &blending,
+        &sampler,
+        &cache.view,
+    );
 
     let instances = device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("text::Pipeline instances"),
@@ -266,66 +383,35 @@ fn build<D>(
         mapped_at_creation: false,
     });
 
-    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: None,
-        push_constant_ranges: &[],
-        bind_group_layouts: &[&uniform_layout],
-    });
-
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Text Shader"),
-        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("text.wgsl"))),
-    });
-
-    let raw = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: None,
-        layout: Some(&layout),
-        vertex: wgpu::VertexState {
-            module: &shader,
-            entry_point: "vs_main",
-            buffers: &[wgpu::VertexBufferLayout {
-                array_stride: mem::size_of::<Instance>() as u64,
-                step_mode: wgpu::VertexStepMode::Instance,
-                attributes: &wgpu::vertex_attr_array![
-                    0 => Float32x3,
-                    1 => Float32x2,
-                    2 => Float32x2,
-                    3 => Float32x2,
-                    4 => Float32x4,
-                ],
-            }],
-        },
-        primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleStrip,
-            front_face: wgpu::FrontFace::Cw,
-            strip_index_format: Some(wgpu::IndexFormat::Uint16),
-            ..Default::default()
-        },
+    let (raw, subpixel_mask) = create_render_pipelines(
+        device,
+        &uniform_layout,
         multisample,
-        depth_stencil,
-        fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: "fs_main",
-            targets: &[Some(wgpu::ColorTargetState {
-                format: render_format,
-                blend: BLEND,
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
-        }),
-        multiview: None,
-    });
+        render_format,
+        depth_stencil.clone(),
+        antialiasing,
+    );
 
     Pipeline {
         transform,
+        blending,
         sampler,
         cache,
         uniform_layout,
         uniforms,
         raw,
+        subpixel_mask,
+        antialiasing,
+        multisample,
+        render_format,
+        depth_stencil,
         instances,
         current_instances: 0,
         supported_instances: Instance::INITIAL_AMOUNT,
         current_transform: [0.0; 16],
+        next_blending: DEFAULT_BLENDING,
+        current_blending: DEFAULT_BLENDING,
+        rebuilds: Rebuilds::default(),
         depth: PhantomData,
     }
 }
@@ -334,7 +420,7 @@ fn draw<D>(
     pipeline: &mut Pipeline<D>,
     config: (
         &wgpu::Device,
-        &mut wgpu::util::StagingBelt,
+        &mut Staging,
         &mut wgpu::CommandEncoder,
         &wgpu::TextureView,
     ),
@@ -344,19 +430,29 @@ fn draw<D>(
 ) {
     let (device, staging_belt, encoder, target) = config;
     if transform != pipeline.current_transform {
-        let mut transform_view = staging_belt.write_buffer(
+        staging_belt.write(
             encoder,
             &pipeline.transform,
             0,
-            unsafe { NonZeroU64::new_unchecked(16 * 4) },
+            bytemuck::cast_slice(&transform),
             device,
         );
 
-        transform_view.copy_from_slice(bytemuck::cast_slice(&transform));
-
         pipeline.current_transform = transform;
     }
 
+    if pipeline.next_blending != pipeline.current_blending {
+        staging_belt.write(
+            encoder,
+            &pipeline.blending,
+            0,
+            bytemuck::cast_slice(&pipeline.next_blending),
+            device,
+        );
+
+        pipeline.current_blending = pipeline.next_blending;
+    }
+
     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
         label: Some("text::pipeline render pass"),
         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -370,7 +466,6 @@ fn draw<D>(
         depth_stencil_attachment,
     });
 
-    render_pass.set_pipeline(&pipeline.raw);
     render_pass.set_bind_group(0, &pipeline.uniforms, &[]);
     render_pass.set_vertex_buffer(0, pipeline.instances.slice(..));
 
@@ -378,13 +473,91 @@ fn draw<D>(
         render_pass.set_scissor_rect(region.x, region.y, region.width, region.height);
     }
 
+    if let Some(subpixel_mask) = &pipeline.subpixel_mask {
+        render_pass.set_pipeline(subpixel_mask);
+        render_pass.draw(0..4, 0..pipeline.current_instances as u32);
+    }
+
+    render_pass.set_pipeline(&pipeline.raw);
     render_pass.draw(0..4, 0..pipeline.current_instances as u32);
 }
 
+/// Text pipeline for `antialiasing`, along with the mask pipeline drawn
+/// before it for subpixel antialiasing.
+fn create_render_pipelines(
+    device: &wgpu::Device,
+    uniform_layout: &wgpu::BindGroupLayout,
+    multisample: wgpu::MultisampleState,
+    render_format: wgpu::TextureFormat,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    antialiasing: Antialiasing,
+) -> (wgpu::RenderPipeline, Option<wgpu::RenderPipeline>) {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        push_constant_ranges: &[],
+        bind_group_layouts: &[uniform_layout],
+    });
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Text Shader"),
+        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("text.wgsl"))),
+    });
+
+    let create = |fragment_entry_point: &str, blend: Option<wgpu::BlendState>| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<Instance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x3,
+                        1 => Float32x2,
+                        2 => Float32x2,
+                        3 => Float32x2,
+                        4 => Float32x4,
+                    ],
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                front_face: wgpu::FrontFace::Cw,
+                strip_index_format: Some(wgpu::IndexFormat::Uint16),
+                ..Default::default()
+            },
+            multisample,
+            depth_stencil: depth_stencil.clone(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: fragment_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_format,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        })
+    };
+
+    if antialiasing.is_subpixel() {
+        (
+            create("fs_subpixel_color", SUBPIXEL_COLOR_BLEND),
+            Some(create("fs_subpixel_mask", SUBPIXEL_MASK_BLEND)),
+        )
+    } else {
+        (create("fs_main", BLEND), None)
+    }
+}
+
 fn create_uniforms(
     device: &wgpu::Device,
     layout: &wgpu::BindGroupLayout,
     transform: &wgpu::Buffer,
+    blending: &wgpu::Buffer,
     sampler: &wgpu::Sampler,
     cache: &wgpu::TextureView,
 ) -> wgpu::BindGroup {
@@ -408,6 +581,14 @@ fn create_uniforms(
                 binding: 2,
                 resource: wgpu::BindingResource::TextureView(cache),
             },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: blending,
+                    offset: 0,
+                    size: None,
+                }),
+            },
         ],
     })
 }
@@ -477,4 +658,12 @@ impl Instance {
             color: extra.color,
         }
     }
+
+    /// Vertex laid out `SUBPIXELS` times wider, narrowed back to pixels.
+    pub fn from_subpixel_vertex(vertex: glyph_brush::GlyphVertex) -> Instance {
+        let mut instance = Instance::from_vertex(vertex);
+        instance.left_top[0] /= SUBPIXELS;
+        instance.right_bottom[0] /= SUBPIXELS;
+        instance
+    }
 }
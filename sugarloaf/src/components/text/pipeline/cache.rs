@@ -1,4 +1,5 @@
-use core::num::NonZeroU64;
+use crate::components::text::subpixel::Antialiasing;
+use crate::context::Staging;
 use std::num::NonZeroU32;
 
 pub struct Cache {
@@ -6,13 +7,19 @@ pub struct Cache {
     pub(super) view: wgpu::TextureView,
     upload_buffer: wgpu::Buffer,
     upload_buffer_size: u64,
+    bytes_per_texel: usize,
 }
 
 impl Cache {
     const INITIAL_UPLOAD_BUFFER_SIZE: u64 =
         wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64 * 100;
 
-    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Cache {
+    pub fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        antialiasing: Antialiasing,
+    ) -> Cache {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("text::Cache"),
             size: wgpu::Extent3d {
@@ -22,7 +29,7 @@ impl Cache {
             },
             view_formats: &[],
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::R8Unorm,
+            format: antialiasing.texture_format(),
             usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
             mip_level_count: 1,
             sample_count: 1,
@@ -42,19 +49,20 @@ impl Cache {
             view,
             upload_buffer,
             upload_buffer_size: Self::INITIAL_UPLOAD_BUFFER_SIZE,
+            bytes_per_texel: antialiasing.bytes_per_texel(),
         }
     }
 
     pub fn update(
         &mut self,
         device: &wgpu::Device,
-        staging_belt: &mut wgpu::util::StagingBelt,
+        staging_belt: &mut Staging,
         encoder: &mut wgpu::CommandEncoder,
         offset: [u16; 2],
         size: [u16; 2],
         data: &[u8],
     ) {
-        let width = size[0] as usize;
+        let width = size[0] as usize * self.bytes_per_texel;
         let height = size[1] as usize;
 
         // It is a webgpu requirement that:
@@ -78,17 +86,20 @@ impl Cache {
             self.upload_buffer_size = padded_data_size;
         }
 
-        let mut padded_data = staging_belt.write_buffer(
+        let padded_data = staging_belt.write_buffer(
             encoder,
             &self.upload_buffer,
             0,
-            NonZeroU64::new(padded_data_size).unwrap(),
+            padded_data_size,
             device,
         );
-
-        for row in 0..height {
-            padded_data[row * padded_width..row * padded_width + width]
-                .copy_from_slice(&data[row * width..(row + 1) * width])
+        if let Some(mut padded_data) = padded_data {
+            for row in 0..height {
+                padded_data[row * padded_width..row * padded_width + width]
+                    .copy_from_slice(&data[row * width..(row + 1) * width])
+            }
+        } else {
+            return;
         }
 
         // TODO: Move to use Queue for less buffer usage
@@ -0,0 +1,112 @@
+// LCD subpixel antialiasing.
+//
+// Text is laid out three times wider (`PxScale::x` and the horizontal
+// section coordinates are multiplied by `SUBPIXELS`), so glyph_brush
+// rasterizes one coverage value per subpixel into its atlas. Before upload
+// the coverage is filtered and every texel stores the coverage of itself and
+// its neighbours as RGB, which the shader samples at the middle subpixel of
+// each pixel. Vertices are narrowed back to pixels in `Instance`.
+
+/// Subpixels per pixel, horizontally.
+pub const SUBPIXELS: f32 = 3.0;
+
+/// FreeType's default LCD filter, spreading coverage over neighbouring
+/// subpixels to reduce color fringes. Weights sum to 256.
+const LCD_FILTER: [u32; 5] = [8, 77, 86, 77, 8];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Antialiasing {
+    /// One coverage value per pixel.
+    #[default]
+    Grayscale,
+    /// Subpixels ordered red, green, blue from left to right.
+    Rgb,
+    /// Subpixels ordered blue, green, red from left to right.
+    Bgr,
+}
+
+impl Antialiasing {
+    #[inline]
+    pub fn is_subpixel(&self) -> bool {
+        *self != Antialiasing::Grayscale
+    }
+
+    #[inline]
+    pub fn texture_format(&self) -> wgpu::TextureFormat {
+        if self.is_subpixel() {
+            wgpu::TextureFormat::Rgba8Unorm
+        } else {
+            wgpu::TextureFormat::R8Unorm
+        }
+    }
+
+    #[inline]
+    pub fn bytes_per_texel(&self) -> usize {
+        if self.is_subpixel() {
+            4
+        } else {
+            1
+        }
+    }
+}
+
+/// Convert rows of `width` subpixel coverage values into RGBA texels.
+pub fn to_rgba(coverage: &[u8], width: usize, antialiasing: Antialiasing) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(coverage.len() * 4);
+    if width == 0 {
+        return rgba;
+    }
+
+    let mut filtered = vec![0u8; width];
+    for row in coverage.chunks(width) {
+        for (x, value) in filtered.iter_mut().enumerate() {
+            let sum: u32 = LCD_FILTER
+                .iter()
+                .enumerate()
+                .filter_map(|(tap, weight)| {
+                    (x + tap)
+                        .checked_sub(2)
+                        .and_then(|x| row.get(x))
+                        .map(|coverage| u32::from(*coverage) * weight)
+                })
+                .sum();
+            *value = (sum / 256) as u8;
+        }
+
+        for x in 0..width {
+            let left = x.checked_sub(1).map_or(0, |x| filtered[x]);
+            let right = filtered.get(x + 1).copied().unwrap_or(0);
+            let (red, blue) = match antialiasing {
+                Antialiasing::Bgr => (right, left),
+                _ => (left, right),
+            };
+            let green = filtered[x];
+            rgba.extend_from_slice(&[red, green, blue, red.max(green).max(blue)]);
+        }
+    }
+
+    rgba
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_rgba_spreads_coverage() {
+        let coverage = [0, 0, 255, 0, 0];
+        let rgba = to_rgba(&coverage, 5, Antialiasing::Rgb);
+        let texel = |x: usize| &rgba[x * 4..x * 4 + 4];
+
+        assert_eq!(rgba.len(), 20);
+        // The lit subpixel is green in its own texel, red in the next one
+        // and blue in the previous one.
+        assert_eq!(texel(2), &[76, 85, 76, 85]);
+        assert_eq!(texel(3)[0], 85);
+        assert_eq!(texel(1)[2], 85);
+
+        let bgr = to_rgba(&coverage, 5, Antialiasing::Bgr);
+        assert_eq!(&bgr[12..16], &[7, 76, 85, 85]);
+        assert!(to_rgba(&[], 0, Antialiasing::Rgb).is_empty());
+    }
+}
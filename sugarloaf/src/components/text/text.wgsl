@@ -6,6 +6,33 @@ struct Globals {
 @group(0) @binding(1) var font_sampler: sampler;
 @group(0) @binding(2) var font_tex: texture_2d<f32>;
 
+struct Blending {
+    gamma: f32,
+    contrast: f32,
+    // Dims text, e.g. of unfocused windows.
+    brightness: f32,
+    _padding: f32,
+}
+
+@group(0) @binding(3) var<uniform> blending: Blending;
+
+// Thin strokes get mostly partial coverage, which looks washed out once
+// blended. Contrast pushes partial coverage towards full and gamma bends the
+// curve, the defaults (1.0 and 0.0) leave coverage untouched.
+fn adjust_coverage(coverage: vec3<f32>) -> vec3<f32> {
+    var contrasted: vec3<f32> = clamp(
+        coverage + coverage * (vec3<f32>(1.0) - coverage) * blending.contrast,
+        vec3<f32>(0.0),
+        vec3<f32>(1.0)
+    );
+    // pow is undefined for 0.0 on some drivers.
+    return select(
+        pow(contrasted, vec3<f32>(1.0 / blending.gamma)),
+        vec3<f32>(0.0),
+        contrasted <= vec3<f32>(0.0)
+    );
+}
+
 struct VertexInput {
     @builtin(vertex_index) vertex_index: u32,
     @location(0) left_top: vec3<f32>,
@@ -59,11 +86,42 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    var alpha: f32 = textureSample(font_tex, font_sampler, input.f_tex_pos).r;
+    var alpha: f32 = adjust_coverage(
+        vec3<f32>(textureSample(font_tex, font_sampler, input.f_tex_pos).r)
+    ).r;
 
     if (alpha <= 0.0) {
         discard;
     }
 
-    return input.f_color * vec4<f32>(1.0, 1.0, 1.0, alpha);
+    return input.f_color * vec4<f32>(vec3<f32>(blending.brightness), alpha);
+}
+
+// Subpixel antialiasing, the atlas holds the coverage of the red, green and
+// blue subpixels of each pixel.
+fn subpixel_coverage(input: VertexOutput) -> vec3<f32> {
+    return adjust_coverage(textureSample(font_tex, font_sampler, input.f_tex_pos).rgb)
+        * input.f_color.a;
+}
+
+@fragment
+fn fs_subpixel_mask(input: VertexOutput) -> @location(0) vec4<f32> {
+    var coverage: vec3<f32> = subpixel_coverage(input);
+
+    if (max(coverage.r, max(coverage.g, coverage.b)) <= 0.0) {
+        discard;
+    }
+
+    return vec4<f32>(coverage, 1.0);
+}
+
+@fragment
+fn fs_subpixel_color(input: VertexOutput) -> @location(0) vec4<f32> {
+    var coverage: vec3<f32> = subpixel_coverage(input);
+
+    if (max(coverage.r, max(coverage.g, coverage.b)) <= 0.0) {
+        discard;
+    }
+
+    return vec4<f32>(input.f_color.rgb * blending.brightness * coverage, 1.0);
 }
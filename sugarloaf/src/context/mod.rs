@@ -1,18 +1,113 @@
+mod staging;
+
+pub use staging::Staging;
+use std::sync::Arc;
+
+/// Which GPU to render with.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AdapterOptions {
+    pub power_preference: wgpu::PowerPreference,
+    /// Use the first adapter whose name contains this, ignoring case.
+    pub name: Option<String>,
+    /// Use a software adapter (e.g. llvmpipe or WARP), if there is one.
+    pub force_fallback: bool,
+}
+
+impl From<wgpu::PowerPreference> for AdapterOptions {
+    fn from(power_preference: wgpu::PowerPreference) -> AdapterOptions {
+        AdapterOptions {
+            power_preference,
+            ..AdapterOptions::default()
+        }
+    }
+}
+
+/// How many times GPU resources were rebuilt because something they were
+/// built against changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rebuilds {
+    pub pipelines: u64,
+    pub bind_groups: u64,
+}
+
+#[inline]
+fn matches_name(adapter_name: &str, name: &str) -> bool {
+    adapter_name.to_lowercase().contains(&name.to_lowercase())
+}
+
+/// Present mode for an adapter of `device_type`. Software adapters wait for
+/// the display, rather than rasterizing frames nobody sees on the CPU.
+fn present_mode(device_type: wgpu::DeviceType) -> wgpu::PresentMode {
+    match device_type {
+        wgpu::DeviceType::Cpu => wgpu::PresentMode::Fifo,
+        _ => wgpu::PresentMode::AutoVsync,
+    }
+}
+
+/// Adapters can only be listed on native targets.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_adapter_by_name(
+    instance: &wgpu::Instance,
+    surface: &wgpu::Surface,
+    name: &str,
+) -> Option<wgpu::Adapter> {
+    let adapter = instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .filter(|adapter| adapter.is_surface_supported(surface))
+        .find(|adapter| matches_name(&adapter.get_info().name, name));
+
+    if adapter.is_none() {
+        log::warn!("no adapter named {name:?} supports the surface");
+    }
+    adapter
+}
+
+#[cfg(target_arch = "wasm32")]
+fn find_adapter_by_name(
+    _instance: &wgpu::Instance,
+    _surface: &wgpu::Surface,
+    name: &str,
+) -> Option<wgpu::Adapter> {
+    log::warn!("adapters can't be selected by name ({name:?}) on the web");
+    None
+}
+
+/// Textures a host application renders into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target {
+    pub format: wgpu::TextureFormat,
+    /// Size in physical pixels.
+    pub width: u32,
+    pub height: u32,
+    /// Physical pixels per logical pixel.
+    pub scale: f32,
+}
+
 #[derive(Debug)]
 pub struct Context {
-    pub device: wgpu::Device,
-    pub surface: wgpu::Surface,
-    pub queue: wgpu::Queue,
-    pub staging_belt: wgpu::util::StagingBelt,
+    /// Shared with the host application when embedded.
+    pub device: Arc<wgpu::Device>,
+    /// Missing when rendering into textures provided by a host application.
+    pub surface: Option<wgpu::Surface>,
+    pub queue: Arc<wgpu::Queue>,
+    pub staging_belt: Staging,
     pub format: wgpu::TextureFormat,
+    pub adapter_info: wgpu::AdapterInfo,
+    pub present_mode: wgpu::PresentMode,
     pub size: winit::dpi::PhysicalSize<u32>,
     pub scale: f32,
+    /// Samples per pixel of the rect pipeline, 1 disables multisampling.
+    pub sample_count: u32,
+    /// Bumped when the sample count changes, resources built with an older
+    /// generation are rebuilt before they are drawn again.
+    pub generation: u64,
+    pub rebuilds: Rebuilds,
 }
 
 impl Context {
     pub async fn new(
         winit_window: &winit::window::Window,
-        power_preference: wgpu::PowerPreference,
+        adapter_options: AdapterOptions,
     ) -> Context {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
@@ -34,7 +129,7 @@ impl Context {
                 .and_then(|win| win.document())
                 .and_then(|doc| doc.body())
                 .and_then(|body| {
-                    body.append_child(&web_sys::Element::from(window.canvas()))
+                    body.append_child(&web_sys::Element::from(winit_window.canvas()))
                         .ok()
                 })
                 .expect("couldn't append canvas to document body");
@@ -57,7 +152,7 @@ impl Context {
                     let offscreen_canvas = OffscreenCanvas::new(1024, 768)
                         .expect("couldn't create OffscreenCanvas");
 
-                    let bitmap_renderer = window
+                    let bitmap_renderer = winit_window
                         .canvas()
                         .get_context("bitmaprenderer")
                         .expect("couldn't create ImageBitmapRenderingContext (Result)")
@@ -89,20 +184,49 @@ impl Context {
                     offscreen_canvas_setup.offscreen_canvas.clone(),
                 )
             } else {
-                instance.create_surface(&window)
+                instance.create_surface(&winit_window)
             }
         }
         .unwrap();
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .expect("Request adapter");
+        let adapter = match adapter_options
+            .name
+            .as_ref()
+            .filter(|_| !adapter_options.force_fallback)
+            .and_then(|name| find_adapter_by_name(&instance, &surface, name))
+        {
+            Some(adapter) => adapter,
+            None => {
+                let mut options = wgpu::RequestAdapterOptions {
+                    power_preference: adapter_options.power_preference,
+                    compatible_surface: Some(&surface),
+                    force_fallback_adapter: adapter_options.force_fallback,
+                };
+                match instance.request_adapter(&options).await {
+                    Some(adapter) => adapter,
+                    // No usable GPU driver, e.g. in a virtual machine.
+                    None if !options.force_fallback_adapter => {
+                        log::warn!("no GPU adapter, retrying with a software one");
+                        options.force_fallback_adapter = true;
+                        instance
+                            .request_adapter(&options)
+                            .await
+                            .expect("Request adapter")
+                    }
+                    None => panic!("Request adapter: no software adapter available"),
+                }
+            }
+        };
+
+        let adapter_info = adapter.get_info();
+        log::info!(
+            "using adapter {} ({:?}, {:?})",
+            adapter_info.name,
+            adapter_info.device_type,
+            adapter_info.backend
+        );
 
+        let present_mode = present_mode(adapter_info.device_type);
         let caps = surface.get_capabilities(&adapter);
         let formats = caps.formats;
         let format = *formats.last().expect("No supported formats for surface");
@@ -115,8 +239,6 @@ impl Context {
         })
         .await;
 
-        let staging_belt = wgpu::util::StagingBelt::new(2 * 1024);
-
         surface.configure(
             &device,
             &wgpu::SurfaceConfiguration {
@@ -126,35 +248,96 @@ impl Context {
                 height: size.height,
                 view_formats: vec![],
                 alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                present_mode: wgpu::PresentMode::AutoVsync,
+                present_mode,
             },
         );
 
         Context {
-            device,
-            queue,
-            surface,
-            staging_belt,
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            surface: Some(surface),
+            staging_belt: Staging::default(),
             format,
+            adapter_info,
+            present_mode,
             size,
             scale: scale as f32,
+            sample_count: 1,
+            generation: 0,
+            rebuilds: Rebuilds::default(),
+        }
+    }
+
+    /// Context rendering with the device of a host application into textures
+    /// described by `target`, see `Sugarloaf::render_to_view`.
+    pub fn from_device(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        adapter_info: wgpu::AdapterInfo,
+        target: Target,
+    ) -> Context {
+        Context {
+            device,
+            queue,
+            surface: None,
+            staging_belt: Staging::default(),
+            format: target.format,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            adapter_info,
+            size: winit::dpi::PhysicalSize::new(target.width, target.height),
+            scale: target.scale,
+            sample_count: 1,
+            generation: 0,
+            rebuilds: Rebuilds::default(),
+        }
+    }
+
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.generation += 1;
         }
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
+        // Surfaces can't be zero-sized, unlike minimized windows on Windows.
+        let (width, height) = (width.max(1), height.max(1));
         self.size.width = width;
         self.size.height = height;
-        self.surface.configure(
-            &self.device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: self.format,
-                width,
-                height,
-                view_formats: vec![],
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                present_mode: wgpu::PresentMode::AutoVsync,
-            },
+        if let Some(surface) = &self.surface {
+            surface.configure(
+                &self.device,
+                &wgpu::SurfaceConfiguration {
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    format: self.format,
+                    width,
+                    height,
+                    view_formats: vec![],
+                    alpha_mode: wgpu::CompositeAlphaMode::Auto,
+                    present_mode: self.present_mode,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_matches_name() {
+        assert!(matches_name("NVIDIA GeForce RTX 3060", "nvidia"));
+        assert!(matches_name("Intel(R) UHD Graphics 620", "UHD Graphics"));
+        assert!(!matches_name("llvmpipe (LLVM 15.0.7, 256 bits)", "intel"));
+    }
+
+    #[test]
+    fn test_present_mode() {
+        assert_eq!(present_mode(wgpu::DeviceType::Cpu), wgpu::PresentMode::Fifo);
+        assert_eq!(
+            present_mode(wgpu::DeviceType::DiscreteGpu),
+            wgpu::PresentMode::AutoVsync
         );
     }
 }
@@ -0,0 +1,100 @@
+use wgpu::util::StagingBelt;
+
+/// Size of the chunks the belt starts with.
+const INITIAL_CHUNK_SIZE: u64 = 2 * 1024;
+/// Largest chunk the belt grows to, bigger writes get a chunk of their own.
+const MAX_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Staging belt which grows its chunks to fit everything uploaded in a
+/// frame, so large text uploads stop allocating a chunk per write.
+#[derive(Debug)]
+pub struct Staging {
+    belt: StagingBelt,
+    chunk_size: u64,
+    /// Bytes written since the last `recall`.
+    used: u64,
+}
+
+impl Default for Staging {
+    fn default() -> Staging {
+        Staging {
+            belt: StagingBelt::new(INITIAL_CHUNK_SIZE),
+            chunk_size: INITIAL_CHUNK_SIZE,
+            used: 0,
+        }
+    }
+}
+
+impl Staging {
+    /// View of `size` bytes to be copied into `target` at `offset`, `None`
+    /// when there is nothing to write.
+    pub fn write_buffer(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        size: u64,
+        device: &wgpu::Device,
+    ) -> Option<wgpu::BufferViewMut<'_>> {
+        let size = wgpu::BufferSize::new(size)?;
+        self.used += size.get();
+        Some(self.belt.write_buffer(encoder, target, offset, size, device))
+    }
+
+    /// Copy `data` into `target` at `offset`.
+    pub fn write(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[u8],
+        device: &wgpu::Device,
+    ) {
+        if let Some(mut view) =
+            self.write_buffer(encoder, target, offset, data.len() as u64, device)
+        {
+            view.copy_from_slice(data);
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.belt.finish();
+    }
+
+    /// Recall the chunks of a submitted frame, growing them when the frame
+    /// did not fit in one.
+    pub fn recall(&mut self) {
+        self.belt.recall();
+
+        let chunk_size = chunk_size_for(self.used, self.chunk_size);
+        if chunk_size != self.chunk_size {
+            log::debug!("growing staging belt chunks to {chunk_size} bytes");
+            self.belt = StagingBelt::new(chunk_size);
+            self.chunk_size = chunk_size;
+        }
+        self.used = 0;
+    }
+}
+
+/// Chunk size fitting `used` bytes, which never shrinks below `current`.
+fn chunk_size_for(used: u64, current: u64) -> u64 {
+    if used <= current {
+        return current;
+    }
+
+    used.next_power_of_two().clamp(current, MAX_CHUNK_SIZE.max(current))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    fn test_chunk_size_for() {
+        assert_eq!(chunk_size_for(0, INITIAL_CHUNK_SIZE), INITIAL_CHUNK_SIZE);
+        assert_eq!(chunk_size_for(2048, 2048), 2048);
+        assert_eq!(chunk_size_for(2049, 2048), 4096);
+        assert_eq!(chunk_size_for(300_000, 2048), 512 * 1024);
+        assert_eq!(chunk_size_for(64 * 1024 * 1024, 2048), MAX_CHUNK_SIZE);
+    }
+}
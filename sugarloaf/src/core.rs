@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use std::ops::Range;
+
+#[derive(Debug, Clone)]
 pub struct Sugar {
     pub content: char,
     pub foreground_color: [f32; 4],
@@ -6,7 +8,7 @@ pub struct Sugar {
     pub style: Option<SugarStyle>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SugarStyle {
     pub is_italic: bool,
     pub is_bold: bool,
@@ -23,6 +25,91 @@ pub struct SugarloafStyle {
     pub text_scale: f32,
 }
 
+/// Highlights are drawn over cell backgrounds and under text, in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HighlightLayer {
+    SearchMatch,
+    Hyperlink,
+    Selection,
+    ViCursor,
+    /// Drawn as the outline of the cells, e.g. the cursor of an unfocused
+    /// window.
+    CursorOutline,
+    /// Drawn as a line through the middle of the cells, e.g. text with
+    /// SGR 9.
+    Strikethrough,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    pub layer: HighlightLayer,
+    /// Index of the stack in the frame, starting at 0.
+    pub line: usize,
+    pub columns: Range<usize>,
+    pub color: [f32; 4],
+}
+
+/// Queue `highlight` ordered by layer, extending a queued highlight of the
+/// same layer and color that ends where it starts.
+pub fn queue_highlight(highlights: &mut Vec<Highlight>, highlight: Highlight) {
+    if let Some(queued) = highlights.iter_mut().rev().find(|queued| {
+        queued.layer == highlight.layer
+            && queued.line == highlight.line
+            && queued.color == highlight.color
+            && queued.columns.end == highlight.columns.start
+    }) {
+        queued.columns.end = highlight.columns.end;
+        return;
+    }
+
+    let index = highlights.partition_point(|queued| queued.layer <= highlight.layer);
+    highlights.insert(index, highlight);
+}
+
 pub fn empty_sugar_pile() -> SugarPile {
     vec![vec![]]
 }
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn highlight(layer: HighlightLayer, columns: Range<usize>) -> Highlight {
+        Highlight {
+            layer,
+            line: 0,
+            columns,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn test_queue_highlight() {
+        let mut highlights = vec![];
+        queue_highlight(&mut highlights, highlight(HighlightLayer::ViCursor, 4..5));
+        queue_highlight(&mut highlights, highlight(HighlightLayer::Selection, 0..1));
+        queue_highlight(&mut highlights, highlight(HighlightLayer::Selection, 1..2));
+        queue_highlight(
+            &mut highlights,
+            highlight(HighlightLayer::SearchMatch, 1..3),
+        );
+
+        assert_eq!(
+            highlights,
+            vec![
+                highlight(HighlightLayer::SearchMatch, 1..3),
+                highlight(HighlightLayer::Selection, 0..2),
+                highlight(HighlightLayer::ViCursor, 4..5),
+            ]
+        );
+
+        // Not contiguous or on another line.
+        queue_highlight(&mut highlights, highlight(HighlightLayer::Selection, 3..4));
+        let mut next_line = highlight(HighlightLayer::Selection, 2..3);
+        next_line.line = 1;
+        queue_highlight(&mut highlights, next_line);
+        assert_eq!(highlights.len(), 5);
+        assert_eq!(highlights[3].layer, HighlightLayer::Selection);
+        assert_eq!(highlights[4].layer, HighlightLayer::ViCursor);
+    }
+}
@@ -1,6 +1,14 @@
+pub mod rasterizer;
+#[cfg(feature = "swash")]
+mod swash;
+
+#[cfg(not(target_arch = "wasm32"))]
 use font_kit::source::SystemSource;
-use glyph_brush::ab_glyph::{FontArc, FontVec};
+use glyph_brush::ab_glyph::FontArc;
+#[cfg(not(target_arch = "wasm32"))]
 use log::warn;
+use rasterizer::{GlyphRasterizer, HintSize, Rasterizer, Synthesis};
+use std::borrow::Cow;
 
 pub const DEFAULT_FONT_NAME: &str = "cascadiamono";
 
@@ -35,14 +43,28 @@ pub struct Font {
     pub symbol: FontArc,
     pub emojis: FontArc,
     pub unicode: FontArc,
+    /// Size the rasterizer hints outlines for, see `Sugarloaf::stack`.
+    pub hint_size: HintSize,
+}
+
+#[inline]
+fn load_bundled(
+    rasterizer: &dyn GlyphRasterizer,
+    data: &'static [u8],
+) -> Result<FontArc, String> {
+    rasterizer.load(Cow::Borrowed(data), 0, Synthesis::default())
 }
 
 impl Font {
-    pub fn new(font_name: String) -> Result<Font, String> {
+    pub fn new(font_name: String, rasterizer: Rasterizer) -> Result<Font, String> {
         // TODO:
         // This code is quite unsafe and needs a proper refactor
         // adding font load fallbacks for all categories.
 
+        let hint_size = HintSize::default();
+        let rasterizer = rasterizer.backend(&hint_size);
+        let rasterizer = rasterizer.as_ref();
+
         let font_arc_unicode;
         let font_arc_symbol;
 
@@ -55,9 +77,11 @@ impl Font {
                 .unwrap();
             let copied_font_symbol = font_symbols.copy_font_data();
             let Some(copied_font_symbol) = copied_font_symbol else { todo!() };
-            let font_vec_symbol =
-                FontVec::try_from_vec_and_index(copied_font_symbol.to_vec(), 1).unwrap();
-            font_arc_symbol = FontArc::new(font_vec_symbol);
+            font_arc_symbol = rasterizer.load(
+                Cow::Owned(copied_font_symbol.to_vec()),
+                1,
+                Synthesis::default(),
+            )?;
 
             // TODO: Load native emojis
             // let font_emojis = SystemSource::new()
@@ -76,32 +100,46 @@ impl Font {
                 .unwrap();
             let copied_font_unicode = font_unicode.copy_font_data();
             let Some(copied_font_unicode) = copied_font_unicode else { todo!() };
-            let font_vec_unicode =
-                FontVec::try_from_vec_and_index(copied_font_unicode.to_vec(), 3).unwrap();
-            font_arc_unicode = FontArc::new(font_vec_unicode);
+            font_arc_unicode = rasterizer.load(
+                Cow::Owned(copied_font_unicode.to_vec()),
+                3,
+                Synthesis::default(),
+            )?;
         }
 
         #[cfg(not(target_os = "macos"))]
         {
-            font_arc_unicode = FontArc::try_from_slice(FONT_DEJAVU_MONO).unwrap();
-            font_arc_symbol = FontArc::try_from_slice(FONT_DEJAVU_MONO).unwrap();
+            font_arc_unicode = load_bundled(rasterizer, FONT_DEJAVU_MONO)?;
+            font_arc_symbol = load_bundled(rasterizer, FONT_DEJAVU_MONO)?;
         }
 
-        if font_name.to_lowercase() == DEFAULT_FONT_NAME {
-            return Ok(Font {
-                text: ComposedFontArc {
-                    regular: FontArc::try_from_slice(FONT_CASCADIAMONO_REGULAR).unwrap(),
-                    bold: FontArc::try_from_slice(FONT_CASCADIAMONO_BOLD).unwrap(),
-                    italic: FontArc::try_from_slice(FONT_CASCADIAMONO_ITALIC).unwrap(),
-                    bold_italic: FontArc::try_from_slice(FONT_CASCADIAMONO_BOLD_ITALIC)
-                        .unwrap(),
-                },
-                symbol: font_arc_symbol,
-                emojis: FontArc::try_from_slice(FONT_EMOJI).unwrap(),
-                unicode: font_arc_unicode,
-            });
-        }
+        let text = if font_name.to_lowercase() == DEFAULT_FONT_NAME {
+            ComposedFontArc {
+                regular: load_bundled(rasterizer, FONT_CASCADIAMONO_REGULAR)?,
+                bold: load_bundled(rasterizer, FONT_CASCADIAMONO_BOLD)?,
+                italic: load_bundled(rasterizer, FONT_CASCADIAMONO_ITALIC)?,
+                bold_italic: load_bundled(rasterizer, FONT_CASCADIAMONO_BOLD_ITALIC)?,
+            }
+        } else {
+            Font::from_system(font_name, rasterizer)?
+        };
+
+        Ok(Font {
+            text,
+            symbol: font_arc_symbol,
+            emojis: load_bundled(rasterizer, FONT_EMOJI)?,
+            unicode: font_arc_unicode,
+            hint_size,
+        })
+    }
 
+    /// Load the first face of a system font family. Rasterizers that can't
+    /// synthesize styles fall back to the bundled bold and italic faces.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_system(
+        font_name: String,
+        rasterizer: &dyn GlyphRasterizer,
+    ) -> Result<ComposedFontArc, String> {
         let system_fonts = SystemSource::new().select_family_by_name(&font_name);
         match system_fonts {
             Ok(system_fonts) => {
@@ -109,30 +147,35 @@ impl Font {
                 if !fonts.is_empty() {
                     let first_font = fonts[0].load();
                     if let Ok(font) = first_font {
-                        let copied_font = font.copy_font_data();
-                        if copied_font.is_some() {
-                            let Some(copied_font) = copied_font else { todo!() };
-                            let font_vec_system =
-                                FontVec::try_from_vec_and_index(copied_font.to_vec(), 0)
-                                    .unwrap();
-
-                            return Ok(Font {
-                                text: ComposedFontArc {
-                                    regular: FontArc::new(font_vec_system),
-                                    bold: FontArc::try_from_slice(FONT_CASCADIAMONO_BOLD)
-                                        .unwrap(),
-                                    italic: FontArc::try_from_slice(
-                                        FONT_CASCADIAMONO_ITALIC,
-                                    )
-                                    .unwrap(),
-                                    bold_italic: FontArc::try_from_slice(
-                                        FONT_CASCADIAMONO_BOLD_ITALIC,
-                                    )
-                                    .unwrap(),
-                                },
-                                symbol: font_arc_symbol,
-                                emojis: FontArc::try_from_slice(FONT_EMOJI).unwrap(),
-                                unicode: font_arc_unicode,
+                        if let Some(copied_font) = font.copy_font_data() {
+                            let load = |bold, italic| {
+                                rasterizer.load(
+                                    Cow::Owned(copied_font.to_vec()),
+                                    0,
+                                    Synthesis { bold, italic },
+                                )
+                            };
+
+                            if rasterizer.synthesizes() {
+                                return Ok(ComposedFontArc {
+                                    regular: load(false, false)?,
+                                    bold: load(true, false)?,
+                                    italic: load(false, true)?,
+                                    bold_italic: load(true, true)?,
+                                });
+                            }
+
+                            return Ok(ComposedFontArc {
+                                regular: load(false, false)?,
+                                bold: load_bundled(rasterizer, FONT_CASCADIAMONO_BOLD)?,
+                                italic: load_bundled(
+                                    rasterizer,
+                                    FONT_CASCADIAMONO_ITALIC,
+                                )?,
+                                bold_italic: load_bundled(
+                                    rasterizer,
+                                    FONT_CASCADIAMONO_BOLD_ITALIC,
+                                )?,
                             });
                         }
                     }
@@ -146,4 +189,14 @@ impl Font {
             }
         }
     }
+
+    /// System fonts can't be listed in the browser, only the bundled font is
+    /// available.
+    #[cfg(target_arch = "wasm32")]
+    fn from_system(
+        font_name: String,
+        _rasterizer: &dyn GlyphRasterizer,
+    ) -> Result<ComposedFontArc, String> {
+        Err(format!("font {font_name} is not available on the web"))
+    }
 }
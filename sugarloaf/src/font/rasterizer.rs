@@ -0,0 +1,134 @@
+use glyph_brush::ab_glyph::{FontArc, FontRef, FontVec};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Backend used to turn glyphs into the outlines drawn into the glyph atlas.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rasterizer {
+    /// Unhinted outlines read by ab_glyph.
+    #[default]
+    AbGlyph,
+    /// Outlines hinted by swash, which can also synthesize bold and italic
+    /// faces. Requires the `swash` feature.
+    Swash,
+}
+
+impl Rasterizer {
+    pub fn backend(&self, hint_size: &HintSize) -> Box<dyn GlyphRasterizer> {
+        match self {
+            Rasterizer::AbGlyph => Box::new(AbGlyph),
+            #[cfg(feature = "swash")]
+            Rasterizer::Swash => Box::new(super::swash::Swash::new(hint_size.clone())),
+            #[cfg(not(feature = "swash"))]
+            Rasterizer::Swash => {
+                let _ = hint_size;
+                log::warn!("sugarloaf was built without swash, using ab_glyph instead");
+                Box::new(AbGlyph)
+            }
+        }
+    }
+}
+
+/// Styles applied to a face that doesn't have them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Synthesis {
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// Pixel size text is currently laid out at, shared with the rasterizers that
+/// hint outlines for a specific size.
+#[derive(Debug, Clone, Default)]
+pub struct HintSize(Arc<AtomicU32>);
+
+impl HintSize {
+    #[inline]
+    pub fn set(&self, px_scale: f32) {
+        self.0.store(px_scale.to_bits(), Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+pub trait GlyphRasterizer {
+    /// Load the face at `index` of the font data.
+    fn load(
+        &self,
+        data: Cow<'static, [u8]>,
+        index: u32,
+        synthesis: Synthesis,
+    ) -> Result<FontArc, String>;
+
+    /// Whether `load` applies `Synthesis`, otherwise separate faces have to be
+    /// provided for bold and italic text.
+    fn synthesizes(&self) -> bool {
+        false
+    }
+}
+
+pub struct AbGlyph;
+
+impl GlyphRasterizer for AbGlyph {
+    fn load(
+        &self,
+        data: Cow<'static, [u8]>,
+        index: u32,
+        _synthesis: Synthesis,
+    ) -> Result<FontArc, String> {
+        let font = match data {
+            Cow::Borrowed(data) => {
+                FontRef::try_from_slice_and_index(data, index).map(FontArc::new)
+            }
+            Cow::Owned(data) => {
+                FontVec::try_from_vec_and_index(data, index).map(FontArc::new)
+            }
+        };
+
+        font.map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::font::FONT_CASCADIAMONO_REGULAR;
+    use glyph_brush::ab_glyph::Font;
+
+    #[test]
+    fn test_hint_size() {
+        let hint_size = HintSize::default();
+        assert_eq!(hint_size.get(), 0.0);
+
+        // Clones share the size.
+        let shared = hint_size.clone();
+        shared.set(18.5);
+        assert_eq!(hint_size.get(), 18.5);
+    }
+
+    #[test]
+    fn test_ab_glyph_loads_borrowed_and_owned_data() {
+        let borrowed = AbGlyph
+            .load(
+                Cow::Borrowed(FONT_CASCADIAMONO_REGULAR),
+                0,
+                Synthesis::default(),
+            )
+            .unwrap();
+        let owned = AbGlyph
+            .load(
+                Cow::Owned(FONT_CASCADIAMONO_REGULAR.to_vec()),
+                0,
+                Synthesis::default(),
+            )
+            .unwrap();
+
+        assert_eq!(borrowed.glyph_count(), owned.glyph_count());
+        assert!(AbGlyph
+            .load(Cow::Borrowed(&[]), 0, Synthesis::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,353 @@
+// Rasterizer backed by swash.
+//
+// glyph_brush only knows about `ab_glyph::Font`, so `SwashFont` implements it:
+// metrics and character mapping come from ab_glyph, while outlines are
+// hinted by swash at the size text is laid out at and scaled back to font
+// units. Synthetic bold emboldens the outline and synthetic italic skews it.
+
+use super::rasterizer::{GlyphRasterizer, HintSize, Synthesis};
+use glyph_brush::ab_glyph::{
+    CodepointIdIter, Font, FontArc, GlyphId, GlyphImage, Outline, OutlineCurve, Point,
+    Rect,
+};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+use swash::scale::ScaleContext;
+use swash::zeno::{Command, PathData, Vector};
+use swash::CacheKey;
+
+/// Horizontal shift per unit of height of synthetic italic, about 12 degrees.
+const ITALIC_SKEW: f32 = 0.21;
+
+pub struct Swash {
+    hint_size: HintSize,
+}
+
+impl Swash {
+    pub fn new(hint_size: HintSize) -> Swash {
+        Swash { hint_size }
+    }
+}
+
+impl GlyphRasterizer for Swash {
+    fn load(
+        &self,
+        data: Cow<'static, [u8]>,
+        index: u32,
+        synthesis: Synthesis,
+    ) -> Result<FontArc, String> {
+        SwashFont::new(data.into_owned(), index, synthesis, self.hint_size.clone())
+            .map(FontArc::new)
+    }
+
+    fn synthesizes(&self) -> bool {
+        true
+    }
+}
+
+pub struct SwashFont {
+    data: Arc<[u8]>,
+    offset: u32,
+    key: CacheKey,
+    /// Same face read by ab_glyph, used for metrics and character mapping.
+    metrics: FontArc,
+    synthesis: Synthesis,
+    hint_size: HintSize,
+    context: Mutex<ScaleContext>,
+}
+
+impl SwashFont {
+    pub fn new(
+        data: Vec<u8>,
+        index: u32,
+        synthesis: Synthesis,
+        hint_size: HintSize,
+    ) -> Result<SwashFont, String> {
+        let data: Arc<[u8]> = Arc::from(data);
+        let font = swash::FontRef::from_index(&data, index as usize)
+            .ok_or_else(|| format!("invalid font data at index {index}"))?;
+        let (offset, key) = (font.offset, font.key);
+        let metrics =
+            glyph_brush::ab_glyph::FontVec::try_from_vec_and_index(data.to_vec(), index)
+                .map_err(|err| err.to_string())?;
+
+        Ok(SwashFont {
+            data,
+            offset,
+            key,
+            metrics: FontArc::new(metrics),
+            synthesis,
+            hint_size,
+            context: Mutex::new(ScaleContext::new()),
+        })
+    }
+
+    #[inline]
+    fn font_ref(&self) -> swash::FontRef {
+        swash::FontRef {
+            data: &self.data,
+            offset: self.offset,
+            key: self.key,
+        }
+    }
+
+    /// Pixels per em matching the glyph_brush scale, which is the height
+    /// from descent to ascent.
+    fn ppem(&self, units_per_em: f32) -> f32 {
+        let height = self.metrics.height_unscaled();
+        let px_scale = self.hint_size.get();
+        if px_scale <= 0.0 || height <= 0.0 {
+            return units_per_em;
+        }
+
+        px_scale * units_per_em / height
+    }
+}
+
+impl Font for SwashFont {
+    fn units_per_em(&self) -> Option<f32> {
+        self.metrics.units_per_em()
+    }
+
+    fn ascent_unscaled(&self) -> f32 {
+        self.metrics.ascent_unscaled()
+    }
+
+    fn descent_unscaled(&self) -> f32 {
+        self.metrics.descent_unscaled()
+    }
+
+    fn line_gap_unscaled(&self) -> f32 {
+        self.metrics.line_gap_unscaled()
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.metrics.glyph_id(c)
+    }
+
+    fn h_advance_unscaled(&self, id: GlyphId) -> f32 {
+        self.metrics.h_advance_unscaled(id)
+    }
+
+    fn h_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        self.metrics.h_side_bearing_unscaled(id)
+    }
+
+    fn v_advance_unscaled(&self, id: GlyphId) -> f32 {
+        self.metrics.v_advance_unscaled(id)
+    }
+
+    fn v_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        self.metrics.v_side_bearing_unscaled(id)
+    }
+
+    fn kern_unscaled(&self, first: GlyphId, second: GlyphId) -> f32 {
+        self.metrics.kern_unscaled(first, second)
+    }
+
+    fn outline(&self, id: GlyphId) -> Option<Outline> {
+        let units_per_em = self.units_per_em()?;
+        let ppem = self.ppem(units_per_em);
+
+        let mut context = self.context.lock().ok()?;
+        let mut scaler = context
+            .builder(self.font_ref())
+            .size(ppem)
+            .hint(true)
+            .build();
+        let mut outline = scaler.scale_outline(id.0)?;
+        if self.synthesis.bold {
+            let strength = ppem / 32.0;
+            outline.embolden(strength, strength);
+        }
+
+        let to_units = units_per_em / ppem;
+        let skew = if self.synthesis.italic {
+            ITALIC_SKEW
+        } else {
+            0.0
+        };
+        let curves = to_curves(outline.path().commands(), |vector| Point {
+            x: (vector.x + vector.y * skew) * to_units,
+            y: vector.y * to_units,
+        });
+        if curves.is_empty() {
+            return None;
+        }
+
+        Some(Outline {
+            bounds: bounds(&curves),
+            curves,
+        })
+    }
+
+    fn glyph_count(&self) -> usize {
+        self.metrics.glyph_count()
+    }
+
+    fn codepoint_ids(&self) -> CodepointIdIter<'_> {
+        self.metrics.codepoint_ids()
+    }
+
+    #[allow(deprecated)]
+    fn glyph_raster_image(&self, id: GlyphId, size: u16) -> Option<GlyphImage> {
+        self.metrics.glyph_raster_image(id, size)
+    }
+}
+
+/// Convert path commands into ab_glyph curves, closing every contour.
+fn to_curves(
+    commands: impl Iterator<Item = Command>,
+    point: impl Fn(Vector) -> Point,
+) -> Vec<OutlineCurve> {
+    let mut curves = Vec::new();
+    let mut start = Point { x: 0.0, y: 0.0 };
+    let mut current = start;
+
+    for command in commands {
+        match command {
+            Command::MoveTo(to) => {
+                if current != start {
+                    curves.push(OutlineCurve::Line(current, start));
+                }
+                start = point(to);
+                current = start;
+            }
+            Command::LineTo(to) => {
+                let to = point(to);
+                curves.push(OutlineCurve::Line(current, to));
+                current = to;
+            }
+            Command::QuadTo(control, to) => {
+                let to = point(to);
+                curves.push(OutlineCurve::Quad(current, point(control), to));
+                current = to;
+            }
+            Command::CurveTo(control1, control2, to) => {
+                let to = point(to);
+                curves.push(OutlineCurve::Cubic(
+                    current,
+                    point(control1),
+                    point(control2),
+                    to,
+                ));
+                current = to;
+            }
+            Command::Close => {
+                if current != start {
+                    curves.push(OutlineCurve::Line(current, start));
+                }
+                current = start;
+            }
+        }
+    }
+
+    if current != start {
+        curves.push(OutlineCurve::Line(current, start));
+    }
+
+    curves
+}
+
+fn bounds(curves: &[OutlineCurve]) -> Rect {
+    let mut min = Point {
+        x: f32::MAX,
+        y: f32::MAX,
+    };
+    let mut max = Point {
+        x: f32::MIN,
+        y: f32::MIN,
+    };
+
+    for curve in curves {
+        let points = match curve {
+            OutlineCurve::Line(p0, p1) => vec![*p0, *p1],
+            OutlineCurve::Quad(p0, p1, p2) => vec![*p0, *p1, *p2],
+            OutlineCurve::Cubic(p0, p1, p2, p3) => vec![*p0, *p1, *p2, *p3],
+        };
+        for point in &points {
+            min.x = min.x.min(point.x);
+            min.y = min.y.min(point.y);
+            max.x = max.x.max(point.x);
+            max.y = max.y.max(point.y);
+        }
+    }
+
+    Rect { min, max }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::font::FONT_CASCADIAMONO_REGULAR;
+
+    fn load(data: &'static [u8], synthesis: Synthesis) -> FontArc {
+        let hint_size = HintSize::default();
+        hint_size.set(16.0);
+        Swash::new(hint_size)
+            .load(Cow::Borrowed(data), 0, synthesis)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_to_curves_closes_contours() {
+        let vector = |x, y| Vector::new(x, y);
+        let curves = to_curves(
+            vec![
+                Command::MoveTo(vector(0.0, 0.0)),
+                Command::LineTo(vector(10.0, 0.0)),
+                Command::QuadTo(vector(10.0, 10.0), vector(0.0, 10.0)),
+                Command::Close,
+            ]
+            .into_iter(),
+            |vector| Point {
+                x: vector.x,
+                y: vector.y,
+            },
+        );
+
+        assert_eq!(curves.len(), 3);
+        assert_eq!(
+            curves[2],
+            OutlineCurve::Line(Point { x: 0.0, y: 10.0 }, Point { x: 0.0, y: 0.0 })
+        );
+        let bounds = bounds(&curves);
+        assert_eq!(bounds.min, Point { x: 0.0, y: 0.0 });
+        assert_eq!(bounds.max, Point { x: 10.0, y: 10.0 });
+    }
+
+    #[test]
+    fn test_metrics_match_ab_glyph() {
+        let swash = load(FONT_CASCADIAMONO_REGULAR, Synthesis::default());
+        let ab_glyph = FontArc::try_from_slice(FONT_CASCADIAMONO_REGULAR).unwrap();
+
+        let id = swash.glyph_id('a');
+        assert_eq!(id, ab_glyph.glyph_id('a'));
+        assert_eq!(
+            swash.h_advance_unscaled(id),
+            ab_glyph.h_advance_unscaled(id)
+        );
+        assert_eq!(swash.height_unscaled(), ab_glyph.height_unscaled());
+        assert!(swash.outline(id).is_some());
+        assert!(swash.outline(swash.glyph_id(' ')).is_none());
+    }
+
+    #[test]
+    fn test_synthetic_bold_is_wider() {
+        let regular = load(FONT_CASCADIAMONO_REGULAR, Synthesis::default());
+        let bold = load(
+            FONT_CASCADIAMONO_REGULAR,
+            Synthesis {
+                bold: true,
+                italic: false,
+            },
+        );
+
+        let id = regular.glyph_id('l');
+        let width = |font: &FontArc| {
+            let bounds = font.outline(id).unwrap().bounds;
+            bounds.max.x - bounds.min.x
+        };
+        assert!(width(&bold) > width(&regular));
+    }
+}
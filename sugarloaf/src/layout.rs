@@ -0,0 +1,121 @@
+use crate::core::SugarStack;
+use glyph_brush::FontId;
+use std::collections::HashMap;
+use std::mem;
+
+/// Font and advance of each sugar of a stack.
+pub type GlyphRun = Vec<(FontId, f32)>;
+
+/// What a glyph run depends on: the content and style of each sugar and the
+/// text scale, but not the colors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutKey {
+    text_scale: u32,
+    glyphs: Vec<(char, u8)>,
+}
+
+impl LayoutKey {
+    pub fn new(stack: &SugarStack, text_scale: f32) -> LayoutKey {
+        let glyphs = stack
+            .iter()
+            .map(|sugar| {
+                let style = sugar.style.as_ref().map_or(0, |style| {
+                    u8::from(style.is_bold)
+                        | u8::from(style.is_italic) << 1
+                        | u8::from(style.is_bold_italic) << 2
+                });
+                (sugar.content, style)
+            })
+            .collect();
+
+        LayoutKey {
+            text_scale: text_scale.to_bits(),
+            glyphs,
+        }
+    }
+}
+
+/// Glyph runs of the stacks queued in the current and the previous frame,
+/// so unchanged lines skip font fallback between frames.
+#[derive(Default)]
+pub struct LayoutCache {
+    current: HashMap<LayoutKey, GlyphRun>,
+    previous: HashMap<LayoutKey, GlyphRun>,
+}
+
+impl LayoutCache {
+    /// Run cached for `key`, laid out with `layout` on a cache miss.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: LayoutKey,
+        layout: impl FnOnce() -> GlyphRun,
+    ) -> &GlyphRun {
+        if let Some(run) = self.previous.remove(&key) {
+            return self.current.entry(key).or_insert(run);
+        }
+
+        self.current.entry(key).or_insert_with(layout)
+    }
+
+    /// Drop the runs which were not used in the frame before the one that
+    /// just ended.
+    pub fn next_frame(&mut self) {
+        self.previous = mem::take(&mut self.current);
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::core::{Sugar, SugarStyle};
+
+    fn stack(content: &str, is_bold: bool) -> SugarStack {
+        content
+            .chars()
+            .map(|content| Sugar {
+                content,
+                foreground_color: [1.0, 1.0, 1.0, 1.0],
+                background_color: [0.0, 0.0, 0.0, 1.0],
+                style: is_bold.then_some(SugarStyle {
+                    is_italic: false,
+                    is_bold: true,
+                    is_bold_italic: false,
+                }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_key() {
+        let key = LayoutKey::new(&stack("ab", false), 16.0);
+        let mut recolored = stack("ab", false);
+        recolored[0].foreground_color = [1.0, 0.0, 0.0, 1.0];
+        assert_eq!(LayoutKey::new(&recolored, 16.0), key);
+
+        assert_ne!(LayoutKey::new(&stack("ab", true), 16.0), key);
+        assert_ne!(LayoutKey::new(&stack("ac", false), 16.0), key);
+        assert_ne!(LayoutKey::new(&stack("ab", false), 18.0), key);
+    }
+
+    #[test]
+    fn test_layout_cache() {
+        let mut cache = LayoutCache::default();
+        let key = LayoutKey::new(&stack("a", false), 16.0);
+        let run = vec![(FontId(0), 8.0)];
+
+        cache.get_or_insert_with(key.clone(), || run.clone());
+        cache.next_frame();
+        // Kept while used in every frame.
+        let cached = cache.get_or_insert_with(key.clone(), || unreachable!());
+        assert_eq!(cached, &run);
+        cache.next_frame();
+        cache.next_frame();
+
+        let mut is_laid_out = false;
+        cache.get_or_insert_with(key, || {
+            is_laid_out = true;
+            run
+        });
+        assert!(is_laid_out);
+    }
+}
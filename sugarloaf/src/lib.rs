@@ -2,6 +2,7 @@ pub mod components;
 pub mod context;
 pub mod core;
 pub mod font;
+mod layout;
 mod sugarloaf;
 mod tools;
 
@@ -1,10 +1,17 @@
-use crate::components::rect::{Rect, RectBrush};
+use crate::components::background_image::{BackgroundImage, ImageFit};
+use crate::components::post_process::PostProcess;
+use crate::components::rect::{Rect, RectBrush, SUPPORTED_SAMPLE_COUNTS};
 use crate::components::text;
-use crate::context::Context;
-use crate::core::{SugarStack, SugarloafStyle};
+use crate::context::{AdapterOptions, Context, Rebuilds, Target};
+use crate::core::{
+    queue_highlight, Highlight, HighlightLayer, SugarStack, SugarloafStyle,
+};
+use crate::font::rasterizer::{HintSize, Rasterizer};
 use crate::font::Font;
+use crate::layout::{GlyphRun, LayoutCache, LayoutKey};
 use glyph_brush::ab_glyph::{self, Font as GFont, FontArc};
 use glyph_brush::{FontId, GlyphCruncher, OwnedSection, OwnedText, Section, Text};
+use std::sync::Arc;
 #[cfg(target_arch = "wasm32")]
 use web_sys::{ImageBitmapRenderingContext, OffscreenCanvas};
 
@@ -29,6 +36,25 @@ pub fn orthographic_projection(width: u32, height: u32) -> [f32; 16] {
     ]
 }
 
+/// Orthographic projection translated by `x` and `y` physical pixels.
+pub fn translated_projection(width: u32, height: u32, x: f32, y: f32) -> [f32; 16] {
+    let mut projection = orthographic_projection(width, height);
+    projection[12] += 2.0 * x / width as f32;
+    projection[13] -= 2.0 * y / height as f32;
+    projection
+}
+
+#[inline]
+fn dim(color: wgpu::Color, brightness: f32) -> wgpu::Color {
+    let brightness = f64::from(brightness);
+    wgpu::Color {
+        r: color.r * brightness,
+        g: color.g * brightness,
+        b: color.b * brightness,
+        a: color.a,
+    }
+}
+
 pub trait Renderable: 'static + Sized {
     fn required_features() -> wgpu::Features {
         wgpu::Features::empty()
@@ -77,11 +103,25 @@ pub struct Sugarloaf {
     text_brush: text::GlyphBrush<()>,
     rect_brush: RectBrush,
     rects: Vec<Rect>,
+    highlights: Vec<Highlight>,
+    layout_cache: LayoutCache,
+    /// Stacks queued in the current frame.
+    line: usize,
     acc_line: f32,
     acc_line_y: f32,
     initial_scale: f32,
     font_bounds: FontBounds,
     background_color: wgpu::Color,
+    scroll_offset: f32,
+    hint_size: HintSize,
+    post_process: Option<PostProcess>,
+    background_image: Option<BackgroundImage>,
+    brightness: f32,
+    /// Whether the window has no width or height, e.g. while minimized,
+    /// in which case nothing is drawn.
+    is_zero_sized: bool,
+    /// Rebuilds already logged.
+    logged_rebuilds: Rebuilds,
 }
 
 const FONT_ID_REGULAR: usize = 0;
@@ -92,15 +132,94 @@ const FONT_ID_BOLD: usize = 4;
 const FONT_ID_ITALIC: usize = 5;
 const FONT_ID_BOLD_ITALIC: usize = 6;
 
+/// Pick the font of each sugar, falling back from the regular font to
+/// symbols, emojis and unicode, and its advance.
+/// Round a logical `value` to the nearest whole physical pixel at `scale`.
+#[inline]
+fn snap(value: f32, scale: f32) -> f32 {
+    (value * scale).round() / scale
+}
+
+fn lay_out(stack: &SugarStack, fonts: &[FontArc], font_bounds: &FontBounds) -> GlyphRun {
+    let system: &FontArc = &fonts[0];
+    let symbols: &FontArc = &fonts[1];
+    let emojis: &FontArc = &fonts[2];
+    let unicode: &FontArc = &fonts[3];
+    let glyph_zero = ab_glyph::GlyphId(0);
+
+    stack
+        .iter()
+        .map(|sugar| {
+            let mut add_pos_x = font_bounds.default.0;
+
+            let mut font_id: FontId = if system.glyph_id(sugar.content) != glyph_zero {
+                FontId(FONT_ID_REGULAR)
+            } else if symbols.glyph_id(sugar.content) != glyph_zero {
+                add_pos_x = font_bounds.symbols.0;
+                FontId(FONT_ID_SYMBOL)
+            } else if emojis.glyph_id(sugar.content) != glyph_zero {
+                add_pos_x = font_bounds.emojis.0;
+                FontId(FONT_ID_EMOJIS)
+            } else if unicode.glyph_id(sugar.content) != glyph_zero {
+                add_pos_x = font_bounds.unicode.0;
+                FontId(FONT_ID_UNICODE)
+            } else {
+                FontId(FONT_ID_REGULAR)
+            };
+
+            if font_id == FontId(FONT_ID_REGULAR) {
+                if let Some(style) = &sugar.style {
+                    if style.is_bold_italic {
+                        font_id = FontId(FONT_ID_BOLD_ITALIC);
+                    } else if style.is_bold {
+                        font_id = FontId(FONT_ID_BOLD);
+                    } else if style.is_italic {
+                        font_id = FontId(FONT_ID_ITALIC);
+                    }
+                }
+            }
+
+            (font_id, add_pos_x)
+        })
+        .collect()
+}
+
+/// Width of outlined highlights, in the units of rect sizes (one logical
+/// pixel).
+const OUTLINE_WIDTH: f32 = 2.0;
+
 impl Sugarloaf {
     pub async fn new(
         winit_window: &winit::window::Window,
-        power_preference: wgpu::PowerPreference,
+        adapter_options: AdapterOptions,
         font_name: String,
+        rasterizer: Rasterizer,
     ) -> Result<Sugarloaf, String> {
-        let ctx = Context::new(winit_window, power_preference).await;
+        let ctx = Context::new(winit_window, adapter_options).await;
+        Sugarloaf::from_context(ctx, font_name, rasterizer)
+    }
 
-        match Font::new(font_name) {
+    /// Render with the device and queue of a host application, for embedding
+    /// the terminal into a game engine or an editor. There is no surface, so
+    /// frames are drawn with `render_to_view` into textures of the host.
+    pub fn with_device(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        adapter_info: wgpu::AdapterInfo,
+        target: Target,
+        font_name: String,
+        rasterizer: Rasterizer,
+    ) -> Result<Sugarloaf, String> {
+        let ctx = Context::from_device(device, queue, adapter_info, target);
+        Sugarloaf::from_context(ctx, font_name, rasterizer)
+    }
+
+    fn from_context(
+        ctx: Context,
+        font_name: String,
+        rasterizer: Rasterizer,
+    ) -> Result<Sugarloaf, String> {
+        match Font::new(font_name, rasterizer) {
             Ok(font) => {
                 let text_brush = text::GlyphBrushBuilder::using_fonts(vec![
                     font.text.regular,
@@ -118,11 +237,21 @@ impl Sugarloaf {
                     ctx,
                     rect_brush,
                     rects: vec![],
+                    highlights: vec![],
+                    layout_cache: LayoutCache::default(),
+                    line: 0,
                     text_brush,
                     acc_line: 0.0,
                     acc_line_y: 0.0,
                     font_bounds: FontBounds::default(),
                     background_color: wgpu::Color::BLACK,
+                    scroll_offset: 0.0,
+                    hint_size: font.hint_size,
+                    post_process: None,
+                    background_image: None,
+                    brightness: 1.0,
+                    is_zero_sized: false,
+                    logged_rebuilds: Rebuilds::default(),
                 })
             }
             Err(err_message) => Err(format!(
@@ -178,55 +307,199 @@ impl Sugarloaf {
         // }
     }
 
-    #[allow(unused)]
-    pub fn clear(&mut self) {
-        match self.ctx.surface.get_current_texture() {
-            Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
-                    &wgpu::CommandEncoderDescriptor { label: None },
-                );
+    /// Queue a single line of `content` starting at the style position, or
+    /// ending there when `align_right` is set.
+    pub fn label(
+        &mut self,
+        content: &str,
+        style: SugarloafStyle,
+        color: [f32; 4],
+        align_right: bool,
+    ) {
+        let align = if align_right {
+            glyph_brush::HorizontalAlign::Right
+        } else {
+            glyph_brush::HorizontalAlign::Left
+        };
 
-                let view = &frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
+        self.text_brush.queue(Section {
+            screen_position: style.screen_position,
+            bounds: style.bounds,
+            text: vec![Text::new(content)
+                .with_color(color)
+                .with_scale(style.text_scale)],
+            layout: glyph_brush::Layout::default_single_line().h_align(align),
+        });
+    }
 
-                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("sugarloaf::init -> Clear frame"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.background_color),
-                            store: true,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                });
-                self.ctx.staging_belt.finish();
-                self.ctx.queue.submit(Some(encoder.finish()));
-                frame.present();
-                self.ctx.staging_belt.recall();
+    /// Texture of the surface to draw the next frame into, if any.
+    fn current_frame(&mut self) -> Option<wgpu::SurfaceTexture> {
+        match self.ctx.surface.as_ref()?.get_current_texture() {
+            Ok(frame) => Some(frame),
+            // The surface no longer matches the window, e.g. while it moves
+            // to a monitor with another scale, it is configured again.
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                log::debug!("surface lost or outdated, configuring it again");
+                let size = self.ctx.size;
+                self.ctx.resize(size.width, size.height);
+                self.ctx.surface.as_ref()?.get_current_texture().ok()
             }
             Err(error) => {
                 if error == wgpu::SurfaceError::OutOfMemory {
                     panic!("Swapchain error: {error}. Rendering cannot continue.")
                 }
+                None
             }
         }
     }
 
+    pub fn clear(&mut self) {
+        if let Some(frame) = self.current_frame() {
+            let mut encoder = self
+                .ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+            let view = &frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("sugarloaf::init -> Clear frame"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.background_color),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            self.ctx.staging_belt.finish();
+            self.ctx.queue.submit(Some(encoder.finish()));
+            frame.present();
+            self.ctx.staging_belt.recall();
+        }
+    }
+
     pub fn resize(&mut self, width: u32, height: u32) -> &mut Self {
+        // The surface keeps its last size until the window has one again.
+        self.is_zero_sized = width == 0 || height == 0;
+        if self.is_zero_sized {
+            return self;
+        }
+
         self.ctx.resize(width, height);
+        self.rect_brush
+            .resize_multisample(&self.ctx.device, width, height);
+        if let Some(post_process) = &mut self.post_process {
+            post_process.resize(&mut self.ctx);
+        }
         self
     }
 
+    /// Run WGSL post-processing passes over every frame, in order. An empty
+    /// list disables post-processing.
+    pub fn set_post_processing(&mut self, sources: &[String]) -> Result<(), String> {
+        self.post_process = if sources.is_empty() {
+            None
+        } else {
+            Some(PostProcess::new(&self.ctx, sources)?)
+        };
+        Ok(())
+    }
+
+    #[inline]
+    pub fn has_post_processing(&self) -> bool {
+        self.post_process.is_some()
+    }
+
+    /// Draw a PNG or JPEG image behind the text. Rects blend over it, so
+    /// cells using the default background should be transparent.
+    pub fn set_background_image(
+        &mut self,
+        data: &[u8],
+        opacity: f32,
+        fit: ImageFit,
+    ) -> Result<(), String> {
+        self.background_image =
+            Some(BackgroundImage::new(&self.ctx, data, opacity, fit)?);
+        Ok(())
+    }
+
+    /// Multisample rects (cursor, selection, underlines and backgrounds)
+    /// with 1 (disabled) or 4 samples per pixel, other counts fall back to 4.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> &mut Self {
+        let sample_count = if SUPPORTED_SAMPLE_COUNTS.contains(&sample_count) {
+            sample_count
+        } else {
+            log::warn!("unsupported sample count {sample_count}, using 4");
+            4
+        };
+
+        // Rect and background image pipelines are rebuilt with the next frame.
+        self.ctx.set_sample_count(sample_count);
+        self
+    }
+
+    /// Pipelines and bind groups rebuilt since sugarloaf was created.
+    pub fn rebuilds(&self) -> Rebuilds {
+        let text = self.text_brush.rebuilds();
+        Rebuilds {
+            pipelines: self.ctx.rebuilds.pipelines + text.pipelines,
+            bind_groups: self.ctx.rebuilds.bind_groups + text.bind_groups,
+        }
+    }
+
     pub fn rescale(&mut self, scale: f32) -> &mut Self {
         self.ctx.scale = scale;
+        // Font bounds were measured at the previous scale, the next `init`
+        // measures them again.
+        self.initial_scale = scale;
+        self.font_bounds = FontBounds::default();
+        self
+    }
+
+    /// Grayscale or LCD subpixel antialiasing for text, changing it
+    /// rasterizes every glyph again.
+    pub fn set_antialiasing(&mut self, antialiasing: text::Antialiasing) -> &mut Self {
+        self.text_brush
+            .set_antialiasing(&self.ctx.device, antialiasing);
+        self
+    }
+
+    /// Gamma and contrast applied to text coverage before blending, thin
+    /// fonts look bolder with `gamma` or `contrast` above their defaults
+    /// (1.0 and 0.0).
+    pub fn set_text_blending(&mut self, gamma: f32, contrast: f32) -> &mut Self {
+        self.text_brush.set_blending(gamma, contrast);
+        self
+    }
+
+    /// Multiply every color of the frame by `brightness`, between 0.0 and 1.0,
+    /// e.g. to dim unfocused windows.
+    pub fn set_brightness(&mut self, brightness: f32) -> &mut Self {
+        self.brightness = brightness.clamp(0.0, 1.0);
+        self.text_brush.set_brightness(self.brightness);
+        self.rect_brush.set_brightness(self.brightness);
+        self
+    }
+
+    /// Vertical offset (in physical pixels) applied to the whole frame,
+    /// used to animate scrolling at sub-line granularity.
+    #[inline]
+    pub fn set_scroll_offset(&mut self, offset: f32) -> &mut Self {
+        self.scroll_offset = offset;
         self
     }
 
     #[inline]
     pub fn stack(&mut self, stack: SugarStack, style: SugarloafStyle) {
+        // Glyphs are cached per scale, a new size is hinted on the next
+        // cache miss.
+        self.hint_size.set(style.text_scale);
+
         let mut text: Vec<OwnedText> = vec![];
         let mut x = 0.;
         let mut mod_size = 1.0;
@@ -244,42 +517,16 @@ impl Sugarloaf {
         }
 
         let fonts = self.text_brush.fonts();
-        let system: &FontArc = &fonts[0];
-        let symbols: &FontArc = &fonts[1];
-        let emojis: &FontArc = &fonts[2];
-        let unicode: &FontArc = &fonts[3];
-        let glyph_zero = ab_glyph::GlyphId(0);
-
-        for sugar in stack.iter() {
-            let mut add_pos_x = self.font_bounds.default.0;
-
-            let mut font_id: FontId = if system.glyph_id(sugar.content) != glyph_zero {
-                FontId(FONT_ID_REGULAR)
-            } else if symbols.glyph_id(sugar.content) != glyph_zero {
-                add_pos_x = self.font_bounds.symbols.0;
-                FontId(FONT_ID_SYMBOL)
-            } else if emojis.glyph_id(sugar.content) != glyph_zero {
-                add_pos_x = self.font_bounds.emojis.0;
-                FontId(FONT_ID_EMOJIS)
-            } else if unicode.glyph_id(sugar.content) != glyph_zero {
-                add_pos_x = self.font_bounds.unicode.0;
-                FontId(FONT_ID_UNICODE)
-            } else {
-                FontId(FONT_ID_REGULAR)
-            };
-
-            if font_id == FontId(FONT_ID_REGULAR) {
-                if let Some(style) = &sugar.style {
-                    if style.is_bold_italic {
-                        font_id = FontId(FONT_ID_BOLD_ITALIC);
-                    } else if style.is_bold {
-                        font_id = FontId(FONT_ID_BOLD);
-                    } else if style.is_italic {
-                        font_id = FontId(FONT_ID_ITALIC);
-                    }
-                }
-            }
+        let font_bounds = &self.font_bounds;
+        let run = self
+            .layout_cache
+            .get_or_insert_with(LayoutKey::new(&stack, style.text_scale), || {
+                lay_out(&stack, fonts, font_bounds)
+            });
 
+        for (column, (sugar, &(font_id, add_pos_x))) in
+            stack.iter().zip(run.iter()).enumerate()
+        {
             text.push(
                 OwnedText::new(sugar.content.to_owned())
                     .with_font_id(font_id)
@@ -287,14 +534,33 @@ impl Sugarloaf {
                     .with_scale(style.text_scale),
             );
 
-            self.rects.push(Rect {
-                position: [
-                    (style.screen_position.0 / self.ctx.scale) + x,
-                    self.acc_line_y,
-                ],
+            // Cells start and end on whole physical pixels, so neighbours
+            // neither overlap nor leave gaps at fractional scales.
+            let left = snap(style.screen_position.0 / self.ctx.scale + x, self.ctx.scale);
+            let right = snap(left + add_pos_x * mod_size, self.ctx.scale);
+            let background = Rect {
+                position: [left, snap(self.acc_line_y, self.ctx.scale)],
                 color: sugar.background_color,
-                size: [add_pos_x * mod_size, self.font_bounds.default.0 * mod_size],
-            });
+                size: [right - left, self.font_bounds.default.0 * mod_size],
+            };
+            self.rects.push(background);
+            for highlight in self.highlights.iter().filter(|highlight| {
+                highlight.line == self.line && highlight.columns.contains(&column)
+            }) {
+                let rect = Rect {
+                    color: highlight.color,
+                    ..background
+                };
+                match highlight.layer {
+                    HighlightLayer::CursorOutline => {
+                        self.rects.extend(rect.outline(OUTLINE_WIDTH))
+                    }
+                    HighlightLayer::Strikethrough => {
+                        self.rects.push(rect.middle_line(OUTLINE_WIDTH))
+                    }
+                    _ => self.rects.push(rect),
+                }
+            }
 
             x += add_pos_x / self.initial_scale;
         }
@@ -314,6 +580,51 @@ impl Sugarloaf {
 
         self.acc_line_y = (style.screen_position.1 + self.acc_line) / self.ctx.scale;
         self.acc_line += style.text_scale;
+        self.line += 1;
+    }
+
+    /// Rasterize printable ASCII of the text fonts at `text_scale` into the
+    /// glyph cache, so the first frames don't stall on it. The cache lives in
+    /// the text brush, so this runs on the calling thread before anything is
+    /// drawn, rather than in the background.
+    pub fn warm_up(&mut self, text_scale: f32) {
+        self.hint_size.set(text_scale);
+
+        let ascii: String = (' '..='~').collect();
+        let text = [
+            FONT_ID_REGULAR,
+            FONT_ID_BOLD,
+            FONT_ID_ITALIC,
+            FONT_ID_BOLD_ITALIC,
+        ]
+        .into_iter()
+        .map(|font_id| {
+            OwnedText::new(ascii.clone())
+                .with_font_id(FontId(font_id))
+                .with_scale(text_scale)
+        })
+        .collect();
+        self.text_brush.queue(&OwnedSection {
+            text,
+            ..OwnedSection::default()
+        });
+
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.text_brush
+            .process(&self.ctx.device, &mut self.ctx.staging_belt, &mut encoder);
+        self.ctx.staging_belt.finish();
+        self.ctx.queue.submit(Some(encoder.finish()));
+        self.ctx.staging_belt.recall();
+    }
+
+    /// Highlight cells of a stack queued later in the frame.
+    #[inline]
+    pub fn highlight(&mut self, highlight: Highlight) -> &mut Self {
+        queue_highlight(&mut self.highlights, highlight);
+        self
     }
 
     pub fn get_context(&self) -> &Context {
@@ -364,58 +675,29 @@ impl Sugarloaf {
         self.rects = vec![];
         self.background_color = color;
 
-        match self.ctx.surface.get_current_texture() {
-            Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
-                    &wgpu::CommandEncoderDescriptor { label: None },
-                );
-
-                let view = &frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
-
-                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("sugarloaf::init -> Clear frame"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(color),
-                            store: true,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                });
-
-                if self.font_bounds.default == (0., 0.) {
-                    // Bounds are defined in runtime
-                    self.font_bounds.default =
-                        self.get_font_bounds(' ', FontId(0), style);
-                    self.font_bounds.symbols =
-                        // U+2AF9 => \u{2AF9} => ⫹
-                        self.get_font_bounds('\u{2AF9}', FontId(1), style);
-                    self.font_bounds.emojis =
-                        // U+1F947 => \u{1F947} => 🥇
-                        self.get_font_bounds('\u{1F947}', FontId(2), style);
-                    self.font_bounds.unicode =
-                        // U+33D1 => \u{33D1} => ㏑
-                        self.get_font_bounds('\u{33D1}', FontId(3), style);
-                }
-
-                self.ctx.queue.submit(Some(encoder.finish()));
-                frame.present();
-            }
-            Err(error) => {
-                if error == wgpu::SurfaceError::OutOfMemory {
-                    panic!("Swapchain error: {error}. Rendering cannot continue.")
-                }
-            }
+        if self.font_bounds.default == (0., 0.) {
+            // Bounds are defined in runtime
+            self.font_bounds.default = self.get_font_bounds(' ', FontId(0), style);
+            self.font_bounds.symbols =
+                // U+2AF9 => \u{2AF9} => ⫹
+                self.get_font_bounds('\u{2AF9}', FontId(1), style);
+            self.font_bounds.emojis =
+                // U+1F947 => \u{1F947} => 🥇
+                self.get_font_bounds('\u{1F947}', FontId(2), style);
+            self.font_bounds.unicode =
+                // U+33D1 => \u{33D1} => ㏑
+                self.get_font_bounds('\u{33D1}', FontId(3), style);
         }
+
+        self.clear();
     }
 
     fn reset_state(&mut self) {
+        self.layout_cache.next_frame();
         self.acc_line = 0.0;
         self.acc_line_y = 0.0;
+        self.line = 0;
+        self.highlights.clear();
     }
 
     pub fn pile_rect(&mut self, instances: Vec<Rect>) -> &mut Self {
@@ -423,61 +705,206 @@ impl Sugarloaf {
         self
     }
 
+    /// Draw `rect` over what was stacked so far.
+    #[inline]
+    pub fn add_rect(&mut self, rect: Rect) -> &mut Self {
+        self.rects.push(rect);
+        self
+    }
+
     #[inline]
     pub fn render(&mut self) {
+        if self.is_zero_sized {
+            return;
+        }
+
+        if let Some(frame) = self.current_frame() {
+            let mut encoder = self
+                .ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+            let view = &frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            self.draw(&mut encoder, view);
+
+            self.ctx.staging_belt.finish();
+            self.ctx.queue.submit(Some(encoder.finish()));
+            frame.present();
+            self.ctx.staging_belt.recall();
+        }
+    }
+
+    /// Draw what was queued into a texture of the host application, created
+    /// with the format and size given to `with_device` or `resize`.
+    pub fn render_to_view(&mut self, view: &wgpu::TextureView) {
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.draw(&mut encoder, view);
+
+        self.ctx.staging_belt.finish();
+        self.ctx.queue.submit(Some(encoder.finish()));
+        self.ctx.staging_belt.recall();
+    }
+
+    /// Draw what was queued into an offscreen texture instead of the surface
+    /// and read it back, at the size of the surface.
+    pub fn screenshot(&mut self) -> Result<image::RgbaImage, String> {
+        let (width, height) = (self.ctx.size.width, self.ctx.size.height);
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("sugarloaf::screenshot"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.ctx.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        // Rows of a copy have to be aligned.
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (width * 4 + align - 1) / align * align;
+        let buffer = self.ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sugarloaf::screenshot"),
+            size: u64::from(padded_row * height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let view = &texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.draw(&mut encoder, view);
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_row),
+                    rows_per_image: std::num::NonZeroU32::new(height),
+                },
+            },
+            size,
+        );
+        self.ctx.staging_belt.finish();
+        self.ctx.queue.submit(Some(encoder.finish()));
+        self.ctx.staging_belt.recall();
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.ctx.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|err| err.to_string())?
+            .map_err(|err| err.to_string())?;
+
+        let is_bgra = matches!(
+            self.ctx.format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for row in slice.get_mapped_range().chunks(padded_row as usize) {
+            for pixel in row[..(width * 4) as usize].chunks(4) {
+                if is_bgra {
+                    pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+                } else {
+                    pixels.extend_from_slice(pixel);
+                }
+            }
+        }
+        buffer.unmap();
+
+        image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| String::from("unexpected screenshot size"))
+    }
+
+    /// Draw everything queued since the last frame into `view`.
+    fn draw(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_view: &wgpu::TextureView,
+    ) {
         self.reset_state();
 
-        match self.ctx.surface.get_current_texture() {
-            Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
-                    &wgpu::CommandEncoderDescriptor { label: None },
-                );
+        if self.rect_brush.generation != self.ctx.generation {
+            self.rect_brush = RectBrush::init(&self.ctx);
+            self.rect_brush.set_brightness(self.brightness);
+            self.ctx.rebuilds.pipelines += 1;
+            self.ctx.rebuilds.bind_groups += 1;
+        }
 
-                let view = &frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
+        let view = match &self.post_process {
+            Some(post_process) => post_process.target(),
+            None => frame_view,
+        };
 
+        {
+            let mut render_pass =
                 encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("sugarloaf::render -> Clear frame"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    color_attachments: &[Some(self.rect_brush.color_attachment(
                         view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.background_color),
-                            store: true,
-                        },
-                    })],
+                        wgpu::LoadOp::Clear(dim(self.background_color, self.brightness)),
+                    ))],
                     depth_stencil_attachment: None,
                 });
 
-                self.rect_brush.render(
-                    &mut encoder,
-                    view,
-                    orthographic_projection(self.ctx.size.width, self.ctx.size.height),
-                    &self.rects,
+            if let Some(background_image) = &mut self.background_image {
+                background_image.render(
                     &mut self.ctx,
+                    self.brightness,
+                    &mut render_pass,
                 );
+            }
+        }
 
-                self.rects = vec![];
+        let transform = translated_projection(
+            self.ctx.size.width,
+            self.ctx.size.height,
+            0.0,
+            self.scroll_offset,
+        );
 
-                let _ = self.text_brush.draw_queued(
-                    &self.ctx.device,
-                    &mut self.ctx.staging_belt,
-                    &mut encoder,
-                    view,
-                    (self.ctx.size.width, self.ctx.size.height),
-                );
+        self.rect_brush
+            .render(encoder, view, transform, &self.rects, &mut self.ctx);
 
-                self.ctx.staging_belt.finish();
-                self.ctx.queue.submit(Some(encoder.finish()));
-                frame.present();
-                self.ctx.staging_belt.recall();
-            }
-            Err(error) => {
-                if error == wgpu::SurfaceError::OutOfMemory {
-                    panic!("Swapchain error: {error}. Rendering cannot continue.")
-                }
-            }
+        self.rects = vec![];
+
+        let _ = self.text_brush.draw_queued_with_transform(
+            &self.ctx.device,
+            &mut self.ctx.staging_belt,
+            encoder,
+            view,
+            transform,
+        );
+
+        if let Some(post_process) = &mut self.post_process {
+            post_process.render(&self.ctx, encoder, frame_view);
+        }
+
+        let rebuilds = self.rebuilds();
+        if rebuilds != self.logged_rebuilds {
+            log::debug!(
+                "rebuilt {} pipelines and {} bind groups so far",
+                rebuilds.pipelines,
+                rebuilds.bind_groups
+            );
+            self.logged_rebuilds = rebuilds;
         }
     }
 }
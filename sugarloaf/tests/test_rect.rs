@@ -21,8 +21,9 @@ pub async fn main() {
 
     let mut sugarloaf = Sugarloaf::new(
         &window,
-        wgpu::PowerPreference::HighPerformance,
+        wgpu::PowerPreference::HighPerformance.into(),
         "Firamono".to_string(),
+        sugarloaf::font::rasterizer::Rasterizer::default(),
     )
     .await
     .expect("Sugarloaf instance should be created");
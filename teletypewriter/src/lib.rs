@@ -71,6 +71,23 @@ fn default_shell_command(shell: &str) {
     }
 }
 
+/// Replace the process with `program`, looked up in `PATH`, and `args`.
+fn command(program: &str, args: &[String]) {
+    let program = CString::new(program).unwrap();
+    let args: Vec<CString> = args
+        .iter()
+        .map(|arg| CString::new(arg.as_str()).unwrap())
+        .collect();
+    let mut argv = vec![program.as_ptr()];
+    argv.extend(args.iter().map(|arg| arg.as_ptr()));
+    argv.push(std::ptr::null());
+    unsafe {
+        libc::execvp(program.as_ptr(), argv.as_ptr());
+        // Like shells do for a command that isn't found.
+        libc::_exit(127);
+    }
+}
+
 pub struct Pty {
     child: Child,
     file: File,
@@ -200,6 +217,16 @@ impl ProcessReadWrite for Pty {
     }
 }
 
+/// `TERM` to set for the configured `term`, `xterm-256color` when it is
+/// `rio` and the `rio` terminfo entry is not installed.
+pub fn terminfo(term: &str) -> &str {
+    if term != "rio" || terminfo_exists("rio") {
+        term
+    } else {
+        "xterm-256color"
+    }
+}
+
 // From alacritty: https://github.com/alacritty/alacritty/blob/2df8f860b960d7c96efaf4f059fe2fbbdce82bcc/alacritty_terminal/src/tty/mod.rs#L83
 /// Check if a terminfo entry exists on the system.
 pub fn terminfo_exists(terminfo: &str) -> bool {
@@ -351,6 +378,20 @@ pub fn create_termp(utf8: bool) -> libc::termios {
 /// It returns two [`Pty`] along with respective process name [`String`] and process id (`libc::pid_`)
 ///
 pub fn create_pty(shell: &str, columns: u16, rows: u16) -> Pty {
+    spawn_pty(columns, rows, || default_shell_command(shell))
+}
+
+/// Creates a pseudoterminal running `program` with `args` instead of a shell.
+pub fn create_pty_with_command(
+    program: &str,
+    args: &[String],
+    columns: u16,
+    rows: u16,
+) -> Pty {
+    spawn_pty(columns, rows, || command(program, args))
+}
+
+fn spawn_pty(columns: u16, rows: u16, exec: impl FnOnce()) -> Pty {
     let mut main = 0;
     let winsize = Winsize {
         ws_row: rows as libc::c_ushort,
@@ -369,7 +410,7 @@ pub fn create_pty(shell: &str, columns: u16, rows: u16) -> Pty {
         )
     } {
         0 => {
-            default_shell_command(shell);
+            exec();
             unreachable!();
         }
         id if id > 0 => {
@@ -469,6 +510,15 @@ impl Child {
         }
     }
 
+    /// Handle to ask which program is in the foreground, still usable once
+    /// the `Pty` moved to the I/O thread.
+    pub fn foreground(&self) -> Foreground {
+        Foreground {
+            fd: *self.id,
+            shell_pid: *self.pid,
+        }
+    }
+
     /// Return the child’s exit status if it has already exited. If the child is still running, return Ok(None).
     /// https://linux.die.net/man/2/waitpid
     pub fn waitpid(&self) -> Result<Option<i32>, String> {
@@ -504,6 +554,49 @@ impl Drop for Child {
     }
 }
 
+/// Foreground process group of the terminal, see [`Child::foreground`].
+#[derive(Debug, Clone, Copy)]
+pub struct Foreground {
+    fd: libc::c_int,
+    shell_pid: libc::pid_t,
+}
+
+impl Foreground {
+    /// Name of the program running in the foreground, `None` when the shell
+    /// is waiting for a command.
+    pub fn process_name(&self) -> Option<String> {
+        let group = unsafe { libc::tcgetpgrp(self.fd) };
+        if group <= 0 || group == self.shell_pid {
+            return None;
+        }
+
+        process_name(group)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_name(pid: libc::pid_t) -> Option<String> {
+    let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    Some(name.trim_end().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_name(pid: libc::pid_t) -> Option<String> {
+    let output = Command::new("ps")
+        .arg("-p")
+        .arg(format!("{pid}"))
+        .arg("-o")
+        .arg("comm=")
+        .output()
+        .ok()?;
+    let name = std::str::from_utf8(&output.stdout).ok()?.trim();
+    // macOS gives the path of the executable.
+    name.rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+}
+
 pub fn command_per_pid(pid: libc::pid_t) -> String {
     let current_process_name = Command::new("ps")
         .arg("-p")
@@ -521,8 +614,9 @@ pub fn command_per_pid(pid: libc::pid_t) -> String {
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ChildEvent {
-    /// Indicates the child has exited.
-    Exited,
+    /// Indicates the child has exited, with its exit code when it exited
+    /// normally.
+    Exited(Option<i32>),
 }
 
 pub trait EventedPty: ProcessReadWrite {
@@ -547,7 +641,9 @@ impl EventedPty for Pty {
                     std::process::exit(1);
                 }
                 Ok(None) => None,
-                Ok(Some(..)) => Some(ChildEvent::Exited),
+                Ok(Some(status)) => Some(ChildEvent::Exited(
+                    libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+                )),
             }
         })
     }
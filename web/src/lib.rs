@@ -0,0 +1,328 @@
+// Rio in the browser: a rio-core terminal rendered by Sugarloaf on a canvas
+// (WebGPU, or WebGL2 with the `webgl` feature) and connected to a remote PTY
+// through a WebSocket, given as `?pty=ws://host:port` in the page URL.
+
+#![cfg(target_arch = "wasm32")]
+
+use colors::term::{List, TermColors};
+use colors::{AnsiColor, ColorArray};
+use rio_core::crosswords::grid::row::Row;
+use rio_core::crosswords::pos::CursorState;
+use rio_core::crosswords::square::{Flags, Square};
+use rio_core::crosswords::{Crosswords, Mode, MIN_COLUMNS, MIN_VISIBLE_ROWS};
+use rio_core::event::{EventListener, RioEvent, WinsizeBuilder};
+use rio_core::performer::handler::ParserProcessor;
+use rio_core::transport::{Transport, WebSocketTransport};
+use std::cell::RefCell;
+use std::rc::Rc;
+use sugarloaf::core::{Sugar, SugarStack, SugarStyle, SugarloafStyle};
+use sugarloaf::Sugarloaf;
+use wasm_bindgen::prelude::*;
+use winit::dpi::PhysicalSize;
+use winit::event::{
+    ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent,
+};
+use winit::event_loop::EventLoop;
+use winit::window::{Window, WindowBuilder};
+
+const DEFAULT_PTY_URL: &str = "ws://localhost:7703";
+const FONT_SIZE: f32 = 16.0;
+const PADDING: f32 = 10.0;
+
+type SharedTransport = Rc<RefCell<Option<WebSocketTransport>>>;
+type SharedTerminal = Rc<RefCell<Crosswords<Listener>>>;
+
+fn write(transport: &SharedTransport, bytes: &[u8]) {
+    if let Some(transport) = transport.borrow_mut().as_mut() {
+        transport.write(bytes);
+    }
+}
+
+/// Sends the terminal replies (e.g. cursor position reports) back to the PTY.
+struct Listener {
+    transport: SharedTransport,
+}
+
+impl EventListener for Listener {
+    fn send_event(&self, event: RioEvent) {
+        match event {
+            RioEvent::PtyWrite(text) => write(&self.transport, text.as_bytes()),
+            RioEvent::Title(title) => {
+                if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+                    document.set_title(&title);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Columns and rows fitting in the canvas.
+fn grid_size(size: PhysicalSize<u32>, scale: f32) -> (usize, usize) {
+    let padding = 2. * PADDING * scale;
+    let columns = (size.width as f32 - padding) / scale / (FONT_SIZE / 2.);
+    let rows = (size.height as f32 - padding) / scale / FONT_SIZE;
+    (
+        std::cmp::max(columns as usize, MIN_COLUMNS),
+        std::cmp::max(rows as usize, MIN_VISIBLE_ROWS),
+    )
+}
+
+fn style(size: PhysicalSize<u32>, scale: f32) -> SugarloafStyle {
+    SugarloafStyle {
+        screen_position: (PADDING * scale, (PADDING + FONT_SIZE) * scale),
+        bounds: (size.width as f32, size.height as f32),
+        text_scale: FONT_SIZE * scale,
+    }
+}
+
+fn color(colors: &List, color: AnsiColor) -> ColorArray {
+    match color {
+        AnsiColor::Named(name) => colors[name],
+        AnsiColor::Spec(rgb) => [
+            rgb.r as f32 / 255.,
+            rgb.g as f32 / 255.,
+            rgb.b as f32 / 255.,
+            1.0,
+        ],
+        AnsiColor::Indexed(index) => colors[index as usize],
+    }
+}
+
+fn sugar(square: &Square, colors: &List) -> Sugar {
+    let mut foreground_color = color(colors, square.fg);
+    let mut background_color = color(colors, square.bg);
+    if square.flags.contains(Flags::INVERSE) {
+        std::mem::swap(&mut foreground_color, &mut background_color);
+    }
+
+    let is_italic = square.flags.contains(Flags::ITALIC);
+    let is_bold_italic = square.flags.contains(Flags::BOLD_ITALIC);
+    let is_bold = square.flags.contains(Flags::BOLD);
+    let style = (is_bold || is_bold_italic || is_italic).then_some(SugarStyle {
+        is_italic,
+        is_bold_italic,
+        is_bold,
+    });
+
+    Sugar {
+        content: square.c,
+        foreground_color,
+        background_color,
+        style,
+    }
+}
+
+fn sugar_stack(
+    row: &Row<Square>,
+    line: usize,
+    cursor: &CursorState,
+    colors: &List,
+) -> SugarStack {
+    let has_cursor = cursor.is_visible() && cursor.pos.row == line;
+    row.inner
+        .iter()
+        .enumerate()
+        .map(|(column, square)| {
+            let mut sugar = sugar(square, colors);
+            if has_cursor && column == cursor.pos.col {
+                std::mem::swap(&mut sugar.foreground_color, &mut sugar.background_color);
+            }
+            sugar
+        })
+        .collect()
+}
+
+/// Bytes sent for keys that don't produce a character.
+fn key_bytes(key: VirtualKeyCode, mode: Mode) -> Option<&'static [u8]> {
+    let app_cursor = mode.contains(Mode::APP_CURSOR);
+    let bytes: &'static [u8] = match key {
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => b"\r",
+        VirtualKeyCode::Back => b"\x7f",
+        VirtualKeyCode::Tab => b"\t",
+        VirtualKeyCode::Escape => b"\x1b",
+        VirtualKeyCode::Up if app_cursor => b"\x1bOA",
+        VirtualKeyCode::Down if app_cursor => b"\x1bOB",
+        VirtualKeyCode::Right if app_cursor => b"\x1bOC",
+        VirtualKeyCode::Left if app_cursor => b"\x1bOD",
+        VirtualKeyCode::Up => b"\x1b[A",
+        VirtualKeyCode::Down => b"\x1b[B",
+        VirtualKeyCode::Right => b"\x1b[C",
+        VirtualKeyCode::Left => b"\x1b[D",
+        VirtualKeyCode::Home => b"\x1b[H",
+        VirtualKeyCode::End => b"\x1b[F",
+        VirtualKeyCode::PageUp => b"\x1b[5~",
+        VirtualKeyCode::PageDown => b"\x1b[6~",
+        VirtualKeyCode::Delete => b"\x1b[3~",
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+fn pty_url() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .and_then(|search| {
+            search
+                .trim_start_matches('?')
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("pty=").map(String::from))
+        })
+        .unwrap_or_else(|| String::from(DEFAULT_PTY_URL))
+}
+
+#[wasm_bindgen(start)]
+pub fn start() {
+    wasm_bindgen_futures::spawn_local(run());
+}
+
+async fn run() {
+    let event_loop = EventLoop::new();
+    let window = Rc::new(
+        WindowBuilder::new()
+            .with_title("Rio")
+            .with_inner_size(PhysicalSize::new(1024, 768))
+            .build(&event_loop)
+            .expect("unable to create the canvas"),
+    );
+
+    let mut sugarloaf = match Sugarloaf::new(
+        &window,
+        wgpu::PowerPreference::HighPerformance.into(),
+        sugarloaf::font::DEFAULT_FONT_NAME.to_string(),
+        sugarloaf::font::rasterizer::Rasterizer::default(),
+    )
+    .await
+    {
+        Ok(sugarloaf) => sugarloaf,
+        Err(err) => {
+            log::error!("{err}");
+            return;
+        }
+    };
+
+    let colors = List::from(&TermColors::default());
+    let background = colors::defaults::background().1;
+    let mut size = window.inner_size();
+    let mut scale = sugarloaf.get_scale();
+    let (columns, rows) = grid_size(size, scale);
+
+    let transport: SharedTransport = Rc::new(RefCell::new(None));
+    let terminal: SharedTerminal = Rc::new(RefCell::new(Crosswords::new(
+        columns,
+        rows,
+        Listener {
+            transport: transport.clone(),
+        },
+    )));
+
+    let connection = {
+        let terminal = terminal.clone();
+        let window = window.clone();
+        let mut parser = ParserProcessor::new();
+        WebSocketTransport::connect(
+            &pty_url(),
+            move |bytes| {
+                parser.advance_bytes(&mut *terminal.borrow_mut(), bytes);
+                window.request_redraw();
+            },
+            || log::warn!("connection to the PTY closed"),
+        )
+    };
+    match connection {
+        Ok(connection) => *transport.borrow_mut() = Some(connection),
+        Err(err) => log::error!("unable to connect to the PTY: {err:?}"),
+    }
+
+    let resize = {
+        let terminal = terminal.clone();
+        let transport = transport.clone();
+        move |size: PhysicalSize<u32>, scale: f32| {
+            let (columns, rows) = grid_size(size, scale);
+            terminal
+                .borrow_mut()
+                .resize::<(usize, usize)>(columns, rows);
+            if let Some(transport) = transport.borrow_mut().as_mut() {
+                transport.resize(WinsizeBuilder {
+                    cols: columns as u16,
+                    rows: rows as u16,
+                    width: size.width as u16,
+                    height: size.height as u16,
+                });
+            }
+        }
+    };
+    resize(size, scale);
+    sugarloaf.init(background, style(size, scale));
+
+    let mut modifiers = ModifiersState::empty();
+    event_loop.run(move |event, _, control_flow| {
+        control_flow.set_wait();
+
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::Resized(new_size) => {
+                    size = new_size;
+                    sugarloaf.resize(size.width, size.height);
+                    resize(size, scale);
+                    window.request_redraw();
+                }
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                } => {
+                    size = *new_inner_size;
+                    scale = scale_factor as f32;
+                    sugarloaf.resize(size.width, size.height).rescale(scale);
+                    resize(size, scale);
+                    window.request_redraw();
+                }
+                WindowEvent::ModifiersChanged(state) => modifiers = state,
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } => {
+                    let mode = terminal.borrow().mode();
+                    if let Some(bytes) = key_bytes(key, mode) {
+                        write(&transport, bytes);
+                    }
+                }
+                WindowEvent::ReceivedCharacter(character) => {
+                    if character.is_control() {
+                        return;
+                    }
+
+                    let mut bytes = [0; 4];
+                    let bytes = character.encode_utf8(&mut bytes).as_bytes();
+                    if modifiers.ctrl()
+                        && bytes.len() == 1
+                        && bytes[0].is_ascii_alphabetic()
+                    {
+                        write(&transport, &[bytes[0].to_ascii_lowercase() & 0x1f]);
+                    } else {
+                        write(&transport, bytes);
+                    }
+                }
+                _ => {}
+            },
+            Event::RedrawRequested(_) => {
+                let mut terminal = terminal.borrow_mut();
+                let visible_rows = terminal.visible_rows();
+                let cursor = terminal.cursor();
+                drop(terminal);
+
+                let style = style(size, scale);
+                for (line, row) in visible_rows.iter().enumerate() {
+                    sugarloaf.stack(sugar_stack(row, line, &cursor, &colors), style);
+                }
+                sugarloaf.render();
+            }
+            _ => {}
+        }
+    });
+}